@@ -0,0 +1,16 @@
+-- Push images built from Git stacks to a team registry
+ALTER TABLE stacks ADD COLUMN push_registry_id TEXT;
+ALTER TABLE stacks ADD COLUMN push_namespace TEXT;
+ALTER TABLE stacks ADD COLUMN push_retention INTEGER NOT NULL DEFAULT 5;
+
+-- Tags pushed for each service, used for retention-based cleanup
+CREATE TABLE IF NOT EXISTS stack_pushed_images (
+    id TEXT PRIMARY KEY NOT NULL,
+    stack_id TEXT NOT NULL,
+    service_name TEXT NOT NULL,
+    image_ref TEXT NOT NULL,
+    pushed_at TEXT NOT NULL,
+    FOREIGN KEY (stack_id) REFERENCES stacks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_stack_pushed_images_service ON stack_pushed_images(stack_id, service_name, pushed_at DESC);
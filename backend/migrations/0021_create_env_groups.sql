@@ -0,0 +1,32 @@
+-- Team-scoped environment variable groups shared across stacks
+CREATE TABLE IF NOT EXISTS env_groups (
+    id TEXT PRIMARY KEY NOT NULL,
+    team_id TEXT NOT NULL REFERENCES teams(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    description TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE(team_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS env_group_vars (
+    id TEXT PRIMARY KEY NOT NULL,
+    group_id TEXT NOT NULL REFERENCES env_groups(id) ON DELETE CASCADE,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    is_secret BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE(group_id, key)
+);
+
+-- Groups attached to a stack; a higher position overrides a lower one
+CREATE TABLE IF NOT EXISTS stack_env_groups (
+    stack_id TEXT NOT NULL REFERENCES stacks(id) ON DELETE CASCADE,
+    group_id TEXT NOT NULL REFERENCES env_groups(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (stack_id, group_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_env_groups_team_id ON env_groups(team_id);
+CREATE INDEX IF NOT EXISTS idx_stack_env_groups_group_id ON stack_env_groups(group_id);
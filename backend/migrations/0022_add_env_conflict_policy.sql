@@ -0,0 +1,5 @@
+-- Which side wins when compose and the UI define the same variable: 'compose' or 'ui'
+ALTER TABLE stacks ADD COLUMN env_conflict_policy TEXT NOT NULL DEFAULT 'compose';
+
+-- Where a variable was last written from: 'ui' (API/dashboard/import) or 'compose'
+ALTER TABLE stack_env_vars ADD COLUMN source TEXT NOT NULL DEFAULT 'ui';
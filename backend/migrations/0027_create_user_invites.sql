@@ -0,0 +1,29 @@
+-- Deactivated users keep their data but can no longer sign in
+ALTER TABLE users ADD COLUMN deactivated_at TEXT;
+
+-- Invitation links: the invitee picks their own password when accepting
+CREATE TABLE IF NOT EXISTS user_invites (
+    id TEXT PRIMARY KEY NOT NULL,
+    team_id TEXT NOT NULL REFERENCES teams(id) ON DELETE CASCADE,
+    email TEXT NOT NULL,
+    name TEXT,
+    role TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    invited_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+    expires_at TEXT NOT NULL,
+    accepted_at TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_invites_team_id ON user_invites(team_id);
+
+-- Single-use password reset links issued by an administrator
+CREATE TABLE IF NOT EXISTS password_resets (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+    expires_at TEXT NOT NULL,
+    used_at TEXT,
+    created_at TEXT NOT NULL
+);
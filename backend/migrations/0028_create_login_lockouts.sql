@@ -0,0 +1,8 @@
+-- Failed login counters, persisted only when RATE_LIMIT_PERSIST is enabled.
+-- Keys are "account:<email>" or "ip:<address>".
+CREATE TABLE IF NOT EXISTS login_lockouts (
+    key TEXT PRIMARY KEY NOT NULL,
+    failures INTEGER NOT NULL,
+    locked_until TEXT,
+    last_failure_at TEXT NOT NULL
+);
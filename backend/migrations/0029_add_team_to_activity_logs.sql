@@ -0,0 +1,4 @@
+-- Scope audit entries to the team that owns the resource
+ALTER TABLE activity_logs ADD COLUMN team_id TEXT REFERENCES teams(id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS idx_activity_logs_team_created ON activity_logs(team_id, created_at);
@@ -0,0 +1,26 @@
+-- Custom team roles: named permission sets layered on top of a member's built-in role
+CREATE TABLE IF NOT EXISTS team_roles (
+    id TEXT PRIMARY KEY,
+    team_id TEXT NOT NULL REFERENCES teams(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    description TEXT,
+    permissions TEXT NOT NULL DEFAULT '', -- space-separated list of permissions
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (team_id, name)
+);
+
+ALTER TABLE team_members ADD COLUMN custom_role_id TEXT REFERENCES team_roles(id) ON DELETE SET NULL;
+
+-- Per-stack grants: extra permissions for one user on one stack
+CREATE TABLE IF NOT EXISTS stack_grants (
+    stack_id TEXT NOT NULL REFERENCES stacks(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    permissions TEXT NOT NULL DEFAULT '', -- space-separated list of permissions
+    granted_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (stack_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_stack_grants_user_id ON stack_grants(user_id);
@@ -0,0 +1,23 @@
+-- Load balancing for a domain's upstreams
+ALTER TABLE domains ADD COLUMN extra_upstreams TEXT NOT NULL DEFAULT ''; -- space-separated container:port targets
+ALTER TABLE domains ADD COLUMN lb_policy TEXT NOT NULL DEFAULT 'round_robin';
+ALTER TABLE domains ADD COLUMN health_check_path TEXT;
+ALTER TABLE domains ADD COLUMN health_check_interval INTEGER NOT NULL DEFAULT 10;
+
+-- Path prefixes of a domain routed to their own upstreams
+CREATE TABLE IF NOT EXISTS domain_routes (
+    id TEXT PRIMARY KEY,
+    domain_id TEXT NOT NULL REFERENCES domains(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    strip_prefix INTEGER NOT NULL DEFAULT 0,
+    container_name TEXT NOT NULL,
+    container_port INTEGER NOT NULL DEFAULT 80,
+    extra_upstreams TEXT NOT NULL DEFAULT '', -- space-separated container:port targets
+    lb_policy TEXT NOT NULL DEFAULT 'round_robin',
+    health_check_path TEXT,
+    health_check_interval INTEGER NOT NULL DEFAULT 10,
+    created_at TEXT NOT NULL,
+    UNIQUE (domain_id, path)
+);
+
+CREATE INDEX IF NOT EXISTS idx_domain_routes_domain_id ON domain_routes(domain_id);
@@ -0,0 +1,3 @@
+-- Per-domain HTTP middleware (redirects, headers, IP filters, ...) as JSON
+ALTER TABLE domains ADD COLUMN middleware TEXT NOT NULL DEFAULT '{}';
+ALTER TABLE domains ADD COLUMN basic_auth TEXT NOT NULL DEFAULT ''; -- space-separated username:bcrypt-hash pairs
@@ -0,0 +1,2 @@
+-- ACME challenge per domain: 'http' (default) or 'dns' (DNS-01 via the team's Cloudflare config)
+ALTER TABLE domains ADD COLUMN tls_challenge TEXT NOT NULL DEFAULT 'http';
@@ -0,0 +1,4 @@
+-- Uploaded PEM certificate chain and private key, both encrypted with the master key.
+-- When set, Caddy loads this pair instead of obtaining a certificate over ACME.
+ALTER TABLE domains ADD COLUMN custom_certificate TEXT;
+ALTER TABLE domains ADD COLUMN custom_private_key TEXT;
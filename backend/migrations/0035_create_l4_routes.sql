@@ -0,0 +1,14 @@
+-- Raw TCP/UDP routes from a host port (optionally split by TLS SNI) to a stack service
+CREATE TABLE IF NOT EXISTS l4_routes (
+    id TEXT PRIMARY KEY,
+    stack_id TEXT NOT NULL REFERENCES stacks(id) ON DELETE CASCADE,
+    protocol TEXT NOT NULL DEFAULT 'tcp',
+    listen_port INTEGER NOT NULL,
+    sni TEXT,
+    container_name TEXT NOT NULL,
+    container_port INTEGER NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_l4_routes_stack_id ON l4_routes(stack_id);
+CREATE INDEX IF NOT EXISTS idx_l4_routes_listen_port ON l4_routes(listen_port);
@@ -0,0 +1,19 @@
+-- Requests per domain and minute, aggregated from Caddy's access log
+CREATE TABLE domain_request_metrics (
+    id TEXT PRIMARY KEY,
+    domain TEXT NOT NULL,
+    stack_id TEXT NOT NULL,
+    bucket TEXT NOT NULL, -- start of the minute, 'YYYY-MM-DD HH:MM:00' UTC
+    requests INTEGER NOT NULL DEFAULT 0,
+    status_1xx INTEGER NOT NULL DEFAULT 0,
+    status_2xx INTEGER NOT NULL DEFAULT 0,
+    status_3xx INTEGER NOT NULL DEFAULT 0,
+    status_4xx INTEGER NOT NULL DEFAULT 0,
+    status_5xx INTEGER NOT NULL DEFAULT 0,
+    bytes_sent INTEGER NOT NULL DEFAULT 0,
+    latency_sum_ms REAL NOT NULL DEFAULT 0,
+    latency_histogram TEXT NOT NULL DEFAULT '[]', -- JSON counts per LATENCY_BOUNDS_MS bucket
+    UNIQUE (domain, stack_id, bucket)
+);
+
+CREATE INDEX idx_domain_request_metrics_stack_bucket ON domain_request_metrics(stack_id, bucket);
@@ -0,0 +1,6 @@
+-- Serve a static maintenance page for the stack's domains instead of its containers
+ALTER TABLE stacks ADD COLUMN maintenance_mode INTEGER NOT NULL DEFAULT 0;
+-- Custom maintenance page HTML; the built-in page is used when NULL
+ALTER TABLE stacks ADD COLUMN maintenance_page TEXT;
+-- JSON object of status code (502, 503, 504) to custom error page HTML
+ALTER TABLE stacks ADD COLUMN error_pages TEXT NOT NULL DEFAULT '{}';
@@ -1,13 +1,16 @@
 use axum::{
     Json,
-    extract::{Request, State},
-    http::{StatusCode, header},
+    extract::{OriginalUri, Request, State},
+    http::{Method, StatusCode, header},
     middleware::Next,
     response::Response,
 };
 use serde::Serialize;
 use std::sync::Arc;
 
+use crate::domain::models::{
+    API_TOKEN_PREFIX, ScopeRequirement, User, required_scope, requires_system_admin, scope_granted,
+};
 use crate::infrastructure::auth::jwt::Claims;
 use crate::usecase::auth::AuthUsecase;
 
@@ -16,6 +19,8 @@ pub struct CurrentUser {
     pub id: String,
     pub email: String,
     pub role: String,
+    /// Login session of the access token; `None` when authenticated with an API token
+    pub session_id: Option<String>,
 }
 
 impl From<Claims> for CurrentUser {
@@ -24,6 +29,7 @@ impl From<Claims> for CurrentUser {
             id: claims.sub,
             email: claims.email,
             role: claims.role,
+            session_id: Some(claims.sid),
         }
     }
 }
@@ -34,6 +40,69 @@ pub struct AuthError {
     message: String,
 }
 
+fn original_path(request: &Request) -> String {
+    request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string())
+}
+
+/// Until users covered by a team 2FA policy enroll, only their own account and
+/// the auth endpoints (where enrollment happens) are reachable
+async fn check_mfa_enrollment(
+    auth_usecase: &AuthUsecase,
+    user: &User,
+    path: &str,
+) -> Result<(), (StatusCode, Json<AuthError>)> {
+    if path == "/api/me" || path.starts_with("/api/auth/") {
+        return Ok(());
+    }
+
+    let required = auth_usecase
+        .mfa_enrollment_required(user)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(AuthError {
+                    error: "internal_error".to_string(),
+                    message: e.to_string(),
+                }),
+            )
+        })?;
+    if required {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(AuthError {
+                error: "mfa_enrollment_required".to_string(),
+                message: "Your team requires two-factor authentication. Enable it in Settings."
+                    .to_string(),
+            }),
+        ));
+    }
+    Ok(())
+}
+
+/// Host-level routes (images, nodes, networks, the host terminal) are not scoped to a
+/// team, so team roles do not apply and only administrators may use them
+fn check_system_admin(
+    user: &User,
+    method: &Method,
+    path: &str,
+) -> Result<(), (StatusCode, Json<AuthError>)> {
+    if requires_system_admin(method.as_str(), path) && !user.is_admin() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(AuthError {
+                error: "admin_required".to_string(),
+                message: "This operation requires a Labuh administrator".to_string(),
+            }),
+        ));
+    }
+    Ok(())
+}
+
 pub async fn auth_middleware(
     State(auth_usecase): State<Arc<AuthUsecase>>,
     mut request: Request,
@@ -46,15 +115,19 @@ pub async fn auth_middleware(
 
     let token_from_header = auth_header.and_then(|h| h.strip_prefix("Bearer "));
 
-    let token_from_query = request
-        .uri()
-        .query()
-        .and_then(|q| q.split('&').find(|pair| pair.starts_with("token=")))
-        .and_then(|pair| pair.strip_prefix("token="));
+    // Browsers cannot set headers on WebSocket or EventSource connections, so
+    // those authenticate with a single-use ticket from `POST /api/auth/stream-ticket`
+    let ticket_from_query = (request.method() == Method::GET)
+        .then(|| request.uri().query())
+        .flatten()
+        .and_then(|q| q.split('&').find_map(|pair| pair.strip_prefix("ticket=")))
+        .map(str::to_string);
 
-    let token = match (token_from_header, token_from_query) {
+    let token = match (token_from_header, ticket_from_query) {
         (Some(t), _) => t,
-        (None, Some(t)) => t,
+        (None, Some(ticket)) => {
+            return authenticate_stream_ticket(&auth_usecase, &ticket, request, next).await;
+        }
         _ => {
             return Err((
                 StatusCode::UNAUTHORIZED,
@@ -66,11 +139,25 @@ pub async fn auth_middleware(
         }
     };
 
-    match auth_usecase.verify_token(token) {
+    if token.starts_with(API_TOKEN_PREFIX) {
+        return authenticate_api_token(&auth_usecase, token.to_string(), request, next).await;
+    }
+
+    match auth_usecase.verify_token(token).await {
         Ok(claims) => {
             // Verify user still exists in database
             match auth_usecase.get_user_by_id(&claims.sub).await {
+                Ok(user) if !user.is_active() => Err((
+                    StatusCode::UNAUTHORIZED,
+                    Json(AuthError {
+                        error: "account_deactivated".to_string(),
+                        message: "This account has been deactivated.".to_string(),
+                    }),
+                )),
                 Ok(user) => {
+                    let path = original_path(&request);
+                    check_system_admin(&user, request.method(), &path)?;
+                    check_mfa_enrollment(&auth_usecase, &user, &path).await?;
                     let mut current_user = CurrentUser::from(claims);
                     current_user.role = user.role;
                     request.extensions_mut().insert(current_user);
@@ -94,3 +181,90 @@ pub async fn auth_middleware(
         )),
     }
 }
+
+async fn authenticate_stream_ticket(
+    auth_usecase: &AuthUsecase,
+    ticket: &str,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, (StatusCode, Json<AuthError>)> {
+    let (user, session_id) = auth_usecase
+        .redeem_stream_ticket(ticket)
+        .await
+        .map_err(|_| {
+            (
+                StatusCode::UNAUTHORIZED,
+                Json(AuthError {
+                    error: "unauthorized".to_string(),
+                    message: "Invalid or expired stream ticket".to_string(),
+                }),
+            )
+        })?;
+    if !user.is_active() {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(AuthError {
+                error: "account_deactivated".to_string(),
+                message: "This account has been deactivated.".to_string(),
+            }),
+        ));
+    }
+    let path = original_path(&request);
+    check_system_admin(&user, request.method(), &path)?;
+    check_mfa_enrollment(auth_usecase, &user, &path).await?;
+
+    request.extensions_mut().insert(CurrentUser {
+        id: user.id,
+        email: user.email,
+        role: user.role,
+        session_id: Some(session_id),
+    });
+    Ok(next.run(request).await)
+}
+
+async fn authenticate_api_token(
+    auth_usecase: &AuthUsecase,
+    token: String,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, (StatusCode, Json<AuthError>)> {
+    let (user, api_token) = auth_usecase.verify_api_token(&token).await.map_err(|_| {
+        (
+            StatusCode::UNAUTHORIZED,
+            Json(AuthError {
+                error: "unauthorized".to_string(),
+                message: "Invalid, revoked or expired API token".to_string(),
+            }),
+        )
+    })?;
+
+    // Nested routers see a stripped path, so check scopes against the original one
+    let path = original_path(&request);
+    let scopes = api_token.scope_list();
+
+    let allowed = match required_scope(request.method().as_str(), &path) {
+        ScopeRequirement::Any => true,
+        ScopeRequirement::Scope(scope) => scope_granted(&scopes, scope),
+        ScopeRequirement::Forbidden => false,
+    };
+    if !allowed {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(AuthError {
+                error: "insufficient_scope".to_string(),
+                message: "API token does not have the scope required for this endpoint".to_string(),
+            }),
+        ));
+    }
+
+    check_system_admin(&user, request.method(), &path)?;
+    check_mfa_enrollment(auth_usecase, &user, &path).await?;
+
+    request.extensions_mut().insert(CurrentUser {
+        id: user.id,
+        email: user.email,
+        role: user.role,
+        session_id: None,
+    });
+    Ok(next.run(request).await)
+}
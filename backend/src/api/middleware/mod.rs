@@ -1,3 +1,5 @@
 pub mod auth;
+pub mod rate_limit;
 
 pub use auth::auth_middleware;
+pub use rate_limit::{ClientIp, RateLimitState, rate_limit_middleware};
@@ -0,0 +1,92 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::infrastructure::rate_limit::RateLimiter;
+
+/// Address of the client that sent the request
+#[derive(Clone)]
+pub struct ClientIp(pub String);
+
+#[derive(Clone)]
+pub struct RateLimitState {
+    pub limiter: Arc<RateLimiter>,
+    /// Take the client address from `X-Forwarded-For`; only safe behind a trusted proxy
+    /// that appends to it
+    pub trust_proxy_headers: bool,
+}
+
+/// The peer address, or with `trust_proxy_headers` the last `X-Forwarded-For` entry:
+/// the one our proxy appended. Earlier entries come from the client and can be forged.
+fn client_ip(request: &Request, trust_proxy_headers: bool) -> String {
+    let forwarded = trust_proxy_headers
+        .then(|| {
+            request
+                .headers()
+                .get_all("x-forwarded-for")
+                .iter()
+                .filter_map(|h| h.to_str().ok())
+                .flat_map(|h| h.split(','))
+                .map(str::trim)
+                .rfind(|ip| !ip.is_empty())
+        })
+        .flatten()
+        .map(String::from);
+
+    forwarded
+        .or_else(|| {
+            request
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Limit requests per client IP and expose the IP to handlers as `ClientIp`
+pub async fn rate_limit_middleware(
+    State(state): State<RateLimitState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response> {
+    let ip = client_ip(&request, state.trust_proxy_headers);
+    state.limiter.check(&ip)?;
+
+    request.extensions_mut().insert(ClientIp(ip));
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    #[test]
+    fn test_client_ip_uses_entry_added_by_proxy() {
+        let request = |forwarded: &[&str]| {
+            let mut builder = Request::builder();
+            for value in forwarded {
+                builder = builder.header("x-forwarded-for", *value);
+            }
+            let mut request = builder.body(Body::empty()).unwrap();
+            request
+                .extensions_mut()
+                .insert(ConnectInfo(SocketAddr::from(([172, 18, 0, 2], 40000))));
+            request
+        };
+
+        let spoofed = request(&["1.2.3.4, 203.0.113.9"]);
+        assert_eq!(client_ip(&spoofed, true), "203.0.113.9");
+        assert_eq!(client_ip(&spoofed, false), "172.18.0.2");
+        assert_eq!(
+            client_ip(&request(&["1.2.3.4", "198.51.100.7 "]), true),
+            "198.51.100.7"
+        );
+        assert_eq!(client_ip(&request(&[]), true), "172.18.0.2");
+    }
+}
@@ -0,0 +1,87 @@
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, State},
+    routing::{delete, get},
+};
+use std::sync::Arc;
+
+use crate::api::middleware::auth::CurrentUser;
+use crate::domain::models::{ApiTokenResponse, CreateApiTokenRequest, CreatedApiToken};
+use crate::error::{AppError, Result};
+use crate::usecase::api_token::ApiTokenUsecase;
+
+async fn list_tokens(
+    State(usecase): State<Arc<ApiTokenUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<Vec<ApiTokenResponse>>> {
+    let tokens = usecase.list_personal(&current_user.id).await?;
+    Ok(Json(tokens))
+}
+
+async fn create_token(
+    State(usecase): State<Arc<ApiTokenUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(request): Json<CreateApiTokenRequest>,
+) -> Result<Json<CreatedApiToken>> {
+    if request.role.is_some() {
+        return Err(AppError::Validation(
+            "Personal tokens act with your own permissions; role is only valid for team tokens"
+                .to_string(),
+        ));
+    }
+    let token = usecase.create_personal(&current_user.id, request).await?;
+    Ok(Json(token))
+}
+
+async fn revoke_token(
+    State(usecase): State<Arc<ApiTokenUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>> {
+    usecase.revoke_personal(&id, &current_user.id).await?;
+    Ok(Json(serde_json::json!({ "status": "revoked" })))
+}
+
+async fn list_team_tokens(
+    State(usecase): State<Arc<ApiTokenUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(team_id): Path<String>,
+) -> Result<Json<Vec<ApiTokenResponse>>> {
+    let tokens = usecase.list_team_tokens(&team_id, &current_user.id).await?;
+    Ok(Json(tokens))
+}
+
+async fn create_team_token(
+    State(usecase): State<Arc<ApiTokenUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(team_id): Path<String>,
+    Json(request): Json<CreateApiTokenRequest>,
+) -> Result<Json<CreatedApiToken>> {
+    let token = usecase
+        .create_team_token(&team_id, &current_user.id, request)
+        .await?;
+    Ok(Json(token))
+}
+
+async fn revoke_team_token(
+    State(usecase): State<Arc<ApiTokenUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((team_id, id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>> {
+    usecase
+        .revoke_team_token(&team_id, &id, &current_user.id)
+        .await?;
+    Ok(Json(serde_json::json!({ "status": "revoked" })))
+}
+
+pub fn api_token_routes(usecase: Arc<ApiTokenUsecase>) -> Router {
+    Router::new()
+        .route("/tokens", get(list_tokens).post(create_token))
+        .route("/tokens/{id}", delete(revoke_token))
+        .route(
+            "/teams/{team_id}/tokens",
+            get(list_team_tokens).post(create_team_token),
+        )
+        .route("/teams/{team_id}/tokens/{id}", delete(revoke_team_token))
+        .with_state(usecase)
+}
@@ -0,0 +1,27 @@
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, Query, State},
+    routing::get,
+};
+use std::sync::Arc;
+
+use crate::api::middleware::auth::CurrentUser;
+use crate::domain::models::{AuditLogPage, AuditLogQuery};
+use crate::error::Result;
+use crate::usecase::audit::AuditUsecase;
+
+async fn list_team_audit(
+    State(usecase): State<Arc<AuditUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(team_id): Path<String>,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<AuditLogPage>> {
+    let page = usecase.list(&team_id, &current_user.id, query).await?;
+    Ok(Json(page))
+}
+
+pub fn audit_routes(usecase: Arc<AuditUsecase>) -> Router {
+    Router::new()
+        .route("/teams/{team_id}/audit", get(list_team_audit))
+        .with_state(usecase)
+}
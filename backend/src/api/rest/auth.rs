@@ -1,28 +1,70 @@
 use axum::{
     Json, Router,
     extract::{Extension, State},
+    http::{HeaderMap, header},
     routing::{get, post},
 };
 use std::sync::Arc;
 
+use crate::api::middleware::ClientIp;
 use crate::api::middleware::auth::CurrentUser;
-use crate::domain::models::{AuthResponse, CreateUser, LoginRequest, UserResponse};
-use crate::error::Result;
+use crate::domain::models::{
+    AuthResponse, CreateUser, LoginOptions, LoginRequest, LoginResponse, MfaLoginRequest,
+    RefreshTokenRequest, SessionResponse, StreamTicket, UserResponse,
+};
+use crate::error::{AppError, Result};
 use crate::usecase::auth::AuthUsecase;
 
+fn user_agent(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.chars().take(255).collect())
+}
+
+fn session_id(current_user: &CurrentUser) -> Result<&str> {
+    current_user
+        .session_id
+        .as_deref()
+        .ok_or_else(|| AppError::BadRequest("Not authenticated with a login session".to_string()))
+}
+
 async fn register(
     State(auth_usecase): State<Arc<AuthUsecase>>,
+    headers: HeaderMap,
     Json(input): Json<CreateUser>,
 ) -> Result<Json<AuthResponse>> {
-    let response = auth_usecase.register(input).await?;
+    let response = auth_usecase.register(input, user_agent(&headers)).await?;
     Ok(Json(response))
 }
 
 async fn login(
     State(auth_usecase): State<Arc<AuthUsecase>>,
+    Extension(ClientIp(ip)): Extension<ClientIp>,
+    headers: HeaderMap,
     Json(input): Json<LoginRequest>,
+) -> Result<Json<LoginResponse>> {
+    let response = auth_usecase.login(input, &ip, user_agent(&headers)).await?;
+    Ok(Json(response))
+}
+
+async fn login_2fa(
+    State(auth_usecase): State<Arc<AuthUsecase>>,
+    Extension(ClientIp(ip)): Extension<ClientIp>,
+    headers: HeaderMap,
+    Json(input): Json<MfaLoginRequest>,
+) -> Result<Json<AuthResponse>> {
+    let response = auth_usecase
+        .complete_mfa_login(&input.mfa_token, &input.code, &ip, user_agent(&headers))
+        .await?;
+    Ok(Json(response))
+}
+
+async fn refresh(
+    State(auth_usecase): State<Arc<AuthUsecase>>,
+    Json(input): Json<RefreshTokenRequest>,
 ) -> Result<Json<AuthResponse>> {
-    let response = auth_usecase.login(input).await?;
+    let response = auth_usecase.refresh(&input.refresh_token).await?;
     Ok(Json(response))
 }
 
@@ -31,24 +73,69 @@ async fn setup_required(State(auth_usecase): State<Arc<AuthUsecase>>) -> Result<
     Ok(Json(required))
 }
 
-async fn me(Extension(current_user): Extension<CurrentUser>) -> Result<Json<UserResponse>> {
-    Ok(Json(UserResponse {
-        id: current_user.id,
-        email: current_user.email,
-        name: None,
-        role: current_user.role,
-        created_at: String::new(),
-    }))
+async fn login_options(State(auth_usecase): State<Arc<AuthUsecase>>) -> Json<LoginOptions> {
+    Json(auth_usecase.login_options().clone())
+}
+
+async fn me(
+    State(auth_usecase): State<Arc<AuthUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<UserResponse>> {
+    let user = auth_usecase.get_user_by_id(&current_user.id).await?;
+    Ok(Json(user.into()))
+}
+
+async fn logout(
+    State(auth_usecase): State<Arc<AuthUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<serde_json::Value>> {
+    auth_usecase.logout(session_id(&current_user)?).await?;
+    Ok(Json(serde_json::json!({ "status": "logged_out" })))
+}
+
+async fn logout_all(
+    State(auth_usecase): State<Arc<AuthUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<serde_json::Value>> {
+    auth_usecase.revoke_all_sessions(&current_user.id).await?;
+    Ok(Json(serde_json::json!({ "status": "logged_out" })))
+}
+
+async fn list_sessions(
+    State(auth_usecase): State<Arc<AuthUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<Vec<SessionResponse>>> {
+    let sessions = auth_usecase
+        .list_sessions(&current_user.id, session_id(&current_user)?)
+        .await?;
+    Ok(Json(sessions))
+}
+
+async fn stream_ticket(
+    State(auth_usecase): State<Arc<AuthUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<StreamTicket>> {
+    let ticket = auth_usecase.issue_stream_ticket(&current_user.id, session_id(&current_user)?);
+    Ok(Json(ticket))
 }
 
 pub fn auth_routes(auth_usecase: Arc<AuthUsecase>) -> Router {
     Router::new()
         .route("/register", post(register))
         .route("/login", post(login))
+        .route("/login/2fa", post(login_2fa))
+        .route("/refresh", post(refresh))
         .route("/setup-required", get(setup_required))
+        .route("/providers", get(login_options))
         .with_state(auth_usecase)
 }
 
-pub fn protected_auth_routes() -> Router {
-    Router::new().route("/me", get(me))
+pub fn protected_auth_routes(auth_usecase: Arc<AuthUsecase>) -> Router {
+    Router::new()
+        .route("/me", get(me))
+        .route("/auth/logout", post(logout))
+        .route("/auth/logout-all", post(logout_all))
+        .route("/auth/sessions", get(list_sessions))
+        .route("/auth/stream-ticket", post(stream_ticket))
+        .with_state(auth_usecase)
 }
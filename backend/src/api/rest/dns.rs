@@ -7,8 +7,8 @@ use std::sync::Arc;
 
 use crate::api::middleware::auth::CurrentUser;
 use crate::app_state::AppState;
-use crate::domain::models::TeamRole;
 use crate::domain::models::dns::{CreateDnsConfigRequest, DnsConfig};
+use crate::domain::models::permission;
 use crate::error::{AppError, Result};
 
 async fn list_dns_configs(
@@ -16,17 +16,14 @@ async fn list_dns_configs(
     Extension(current_user): Extension<CurrentUser>,
     Path(team_id): Path<String>,
 ) -> Result<Json<Vec<DnsConfig>>> {
-    let team_uc = state
-        .team_usecase
-        .as_ref()
-        .ok_or(AppError::Internal("Team usecase not available".to_string()))?;
     let dns_uc = state
         .dns_usecase
         .as_ref()
         .ok_or(AppError::Internal("DNS usecase not available".to_string()))?;
 
-    team_uc
-        .verify_permission(&team_id, &current_user.id, TeamRole::Admin)
+    state
+        .authz_usecase
+        .require(&team_id, &current_user.id, permission::DNS_MANAGE)
         .await?;
     let configs = dns_uc.list_configs(&team_id).await?;
     Ok(Json(configs))
@@ -38,17 +35,14 @@ async fn save_dns_config(
     Path(team_id): Path<String>,
     Json(request): Json<CreateDnsConfigRequest>,
 ) -> Result<Json<DnsConfig>> {
-    let team_uc = state
-        .team_usecase
-        .as_ref()
-        .ok_or(AppError::Internal("Team usecase not available".to_string()))?;
     let dns_uc = state
         .dns_usecase
         .as_ref()
         .ok_or(AppError::Internal("DNS usecase not available".to_string()))?;
 
-    team_uc
-        .verify_permission(&team_id, &current_user.id, TeamRole::Admin)
+    state
+        .authz_usecase
+        .require(&team_id, &current_user.id, permission::DNS_MANAGE)
         .await?;
     let config = dns_uc
         .save_config(&team_id, &request.provider, request.config)
@@ -61,17 +55,14 @@ async fn remove_dns_config(
     Extension(current_user): Extension<CurrentUser>,
     Path((team_id, provider)): Path<(String, String)>,
 ) -> Result<Json<serde_json::Value>> {
-    let team_uc = state
-        .team_usecase
-        .as_ref()
-        .ok_or(AppError::Internal("Team usecase not available".to_string()))?;
     let dns_uc = state
         .dns_usecase
         .as_ref()
         .ok_or(AppError::Internal("DNS usecase not available".to_string()))?;
 
-    team_uc
-        .verify_permission(&team_id, &current_user.id, TeamRole::Admin)
+    state
+        .authz_usecase
+        .require(&team_id, &current_user.id, permission::DNS_MANAGE)
         .await?;
     dns_uc.remove_config(&team_id, &provider).await?;
     Ok(Json(serde_json::json!({ "status": "removed" })))
@@ -82,17 +73,14 @@ async fn get_available_domains(
     Extension(current_user): Extension<CurrentUser>,
     Path((team_id, provider)): Path<(String, String)>,
 ) -> Result<Json<Vec<String>>> {
-    let team_uc = state
-        .team_usecase
-        .as_ref()
-        .ok_or(AppError::Internal("Team usecase not available".to_string()))?;
     let dns_uc = state
         .dns_usecase
         .as_ref()
         .ok_or(AppError::Internal("DNS usecase not available".to_string()))?;
 
-    team_uc
-        .verify_permission(&team_id, &current_user.id, TeamRole::Viewer)
+    state
+        .authz_usecase
+        .require(&team_id, &current_user.id, permission::TEAM_VIEW)
         .await?;
 
     let provider_enum = match provider.to_lowercase().as_str() {
@@ -117,17 +105,14 @@ async fn get_remote_records(
     Extension(current_user): Extension<CurrentUser>,
     Path((team_id, provider)): Path<(String, String)>,
 ) -> Result<Json<Vec<crate::domain::models::dns::RemoteDnsRecord>>> {
-    let team_uc = state
-        .team_usecase
-        .as_ref()
-        .ok_or(AppError::Internal("Team usecase not available".to_string()))?;
     let dns_uc = state
         .dns_usecase
         .as_ref()
         .ok_or(AppError::Internal("DNS usecase not available".to_string()))?;
 
-    team_uc
-        .verify_permission(&team_id, &current_user.id, TeamRole::Viewer)
+    state
+        .authz_usecase
+        .require(&team_id, &current_user.id, permission::TEAM_VIEW)
         .await?;
 
     let provider_enum = match provider.to_lowercase().as_str() {
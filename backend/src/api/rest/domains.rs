@@ -8,7 +8,12 @@ use std::sync::Arc;
 
 use crate::api::middleware::auth::CurrentUser;
 use crate::app_state::AppState;
-use crate::domain::models::{CreateDomain, DomainProvider, DomainResponse, DomainType};
+use crate::domain::models::permission;
+use crate::domain::models::{
+    CertificateStatus, CreateDomain, CreateDomainRoute, CreateL4Route, DomainProvider,
+    DomainResponse, DomainRouteResponse, DomainType, L4Route, UpdateDomainMiddleware,
+    UpdateDomainTls, UpstreamSettings,
+};
 use crate::error::{AppError, Result};
 use crate::usecase::domain::DnsVerificationResult;
 
@@ -29,20 +34,13 @@ async fn list_all_domains(
     Extension(current_user): Extension<CurrentUser>,
     Query(query): Query<ListDomainsQuery>,
 ) -> Result<Json<Vec<DomainResponse>>> {
-    let team_uc = state
-        .team_usecase
-        .as_ref()
-        .ok_or(AppError::Internal("Team usecase not available".to_string()))?;
     let domain_uc = state.domain_usecase.as_ref().ok_or(AppError::Internal(
         "Domain usecase not available".to_string(),
     ))?;
 
-    team_uc
-        .verify_permission(
-            &query.team_id,
-            &current_user.id,
-            crate::domain::models::TeamRole::Viewer,
-        )
+    state
+        .authz_usecase
+        .require(&query.team_id, &current_user.id, permission::TEAM_VIEW)
         .await?;
 
     let domains = domain_uc.list_team_domains(&query.team_id).await?;
@@ -50,6 +48,24 @@ async fn list_all_domains(
     Ok(Json(responses))
 }
 
+async fn list_certificates(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Query(query): Query<ListDomainsQuery>,
+) -> Result<Json<Vec<CertificateStatus>>> {
+    let domain_uc = state.domain_usecase.as_ref().ok_or(AppError::Internal(
+        "Domain usecase not available".to_string(),
+    ))?;
+
+    state
+        .authz_usecase
+        .require(&query.team_id, &current_user.id, permission::TEAM_VIEW)
+        .await?;
+
+    let statuses = domain_uc.team_certificate_statuses(&query.team_id).await?;
+    Ok(Json(statuses))
+}
+
 async fn list_domains(
     State(state): State<Arc<AppState>>,
     Extension(current_user): Extension<CurrentUser>,
@@ -81,25 +97,32 @@ async fn add_domain(
         "Domain usecase not available".to_string(),
     ))?;
 
-    stack_uc.get_stack(&stack_id, &current_user.id).await?;
+    stack_uc
+        .authorize(&stack_id, &current_user.id, permission::DOMAIN_MANAGE)
+        .await?;
     let container_port = request.container_port.unwrap_or(80);
     let provider = request.provider.unwrap_or(DomainProvider::Custom);
     let domain_type = request.r#type.unwrap_or(DomainType::Caddy);
 
     let domain = domain_uc
-        .add_domain(crate::usecase::domain::AddDomainRequest {
-            stack_id: stack_id.to_string(),
-            domain: request.domain.to_string(),
-            container_name: request.container_name.to_string(),
-            container_port,
-            provider,
-            domain_type,
-            tunnel_id: request.tunnel_id.clone(),
-            tunnel_token: request.tunnel_token.clone(),
-            dns_record_type: request.dns_record_type.clone(),
-            dns_record_content: request.dns_record_content.clone(),
-            proxied: request.proxied.unwrap_or(false),
-        })
+        .add_domain(
+            crate::usecase::domain::AddDomainRequest {
+                stack_id: stack_id.to_string(),
+                domain: request.domain.to_string(),
+                container_name: request.container_name.to_string(),
+                container_port,
+                provider,
+                domain_type,
+                tunnel_id: request.tunnel_id.clone(),
+                tunnel_token: request.tunnel_token.clone(),
+                dns_record_type: request.dns_record_type.clone(),
+                dns_record_content: request.dns_record_content.clone(),
+                proxied: request.proxied.unwrap_or(false),
+                tls_challenge: request.tls_challenge,
+                upstream: request.upstream,
+            },
+            &current_user.id,
+        )
         .await?;
     Ok(Json(domain.into()))
 }
@@ -116,16 +139,26 @@ async fn remove_domain(
         "Domain usecase not available".to_string(),
     ))?;
 
-    stack_uc.get_stack(&stack_id, &current_user.id).await?;
-    domain_uc.remove_domain(&stack_id, &domain).await?;
+    stack_uc
+        .authorize(&stack_id, &current_user.id, permission::DOMAIN_MANAGE)
+        .await?;
+    domain_uc
+        .remove_domain(&stack_id, &domain, &current_user.id)
+        .await?;
     Ok(Json(serde_json::json!({ "status": "removed" })))
 }
 
 async fn verify_domain(
     State(state): State<Arc<AppState>>,
-    Extension(_current_user): Extension<CurrentUser>,
-    Path((_stack_id, domain)): Path<(String, String)>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((stack_id, domain)): Path<(String, String)>,
 ) -> Result<Json<DnsVerificationResult>> {
+    let stack_uc = state.stack_usecase.as_ref().ok_or(AppError::Internal(
+        "Stack usecase not available".to_string(),
+    ))?;
+    stack_uc
+        .authorize(&stack_id, &current_user.id, permission::STACK_VIEW)
+        .await?;
     let domain_uc = state.domain_usecase.as_ref().ok_or(AppError::Internal(
         "Domain usecase not available".to_string(),
     ))?;
@@ -146,7 +179,9 @@ async fn update_dns(
         "Domain usecase not available".to_string(),
     ))?;
 
-    stack_uc.get_stack(&stack_id, &current_user.id).await?;
+    stack_uc
+        .authorize(&stack_id, &current_user.id, permission::DOMAIN_MANAGE)
+        .await?;
     domain_uc
         .update_domain_dns(
             &stack_id,
@@ -191,22 +226,235 @@ async fn toggle_branding(
         "Domain usecase not available".to_string(),
     ))?;
 
+    stack_uc
+        .authorize(&stack_id, &current_user.id, permission::DOMAIN_MANAGE)
+        .await?;
+    let badge_active = domain_uc
+        .toggle_branding(&stack_id, &domain, request.show_branding)
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "status": "updated",
+        "show_branding": request.show_branding,
+        "badge_active": badge_active,
+    })))
+}
+
+async fn update_upstreams(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((stack_id, domain)): Path<(String, String)>,
+    Json(request): Json<UpstreamSettings>,
+) -> Result<Json<DomainResponse>> {
+    let stack_uc = state.stack_usecase.as_ref().ok_or(AppError::Internal(
+        "Stack usecase not available".to_string(),
+    ))?;
+    let domain_uc = state.domain_usecase.as_ref().ok_or(AppError::Internal(
+        "Domain usecase not available".to_string(),
+    ))?;
+
+    stack_uc
+        .authorize(&stack_id, &current_user.id, permission::DOMAIN_MANAGE)
+        .await?;
+    let updated = domain_uc
+        .update_upstreams(&stack_id, &domain, request, &current_user.id)
+        .await?;
+    Ok(Json(updated.into()))
+}
+
+async fn update_middleware(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((stack_id, domain)): Path<(String, String)>,
+    Json(request): Json<UpdateDomainMiddleware>,
+) -> Result<Json<DomainResponse>> {
+    let stack_uc = state.stack_usecase.as_ref().ok_or(AppError::Internal(
+        "Stack usecase not available".to_string(),
+    ))?;
+    let domain_uc = state.domain_usecase.as_ref().ok_or(AppError::Internal(
+        "Domain usecase not available".to_string(),
+    ))?;
+
+    stack_uc
+        .authorize(&stack_id, &current_user.id, permission::DOMAIN_MANAGE)
+        .await?;
+    let updated: DomainResponse = domain_uc
+        .update_middleware(&stack_id, &domain, request, &current_user.id)
+        .await?
+        .into();
+    Ok(Json(updated))
+}
+
+async fn update_tls(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((stack_id, domain)): Path<(String, String)>,
+    Json(request): Json<UpdateDomainTls>,
+) -> Result<Json<DomainResponse>> {
+    let stack_uc = state.stack_usecase.as_ref().ok_or(AppError::Internal(
+        "Stack usecase not available".to_string(),
+    ))?;
+    let domain_uc = state.domain_usecase.as_ref().ok_or(AppError::Internal(
+        "Domain usecase not available".to_string(),
+    ))?;
+
+    stack_uc
+        .authorize(&stack_id, &current_user.id, permission::DOMAIN_MANAGE)
+        .await?;
+    let updated = domain_uc
+        .update_tls(&stack_id, &domain, request, &current_user.id)
+        .await?;
+    Ok(Json(updated.into()))
+}
+
+async fn certificate_status(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((stack_id, domain)): Path<(String, String)>,
+) -> Result<Json<CertificateStatus>> {
+    let stack_uc = state.stack_usecase.as_ref().ok_or(AppError::Internal(
+        "Stack usecase not available".to_string(),
+    ))?;
+    let domain_uc = state.domain_usecase.as_ref().ok_or(AppError::Internal(
+        "Domain usecase not available".to_string(),
+    ))?;
+
     stack_uc.get_stack(&stack_id, &current_user.id).await?;
+    let status = domain_uc.certificate_status(&stack_id, &domain).await?;
+    Ok(Json(status))
+}
+
+async fn list_routes(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((stack_id, domain)): Path<(String, String)>,
+) -> Result<Json<Vec<DomainRouteResponse>>> {
+    let stack_uc = state.stack_usecase.as_ref().ok_or(AppError::Internal(
+        "Stack usecase not available".to_string(),
+    ))?;
+    let domain_uc = state.domain_usecase.as_ref().ok_or(AppError::Internal(
+        "Domain usecase not available".to_string(),
+    ))?;
+
+    stack_uc.get_stack(&stack_id, &current_user.id).await?;
+    let routes = domain_uc.list_routes(&stack_id, &domain).await?;
+    Ok(Json(routes.into_iter().map(Into::into).collect()))
+}
+
+async fn add_route(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((stack_id, domain)): Path<(String, String)>,
+    Json(request): Json<CreateDomainRoute>,
+) -> Result<Json<DomainRouteResponse>> {
+    let stack_uc = state.stack_usecase.as_ref().ok_or(AppError::Internal(
+        "Stack usecase not available".to_string(),
+    ))?;
+    let domain_uc = state.domain_usecase.as_ref().ok_or(AppError::Internal(
+        "Domain usecase not available".to_string(),
+    ))?;
+
+    stack_uc
+        .authorize(&stack_id, &current_user.id, permission::DOMAIN_MANAGE)
+        .await?;
+    let route = domain_uc
+        .add_route(&stack_id, &domain, request, &current_user.id)
+        .await?;
+    Ok(Json(route.into()))
+}
+
+async fn remove_route(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((stack_id, domain, route_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>> {
+    let stack_uc = state.stack_usecase.as_ref().ok_or(AppError::Internal(
+        "Stack usecase not available".to_string(),
+    ))?;
+    let domain_uc = state.domain_usecase.as_ref().ok_or(AppError::Internal(
+        "Domain usecase not available".to_string(),
+    ))?;
+
+    stack_uc
+        .authorize(&stack_id, &current_user.id, permission::DOMAIN_MANAGE)
+        .await?;
     domain_uc
-        .toggle_branding(&stack_id, &domain, request.show_branding)
+        .remove_route(&stack_id, &domain, &route_id, &current_user.id)
+        .await?;
+    Ok(Json(serde_json::json!({ "status": "removed" })))
+}
+
+async fn list_l4_routes(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(stack_id): Path<String>,
+) -> Result<Json<Vec<L4Route>>> {
+    let stack_uc = state.stack_usecase.as_ref().ok_or(AppError::Internal(
+        "Stack usecase not available".to_string(),
+    ))?;
+    let domain_uc = state.domain_usecase.as_ref().ok_or(AppError::Internal(
+        "Domain usecase not available".to_string(),
+    ))?;
+
+    stack_uc.get_stack(&stack_id, &current_user.id).await?;
+    Ok(Json(domain_uc.list_l4_routes(&stack_id).await?))
+}
+
+async fn add_l4_route(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(stack_id): Path<String>,
+    Json(request): Json<CreateL4Route>,
+) -> Result<Json<L4Route>> {
+    let stack_uc = state.stack_usecase.as_ref().ok_or(AppError::Internal(
+        "Stack usecase not available".to_string(),
+    ))?;
+    let domain_uc = state.domain_usecase.as_ref().ok_or(AppError::Internal(
+        "Domain usecase not available".to_string(),
+    ))?;
+
+    stack_uc
+        .authorize(&stack_id, &current_user.id, permission::DOMAIN_MANAGE)
+        .await?;
+    let route = domain_uc
+        .add_l4_route(&stack_id, request, &current_user.id)
         .await?;
+    Ok(Json(route))
+}
 
-    Ok(Json(
-        serde_json::json!({ "status": "updated", "show_branding": request.show_branding }),
-    ))
+async fn remove_l4_route(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((stack_id, route_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>> {
+    let stack_uc = state.stack_usecase.as_ref().ok_or(AppError::Internal(
+        "Stack usecase not available".to_string(),
+    ))?;
+    let domain_uc = state.domain_usecase.as_ref().ok_or(AppError::Internal(
+        "Domain usecase not available".to_string(),
+    ))?;
+
+    stack_uc
+        .authorize(&stack_id, &current_user.id, permission::DOMAIN_MANAGE)
+        .await?;
+    domain_uc
+        .remove_l4_route(&stack_id, &route_id, &current_user.id)
+        .await?;
+    Ok(Json(serde_json::json!({ "status": "removed" })))
 }
 
 pub fn domain_routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/domains", get(list_all_domains))
+        .route("/domains/certificates", get(list_certificates))
         .route("/{stack_id}/domains", get(list_domains))
         .route("/{stack_id}/domains", post(add_domain))
         .route("/{stack_id}/domains/{domain}", delete(remove_domain))
+        .route(
+            "/{stack_id}/domains/l4",
+            get(list_l4_routes).post(add_l4_route),
+        )
+        .route("/{stack_id}/domains/l4/{route_id}", delete(remove_l4_route))
         .route("/{stack_id}/domains/{domain}/verify", post(verify_domain))
         .route(
             "/{stack_id}/domains/{domain}/dns",
@@ -216,6 +464,30 @@ pub fn domain_routes(state: Arc<AppState>) -> Router {
             "/{stack_id}/domains/{domain}/branding",
             axum::routing::put(toggle_branding),
         )
+        .route(
+            "/{stack_id}/domains/{domain}/upstreams",
+            axum::routing::put(update_upstreams),
+        )
+        .route(
+            "/{stack_id}/domains/{domain}/middleware",
+            axum::routing::put(update_middleware),
+        )
+        .route(
+            "/{stack_id}/domains/{domain}/tls",
+            axum::routing::put(update_tls),
+        )
+        .route(
+            "/{stack_id}/domains/{domain}/certificate",
+            get(certificate_status),
+        )
+        .route(
+            "/{stack_id}/domains/{domain}/routes",
+            get(list_routes).post(add_route),
+        )
+        .route(
+            "/{stack_id}/domains/{domain}/routes/{route_id}",
+            delete(remove_route),
+        )
         .route("/domains/sync", post(sync_domains))
         .with_state(state)
 }
@@ -0,0 +1,151 @@
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, Query, State},
+    routing::{delete, get, post, put},
+};
+use std::sync::Arc;
+
+use crate::api::middleware::auth::CurrentUser;
+use crate::domain::models::permission;
+use crate::domain::models::{
+    AttachEnvGroupsRequest, BulkRedeployResult, CreateEnvGroupRequest, EnvGroup,
+    EnvGroupVarResponse, SetEnvGroupVarRequest,
+};
+use crate::error::Result;
+use crate::usecase::env_group::EnvGroupUsecase;
+use crate::usecase::stack::StackUsecase;
+
+type EnvGroupState = (Arc<EnvGroupUsecase>, Arc<StackUsecase>);
+
+#[derive(serde::Deserialize)]
+struct TeamQuery {
+    team_id: String,
+}
+
+async fn list_groups(
+    State((usecase, _)): State<EnvGroupState>,
+    Extension(current_user): Extension<CurrentUser>,
+    Query(query): Query<TeamQuery>,
+) -> Result<Json<Vec<EnvGroup>>> {
+    let groups = usecase
+        .list_groups(&query.team_id, &current_user.id)
+        .await?;
+    Ok(Json(groups))
+}
+
+async fn create_group(
+    State((usecase, _)): State<EnvGroupState>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(request): Json<CreateEnvGroupRequest>,
+) -> Result<Json<EnvGroup>> {
+    let group = usecase.create_group(&current_user.id, request).await?;
+    Ok(Json(group))
+}
+
+async fn delete_group(
+    State((usecase, _)): State<EnvGroupState>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>> {
+    usecase.delete_group(&id, &current_user.id).await?;
+    Ok(Json(serde_json::json!({ "status": "deleted" })))
+}
+
+async fn list_group_vars(
+    State((usecase, _)): State<EnvGroupState>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<EnvGroupVarResponse>>> {
+    let vars = usecase.list_vars(&id, &current_user.id).await?;
+    Ok(Json(vars))
+}
+
+async fn set_group_var(
+    State((usecase, _)): State<EnvGroupState>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(id): Path<String>,
+    Json(request): Json<SetEnvGroupVarRequest>,
+) -> Result<Json<EnvGroupVarResponse>> {
+    let var = usecase
+        .set_var(
+            &id,
+            &current_user.id,
+            &request.key,
+            &request.value,
+            request.is_secret,
+        )
+        .await?;
+    Ok(Json(var))
+}
+
+async fn delete_group_var(
+    State((usecase, _)): State<EnvGroupState>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((id, key)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>> {
+    usecase.delete_var(&id, &current_user.id, &key).await?;
+    Ok(Json(serde_json::json!({ "status": "deleted" })))
+}
+
+/// Redeploy every stack that uses the group so they pick up its current values
+async fn redeploy_group_stacks(
+    State((usecase, stack_usecase)): State<EnvGroupState>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(id): Path<String>,
+) -> Result<Json<BulkRedeployResult>> {
+    let stack_ids = usecase.affected_stacks(&id, &current_user.id).await?;
+    let result = stack_usecase
+        .redeploy_stacks(stack_ids, &current_user.id, "env_group")
+        .await;
+    Ok(Json(result))
+}
+
+async fn list_stack_groups(
+    State((usecase, stack_usecase)): State<EnvGroupState>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(stack_id): Path<String>,
+) -> Result<Json<Vec<EnvGroup>>> {
+    stack_usecase
+        .authorize(&stack_id, &current_user.id, permission::ENV_READ)
+        .await?;
+
+    let groups = usecase.list_stack_groups(&stack_id).await?;
+    Ok(Json(groups))
+}
+
+async fn set_stack_groups(
+    State((usecase, stack_usecase)): State<EnvGroupState>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(stack_id): Path<String>,
+    Json(request): Json<AttachEnvGroupsRequest>,
+) -> Result<Json<Vec<EnvGroup>>> {
+    let stack = stack_usecase
+        .authorize(&stack_id, &current_user.id, permission::ENV_WRITE)
+        .await?;
+
+    let groups = usecase.set_stack_groups(&stack, request.group_ids).await?;
+    Ok(Json(groups))
+}
+
+pub fn env_group_routes(usecase: Arc<EnvGroupUsecase>, stack_usecase: Arc<StackUsecase>) -> Router {
+    Router::new()
+        .route("/", get(list_groups))
+        .route("/", post(create_group))
+        .route("/{id}", delete(delete_group))
+        .route("/{id}/vars", get(list_group_vars))
+        .route("/{id}/vars", post(set_group_var))
+        .route("/{id}/vars/{key}", delete(delete_group_var))
+        .route("/{id}/redeploy", post(redeploy_group_stacks))
+        .with_state((usecase, stack_usecase))
+}
+
+/// Attachment of groups to a stack, nested under `/stacks`
+pub fn stack_env_group_routes(
+    usecase: Arc<EnvGroupUsecase>,
+    stack_usecase: Arc<StackUsecase>,
+) -> Router {
+    Router::new()
+        .route("/{stack_id}/env-groups", get(list_stack_groups))
+        .route("/{stack_id}/env-groups", put(set_stack_groups))
+        .with_state((usecase, stack_usecase))
+}
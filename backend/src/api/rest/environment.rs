@@ -1,12 +1,18 @@
 use axum::{
     Json, Router,
     extract::{Extension, Path, State},
+    http::header,
+    response::IntoResponse,
     routing::{delete, get, post, put},
 };
 use std::sync::Arc;
 
 use crate::api::middleware::auth::CurrentUser;
-use crate::domain::models::{BulkSetEnvVarRequest, SetEnvVarRequest, StackEnvVarResponse};
+use crate::domain::models::permission;
+use crate::domain::models::{
+    BulkSetEnvVarRequest, DotenvImportRequest, EffectiveEnvVar, EnvDiff, SetEnvVarRequest,
+    StackEnvVarResponse,
+};
 use crate::error::Result;
 use crate::usecase::environment::EnvironmentUsecase;
 use crate::usecase::stack::StackUsecase;
@@ -16,8 +22,9 @@ async fn list_env_vars(
     Extension(current_user): Extension<CurrentUser>,
     Path(stack_id): Path<String>,
 ) -> Result<Json<Vec<StackEnvVarResponse>>> {
-    // Verify user owns the stack
-    stack_usecase.get_stack(&stack_id, &current_user.id).await?;
+    stack_usecase
+        .authorize(&stack_id, &current_user.id, permission::ENV_READ)
+        .await?;
 
     let vars = env_usecase.list_vars(&stack_id).await?;
     Ok(Json(vars))
@@ -29,8 +36,9 @@ async fn set_env_var(
     Path(stack_id): Path<String>,
     Json(request): Json<SetEnvVarRequest>,
 ) -> Result<Json<StackEnvVarResponse>> {
-    // Verify user owns the stack
-    stack_usecase.get_stack(&stack_id, &current_user.id).await?;
+    stack_usecase
+        .authorize(&stack_id, &current_user.id, permission::ENV_WRITE)
+        .await?;
 
     let var = env_usecase
         .set_var(
@@ -39,6 +47,7 @@ async fn set_env_var(
             &request.key,
             &request.value,
             request.is_secret,
+            &current_user.id,
         )
         .await?;
     Ok(Json(var))
@@ -50,8 +59,9 @@ async fn bulk_set_env_vars(
     Path(stack_id): Path<String>,
     Json(request): Json<BulkSetEnvVarRequest>,
 ) -> Result<Json<Vec<StackEnvVarResponse>>> {
-    // Verify user owns the stack
-    stack_usecase.get_stack(&stack_id, &current_user.id).await?;
+    stack_usecase
+        .authorize(&stack_id, &current_user.id, permission::ENV_WRITE)
+        .await?;
 
     let vars: Vec<(String, String, bool)> = request
         .vars
@@ -60,13 +70,13 @@ async fn bulk_set_env_vars(
         .collect();
 
     let results = env_usecase
-        .bulk_set(&stack_id, &request.container_name, vars)
+        .bulk_set(&stack_id, &request.container_name, vars, &current_user.id)
         .await?;
     Ok(Json(results))
 }
 
 #[derive(serde::Deserialize)]
-pub struct DeleteEnvVarQuery {
+pub struct EnvVarQuery {
     #[serde(default)]
     pub container_name: String,
 }
@@ -75,17 +85,120 @@ async fn delete_env_var(
     State((env_usecase, stack_usecase)): State<(Arc<EnvironmentUsecase>, Arc<StackUsecase>)>,
     Extension(current_user): Extension<CurrentUser>,
     Path((stack_id, key)): Path<(String, String)>,
-    axum::extract::Query(query): axum::extract::Query<DeleteEnvVarQuery>,
+    axum::extract::Query(query): axum::extract::Query<EnvVarQuery>,
 ) -> Result<Json<serde_json::Value>> {
-    // Verify user owns the stack
-    stack_usecase.get_stack(&stack_id, &current_user.id).await?;
+    stack_usecase
+        .authorize(&stack_id, &current_user.id, permission::ENV_WRITE)
+        .await?;
 
     env_usecase
-        .delete_var(&stack_id, &query.container_name, &key)
+        .delete_var(&stack_id, &query.container_name, &key, &current_user.id)
         .await?;
     Ok(Json(serde_json::json!({ "status": "deleted" })))
 }
 
+/// Variables a service receives after merging team groups, stack and container scopes
+async fn effective_env_vars(
+    State((env_usecase, stack_usecase)): State<(Arc<EnvironmentUsecase>, Arc<StackUsecase>)>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(stack_id): Path<String>,
+    axum::extract::Query(query): axum::extract::Query<EnvVarQuery>,
+) -> Result<Json<Vec<EffectiveEnvVar>>> {
+    stack_usecase
+        .authorize(&stack_id, &current_user.id, permission::ENV_READ)
+        .await?;
+
+    let vars = env_usecase
+        .effective_env(&stack_id, &query.container_name)
+        .await?;
+    Ok(Json(vars))
+}
+
+async fn reveal_env_var(
+    State((env_usecase, stack_usecase)): State<(Arc<EnvironmentUsecase>, Arc<StackUsecase>)>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((stack_id, key)): Path<(String, String)>,
+    axum::extract::Query(query): axum::extract::Query<EnvVarQuery>,
+) -> Result<Json<serde_json::Value>> {
+    stack_usecase
+        .authorize(&stack_id, &current_user.id, permission::ENV_SECRET_READ)
+        .await?;
+
+    let value = env_usecase
+        .reveal_var(&stack_id, &query.container_name, &key, &current_user.id)
+        .await?;
+    Ok(Json(serde_json::json!({ "key": key, "value": value })))
+}
+
+#[derive(serde::Deserialize)]
+pub struct DotenvExportQuery {
+    #[serde(default)]
+    pub container_name: String,
+    /// Include secret values (requires `env.secret.read`, audited)
+    #[serde(default)]
+    pub reveal: bool,
+}
+
+async fn export_dotenv(
+    State((env_usecase, stack_usecase)): State<(Arc<EnvironmentUsecase>, Arc<StackUsecase>)>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(stack_id): Path<String>,
+    axum::extract::Query(query): axum::extract::Query<DotenvExportQuery>,
+) -> Result<impl IntoResponse> {
+    let revealed_by = if query.reveal {
+        stack_usecase
+            .authorize(&stack_id, &current_user.id, permission::ENV_SECRET_READ)
+            .await?;
+        Some(current_user.id.as_str())
+    } else {
+        stack_usecase
+            .authorize(&stack_id, &current_user.id, permission::ENV_READ)
+            .await?;
+        None
+    };
+
+    let content = env_usecase
+        .export_dotenv(&stack_id, &query.container_name, revealed_by)
+        .await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/plain; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\".env\""),
+        ],
+        content,
+    ))
+}
+
+async fn preview_dotenv_import(
+    State((env_usecase, stack_usecase)): State<(Arc<EnvironmentUsecase>, Arc<StackUsecase>)>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(stack_id): Path<String>,
+    Json(request): Json<DotenvImportRequest>,
+) -> Result<Json<EnvDiff>> {
+    stack_usecase
+        .authorize(&stack_id, &current_user.id, permission::ENV_WRITE)
+        .await?;
+
+    let diff = env_usecase.preview_dotenv(&stack_id, &request).await?;
+    Ok(Json(diff))
+}
+
+async fn import_dotenv(
+    State((env_usecase, stack_usecase)): State<(Arc<EnvironmentUsecase>, Arc<StackUsecase>)>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(stack_id): Path<String>,
+    Json(request): Json<DotenvImportRequest>,
+) -> Result<Json<EnvDiff>> {
+    stack_usecase
+        .authorize(&stack_id, &current_user.id, permission::ENV_WRITE)
+        .await?;
+
+    let diff = env_usecase
+        .import_dotenv(&stack_id, &request, &current_user.id)
+        .await?;
+    Ok(Json(diff))
+}
+
 pub fn environment_routes(
     env_usecase: Arc<EnvironmentUsecase>,
     stack_usecase: Arc<StackUsecase>,
@@ -94,6 +207,14 @@ pub fn environment_routes(
         .route("/{stack_id}/env", get(list_env_vars))
         .route("/{stack_id}/env", post(set_env_var))
         .route("/{stack_id}/env/bulk", put(bulk_set_env_vars))
+        .route("/{stack_id}/env/effective", get(effective_env_vars))
+        .route("/{stack_id}/env/dotenv", get(export_dotenv))
+        .route("/{stack_id}/env/dotenv", post(import_dotenv))
+        .route(
+            "/{stack_id}/env/dotenv/preview",
+            post(preview_dotenv_import),
+        )
         .route("/{stack_id}/env/{key}", delete(delete_env_var))
+        .route("/{stack_id}/env/{key}/reveal", post(reveal_env_var))
         .with_state((env_usecase, stack_usecase))
 }
@@ -1,42 +1,138 @@
+use crate::api::middleware::auth::CurrentUser;
 use crate::domain::metrics_repository::{HistoricalContainerMetrics, HistoricalNodeMetrics};
 use crate::error::Result;
-use crate::usecase::metrics::MetricsUsecase;
+use crate::usecase::access_log::AccessLogEntry;
+use crate::usecase::metrics::{DomainTraffic, DomainTrafficStats, MetricsUsecase};
+use crate::usecase::stack::StackUsecase;
 use axum::{
     Json, Router,
-    extract::{Path, Query, State},
+    extract::{Extension, Path, Query, State},
 };
 use std::sync::Arc;
 
+#[derive(Clone)]
+pub struct MetricsState {
+    pub metrics_usecase: Arc<MetricsUsecase>,
+    pub stack_usecase: Arc<StackUsecase>,
+}
+
 #[derive(serde::Deserialize)]
 pub struct MetricsQuery {
     pub last_hours: Option<i32>,
 }
 
 async fn get_node_metrics(
-    State(usecase): State<Arc<MetricsUsecase>>,
+    State(state): State<MetricsState>,
     Query(query): Query<MetricsQuery>,
 ) -> Result<Json<Vec<HistoricalNodeMetrics>>> {
-    let metrics = usecase.get_node_metrics(query.last_hours).await?;
+    let metrics = state
+        .metrics_usecase
+        .get_node_metrics(query.last_hours)
+        .await?;
     Ok(Json(metrics))
 }
 
 async fn get_container_metrics(
-    State(usecase): State<Arc<MetricsUsecase>>,
-    Path((_stack_id, container_id)): Path<(String, String)>,
+    State(state): State<MetricsState>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((stack_id, container_id)): Path<(String, String)>,
     Query(query): Query<MetricsQuery>,
 ) -> Result<Json<Vec<HistoricalContainerMetrics>>> {
-    let metrics = usecase
+    state
+        .stack_usecase
+        .get_stack_no_health(&stack_id, &current_user.id)
+        .await?;
+    // History outlives the container, so match samples against the stack instead
+    // of inspecting the container
+    let mut metrics = state
+        .metrics_usecase
         .get_container_metrics(&container_id, query.last_hours)
         .await?;
+    metrics.retain(|m| m.stack_id == stack_id);
     Ok(Json(metrics))
 }
 
-pub fn metrics_routes(usecase: Arc<MetricsUsecase>) -> Router {
+#[derive(serde::Deserialize)]
+pub struct RecentRequestsQuery {
+    pub limit: Option<usize>,
+}
+
+async fn get_domain_traffic(
+    State(state): State<MetricsState>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(stack_id): Path<String>,
+    Query(query): Query<MetricsQuery>,
+) -> Result<Json<Vec<DomainTrafficStats>>> {
+    state
+        .stack_usecase
+        .get_stack_no_health(&stack_id, &current_user.id)
+        .await?;
+    let traffic = state
+        .metrics_usecase
+        .get_domain_traffic(&stack_id, query.last_hours)
+        .await?;
+    Ok(Json(traffic))
+}
+
+async fn get_domain_traffic_series(
+    State(state): State<MetricsState>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((stack_id, domain)): Path<(String, String)>,
+    Query(query): Query<MetricsQuery>,
+) -> Result<Json<DomainTraffic>> {
+    state
+        .stack_usecase
+        .get_stack_no_health(&stack_id, &current_user.id)
+        .await?;
+    let traffic = state
+        .metrics_usecase
+        .get_domain_traffic_series(&stack_id, &domain, query.last_hours)
+        .await?;
+    Ok(Json(traffic))
+}
+
+async fn get_recent_requests(
+    State(state): State<MetricsState>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((stack_id, domain)): Path<(String, String)>,
+    Query(query): Query<RecentRequestsQuery>,
+) -> Result<Json<Vec<AccessLogEntry>>> {
+    state
+        .stack_usecase
+        .get_stack_no_health(&stack_id, &current_user.id)
+        .await?;
+    Ok(Json(state.metrics_usecase.get_recent_requests(
+        &stack_id,
+        &domain,
+        query.limit,
+    )))
+}
+
+pub fn metrics_routes(
+    metrics_usecase: Arc<MetricsUsecase>,
+    stack_usecase: Arc<StackUsecase>,
+) -> Router {
+    let state = MetricsState {
+        metrics_usecase,
+        stack_usecase,
+    };
     Router::new()
         .route("/nodes/metrics", axum::routing::get(get_node_metrics))
         .route(
             "/stacks/{stack_id}/containers/{container_id}/metrics",
             axum::routing::get(get_container_metrics),
         )
-        .with_state(usecase)
+        .route(
+            "/stacks/{stack_id}/domains/traffic",
+            axum::routing::get(get_domain_traffic),
+        )
+        .route(
+            "/stacks/{stack_id}/domains/{domain}/traffic",
+            axum::routing::get(get_domain_traffic_series),
+        )
+        .route(
+            "/stacks/{stack_id}/domains/{domain}/requests",
+            axum::routing::get(get_recent_requests),
+        )
+        .with_state(state)
 }
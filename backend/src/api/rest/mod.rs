@@ -1,36 +1,50 @@
+pub mod api_tokens;
+pub mod audit;
 pub mod auth;
 pub mod containers;
 pub mod deployment_logs;
 pub mod dns;
 pub mod domains;
+pub mod env_groups;
 pub mod environment;
 pub mod health;
 pub mod images;
 pub mod metrics;
 pub mod network;
 pub mod nodes;
+pub mod oidc;
 pub mod registries;
 pub mod resources;
+pub mod roles;
 pub mod stacks;
 pub mod system;
 pub mod team;
 pub mod templates;
+pub mod two_factor;
+pub mod users;
 pub mod webhooks;
 
+pub use api_tokens::api_token_routes;
+pub use audit::audit_routes;
 pub use auth::auth_routes;
 pub use containers::container_routes;
 pub use deployment_logs::deployment_log_routes;
 pub use dns::dns_routes;
 pub use domains::domain_routes;
+pub use env_groups::{env_group_routes, stack_env_group_routes};
 pub use environment::environment_routes;
 pub use health::health_routes;
 pub use images::image_routes;
 pub use metrics::metrics_routes;
 pub use network::network_routes;
 pub use nodes::node_routes;
+pub use oidc::oidc_routes;
 pub use registries::registry_routes;
 pub use resources::resource_routes;
+pub use roles::role_routes;
 pub use stacks::stack_routes;
 pub use system::system_routes;
 pub use team::team_routes;
 pub use templates::template_routes;
+pub use two_factor::two_factor_routes;
+pub use users::{public_user_routes, user_routes};
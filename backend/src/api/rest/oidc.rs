@@ -0,0 +1,76 @@
+use axum::{
+    Router,
+    extract::{Query, State},
+    http::{HeaderMap, header},
+    response::Redirect,
+    routing::get,
+};
+use std::sync::Arc;
+
+use crate::domain::models::{LoginResponse, OidcCallbackQuery};
+use crate::error::Result;
+use crate::usecase::oidc::OidcUsecase;
+
+/// Frontend page that stores the tokens handed over in the URL fragment
+const FRONTEND_CALLBACK: &str = "/auth/callback";
+
+fn encode_pairs(pairs: &[(&str, &str)]) -> String {
+    let mut url = reqwest::Url::parse("http://localhost/").expect("static URL is valid");
+    url.query_pairs_mut().extend_pairs(pairs);
+    url.query().unwrap_or_default().to_string()
+}
+
+fn login_error(message: &str) -> Redirect {
+    Redirect::to(&format!("/login?{}", encode_pairs(&[("error", message)])))
+}
+
+async fn login(State(usecase): State<Arc<OidcUsecase>>) -> Result<Redirect> {
+    let url = usecase.begin().await?;
+    Ok(Redirect::to(&url))
+}
+
+async fn callback(
+    State(usecase): State<Arc<OidcUsecase>>,
+    headers: HeaderMap,
+    Query(query): Query<OidcCallbackQuery>,
+) -> Redirect {
+    if let Some(error) = query.error {
+        return login_error(query.error_description.as_deref().unwrap_or(&error));
+    }
+    let (Some(code), Some(state)) = (query.code, query.state) else {
+        return login_error("Missing authorization code");
+    };
+
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.chars().take(255).collect());
+
+    match usecase.complete(&code, &state, user_agent).await {
+        // Tokens go in the fragment so they never reach server logs
+        Ok(LoginResponse::Authenticated(auth)) => Redirect::to(&format!(
+            "{}#{}",
+            FRONTEND_CALLBACK,
+            encode_pairs(&[
+                ("token", &auth.token),
+                ("refresh_token", &auth.refresh_token),
+            ])
+        )),
+        // The login page asks for the second factor
+        Ok(LoginResponse::MfaRequired { mfa_token, .. }) => Redirect::to(&format!(
+            "/login#{}",
+            encode_pairs(&[("mfa_token", &mfa_token)])
+        )),
+        Err(e) => {
+            tracing::warn!("OIDC login failed: {}", e);
+            login_error(&e.to_string())
+        }
+    }
+}
+
+pub fn oidc_routes(usecase: Arc<OidcUsecase>) -> Router {
+    Router::new()
+        .route("/login", get(login))
+        .route("/callback", get(callback))
+        .with_state(usecase)
+}
@@ -0,0 +1,152 @@
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, State},
+    routing::{get, put},
+};
+use std::sync::Arc;
+
+use crate::api::middleware::auth::CurrentUser;
+use crate::domain::models::{
+    AssignCustomRoleRequest, CustomRoleResponse, PermissionSet, SaveCustomRoleRequest,
+    SaveStackGrantRequest, StackGrantResponse,
+};
+use crate::error::Result;
+use crate::usecase::role::RoleUsecase;
+
+async fn team_permissions(
+    State(usecase): State<Arc<RoleUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(team_id): Path<String>,
+) -> Result<Json<PermissionSet>> {
+    let permissions = usecase.team_permissions(&team_id, &current_user.id).await?;
+    Ok(Json(permissions))
+}
+
+async fn list_roles(
+    State(usecase): State<Arc<RoleUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(team_id): Path<String>,
+) -> Result<Json<Vec<CustomRoleResponse>>> {
+    let roles = usecase.list_roles(&team_id, &current_user.id).await?;
+    Ok(Json(roles))
+}
+
+async fn create_role(
+    State(usecase): State<Arc<RoleUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(team_id): Path<String>,
+    Json(request): Json<SaveCustomRoleRequest>,
+) -> Result<Json<CustomRoleResponse>> {
+    let role = usecase
+        .create_role(&team_id, &current_user.id, request)
+        .await?;
+    Ok(Json(role))
+}
+
+async fn update_role(
+    State(usecase): State<Arc<RoleUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((team_id, role_id)): Path<(String, String)>,
+    Json(request): Json<SaveCustomRoleRequest>,
+) -> Result<Json<CustomRoleResponse>> {
+    let role = usecase
+        .update_role(&team_id, &role_id, &current_user.id, request)
+        .await?;
+    Ok(Json(role))
+}
+
+async fn delete_role(
+    State(usecase): State<Arc<RoleUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((team_id, role_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>> {
+    usecase
+        .delete_role(&team_id, &role_id, &current_user.id)
+        .await?;
+    Ok(Json(serde_json::json!({ "status": "deleted" })))
+}
+
+async fn assign_role(
+    State(usecase): State<Arc<RoleUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((team_id, user_id)): Path<(String, String)>,
+    Json(request): Json<AssignCustomRoleRequest>,
+) -> Result<Json<serde_json::Value>> {
+    usecase
+        .assign_role(
+            &team_id,
+            &user_id,
+            request.role_id.as_deref(),
+            &current_user.id,
+        )
+        .await?;
+    Ok(Json(serde_json::json!({ "status": "updated" })))
+}
+
+async fn stack_permissions(
+    State(usecase): State<Arc<RoleUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(stack_id): Path<String>,
+) -> Result<Json<PermissionSet>> {
+    let permissions = usecase
+        .stack_permissions(&stack_id, &current_user.id)
+        .await?;
+    Ok(Json(permissions))
+}
+
+async fn list_grants(
+    State(usecase): State<Arc<RoleUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(stack_id): Path<String>,
+) -> Result<Json<Vec<StackGrantResponse>>> {
+    let grants = usecase.list_grants(&stack_id, &current_user.id).await?;
+    Ok(Json(grants))
+}
+
+async fn save_grant(
+    State(usecase): State<Arc<RoleUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(stack_id): Path<String>,
+    Json(request): Json<SaveStackGrantRequest>,
+) -> Result<Json<Vec<StackGrantResponse>>> {
+    usecase
+        .save_grant(&stack_id, &current_user.id, request)
+        .await?;
+    let grants = usecase.list_grants(&stack_id, &current_user.id).await?;
+    Ok(Json(grants))
+}
+
+async fn revoke_grant(
+    State(usecase): State<Arc<RoleUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((stack_id, user_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>> {
+    usecase
+        .revoke_grant(&stack_id, &user_id, &current_user.id)
+        .await?;
+    Ok(Json(serde_json::json!({ "status": "revoked" })))
+}
+
+pub fn role_routes(usecase: Arc<RoleUsecase>) -> Router {
+    Router::new()
+        .route("/teams/{team_id}/permissions", get(team_permissions))
+        .route("/teams/{team_id}/roles", get(list_roles).post(create_role))
+        .route(
+            "/teams/{team_id}/roles/{role_id}",
+            put(update_role).delete(delete_role),
+        )
+        .route(
+            "/teams/{team_id}/members/{user_id}/custom-role",
+            put(assign_role),
+        )
+        .route("/stacks/{stack_id}/permissions", get(stack_permissions))
+        .route(
+            "/stacks/{stack_id}/grants",
+            get(list_grants).post(save_grant),
+        )
+        .route(
+            "/stacks/{stack_id}/grants/{user_id}",
+            axum::routing::delete(revoke_grant),
+        )
+        .with_state(usecase)
+}
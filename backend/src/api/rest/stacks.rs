@@ -6,8 +6,10 @@ use axum::{
 use std::sync::Arc;
 
 use crate::api::middleware::auth::CurrentUser;
+use crate::domain::models::permission;
 use crate::domain::models::{
-    CreateStack, Stack, StackBackup, StackHealth, StackLogEntry, StackResponse,
+    CreateStack, PushedImage, RestoreSecret, Stack, StackBackup, StackHealth, StackLogEntry,
+    StackResponse,
 };
 use crate::error::Result;
 use crate::usecase::stack::StackUsecase;
@@ -168,8 +170,12 @@ async fn redeploy_stack(
     Extension(current_user): Extension<CurrentUser>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>> {
-    let _stack = usecase.get_stack(&id, &current_user.id).await?;
-    let _: () = usecase.redeploy_stack(&id).await?;
+    usecase
+        .authorize(&id, &current_user.id, permission::STACK_DEPLOY)
+        .await?;
+    let _: () = usecase
+        .redeploy_stack(&id, Some(&current_user.id), "manual")
+        .await?;
     Ok(Json(serde_json::json!({ "status": "redeployed" })))
 }
 
@@ -207,6 +213,45 @@ async fn update_stack_automation(
     Ok(Json(serde_json::json!({ "status": "updated" })))
 }
 
+#[derive(serde::Deserialize)]
+struct UpdateRegistryPush {
+    registry_id: Option<String>,
+    namespace: Option<String>,
+    #[serde(default = "default_push_retention")]
+    retention: i32,
+}
+
+fn default_push_retention() -> i32 {
+    5
+}
+
+async fn update_registry_push(
+    State(usecase): State<Arc<StackUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateRegistryPush>,
+) -> Result<Json<serde_json::Value>> {
+    usecase
+        .update_registry_push(
+            &id,
+            &current_user.id,
+            request.registry_id,
+            request.namespace,
+            request.retention,
+        )
+        .await?;
+    Ok(Json(serde_json::json!({ "status": "updated" })))
+}
+
+async fn list_pushed_images(
+    State(usecase): State<Arc<StackUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<PushedImage>>> {
+    let images = usecase.list_pushed_images(&id, &current_user.id).await?;
+    Ok(Json(images))
+}
+
 async fn redeploy_service(
     State(usecase): State<Arc<StackUsecase>>,
     Extension(current_user): Extension<CurrentUser>,
@@ -251,7 +296,26 @@ async fn get_stack_backup(
     Extension(current_user): Extension<CurrentUser>,
     Path(id): Path<String>,
 ) -> Result<Json<StackBackup>> {
-    let backup = usecase.get_stack_backup(&id, &current_user.id).await?;
+    let backup = usecase
+        .get_stack_backup(&id, &current_user.id, None)
+        .await?;
+    Ok(Json(backup))
+}
+
+#[derive(serde::Deserialize)]
+struct EncryptedBackupRequest {
+    passphrase: String,
+}
+
+async fn get_encrypted_stack_backup(
+    State(usecase): State<Arc<StackUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(id): Path<String>,
+    Json(request): Json<EncryptedBackupRequest>,
+) -> Result<Json<StackBackup>> {
+    let backup = usecase
+        .get_stack_backup(&id, &current_user.id, Some(&request.passphrase))
+        .await?;
     Ok(Json(backup))
 }
 
@@ -259,6 +323,9 @@ async fn get_stack_backup(
 struct RestoreStackRequest {
     team_id: String,
     backup: StackBackup,
+    passphrase: Option<String>,
+    #[serde(default)]
+    secrets: Vec<RestoreSecret>,
 }
 
 async fn restore_stack(
@@ -267,7 +334,13 @@ async fn restore_stack(
     Json(request): Json<RestoreStackRequest>,
 ) -> Result<Json<StackResponse>> {
     let stack = usecase
-        .restore_stack(request.backup, &current_user.id, &request.team_id)
+        .restore_stack(
+            request.backup,
+            &current_user.id,
+            &request.team_id,
+            request.passphrase.as_deref(),
+            request.secrets,
+        )
         .await?;
     Ok(Json(stack.into()))
 }
@@ -340,6 +413,64 @@ async fn build_logs_stream(
     Ok(axum::response::Sse::new(filtered_stream))
 }
 
+#[derive(serde::Deserialize)]
+struct UpdateEnvPolicyRequest {
+    policy: String,
+}
+
+async fn update_env_policy(
+    State(usecase): State<Arc<StackUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateEnvPolicyRequest>,
+) -> Result<Json<serde_json::Value>> {
+    usecase
+        .update_env_conflict_policy(&id, &current_user.id, &request.policy)
+        .await?;
+    Ok(Json(serde_json::json!({ "status": "updated" })))
+}
+
+#[derive(serde::Deserialize)]
+struct MaintenanceRequest {
+    enabled: bool,
+}
+
+async fn set_maintenance(
+    State(usecase): State<Arc<StackUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(id): Path<String>,
+    Json(request): Json<MaintenanceRequest>,
+) -> Result<Json<serde_json::Value>> {
+    usecase
+        .set_maintenance_mode(&id, &current_user.id, request.enabled)
+        .await?;
+    Ok(Json(serde_json::json!({ "status": "updated" })))
+}
+
+#[derive(serde::Deserialize)]
+struct UpdatePagesRequest {
+    maintenance_page: Option<String>,
+    #[serde(default)]
+    error_pages: std::collections::BTreeMap<u16, String>,
+}
+
+async fn update_pages(
+    State(usecase): State<Arc<StackUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdatePagesRequest>,
+) -> Result<Json<serde_json::Value>> {
+    usecase
+        .update_pages(
+            &id,
+            &current_user.id,
+            request.maintenance_page,
+            request.error_pages,
+        )
+        .await?;
+    Ok(Json(serde_json::json!({ "status": "updated" })))
+}
+
 pub fn stack_routes(usecase: Arc<StackUsecase>) -> Router {
     Router::new()
         .route("/", get(list_stacks))
@@ -355,8 +486,12 @@ pub fn stack_routes(usecase: Arc<StackUsecase>) -> Router {
         .route("/{id}/start", post(start_stack))
         .route("/{id}/stop", post(stop_stack))
         .route("/{id}/redeploy", post(redeploy_stack))
+        .route("/{id}/env-policy", axum::routing::put(update_env_policy))
+        .route("/{id}/maintenance", axum::routing::put(set_maintenance))
+        .route("/{id}/pages", axum::routing::put(update_pages))
         .route("/{id}/build", post(build_stack))
         .route("/{id}/backup", get(get_stack_backup))
+        .route("/{id}/backup", post(get_encrypted_stack_backup))
         .route("/{id}/git/sync", post(sync_git))
         .route(
             "/{id}/services/{service_name}/redeploy",
@@ -370,6 +505,11 @@ pub fn stack_routes(usecase: Arc<StackUsecase>) -> Router {
             "/{id}/automation",
             axum::routing::put(update_stack_automation),
         )
+        .route(
+            "/{id}/registry-push",
+            axum::routing::put(update_registry_push),
+        )
+        .route("/{id}/pushed-images", get(list_pushed_images))
         .route("/{id}/rollback", post(rollback_stack))
         .with_state(usecase)
 }
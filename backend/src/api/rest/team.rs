@@ -16,7 +16,7 @@ pub struct AddMemberRequest {
     pub name: String,
     pub email: String,
     pub password: Option<String>,
-    pub role: String,
+    pub role: TeamRole,
 }
 
 pub fn team_routes(usecase: Arc<TeamUsecase>) -> Router {
@@ -43,9 +43,17 @@ async fn create_team(
     Json(payload): Json<CreateTeamRequest>,
 ) -> Result<Json<TeamResponse>> {
     let team = usecase.create_team(&payload.name, &user.id).await?;
+    let mut permissions: Vec<String> = TeamRole::Owner
+        .permissions()
+        .into_iter()
+        .map(String::from)
+        .collect();
+    permissions.sort();
     Ok(Json(TeamResponse {
         team,
         role: TeamRole::Owner,
+        custom_role: None,
+        permissions,
     }))
 }
 
@@ -81,17 +89,14 @@ async fn add_member(
                 &payload.name,
                 &payload.email,
                 &password,
-                TeamRole::from(payload.role),
+                payload.role,
                 &user.id,
             )
             .await?;
     } else {
-        // Try to find existing user by email
-        // We'll actually need a way to look up user_id by email if we want to support existing users without passwords here.
-        // For now, let's assume we always provide name/email/password for new flow.
-        return Err(crate::error::AppError::BadRequest(
-            "Password is required for new members".to_string(),
-        ));
+        usecase
+            .add_existing_member(&id, &payload.email, payload.role, &user.id)
+            .await?;
     }
     Ok(Json(()))
 }
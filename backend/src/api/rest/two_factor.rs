@@ -0,0 +1,84 @@
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, State},
+    routing::{get, post, put},
+};
+use std::sync::Arc;
+
+use crate::api::middleware::auth::CurrentUser;
+use crate::domain::models::{
+    RecoveryCodes, TeamTwoFactorPolicyRequest, TotpEnrollment, TwoFactorCodeRequest,
+    TwoFactorStatus,
+};
+use crate::error::Result;
+use crate::usecase::two_factor::TwoFactorUsecase;
+
+async fn status(
+    State(usecase): State<Arc<TwoFactorUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<TwoFactorStatus>> {
+    let status = usecase.status(&current_user.id).await?;
+    Ok(Json(status))
+}
+
+async fn enroll(
+    State(usecase): State<Arc<TwoFactorUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<TotpEnrollment>> {
+    let enrollment = usecase
+        .enroll(&current_user.id, &current_user.email)
+        .await?;
+    Ok(Json(enrollment))
+}
+
+async fn confirm(
+    State(usecase): State<Arc<TwoFactorUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(request): Json<TwoFactorCodeRequest>,
+) -> Result<Json<RecoveryCodes>> {
+    let codes = usecase.confirm(&current_user.id, &request.code).await?;
+    Ok(Json(codes))
+}
+
+async fn disable(
+    State(usecase): State<Arc<TwoFactorUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(request): Json<TwoFactorCodeRequest>,
+) -> Result<Json<serde_json::Value>> {
+    usecase.disable(&current_user.id, &request.code).await?;
+    Ok(Json(serde_json::json!({ "status": "disabled" })))
+}
+
+async fn regenerate_recovery_codes(
+    State(usecase): State<Arc<TwoFactorUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(request): Json<TwoFactorCodeRequest>,
+) -> Result<Json<RecoveryCodes>> {
+    let codes = usecase
+        .regenerate_recovery_codes(&current_user.id, &request.code)
+        .await?;
+    Ok(Json(codes))
+}
+
+async fn set_team_policy(
+    State(usecase): State<Arc<TwoFactorUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(team_id): Path<String>,
+    Json(request): Json<TeamTwoFactorPolicyRequest>,
+) -> Result<Json<serde_json::Value>> {
+    usecase
+        .set_team_policy(&team_id, &current_user.id, request.require_2fa)
+        .await?;
+    Ok(Json(serde_json::json!({ "status": "updated" })))
+}
+
+pub fn two_factor_routes(usecase: Arc<TwoFactorUsecase>) -> Router {
+    Router::new()
+        .route("/auth/2fa", get(status))
+        .route("/auth/2fa/enroll", post(enroll))
+        .route("/auth/2fa/confirm", post(confirm))
+        .route("/auth/2fa/disable", post(disable))
+        .route("/auth/2fa/recovery-codes", post(regenerate_recovery_codes))
+        .route("/teams/{team_id}/2fa-policy", put(set_team_policy))
+        .with_state(usecase)
+}
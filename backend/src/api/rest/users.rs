@@ -0,0 +1,163 @@
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, State},
+    http::{HeaderMap, header},
+    routing::{delete, get, post, put},
+};
+use std::sync::Arc;
+
+use crate::api::middleware::auth::CurrentUser;
+use crate::domain::models::{
+    AcceptInviteRequest, AuthResponse, ChangePasswordRequest, CreateInviteRequest, CreatedInvite,
+    InviteDetails, InviteTokenRequest, PasswordResetLink, ResetPasswordRequest,
+    UpdateProfileRequest, UserInviteResponse, UserResponse,
+};
+use crate::error::Result;
+use crate::usecase::user::UserUsecase;
+
+fn user_agent(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.chars().take(255).collect())
+}
+
+async fn update_profile(
+    State(usecase): State<Arc<UserUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(request): Json<UpdateProfileRequest>,
+) -> Result<Json<UserResponse>> {
+    let user = usecase.update_profile(&current_user.id, request).await?;
+    Ok(Json(user))
+}
+
+async fn change_password(
+    State(usecase): State<Arc<UserUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    headers: HeaderMap,
+    Json(request): Json<ChangePasswordRequest>,
+) -> Result<Json<AuthResponse>> {
+    let response = usecase
+        .change_password(&current_user.id, request, user_agent(&headers))
+        .await?;
+    Ok(Json(response))
+}
+
+async fn list_users(
+    State(usecase): State<Arc<UserUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<Vec<UserResponse>>> {
+    let users = usecase.list_users(&current_user.id).await?;
+    Ok(Json(users))
+}
+
+async fn issue_password_reset(
+    State(usecase): State<Arc<UserUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(id): Path<String>,
+) -> Result<Json<PasswordResetLink>> {
+    let link = usecase.issue_password_reset(&current_user.id, &id).await?;
+    Ok(Json(link))
+}
+
+async fn deactivate_user(
+    State(usecase): State<Arc<UserUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>> {
+    usecase.set_active(&current_user.id, &id, false).await?;
+    Ok(Json(serde_json::json!({ "status": "deactivated" })))
+}
+
+async fn activate_user(
+    State(usecase): State<Arc<UserUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>> {
+    usecase.set_active(&current_user.id, &id, true).await?;
+    Ok(Json(serde_json::json!({ "status": "active" })))
+}
+
+async fn list_invites(
+    State(usecase): State<Arc<UserUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(team_id): Path<String>,
+) -> Result<Json<Vec<UserInviteResponse>>> {
+    let invites = usecase.list_invites(&team_id, &current_user.id).await?;
+    Ok(Json(invites))
+}
+
+async fn create_invite(
+    State(usecase): State<Arc<UserUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(team_id): Path<String>,
+    Json(request): Json<CreateInviteRequest>,
+) -> Result<Json<CreatedInvite>> {
+    let invite = usecase
+        .create_invite(&team_id, &current_user.id, request)
+        .await?;
+    Ok(Json(invite))
+}
+
+async fn revoke_invite(
+    State(usecase): State<Arc<UserUsecase>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((team_id, id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>> {
+    usecase
+        .revoke_invite(&team_id, &id, &current_user.id)
+        .await?;
+    Ok(Json(serde_json::json!({ "status": "revoked" })))
+}
+
+// Links carry their token in the request body rather than the URL so it stays out of logs
+
+async fn invite_details(
+    State(usecase): State<Arc<UserUsecase>>,
+    Json(request): Json<InviteTokenRequest>,
+) -> Result<Json<InviteDetails>> {
+    let details = usecase.invite_details(&request.token).await?;
+    Ok(Json(details))
+}
+
+async fn accept_invite(
+    State(usecase): State<Arc<UserUsecase>>,
+    headers: HeaderMap,
+    Json(request): Json<AcceptInviteRequest>,
+) -> Result<Json<AuthResponse>> {
+    let response = usecase.accept_invite(request, user_agent(&headers)).await?;
+    Ok(Json(response))
+}
+
+async fn reset_password(
+    State(usecase): State<Arc<UserUsecase>>,
+    Json(request): Json<ResetPasswordRequest>,
+) -> Result<Json<serde_json::Value>> {
+    usecase.reset_password(request).await?;
+    Ok(Json(serde_json::json!({ "status": "password_reset" })))
+}
+
+pub fn user_routes(usecase: Arc<UserUsecase>) -> Router {
+    Router::new()
+        .route("/me", put(update_profile))
+        .route("/me/password", post(change_password))
+        .route("/users", get(list_users))
+        .route("/users/{id}/password-reset", post(issue_password_reset))
+        .route("/users/{id}/deactivate", post(deactivate_user))
+        .route("/users/{id}/activate", post(activate_user))
+        .route(
+            "/teams/{team_id}/invites",
+            get(list_invites).post(create_invite),
+        )
+        .route("/teams/{team_id}/invites/{id}", delete(revoke_invite))
+        .with_state(usecase)
+}
+
+/// Unauthenticated endpoints used from invitation and reset links
+pub fn public_user_routes(usecase: Arc<UserUsecase>) -> Router {
+    Router::new()
+        .route("/invite", post(invite_details))
+        .route("/invite/accept", post(accept_invite))
+        .route("/password-reset", post(reset_password))
+        .with_state(usecase)
+}
@@ -46,7 +46,10 @@ pub async fn trigger_deploy(
             .redeploy_service(&stack.id, service_name, &stack.user_id)
             .await
     } else {
-        state.stack_usecase.redeploy_stack(&stack.id).await
+        state
+            .stack_usecase
+            .redeploy_stack(&stack.id, None, "webhook")
+            .await
     };
 
     match result {
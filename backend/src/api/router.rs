@@ -4,10 +4,11 @@ use tower_http::cors::{Any, CorsLayer};
 use tower_http::services::{ServeDir, ServeFile};
 use tower_http::trace::TraceLayer;
 
-use crate::api::middleware::auth_middleware;
+use crate::api::middleware::{RateLimitState, auth_middleware, rate_limit_middleware};
 use crate::api::rest::auth::protected_auth_routes;
 use crate::api::rest::*;
 use crate::app_state::AppState;
+use crate::infrastructure::rate_limit::RateLimiter;
 
 /// Create the main application router
 pub fn create_router(state: Arc<AppState>) -> Router {
@@ -44,11 +45,33 @@ fn create_cors_layer() -> CorsLayer {
         ])
 }
 
+/// Per-IP request limit for unauthenticated endpoints
+fn rate_limited(router: Router, state: &AppState, limiter: &Arc<RateLimiter>) -> Router {
+    router.layer(axum_middleware::from_fn_with_state(
+        RateLimitState {
+            limiter: limiter.clone(),
+            trust_proxy_headers: state._config.rate_limit.trust_proxy_headers,
+        },
+        rate_limit_middleware,
+    ))
+}
+
 fn create_public_routes(state: &AppState) -> Router {
+    let mut auth = auth_routes(state.auth_usecase.clone());
+    if let Some(user_uc) = &state.user_usecase {
+        auth = auth.merge(public_user_routes(user_uc.clone()));
+    }
+    if let Some(oidc_uc) = &state.oidc_usecase {
+        auth = auth.nest("/oidc", oidc_routes(oidc_uc.clone()));
+    }
+
     Router::new()
         .nest("/api", health_routes())
         .nest("/api/system", system_routes(state.system_usecase.clone()))
-        .nest("/api/auth", auth_routes(state.auth_usecase.clone()))
+        .nest(
+            "/api/auth",
+            rate_limited(auth, state, &state.auth_rate_limiter),
+        )
 }
 
 fn create_webhook_routes(state: &AppState) -> Option<Router> {
@@ -58,30 +81,37 @@ fn create_webhook_routes(state: &AppState) -> Option<Router> {
             deployment_log_usecase: log_uc.clone(),
         };
 
-        Some(
-            Router::new()
-                .route(
-                    "/deploy/{stack_id}/{token}",
-                    axum::routing::post(crate::api::rest::webhooks::trigger_deploy),
-                )
-                .with_state(webhook_state),
-        )
+        let router = Router::new()
+            .route(
+                "/deploy/{stack_id}/{token}",
+                axum::routing::post(crate::api::rest::webhooks::trigger_deploy),
+            )
+            .with_state(webhook_state);
+
+        Some(rate_limited(router, state, &state.webhook_rate_limiter))
     } else {
         None
     }
 }
 
 fn create_protected_routes(state: Arc<AppState>) -> Router {
-    let mut routes = Router::new().merge(protected_auth_routes());
+    let mut routes = Router::new()
+        .merge(protected_auth_routes(state.auth_usecase.clone()))
+        .merge(two_factor_routes(state.two_factor_usecase.clone()))
+        .merge(audit_routes(state.audit_usecase.clone()));
 
     // Add container-dependent routes if available
     if let (
         Some(stack_uc),
         Some(team_uc),
         Some(registry_uc),
+        Some(api_token_uc),
+        Some(user_uc),
         Some(env_uc),
+        Some(env_group_uc),
         Some(template_uc),
         Some(resource_uc),
+        Some(role_uc),
         Some(log_uc),
         Some(_domain_uc),
         Some(_dns_uc),
@@ -90,9 +120,13 @@ fn create_protected_routes(state: Arc<AppState>) -> Router {
         &state.stack_usecase,
         &state.team_usecase,
         &state.registry_usecase,
+        &state.api_token_usecase,
+        &state.user_usecase,
         &state.env_usecase,
+        &state.env_group_usecase,
         &state.template_usecase,
         &state.resource_usecase,
+        &state.role_usecase,
         &state.log_usecase,
         &state.domain_usecase,
         &state.dns_usecase,
@@ -100,6 +134,9 @@ fn create_protected_routes(state: Arc<AppState>) -> Router {
     ) {
         routes = routes
             .nest("/teams", team_routes(team_uc.clone()))
+            .merge(api_token_routes(api_token_uc.clone()))
+            .merge(user_routes(user_uc.clone()))
+            .merge(role_routes(role_uc.clone()))
             .nest("/registries", registry_routes(registry_uc.clone()))
             .nest("/containers", container_routes(stack_uc.clone()))
             .nest(
@@ -126,10 +163,21 @@ fn create_protected_routes(state: Arc<AppState>) -> Router {
                 "/stacks",
                 environment_routes(env_uc.clone(), stack_uc.clone()),
             )
+            .nest(
+                "/stacks",
+                stack_env_group_routes(env_group_uc.clone(), stack_uc.clone()),
+            )
+            .nest(
+                "/env-groups",
+                env_group_routes(env_group_uc.clone(), stack_uc.clone()),
+            )
             .nest("/templates", template_routes(template_uc.clone()))
             .nest("/nodes", node_routes(state.node_usecase.clone()))
             .nest("/networks", network_routes(state.clone()))
-            .nest("/metrics", metrics_routes(metrics_uc.clone()))
+            .nest(
+                "/metrics",
+                metrics_routes(metrics_uc.clone(), stack_uc.clone()),
+            )
             .nest(
                 "/nodes/terminal",
                 Router::new()
@@ -164,3 +212,215 @@ fn add_frontend_route(app: Router) -> Router {
         app
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, RateLimitConfig};
+    use crate::domain::models::{ADMIN_ROLE, USER_ROLE, User};
+    use crate::domain::runtime::tests::FakeRuntime;
+    use crate::infrastructure::crypto::envelope::EnvelopeCipher;
+    use crate::usecase::auth::tests::{create_user, memory_pool};
+    use reqwest::{Method, StatusCode};
+    use sqlx::SqlitePool;
+    use std::net::SocketAddr;
+
+    fn test_config() -> Config {
+        Config {
+            host: "127.0.0.1".to_string(),
+            port: 0,
+            database_url: "sqlite::memory:".to_string(),
+            jwt_secret: "test-secret".to_string(),
+            jwt_access_ttl_minutes: 15,
+            refresh_token_ttl_days: 30,
+            // Nothing listens on the discard port, so reconciles fail fast
+            caddy_admin_api: "http://127.0.0.1:9".to_string(),
+            caddy_image: "caddy:2-alpine".to_string(),
+            caddy_sync_interval_seconds: 3600,
+            certificate_warning_days: 14,
+            dashboard_domain: None,
+            dashboard_upstream: "127.0.0.1:0".to_string(),
+            master_key: Some(EnvelopeCipher::generate().to_base64()),
+            master_key_file: String::new(),
+            password_login_enabled: true,
+            oidc: None,
+            rate_limit: RateLimitConfig {
+                login_max_attempts: 5,
+                login_lockout_seconds: 30,
+                auth_per_minute: 0,
+                webhook_per_minute: 0,
+                persist: false,
+                trust_proxy_headers: false,
+            },
+            audit_retention_days: 0,
+        }
+    }
+
+    struct Server {
+        url: String,
+        state: Arc<AppState>,
+        client: reqwest::Client,
+    }
+
+    impl Server {
+        async fn start() -> Self {
+            let pool = memory_pool().await;
+            let state = Arc::new(
+                AppState::with_runtime(test_config(), pool, Arc::new(FakeRuntime::default()))
+                    .await
+                    .unwrap(),
+            );
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let app = create_router(state.clone());
+            tokio::spawn(async move {
+                axum::serve(
+                    listener,
+                    app.into_make_service_with_connect_info::<SocketAddr>(),
+                )
+                .await
+                .unwrap()
+            });
+            Self {
+                url,
+                state,
+                client: reqwest::Client::new(),
+            }
+        }
+
+        async fn token(&self, user: User) -> String {
+            self.state
+                .auth_usecase
+                .start_session(user, None)
+                .await
+                .unwrap()
+                .token
+        }
+
+        async fn status(&self, token: &str, method: Method, path: &str) -> StatusCode {
+            self.client
+                .request(method, format!("{}{}", self.url, path))
+                .bearer_auth(token)
+                .json(&serde_json::json!({ "replicas": 2 }))
+                .send()
+                .await
+                .unwrap()
+                .status()
+        }
+    }
+
+    /// Team "Web" with stack s1, where `viewer` holds the Viewer role
+    async fn seed_team(pool: &SqlitePool, viewer: &User) {
+        sqlx::query(
+            "INSERT INTO teams (id, name, created_at, updated_at) VALUES ('t1', 'Web', '', '')",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO team_members (team_id, user_id, role, created_at, updated_at) VALUES ('t1', ?, 'VIEWER', '', '')",
+        )
+        .bind(&viewer.id)
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO stacks (id, name, user_id, team_id, created_at, updated_at) VALUES ('s1', 'shop', ?, 't1', '', '')",
+        )
+        .bind(&viewer.id)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_host_routes_require_administrator() {
+        let server = Server::start().await;
+        let pool = &server.state._pool;
+        let admin = create_user(pool, "admin@example.com", Some("hunter22"), ADMIN_ROLE).await;
+        let viewer = create_user(pool, "viewer@example.com", Some("hunter22"), USER_ROLE).await;
+        let outsider = create_user(pool, "other@example.com", Some("hunter22"), USER_ROLE).await;
+        seed_team(pool, &viewer).await;
+
+        let routes = [
+            (Method::POST, "/api/images/pull"),
+            (Method::DELETE, "/api/images/sha256:abc"),
+            (Method::POST, "/api/nodes/swarm/init"),
+            (Method::GET, "/api/nodes"),
+            (Method::GET, "/api/nodes/terminal"),
+            (Method::GET, "/api/networks/topology"),
+            (Method::GET, "/api/metrics/nodes/metrics"),
+            (Method::POST, "/api/templates"),
+        ];
+        for user in [viewer, outsider] {
+            let token = server.token(user).await;
+            for (method, path) in &routes {
+                assert_eq!(
+                    server.status(&token, method.clone(), path).await,
+                    StatusCode::FORBIDDEN,
+                    "{} {}",
+                    method,
+                    path
+                );
+            }
+        }
+
+        let token = server.token(admin).await;
+        for (method, path) in &routes {
+            assert_ne!(
+                server.status(&token, method.clone(), path).await,
+                StatusCode::FORBIDDEN,
+                "{} {}",
+                method,
+                path
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stack_routes_check_team_role() {
+        let server = Server::start().await;
+        let pool = &server.state._pool;
+        let viewer = create_user(pool, "viewer@example.com", Some("hunter22"), USER_ROLE).await;
+        let outsider = create_user(pool, "other@example.com", Some("hunter22"), USER_ROLE).await;
+        seed_team(pool, &viewer).await;
+        let viewer = server.token(viewer).await;
+        let outsider = server.token(outsider).await;
+
+        let reads = [
+            (Method::GET, "/api/stacks/s1"),
+            (Method::GET, "/api/metrics/stacks/s1/containers/c1/metrics"),
+        ];
+        let writes = [
+            (Method::POST, "/api/stacks/s1/services/web/scale"),
+            (Method::POST, "/api/stacks/s1/services/web/redeploy"),
+        ];
+        for (method, path) in reads.iter().chain(&writes) {
+            assert_eq!(
+                server.status(&outsider, method.clone(), path).await,
+                StatusCode::FORBIDDEN,
+                "{} {}",
+                method,
+                path
+            );
+        }
+        for (method, path) in &writes {
+            assert_eq!(
+                server.status(&viewer, method.clone(), path).await,
+                StatusCode::FORBIDDEN,
+                "{} {}",
+                method,
+                path
+            );
+        }
+        for (method, path) in &reads {
+            assert_eq!(
+                server.status(&viewer, method.clone(), path).await,
+                StatusCode::OK,
+                "{} {}",
+                method,
+                path
+            );
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::api::middleware::auth::CurrentUser;
+use crate::domain::models::permission;
 use crate::error::Result;
 use crate::usecase::stack::StackUsecase;
 use axum::{
@@ -25,15 +26,27 @@ pub async fn container_exec_handler(
         .as_ref()
         .ok_or_else(|| AppError::Internal("Stack usecase missing".to_string()))?;
 
-    // Verify ownership
-    let _ = stack_usecase
-        .verify_container_ownership(&id, &user.id)
+    let container = stack_usecase
+        .verify_container_ownership(&id, &user.id, permission::STACK_EXEC)
         .await?;
 
+    if let Some(stack_id) = container.labels.get("labuh.stack.id") {
+        state
+            .audit_usecase
+            .record_stack(
+                Some(&user.id),
+                stack_id,
+                "terminal.open",
+                serde_json::json!({ "container_id": id, "container": container.names.first() }),
+            )
+            .await?;
+    }
+
     let stack_uc = stack_usecase.clone();
     Ok(ws.on_upgrade(move |socket| handle_container_socket(socket, stack_uc, id)))
 }
 
+#[allow(clippy::collapsible_match)]
 async fn handle_container_socket(
     mut socket: WebSocket,
     stack_usecase: Arc<StackUsecase>,
@@ -1,5 +1,6 @@
 use crate::api::middleware::auth::CurrentUser;
 use crate::app_state::AppState;
+use crate::error::Result;
 use axum::{
     extract::{
         Extension, Query, State,
@@ -23,8 +24,24 @@ pub async fn node_terminal_handler(
     Query(query): Query<TerminalQuery>,
     Extension(current_user): Extension<CurrentUser>,
     State(state): State<Arc<AppState>>,
-) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| node_terminal_session(socket, query.node_id, current_user, state))
+) -> Result<impl IntoResponse> {
+    // Host shells are not scoped to a team, so the entry has no team
+    let node = query.node_id.clone().unwrap_or_else(|| "local".to_string());
+    state
+        .audit_usecase
+        .record(
+            Some(&current_user.id),
+            None,
+            "terminal.open",
+            "node",
+            &node,
+            serde_json::json!({}),
+        )
+        .await?;
+
+    Ok(ws.on_upgrade(move |socket| {
+        node_terminal_session(socket, query.node_id, current_user, state)
+    }))
 }
 
 async fn node_terminal_session(
@@ -50,6 +67,7 @@ async fn node_terminal_session(
     terminal_session(socket, current_user).await;
 }
 
+#[allow(clippy::collapsible_match)]
 async fn terminal_session(socket: WebSocket, current_user: CurrentUser) {
     tracing::info!("User {} connected to node terminal", current_user.email);
 
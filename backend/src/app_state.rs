@@ -1,22 +1,35 @@
 use sqlx::SqlitePool;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::config::Config;
 use crate::domain::runtime::RuntimePort;
+use crate::domain::secret_cipher::SecretCipher;
 use crate::domain::system::SystemProvider;
 use crate::infrastructure::caddy::client::CaddyClient;
+use crate::infrastructure::rate_limit::RateLimiter;
 use crate::infrastructure::tunnel::manager::TunnelManager;
+use crate::usecase::api_token::ApiTokenUsecase;
+use crate::usecase::audit::AuditUsecase;
 use crate::usecase::auth::AuthUsecase;
+use crate::usecase::authorization::AuthorizationUsecase;
+use crate::usecase::caddy_reconciler::CaddyReconciler;
 use crate::usecase::deployment_log::DeploymentLogUsecase;
+use crate::usecase::env_group::EnvGroupUsecase;
 use crate::usecase::environment::EnvironmentUsecase;
+use crate::usecase::login_throttle::LoginThrottle;
 use crate::usecase::metrics::MetricsUsecase;
 use crate::usecase::node::NodeUsecase;
+use crate::usecase::oidc::OidcUsecase;
 use crate::usecase::registry::RegistryUsecase;
 use crate::usecase::resource::ResourceUsecase;
+use crate::usecase::role::RoleUsecase;
 use crate::usecase::stack::StackUsecase;
 use crate::usecase::system::SystemUsecase;
 use crate::usecase::team::TeamUsecase;
 use crate::usecase::template::TemplateUsecase;
+use crate::usecase::two_factor::TwoFactorUsecase;
+use crate::usecase::user::UserUsecase;
 
 /// Central application state (Dependency Injection Container)
 pub struct AppState {
@@ -28,48 +41,150 @@ pub struct AppState {
     pub caddy_client: Arc<CaddyClient>,
     pub system_provider: Arc<dyn SystemProvider>,
     pub tunnel_manager: Option<Arc<TunnelManager>>,
+    pub secret_cipher: Arc<dyn SecretCipher>,
+    pub auth_rate_limiter: Arc<RateLimiter>,
+    pub webhook_rate_limiter: Arc<RateLimiter>,
 
     // Usecases
     pub auth_usecase: Arc<AuthUsecase>,
+    pub authz_usecase: Arc<AuthorizationUsecase>,
+    pub audit_usecase: Arc<AuditUsecase>,
+    pub two_factor_usecase: Arc<TwoFactorUsecase>,
     pub system_usecase: Arc<SystemUsecase>,
     pub node_usecase: Arc<NodeUsecase>,
 
     // Optional/Conditional Usecases
+    pub api_token_usecase: Option<Arc<ApiTokenUsecase>>,
     pub env_usecase: Option<Arc<EnvironmentUsecase>>,
+    pub env_group_usecase: Option<Arc<EnvGroupUsecase>>,
     pub registry_usecase: Option<Arc<RegistryUsecase>>,
     pub stack_usecase: Option<Arc<StackUsecase>>,
     pub team_usecase: Option<Arc<TeamUsecase>>,
     pub template_usecase: Option<Arc<TemplateUsecase>>,
     pub resource_usecase: Option<Arc<ResourceUsecase>>,
+    pub role_usecase: Option<Arc<RoleUsecase>>,
     pub log_usecase: Option<Arc<DeploymentLogUsecase>>,
     pub domain_usecase: Option<Arc<crate::usecase::domain::DomainUsecase>>,
     pub dns_usecase: Option<Arc<crate::usecase::dns::DnsUsecase>>,
     pub metrics_usecase: Option<Arc<MetricsUsecase>>,
+    pub oidc_usecase: Option<Arc<OidcUsecase>>,
+    pub user_usecase: Option<Arc<UserUsecase>>,
 }
 
 impl AppState {
     pub async fn new(config: Config, pool: SqlitePool) -> anyhow::Result<Self> {
-        // 1. Initialize Infrastructure
-
         // Runtime (Docker)
         let runtime: Arc<dyn RuntimePort> =
             Arc::new(crate::infrastructure::docker::runtime::DockerRuntimeAdapter::new().await?);
 
+        Self::with_runtime(config, pool, runtime).await
+    }
+
+    /// Build the state on top of an existing container runtime
+    pub async fn with_runtime(
+        config: Config,
+        pool: SqlitePool,
+        runtime: Arc<dyn RuntimePort>,
+    ) -> anyhow::Result<Self> {
+        // 1. Initialize Infrastructure
+
+        // Secrets encryption
+        let secret_cipher: Arc<dyn SecretCipher> = Arc::new(
+            crate::infrastructure::crypto::envelope::EnvelopeCipher::from_config(&config)?,
+        );
+
         // Caddy
         let caddy_client = Arc::new(CaddyClient::new(config.caddy_admin_api.clone()));
 
         // Auth Infra
         let jwt_service = Arc::new(crate::infrastructure::auth::jwt::JwtService::new(
             config.jwt_secret.clone(),
-            config.jwt_expiration_hours,
+            config.jwt_access_ttl_minutes,
         ));
 
         // User Repo
         let user_repo =
             Arc::new(crate::infrastructure::sqlite::user::SqliteUserRepository::new(pool.clone()));
 
+        // API tokens
+        let api_token_repo = Arc::new(
+            crate::infrastructure::sqlite::api_token::SqliteApiTokenRepository::new(pool.clone()),
+        );
+
+        // Login sessions
+        let session_repo = Arc::new(
+            crate::infrastructure::sqlite::session::SqliteSessionRepository::new(pool.clone()),
+        );
+
+        // Authorization: built-in roles, custom roles and stack grants
+        let team_repo =
+            Arc::new(crate::infrastructure::sqlite::team::SqliteTeamRepository::new(pool.clone()));
+        let authz_usecase = Arc::new(AuthorizationUsecase::new(
+            team_repo.clone(),
+            Arc::new(crate::infrastructure::sqlite::role::SqliteRoleRepository::new(pool.clone())),
+            Arc::new(
+                crate::infrastructure::sqlite::stack_grant::SqliteStackGrantRepository::new(
+                    pool.clone(),
+                ),
+            ),
+        ));
+
+        // Audit log
+        let audit_usecase = Arc::new(AuditUsecase::new(
+            Arc::new(
+                crate::infrastructure::sqlite::activity_log::SqliteActivityLogRepository::new(
+                    pool.clone(),
+                ),
+            ),
+            authz_usecase.clone(),
+            Arc::new(
+                crate::infrastructure::sqlite::stack::SqliteStackRepository::new(pool.clone()),
+            ),
+        ));
+
+        // Two-factor authentication
+        let two_factor_usecase = Arc::new(TwoFactorUsecase::new(
+            Arc::new(
+                crate::infrastructure::sqlite::two_factor::SqliteTwoFactorRepository::new(
+                    pool.clone(),
+                ),
+            ),
+            team_repo,
+            authz_usecase.clone(),
+            audit_usecase.clone(),
+            secret_cipher.clone(),
+        ));
+
+        // Login brute-force protection
+        let lockout_store = config.rate_limit.persist.then(|| {
+            Arc::new(
+                crate::infrastructure::sqlite::login_lockout::SqliteLoginLockoutRepository::new(
+                    pool.clone(),
+                ),
+            )
+                as Arc<dyn crate::domain::login_lockout_repository::LoginLockoutRepository>
+        });
+        let login_throttle = Arc::new(LoginThrottle::new(
+            lockout_store,
+            config.rate_limit.login_max_attempts,
+            config.rate_limit.login_lockout_seconds,
+        ));
+        login_throttle.load().await?;
+
         // 2. Initialize Core Usecases
-        let auth_usecase = Arc::new(AuthUsecase::new(user_repo, jwt_service));
+        let auth_usecase = Arc::new(AuthUsecase::new(
+            user_repo,
+            api_token_repo,
+            session_repo,
+            jwt_service,
+            two_factor_usecase.clone(),
+            login_throttle,
+            config.refresh_token_ttl_days,
+            crate::domain::models::LoginOptions {
+                password_enabled: config.password_login_enabled,
+                sso_provider: config.oidc.as_ref().map(|o| o.provider_name.clone()),
+            },
+        ));
 
         let system_provider =
             Arc::new(crate::infrastructure::linux_system::LinuxSystemProvider::new());
@@ -84,19 +199,32 @@ impl AppState {
             caddy_client,
             system_provider: system_provider.clone(),
             tunnel_manager: None,
+            secret_cipher,
+            auth_rate_limiter: Arc::new(RateLimiter::per_minute(config.rate_limit.auth_per_minute)),
+            webhook_rate_limiter: Arc::new(RateLimiter::per_minute(
+                config.rate_limit.webhook_per_minute,
+            )),
             auth_usecase,
+            authz_usecase,
+            audit_usecase,
+            two_factor_usecase,
             system_usecase,
             node_usecase: Arc::new(NodeUsecase::new(runtime.clone())),
+            api_token_usecase: None,
             env_usecase: None,
+            env_group_usecase: None,
             registry_usecase: None,
             stack_usecase: None,
             team_usecase: None,
             template_usecase: None,
             resource_usecase: None,
+            role_usecase: None,
             log_usecase: None,
             domain_usecase: None,
             dns_usecase: None,
             metrics_usecase: None,
+            oidc_usecase: None,
+            user_usecase: None,
         };
 
         app_state.init_full_stack().await?;
@@ -108,6 +236,18 @@ impl AppState {
     async fn init_full_stack(&mut self) -> anyhow::Result<()> {
         let pool = self._pool.clone();
         let runtime = self.runtime.clone();
+        let secret_cipher = self.secret_cipher.clone();
+
+        // Secrets: encrypt rows stored before encryption at rest
+        let secret_repo = Arc::new(
+            crate::infrastructure::sqlite::secret::SqliteSecretRepository::new(pool.clone()),
+        );
+        let secret_uc =
+            crate::usecase::secret::SecretUsecase::new(secret_repo, secret_cipher.clone());
+        let migrated = secret_uc.encrypt_legacy_secrets().await?;
+        if migrated > 0 {
+            tracing::info!("Encrypted {} legacy secrets", migrated);
+        }
 
         // User
         let user_repo =
@@ -119,23 +259,112 @@ impl AppState {
                 pool.clone(),
             ),
         );
-        let env_uc = Arc::new(EnvironmentUsecase::new(env_repo));
+        let audit_uc = self.audit_usecase.clone();
+        let authz_uc = self.authz_usecase.clone();
+
+        // Background Task: Audit log retention
+        let retention_days = self._config.audit_retention_days;
+        if retention_days > 0 {
+            let prune_uc = audit_uc.clone();
+            tokio::spawn(async move {
+                let mut interval =
+                    tokio::time::interval(std::time::Duration::from_secs(24 * 60 * 60));
+                loop {
+                    interval.tick().await;
+                    match prune_uc.prune(retention_days).await {
+                        Ok(0) => {}
+                        Ok(n) => tracing::info!("Pruned {} audit log entries", n),
+                        Err(e) => tracing::error!("Failed to prune audit log: {}", e),
+                    }
+                }
+            });
+        }
+        let env_group_repo = Arc::new(
+            crate::infrastructure::sqlite::env_group::SqliteEnvGroupRepository::new(pool.clone()),
+        );
+        let env_uc = Arc::new(EnvironmentUsecase::new(
+            env_repo,
+            env_group_repo.clone(),
+            secret_cipher.clone(),
+            audit_uc.clone(),
+        ));
         self.env_usecase = Some(env_uc.clone());
 
         // Team
         let team_repo =
             Arc::new(crate::infrastructure::sqlite::team::SqliteTeamRepository::new(pool.clone()));
+        let session_repo = Arc::new(
+            crate::infrastructure::sqlite::session::SqliteSessionRepository::new(pool.clone()),
+        );
         let team_uc = Arc::new(crate::usecase::team::TeamUsecase::new(
             team_repo.clone(),
             user_repo.clone(),
+            session_repo,
+            authz_uc.clone(),
+            audit_uc.clone(),
         ));
         self.team_usecase = Some(team_uc.clone());
 
+        // API tokens
+        let api_token_repo = Arc::new(
+            crate::infrastructure::sqlite::api_token::SqliteApiTokenRepository::new(pool.clone()),
+        );
+        self.api_token_usecase = Some(Arc::new(ApiTokenUsecase::new(
+            api_token_repo,
+            user_repo.clone(),
+            team_repo.clone(),
+            authz_uc.clone(),
+        )));
+
+        // Users: profile, invites, password resets and deactivation
+        self.user_usecase = Some(Arc::new(UserUsecase::new(
+            user_repo.clone(),
+            team_repo.clone(),
+            authz_uc.clone(),
+            Arc::new(
+                crate::infrastructure::sqlite::invite::SqliteInviteRepository::new(pool.clone()),
+            ),
+            audit_uc.clone(),
+            self.auth_usecase.clone(),
+        )));
+
+        // OIDC single sign-on
+        if let Some(oidc_config) = self._config.oidc.clone() {
+            let mappings =
+                crate::domain::models::TeamMapping::parse_list(&oidc_config.team_mapping)
+                    .map_err(|e| anyhow::anyhow!("OIDC_TEAM_MAPPING: {}", e))?;
+            let identity_repo = Arc::new(
+                crate::infrastructure::sqlite::user_identity::SqliteUserIdentityRepository::new(
+                    pool.clone(),
+                ),
+            );
+            self.oidc_usecase = Some(Arc::new(OidcUsecase::new(
+                Arc::new(crate::infrastructure::oidc::OidcClient::new(oidc_config)),
+                self.auth_usecase.clone(),
+                user_repo.clone(),
+                identity_repo,
+                team_repo.clone(),
+                mappings,
+            )));
+        }
+
+        // Environment groups
+        self.env_group_usecase = Some(Arc::new(EnvGroupUsecase::new(
+            env_group_repo,
+            authz_uc.clone(),
+            secret_cipher.clone(),
+        )));
+
         // Registry
         let registry_repo = Arc::new(
             crate::infrastructure::sqlite::registry::SqliteRegistryRepository::new(pool.clone()),
         );
-        let registry_uc = Arc::new(RegistryUsecase::new(registry_repo, team_repo.clone()));
+        let registry_uc = Arc::new(RegistryUsecase::new(
+            registry_repo,
+            authz_uc.clone(),
+            secret_cipher.clone(),
+            audit_uc.clone(),
+        ));
         self.registry_usecase = Some(registry_uc.clone());
 
         // Template
@@ -164,10 +393,25 @@ impl AppState {
         let resource_uc = Arc::new(ResourceUsecase::new(
             resource_repo.clone(),
             stack_repo.clone(),
-            team_repo.clone(),
+            authz_uc.clone(),
         ));
         self.resource_usecase = Some(resource_uc);
 
+        // Custom roles and stack grants
+        self.role_usecase = Some(Arc::new(RoleUsecase::new(
+            authz_uc.clone(),
+            Arc::new(crate::infrastructure::sqlite::role::SqliteRoleRepository::new(pool.clone())),
+            Arc::new(
+                crate::infrastructure::sqlite::stack_grant::SqliteStackGrantRepository::new(
+                    pool.clone(),
+                ),
+            ),
+            team_repo.clone(),
+            user_repo.clone(),
+            stack_repo.clone(),
+            audit_uc.clone(),
+        )));
+
         // Background Task: Metrics Collector
         let metrics_repo = Arc::new(
             crate::infrastructure::sqlite::metrics::SqliteMetricsRepository::new(pool.clone()),
@@ -183,7 +427,47 @@ impl AppState {
             metrics_collector.start().await;
         });
 
-        self.metrics_usecase = Some(Arc::new(MetricsUsecase::new(metrics_repo)));
+        let recent_requests = Arc::new(crate::usecase::access_log::RecentRequests::new(2000));
+        self.metrics_usecase = Some(Arc::new(MetricsUsecase::new(
+            metrics_repo.clone(),
+            recent_requests.clone(),
+        )));
+
+        // Domain & DNS
+        let domain_repo = Arc::new(
+            crate::infrastructure::sqlite::domain::SqliteDomainRepository::new(pool.clone()),
+        );
+        let dns_config_repo = Arc::new(
+            crate::infrastructure::sqlite::dns::SqliteDnsConfigRepository::new(pool.clone()),
+        );
+        let dns_uc = Arc::new(crate::usecase::dns::DnsUsecase::new(
+            dns_config_repo,
+            secret_cipher.clone(),
+        ));
+        self.dns_usecase = Some(dns_uc.clone());
+
+        // Initialize Tunnel Manager
+        let tunnel_manager = Arc::new(TunnelManager::new(runtime.clone()));
+        self.tunnel_manager = Some(tunnel_manager.clone());
+
+        // Caddy config is rendered from the domains table as a whole
+        let dashboard_site = self._config.dashboard_domain.as_deref().map(|domain| {
+            use crate::infrastructure::caddy::config::{ProxyRoute, Site};
+            Site::new(
+                domain,
+                vec![ProxyRoute::new(&self._config.dashboard_upstream)],
+            )
+        });
+        let reconciler = Arc::new(CaddyReconciler::new(
+            domain_repo.clone(),
+            stack_repo.clone(),
+            dns_uc.clone(),
+            secret_cipher.clone(),
+            runtime.clone(),
+            self.caddy_client.clone(),
+            self._config.caddy_image.clone(),
+            dashboard_site,
+        ));
 
         let stack_uc = Arc::new(StackUsecase::new(
             stack_repo.clone(),
@@ -192,6 +476,9 @@ impl AppState {
             registry_uc,
             resource_repo.clone(),
             team_repo.clone(),
+            authz_uc,
+            audit_uc.clone(),
+            reconciler.clone(),
         ));
         self.stack_usecase = Some(stack_uc.clone());
 
@@ -213,28 +500,26 @@ impl AppState {
         );
         self.log_usecase = Some(Arc::new(DeploymentLogUsecase::new(log_repo)));
 
-        // Domain & DNS
-        let domain_repo = Arc::new(
-            crate::infrastructure::sqlite::domain::SqliteDomainRepository::new(pool.clone()),
-        );
-        let dns_config_repo = Arc::new(
-            crate::infrastructure::sqlite::dns::SqliteDnsConfigRepository::new(pool.clone()),
-        );
-        let dns_uc = Arc::new(crate::usecase::dns::DnsUsecase::new(dns_config_repo));
-        self.dns_usecase = Some(dns_uc.clone());
-
-        // Initialize Tunnel Manager
-        let tunnel_manager = Arc::new(TunnelManager::new(runtime.clone()));
-        self.tunnel_manager = Some(tunnel_manager.clone());
+        // Background Task: Access log collector, per-domain request metrics
+        let access_log_collector = Arc::new(crate::usecase::access_log::AccessLogCollector::new(
+            crate::infrastructure::caddy::client::access_log_path(),
+            domain_repo.clone(),
+            metrics_repo,
+            recent_requests,
+        ));
+        tokio::spawn(async move {
+            access_log_collector.start(Duration::from_secs(15)).await;
+        });
 
-        let caddy_client = self.caddy_client.clone();
         let domain_uc = Arc::new(crate::usecase::domain::DomainUsecase::new(
             domain_repo,
             stack_repo,
-            caddy_client,
+            reconciler.clone(),
             dns_uc,
-            runtime.clone(),
+            secret_cipher,
             Some(tunnel_manager),
+            self._config.certificate_warning_days,
+            self.audit_usecase.clone(),
         ));
         self.domain_usecase = Some(domain_uc.clone());
 
@@ -244,8 +529,8 @@ impl AppState {
             tracing::error!("Failed to create labuh-network: {}", e);
         }
 
-        // Bootstrap Caddy
-        if let Err(e) = self.caddy_client.bootstrap(&runtime).await {
+        // Bootstrap Caddy, publishing the ports of L4 routes
+        if let Err(e) = reconciler.bootstrap().await {
             tracing::error!("Failed to bootstrap Caddy: {}", e);
         }
 
@@ -257,10 +542,9 @@ impl AppState {
             tracing::warn!("Could not connect Caddy to labuh-network: {}", e);
         }
 
-        // Sync Dommains
-        if let Err(e) = domain_uc.sync_all_routes().await {
-            tracing::error!("Failed to sync domains to Caddy: {}", e);
-        }
+        // Background Task: Caddy reconciler, starting with an immediate sync
+        let period = Duration::from_secs(self._config.caddy_sync_interval_seconds.max(5));
+        tokio::spawn(reconciler.start(period));
 
         Ok(())
     }
@@ -0,0 +1,56 @@
+use sqlx::SqlitePool;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::infrastructure::crypto::envelope::EnvelopeCipher;
+use crate::infrastructure::sqlite::secret::SqliteSecretRepository;
+use crate::usecase::secret::SecretUsecase;
+
+/// `labuh rotate-master-key`: re-encrypt every stored secret under a new master key.
+///
+/// The new key is taken from `LABUH_NEW_MASTER_KEY` or generated. With a key file it is
+/// written next to the old one before the database changes and swapped in afterwards;
+/// with `LABUH_MASTER_KEY` it is printed so the environment can be updated.
+/// The server must be stopped while this runs.
+pub async fn rotate_master_key(config: &Config, pool: SqlitePool) -> anyhow::Result<()> {
+    let current = Arc::new(EnvelopeCipher::from_config(config)?);
+    let next = match std::env::var("LABUH_NEW_MASTER_KEY") {
+        Ok(key) => EnvelopeCipher::from_base64(&key)?,
+        Err(_) => EnvelopeCipher::generate(),
+    };
+
+    if next.key_id() == current.key_id() {
+        anyhow::bail!("The new master key is the same as the current one");
+    }
+
+    let usecase = SecretUsecase::new(Arc::new(SqliteSecretRepository::new(pool)), current.clone());
+    usecase.encrypt_legacy_secrets().await?;
+
+    let key_file = Path::new(&config.master_key_file);
+    let staged_file = key_file.with_extension("key.new");
+    if config.master_key.is_none() {
+        next.write_key_file(&staged_file)?;
+    }
+
+    let count = usecase.rotate(&next).await?;
+    tracing::info!(
+        "Re-encrypted {} secrets from key {} to key {}",
+        count,
+        current.key_id(),
+        next.key_id()
+    );
+
+    if config.master_key.is_none() {
+        std::fs::rename(&staged_file, key_file)?;
+        println!(
+            "Master key rotated. New key written to {}",
+            key_file.display()
+        );
+    } else {
+        println!("Master key rotated. Set LABUH_MASTER_KEY to the new key before restarting:");
+        println!("{}", next.to_base64());
+    }
+
+    Ok(())
+}
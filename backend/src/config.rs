@@ -6,27 +6,141 @@ pub struct Config {
     pub port: u16,
     pub database_url: String,
     pub jwt_secret: String,
-    pub jwt_expiration_hours: u64,
+    pub jwt_access_ttl_minutes: u64,
+    pub refresh_token_ttl_days: u64,
     pub caddy_admin_api: String,
+    /// Image for the managed Caddy container; DNS-01 challenges need a build with
+    /// the Cloudflare DNS module
+    pub caddy_image: String,
+    /// Seconds between Caddy reconciles; domain changes also reconcile immediately
+    pub caddy_sync_interval_seconds: u64,
+    /// Certificates expiring within this many days are reported with a warning
+    pub certificate_warning_days: i64,
+    /// Hostname Caddy serves the dashboard on (`LABUH_DOMAIN`), if any
+    pub dashboard_domain: Option<String>,
+    /// Address Caddy dials for the dashboard route
+    pub dashboard_upstream: String,
+    pub master_key: Option<String>,
+    pub master_key_file: String,
+    pub password_login_enabled: bool,
+    pub oidc: Option<OidcConfig>,
+    pub rate_limit: RateLimitConfig,
+    /// Days to keep audit log entries; 0 keeps them forever
+    pub audit_retention_days: u64,
+}
+
+/// Brute-force protection for login and request limits for public endpoints
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitConfig {
+    /// Failed logins per account before it is locked; IPs get four times as many
+    pub login_max_attempts: u32,
+    /// First lockout duration, doubled on every further failure
+    pub login_lockout_seconds: u64,
+    /// Requests per minute and client IP on public auth endpoints (0 disables)
+    pub auth_per_minute: u32,
+    /// Requests per minute and client IP on deploy webhooks (0 disables)
+    pub webhook_per_minute: u32,
+    /// Keep lockouts in SQLite so they survive restarts
+    pub persist: bool,
+    /// Take the client IP from `X-Forwarded-For` (only behind a trusted proxy)
+    pub trust_proxy_headers: bool,
+}
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+impl RateLimitConfig {
+    pub fn from_env() -> Self {
+        Self {
+            login_max_attempts: env_or("LOGIN_MAX_ATTEMPTS", 5),
+            login_lockout_seconds: env_or("LOGIN_LOCKOUT_SECONDS", 30),
+            auth_per_minute: env_or("AUTH_RATE_LIMIT_PER_MINUTE", 30),
+            webhook_per_minute: env_or("WEBHOOK_RATE_LIMIT_PER_MINUTE", 60),
+            persist: env_or("RATE_LIMIT_PERSIST", false),
+            trust_proxy_headers: env_or("TRUST_PROXY_HEADERS", false),
+        }
+    }
+}
+
+/// OpenID Connect client settings; SSO is enabled when `OIDC_ISSUER_URL` is set
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcConfig {
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    /// Must point at `/api/auth/oidc/callback` of this instance
+    pub redirect_url: String,
+    pub scopes: String,
+    pub provider_name: String,
+    /// ID token claim holding the user's groups
+    pub groups_claim: String,
+    /// `group=Team Name:Role` pairs separated by commas
+    pub team_mapping: String,
+}
+
+impl OidcConfig {
+    fn from_env() -> Option<Self> {
+        let issuer_url = std::env::var("OIDC_ISSUER_URL").ok()?;
+        Some(Self {
+            issuer_url,
+            client_id: std::env::var("OIDC_CLIENT_ID").unwrap_or_default(),
+            client_secret: std::env::var("OIDC_CLIENT_SECRET").ok(),
+            redirect_url: std::env::var("OIDC_REDIRECT_URL").unwrap_or_default(),
+            scopes: std::env::var("OIDC_SCOPES")
+                .unwrap_or_else(|_| "openid email profile".to_string()),
+            provider_name: std::env::var("OIDC_PROVIDER_NAME")
+                .unwrap_or_else(|_| "SSO".to_string()),
+            groups_claim: std::env::var("OIDC_GROUPS_CLAIM")
+                .unwrap_or_else(|_| "groups".to_string()),
+            team_mapping: std::env::var("OIDC_TEAM_MAPPING").unwrap_or_default(),
+        })
+    }
 }
 
 impl Config {
     pub fn from_env() -> Result<Self, std::env::VarError> {
+        let port = std::env::var("PORT")
+            .unwrap_or_else(|_| "3000".to_string())
+            .parse()
+            .unwrap_or(3000);
         Ok(Self {
             host: std::env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
-            port: std::env::var("PORT")
-                .unwrap_or_else(|_| "3000".to_string())
-                .parse()
-                .unwrap_or(3000),
+            port,
             database_url: std::env::var("DATABASE_URL")
                 .unwrap_or_else(|_| "sqlite:./labuh.db?mode=rwc".to_string()),
             jwt_secret: std::env::var("JWT_SECRET")?,
-            jwt_expiration_hours: std::env::var("JWT_EXPIRATION_HOURS")
-                .unwrap_or_else(|_| "24".to_string())
+            jwt_access_ttl_minutes: std::env::var("JWT_ACCESS_TTL_MINUTES")
+                .unwrap_or_else(|_| "15".to_string())
+                .parse()
+                .unwrap_or(15),
+            refresh_token_ttl_days: std::env::var("REFRESH_TOKEN_TTL_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
                 .parse()
-                .unwrap_or(24),
+                .unwrap_or(30),
             caddy_admin_api: std::env::var("CADDY_ADMIN_API")
                 .unwrap_or_else(|_| "http://localhost:2019".to_string()),
+            caddy_image: std::env::var("CADDY_IMAGE")
+                .unwrap_or_else(|_| "caddy:2-alpine".to_string()),
+            caddy_sync_interval_seconds: env_or("CADDY_SYNC_INTERVAL_SECONDS", 60),
+            certificate_warning_days: env_or("CERTIFICATE_WARNING_DAYS", 14),
+            dashboard_domain: std::env::var("LABUH_DOMAIN")
+                .ok()
+                .filter(|d| !d.trim().is_empty()),
+            dashboard_upstream: std::env::var("LABUH_DASHBOARD_UPSTREAM")
+                .unwrap_or_else(|_| format!("host.docker.internal:{}", port)),
+            master_key: std::env::var("LABUH_MASTER_KEY").ok(),
+            master_key_file: std::env::var("LABUH_MASTER_KEY_FILE")
+                .unwrap_or_else(|_| "./master.key".to_string()),
+            password_login_enabled: std::env::var("PASSWORD_LOGIN_ENABLED")
+                .map(|v| v != "false")
+                .unwrap_or(true),
+            oidc: OidcConfig::from_env(),
+            rate_limit: RateLimitConfig::from_env(),
+            audit_retention_days: env_or("AUDIT_RETENTION_DAYS", 90),
         })
     }
 
@@ -0,0 +1,19 @@
+use crate::domain::models::{ActivityLog, AuditLogEntry, AuditLogQuery};
+use crate::error::Result;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait ActivityLogRepository: Send + Sync {
+    async fn create(&self, log: ActivityLog) -> Result<()>;
+    /// Newest first; `page` and `per_page` of the query are ignored
+    async fn list_by_team(
+        &self,
+        team_id: &str,
+        query: &AuditLogQuery,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<AuditLogEntry>>;
+    async fn count_by_team(&self, team_id: &str, query: &AuditLogQuery) -> Result<i64>;
+    /// Delete entries created before `cutoff`, returning how many were removed
+    async fn delete_before(&self, cutoff: &str) -> Result<u64>;
+}
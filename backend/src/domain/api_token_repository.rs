@@ -0,0 +1,15 @@
+use crate::domain::models::ApiToken;
+use crate::error::Result;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait ApiTokenRepository: Send + Sync {
+    async fn create(&self, token: ApiToken) -> Result<ApiToken>;
+    async fn find_by_id(&self, id: &str) -> Result<ApiToken>;
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<ApiToken>>;
+    /// Personal tokens created by a user (team tokens excluded)
+    async fn list_personal(&self, user_id: &str) -> Result<Vec<ApiToken>>;
+    async fn list_by_team(&self, team_id: &str) -> Result<Vec<ApiToken>>;
+    async fn revoke(&self, id: &str, revoked_at: &str) -> Result<()>;
+    async fn touch_last_used(&self, id: &str, used_at: &str) -> Result<()>;
+}
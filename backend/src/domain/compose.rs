@@ -14,6 +14,10 @@ pub struct ComposeFile {
     pub services: HashMap<String, ComposeService>,
     #[serde(default)]
     pub networks: HashMap<String, ComposeNetwork>,
+    #[serde(default)]
+    pub secrets: HashMap<String, ComposeFileObject>,
+    #[serde(default)]
+    pub configs: HashMap<String, ComposeFileObject>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -35,6 +39,10 @@ pub struct ComposeService {
     #[serde(default)]
     pub labels: HashMap<String, String>,
     pub deploy: Option<ComposeDeploy>,
+    #[serde(default)]
+    pub secrets: Vec<ComposeFileReference>,
+    #[serde(default)]
+    pub configs: Vec<ComposeFileReference>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -84,11 +92,62 @@ pub enum ComposeEnvironment {
 #[derive(Debug, Deserialize, Default)]
 pub struct ComposeNetwork {}
 
+/// Top-level `secrets:` / `configs:` entry
+#[derive(Debug, Deserialize, Default)]
+pub struct ComposeFileObject {
+    pub name: Option<String>,
+    pub file: Option<String>,
+    pub environment: Option<String>,
+    pub content: Option<String>,
+    #[serde(default)]
+    pub external: bool,
+}
+
+/// Per-service `secrets:` / `configs:` entry, either `- name` or the long syntax
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ComposeFileReference {
+    Short(String),
+    Long {
+        source: String,
+        target: Option<String>,
+        uid: Option<String>,
+        gid: Option<String>,
+        mode: Option<serde_yaml::Value>,
+    },
+}
+
 /// Result of parsing a compose file
 #[derive(Debug)]
 pub struct ParsedCompose {
     pub services: Vec<ParsedService>,
     pub networks: Vec<String>,
+    pub secrets: HashMap<String, FileObjectSource>,
+    pub configs: HashMap<String, FileObjectSource>,
+}
+
+/// Where the content of a compose secret or config comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileObjectSource {
+    /// Key in the stack's environment variable store
+    Store(String),
+    /// Inline `content:` (configs only)
+    Content(String),
+    /// File relative to the stack's Git checkout (configs only)
+    File(String),
+    /// Pre-existing swarm object, referenced by name
+    External(String),
+}
+
+/// A secret or config mounted into a service
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedFileMount {
+    pub source: String,
+    /// Absolute path inside the container
+    pub target: String,
+    pub uid: Option<String>,
+    pub gid: Option<String>,
+    pub mode: Option<u32>,
 }
 
 #[derive(Debug, Clone)]
@@ -105,6 +164,8 @@ pub struct ParsedService {
     pub cpu_limit: Option<f64>,
     pub memory_limit: Option<i64>,
     pub deploy: Option<ParsedDeploy>,
+    pub secrets: Vec<ParsedFileMount>,
+    pub configs: Vec<ParsedFileMount>,
 }
 
 #[derive(Debug, Clone)]
@@ -193,14 +254,34 @@ pub fn validate_volume_security(volumes: &[String]) -> Result<Vec<String>> {
     Ok(warnings)
 }
 
+/// Service names become container names and host directory names, so only
+/// Docker's name charset `[a-zA-Z0-9][a-zA-Z0-9_.-]*` is accepted
+fn validate_service_name(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphanumeric())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+    if !valid {
+        return Err(AppError::Validation(format!(
+            "Invalid service name '{}': use letters, digits, '_', '.' or '-', starting with a letter or digit",
+            name
+        )));
+    }
+    Ok(())
+}
+
 /// Parse docker-compose.yml content
 pub fn parse_compose(yaml_content: &str) -> Result<ParsedCompose> {
     let compose: ComposeFile = serde_yaml::from_str(yaml_content)
         .map_err(|e| AppError::Validation(format!("Invalid compose file: {}", e)))?;
 
+    let secrets = parse_file_objects(compose.secrets, "secret")?;
+    let configs = parse_file_objects(compose.configs, "config")?;
+
     let mut services = Vec::new();
 
     for (name, service) in compose.services {
+        validate_service_name(&name)?;
+
         // Parse build context
         let build = match &service.build {
             Some(ComposeBuild::Simple(context)) => Some(ParsedBuild {
@@ -299,6 +380,9 @@ pub fn parse_compose(yaml_content: &str) -> Result<ParsedCompose> {
             }
         }
 
+        let service_secrets = parse_file_mounts(&name, service.secrets, &secrets, "secret")?;
+        let service_configs = parse_file_mounts(&name, service.configs, &configs, "config")?;
+
         services.push(ParsedService {
             name,
             image,
@@ -315,6 +399,8 @@ pub fn parse_compose(yaml_content: &str) -> Result<ParsedCompose> {
                 replicas,
                 placement: ParsedPlacement { constraints },
             }),
+            secrets: service_secrets,
+            configs: service_configs,
         });
     }
 
@@ -331,7 +417,127 @@ pub fn parse_compose(yaml_content: &str) -> Result<ParsedCompose> {
 
     let networks: Vec<String> = compose.networks.keys().cloned().collect();
 
-    Ok(ParsedCompose { services, networks })
+    Ok(ParsedCompose {
+        services,
+        networks,
+        secrets,
+        configs,
+    })
+}
+
+/// Resolve top-level `secrets:` / `configs:` into their content source.
+/// Secret values never come from files in the repository, only from the stack's secret store.
+fn parse_file_objects(
+    objects: HashMap<String, ComposeFileObject>,
+    kind: &str,
+) -> Result<HashMap<String, FileObjectSource>> {
+    let mut parsed = HashMap::new();
+
+    for (name, object) in objects {
+        let source = if object.external {
+            FileObjectSource::External(object.name.unwrap_or_else(|| name.clone()))
+        } else if let Some(key) = object.environment {
+            FileObjectSource::Store(key)
+        } else if kind == "config" && object.content.is_some() {
+            FileObjectSource::Content(object.content.unwrap_or_default())
+        } else if kind == "config" && object.file.is_some() {
+            let file = object.file.unwrap_or_default();
+            if file.contains("..") || file.starts_with('/') {
+                return Err(AppError::Validation(format!(
+                    "Config '{}' must reference a file inside the repository",
+                    name
+                )));
+            }
+            FileObjectSource::File(file)
+        } else {
+            if object.file.is_some() {
+                tracing::warn!(
+                    "Compose validation: secret '{}' uses 'file', reading it from the stack secret store instead",
+                    name
+                );
+            }
+            FileObjectSource::Store(name.clone())
+        };
+        parsed.insert(name, source);
+    }
+
+    Ok(parsed)
+}
+
+/// Resolve a service's secret/config references into absolute mount targets
+fn parse_file_mounts(
+    service_name: &str,
+    references: Vec<ComposeFileReference>,
+    declared: &HashMap<String, FileObjectSource>,
+    kind: &str,
+) -> Result<Vec<ParsedFileMount>> {
+    // Docker mounts secrets under /run/secrets and configs at the root by default
+    let default_dir = if kind == "secret" { "/run/secrets" } else { "" };
+    let mut mounts = Vec::new();
+
+    for reference in references {
+        let (source, target, uid, gid, mode) = match reference {
+            ComposeFileReference::Short(source) => (source, None, None, None, None),
+            ComposeFileReference::Long {
+                source,
+                target,
+                uid,
+                gid,
+                mode,
+            } => (source, target, uid, gid, mode),
+        };
+
+        if !declared.contains_key(&source) {
+            return Err(AppError::Validation(format!(
+                "Service '{}' references undefined {} '{}'",
+                service_name, kind, source
+            )));
+        }
+
+        let target = target.unwrap_or_else(|| source.clone());
+        if target.is_empty() || target.split('/').any(|part| part == "..") {
+            return Err(AppError::Validation(format!(
+                "Service '{}' has an invalid target for {} '{}'",
+                service_name, kind, source
+            )));
+        }
+        let target = if target.starts_with('/') {
+            target
+        } else {
+            format!("{}/{}", default_dir, target)
+        };
+
+        let mode = match mode {
+            Some(value) => Some(parse_file_mode(&value).ok_or_else(|| {
+                AppError::Validation(format!(
+                    "Service '{}' has an invalid mode for {} '{}'",
+                    service_name, kind, source
+                ))
+            })?),
+            None => None,
+        };
+
+        mounts.push(ParsedFileMount {
+            source,
+            target,
+            uid,
+            gid,
+            mode,
+        });
+    }
+
+    Ok(mounts)
+}
+
+/// File modes are written in octal (`0440`, `"0440"` or `0o440`)
+fn parse_file_mode(value: &serde_yaml::Value) -> Option<u32> {
+    let raw = match value {
+        serde_yaml::Value::Number(n) => n.to_string(),
+        serde_yaml::Value::String(s) => s.clone(),
+        _ => return None,
+    };
+    let digits = raw.trim_start_matches("0o");
+    u32::from_str_radix(digits, 8).ok().filter(|m| *m <= 0o777)
 }
 
 fn parse_memory(memory: &str) -> Option<i64> {
@@ -448,6 +654,25 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("path traversal"));
     }
 
+    #[test]
+    fn test_reject_hostile_service_names() {
+        for name in ["../../git", "..", "web/api", "-web", ".hidden", "\"a b\""] {
+            let yaml = format!("services:\n  {}:\n    image: nginx\n", name);
+            let result = parse_compose(&yaml);
+            assert!(
+                result
+                    .unwrap_err()
+                    .to_string()
+                    .contains("Invalid service name"),
+                "{}",
+                name
+            );
+        }
+
+        let parsed = parse_compose("services:\n  web_1.v2-b:\n    image: nginx\n").unwrap();
+        assert_eq!(parsed.services[0].name, "web_1.v2-b");
+    }
+
     #[test]
     fn test_allow_named_volumes() {
         let volumes = vec!["postgres_data:/var/lib/postgresql/data".to_string()];
@@ -484,4 +709,57 @@ services:
         let result = parse_compose(yaml);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_parse_compose_secrets_and_configs() {
+        let yaml = r#"
+services:
+  api:
+    image: alpine
+    secrets:
+      - db_password
+      - source: api_key
+        target: key.txt
+        mode: 0400
+    configs:
+      - source: nginx
+        target: /etc/nginx/nginx.conf
+secrets:
+  db_password:
+    file: ./db_password.txt
+  api_key:
+    environment: API_KEY
+configs:
+  nginx:
+    file: deploy/nginx.conf
+"#;
+        let parsed = parse_compose(yaml).unwrap();
+        assert_eq!(
+            parsed.secrets.get("db_password"),
+            Some(&FileObjectSource::Store("db_password".to_string()))
+        );
+        assert_eq!(
+            parsed.secrets.get("api_key"),
+            Some(&FileObjectSource::Store("API_KEY".to_string()))
+        );
+
+        let api = &parsed.services[0];
+        assert_eq!(api.secrets[0].target, "/run/secrets/db_password");
+        assert_eq!(api.secrets[1].target, "/run/secrets/key.txt");
+        assert_eq!(api.secrets[1].mode, Some(0o400));
+        assert_eq!(api.configs[0].target, "/etc/nginx/nginx.conf");
+    }
+
+    #[test]
+    fn test_parse_compose_undefined_secret() {
+        let yaml = r#"
+services:
+  api:
+    image: alpine
+    secrets:
+      - missing
+"#;
+        let result = parse_compose(yaml);
+        assert!(result.unwrap_err().to_string().contains("undefined secret"));
+    }
 }
@@ -1,4 +1,4 @@
-use crate::domain::models::domain::Domain;
+use crate::domain::models::domain::{Domain, DomainRoute, L4Route};
 use crate::error::Result;
 use async_trait::async_trait;
 
@@ -13,4 +13,17 @@ pub trait DomainRepository: Send + Sync {
     async fn update_verification(&self, domain: &str, verified: bool) -> Result<()>;
     async fn update_dns_record_id(&self, id: &str, dns_record_id: &str) -> Result<()>;
     async fn update_branding(&self, domain: &str, show_branding: bool) -> Result<()>;
+    async fn update_upstreams(&self, domain: &Domain) -> Result<()>;
+    async fn update_middleware(&self, domain: &Domain) -> Result<()>;
+    async fn update_tls(&self, domain: &Domain) -> Result<()>;
+
+    async fn list_routes(&self, domain_id: &str) -> Result<Vec<DomainRoute>>;
+    async fn list_all_routes(&self) -> Result<Vec<DomainRoute>>;
+    async fn create_route(&self, route: DomainRoute) -> Result<DomainRoute>;
+    async fn delete_route(&self, id: &str) -> Result<()>;
+
+    async fn list_l4_routes(&self, stack_id: &str) -> Result<Vec<L4Route>>;
+    async fn list_all_l4_routes(&self) -> Result<Vec<L4Route>>;
+    async fn create_l4_route(&self, route: L4Route) -> Result<L4Route>;
+    async fn delete_l4_route(&self, id: &str) -> Result<()>;
 }
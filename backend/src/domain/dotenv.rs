@@ -0,0 +1,170 @@
+//! dotenv file parser
+//!
+//! Reads and writes `.env` files for importing/exporting environment variables.
+
+use crate::error::{AppError, Result};
+
+fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-')
+}
+
+/// Parse `.env` content into key/value pairs, in file order.
+/// Supports comments, `export` prefixes, single quotes (literal) and double quotes (with escapes).
+pub fn parse_dotenv(content: &str) -> Result<Vec<(String, String)>> {
+    let mut vars: Vec<(String, String)> = Vec::new();
+    let mut lines = content.lines().enumerate();
+
+    while let Some((index, line)) = lines.next() {
+        let line_no = index + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let trimmed = trimmed.strip_prefix("export ").unwrap_or(trimmed);
+        let (key, raw) = trimmed
+            .split_once('=')
+            .ok_or_else(|| AppError::Validation(format!("Line {}: expected KEY=VALUE", line_no)))?;
+        let key = key.trim();
+        if !is_valid_key(key) {
+            return Err(AppError::Validation(format!(
+                "Line {}: invalid variable name '{}'",
+                line_no, key
+            )));
+        }
+
+        let raw = raw.trim_start();
+        let value = if let Some(rest) = raw.strip_prefix('"') {
+            // Double-quoted values may span several lines
+            let mut value = String::new();
+            let mut chars: Vec<char> = rest.chars().collect();
+            let mut i = 0;
+            loop {
+                if i >= chars.len() {
+                    match lines.next() {
+                        Some((_, next)) => {
+                            value.push('\n');
+                            chars = next.chars().collect();
+                            i = 0;
+                            continue;
+                        }
+                        None => {
+                            return Err(AppError::Validation(format!(
+                                "Line {}: unterminated quoted value",
+                                line_no
+                            )));
+                        }
+                    }
+                }
+                match chars[i] {
+                    '"' => break,
+                    '\\' if i + 1 < chars.len() => {
+                        i += 1;
+                        value.push(match chars[i] {
+                            'n' => '\n',
+                            'r' => '\r',
+                            't' => '\t',
+                            other => other,
+                        });
+                    }
+                    c => value.push(c),
+                }
+                i += 1;
+            }
+            value
+        } else if let Some(rest) = raw.strip_prefix('\'') {
+            let end = rest.find('\'').ok_or_else(|| {
+                AppError::Validation(format!("Line {}: unterminated quoted value", line_no))
+            })?;
+            rest[..end].to_string()
+        } else {
+            // Unquoted: an inline comment starts at " #"
+            let value = match raw.find(" #") {
+                Some(pos) => &raw[..pos],
+                None => raw,
+            };
+            value.trim_end().to_string()
+        };
+
+        match vars.iter_mut().find(|(k, _)| k == key) {
+            Some(existing) => existing.1 = value,
+            None => vars.push((key.to_string(), value)),
+        }
+    }
+
+    Ok(vars)
+}
+
+/// Render key/value pairs as `.env` content, quoting values where needed
+pub fn to_dotenv(vars: &[(String, String)]) -> String {
+    let mut out = String::new();
+    for (key, value) in vars {
+        let needs_quotes = value
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '#' | '\\' | '$' | '`'));
+        if needs_quotes {
+            let escaped = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r")
+                .replace('\t', "\\t");
+            out.push_str(&format!("{}=\"{}\"\n", key, escaped));
+        } else {
+            out.push_str(&format!("{}={}\n", key, value));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dotenv() {
+        let content = r#"
+# comment
+export APP_ENV=production
+PLAIN=value # trailing comment
+SINGLE='$literal #not a comment'
+DOUBLE="line1\nline2 \"quoted\""
+MULTI="first
+second"
+EMPTY=
+"#;
+        let vars = parse_dotenv(content).unwrap();
+        let get = |k: &str| {
+            vars.iter()
+                .find(|(key, _)| key == k)
+                .map(|(_, v)| v.as_str())
+        };
+
+        assert_eq!(get("APP_ENV"), Some("production"));
+        assert_eq!(get("PLAIN"), Some("value"));
+        assert_eq!(get("SINGLE"), Some("$literal #not a comment"));
+        assert_eq!(get("DOUBLE"), Some("line1\nline2 \"quoted\""));
+        assert_eq!(get("MULTI"), Some("first\nsecond"));
+        assert_eq!(get("EMPTY"), Some(""));
+    }
+
+    #[test]
+    fn test_parse_dotenv_rejects_invalid_lines() {
+        assert!(parse_dotenv("NOT A VAR").is_err());
+        assert!(parse_dotenv("1KEY=value").is_err());
+        assert!(parse_dotenv("KEY=\"open").is_err());
+    }
+
+    #[test]
+    fn test_dotenv_round_trip() {
+        let vars = vec![
+            ("SIMPLE".to_string(), "abc".to_string()),
+            ("SPACED".to_string(), "hello world".to_string()),
+            ("TRICKY".to_string(), "a\"b\\c\nd #e".to_string()),
+        ];
+        let parsed = parse_dotenv(&to_dotenv(&vars)).unwrap();
+        assert_eq!(parsed, vars);
+    }
+}
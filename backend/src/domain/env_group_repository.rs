@@ -0,0 +1,22 @@
+use crate::domain::models::{EnvGroup, EnvGroupVar};
+use crate::error::Result;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait EnvGroupRepository: Send + Sync {
+    async fn list_by_team(&self, team_id: &str) -> Result<Vec<EnvGroup>>;
+    async fn find_by_id(&self, id: &str) -> Result<EnvGroup>;
+    async fn create(&self, group: EnvGroup) -> Result<EnvGroup>;
+    async fn delete(&self, id: &str) -> Result<()>;
+
+    async fn list_vars(&self, group_id: &str) -> Result<Vec<EnvGroupVar>>;
+    async fn find_var(&self, group_id: &str, key: &str) -> Result<Option<EnvGroupVar>>;
+    async fn save_var(&self, var: EnvGroupVar) -> Result<EnvGroupVar>;
+    async fn delete_var(&self, group_id: &str, key: &str) -> Result<()>;
+
+    /// Groups attached to a stack, lowest precedence first
+    async fn list_for_stack(&self, stack_id: &str) -> Result<Vec<EnvGroup>>;
+    /// Replace the stack's groups; the order of `group_ids` is the precedence order
+    async fn set_for_stack(&self, stack_id: &str, group_ids: &[String]) -> Result<()>;
+    async fn list_stack_ids(&self, group_id: &str) -> Result<Vec<String>>;
+}
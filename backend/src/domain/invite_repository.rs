@@ -0,0 +1,20 @@
+use crate::domain::models::{PasswordReset, UserInvite};
+use crate::error::Result;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait InviteRepository: Send + Sync {
+    async fn create(&self, invite: UserInvite) -> Result<UserInvite>;
+    async fn find_by_id(&self, id: &str) -> Result<Option<UserInvite>>;
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<UserInvite>>;
+    /// Invites of a team that are neither accepted nor expired
+    async fn list_pending(&self, team_id: &str, now: &str) -> Result<Vec<UserInvite>>;
+    /// Mark an invite as accepted; false if it was accepted concurrently
+    async fn mark_accepted(&self, id: &str, accepted_at: &str) -> Result<bool>;
+    async fn delete(&self, id: &str) -> Result<()>;
+
+    async fn create_password_reset(&self, reset: PasswordReset) -> Result<()>;
+    async fn find_password_reset(&self, token_hash: &str) -> Result<Option<PasswordReset>>;
+    /// Mark a reset as used; false if it was used concurrently
+    async fn use_password_reset(&self, id: &str, used_at: &str) -> Result<bool>;
+}
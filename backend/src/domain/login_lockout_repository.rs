@@ -0,0 +1,11 @@
+use crate::domain::models::LoginLockout;
+use crate::error::Result;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait LoginLockoutRepository: Send + Sync {
+    /// Counters with a failure after `since`
+    async fn list_recent(&self, since: &str) -> Result<Vec<LoginLockout>>;
+    async fn upsert(&self, lockout: LoginLockout) -> Result<()>;
+    async fn delete(&self, key: &str) -> Result<()>;
+}
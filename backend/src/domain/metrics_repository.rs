@@ -21,6 +21,97 @@ pub struct HistoricalContainerMetrics {
     pub timestamp: String,
 }
 
+/// Upper bounds (ms) of the latency histogram buckets; one more bucket counts
+/// everything slower
+pub const LATENCY_BOUNDS_MS: [f64; 11] = [
+    5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0,
+];
+
+/// Requests to one domain in one time bucket, from Caddy's access log. Latency is
+/// kept as a histogram so buckets can be merged and percentiles estimated over
+/// any range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomainRequestMetrics {
+    pub domain: String,
+    pub stack_id: String,
+    /// Start of the bucket, `YYYY-MM-DD HH:MM:SS` UTC
+    pub bucket: String,
+    pub requests: u64,
+    /// Responses per status class, 1xx to 5xx
+    pub status: [u64; 5],
+    pub bytes_sent: u64,
+    pub latency_sum_ms: f64,
+    /// Counts per [`LATENCY_BOUNDS_MS`] bucket plus the overflow bucket
+    pub latency_histogram: Vec<u64>,
+}
+
+impl DomainRequestMetrics {
+    pub fn new(domain: &str, stack_id: &str, bucket: &str) -> Self {
+        Self {
+            domain: domain.to_string(),
+            stack_id: stack_id.to_string(),
+            bucket: bucket.to_string(),
+            requests: 0,
+            status: [0; 5],
+            bytes_sent: 0,
+            latency_sum_ms: 0.0,
+            latency_histogram: vec![0; LATENCY_BOUNDS_MS.len() + 1],
+        }
+    }
+
+    pub fn record(&mut self, status: u16, bytes: u64, latency_ms: f64) {
+        self.requests += 1;
+        if (100..600).contains(&status) {
+            self.status[(status / 100 - 1) as usize] += 1;
+        }
+        self.bytes_sent += bytes;
+        self.latency_sum_ms += latency_ms;
+        let slot = LATENCY_BOUNDS_MS
+            .iter()
+            .position(|bound| latency_ms <= *bound)
+            .unwrap_or(LATENCY_BOUNDS_MS.len());
+        self.latency_histogram[slot] += 1;
+    }
+
+    pub fn merge(&mut self, other: &DomainRequestMetrics) {
+        self.requests += other.requests;
+        for (total, count) in self.status.iter_mut().zip(other.status) {
+            *total += count;
+        }
+        self.bytes_sent += other.bytes_sent;
+        self.latency_sum_ms += other.latency_sum_ms;
+        if self.latency_histogram.len() < other.latency_histogram.len() {
+            self.latency_histogram
+                .resize(other.latency_histogram.len(), 0);
+        }
+        for (total, count) in self
+            .latency_histogram
+            .iter_mut()
+            .zip(&other.latency_histogram)
+        {
+            *total += count;
+        }
+    }
+
+    /// Upper bound of the histogram bucket holding the `quantile` (0..1) request;
+    /// requests slower than the last bound report that bound
+    pub fn latency_percentile_ms(&self, quantile: f64) -> Option<f64> {
+        let total: u64 = self.latency_histogram.iter().sum();
+        if total == 0 {
+            return None;
+        }
+        let rank = ((quantile * total as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (slot, count) in self.latency_histogram.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(LATENCY_BOUNDS_MS[slot.min(LATENCY_BOUNDS_MS.len() - 1)]);
+            }
+        }
+        LATENCY_BOUNDS_MS.last().copied()
+    }
+}
+
 #[async_trait]
 pub trait MetricsRepository: Send + Sync {
     async fn save_node_metrics(&self, metrics: HistoricalNodeMetrics) -> Result<()>;
@@ -31,4 +122,43 @@ pub trait MetricsRepository: Send + Sync {
         container_id: &str,
         last_hours: i32,
     ) -> Result<Vec<HistoricalContainerMetrics>>;
+    /// Add to the stored buckets, creating those that do not exist yet
+    async fn save_domain_requests(&self, metrics: &[DomainRequestMetrics]) -> Result<()>;
+    /// Buckets of a stack's domains, oldest first; all domains when `domain` is `None`
+    async fn get_domain_requests(
+        &self,
+        stack_id: &str,
+        domain: Option<&str>,
+        last_hours: i32,
+    ) -> Result<Vec<DomainRequestMetrics>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_request_percentiles_survive_merge() {
+        let mut fast = DomainRequestMetrics::new("app.example.com", "s1", "2026-10-18 10:00:00");
+        for _ in 0..90 {
+            fast.record(200, 100, 3.0);
+        }
+        let mut slow = DomainRequestMetrics::new("app.example.com", "s1", "2026-10-18 10:01:00");
+        for _ in 0..9 {
+            slow.record(502, 0, 180.0);
+        }
+        slow.record(504, 0, 60_000.0);
+
+        fast.merge(&slow);
+        assert_eq!(fast.requests, 100);
+        assert_eq!(fast.status, [0, 90, 0, 0, 10]);
+        assert_eq!(fast.bytes_sent, 9000);
+        assert_eq!(fast.latency_percentile_ms(0.5), Some(5.0));
+        assert_eq!(fast.latency_percentile_ms(0.95), Some(250.0));
+        assert_eq!(fast.latency_percentile_ms(1.0), Some(10000.0));
+        assert_eq!(
+            DomainRequestMetrics::new("a", "b", "c").latency_percentile_ms(0.5),
+            None
+        );
+    }
 }
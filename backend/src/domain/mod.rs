@@ -1,18 +1,32 @@
+pub mod activity_log_repository;
+pub mod api_token_repository;
 pub mod compose;
 pub mod deployment_log_repository;
 pub mod dns_provider;
 pub mod dns_repository;
 pub mod domain_repository;
+pub mod dotenv;
+pub mod env_group_repository;
 pub mod environment_repository;
+pub mod invite_repository;
+pub mod login_lockout_repository;
 pub mod metrics_repository;
 pub mod models;
+pub mod oidc_provider;
 pub mod registry_repository;
 pub mod resource_repository;
+pub mod role_repository;
 pub mod runtime;
+pub mod secret_cipher;
+pub mod secret_repository;
+pub mod session_repository;
+pub mod stack_grant_repository;
 pub mod stack_repository;
 pub mod system;
 pub mod team_repository;
 pub mod template_repository;
+pub mod two_factor_repository;
+pub mod user_identity_repository;
 pub mod user_repository;
 
 pub use team_repository::TeamRepository;
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ActivityLog {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub team_id: Option<String>,
+    pub action: String,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub details: Option<String>, // JSON
+    pub created_at: String,
+}
+
+/// Filters for the team audit log; timestamps are RFC 3339
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditLogQuery {
+    /// Exact action, or a prefix ending in `.` such as `stack.`
+    pub action: Option<String>,
+    pub user_id: Option<String>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+/// Activity log row joined with the acting user
+#[derive(Debug, Clone, FromRow)]
+pub struct AuditLogEntry {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub user_email: Option<String>,
+    pub user_name: Option<String>,
+    pub action: String,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub details: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditLogResponse {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub user_email: Option<String>,
+    pub user_name: Option<String>,
+    pub action: String,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub details: Option<serde_json::Value>,
+    pub created_at: String,
+}
+
+impl From<AuditLogEntry> for AuditLogResponse {
+    fn from(entry: AuditLogEntry) -> Self {
+        Self {
+            id: entry.id,
+            user_id: entry.user_id,
+            user_email: entry.user_email,
+            user_name: entry.user_name,
+            action: entry.action,
+            resource_type: entry.resource_type,
+            resource_id: entry.resource_id,
+            details: entry
+                .details
+                .as_deref()
+                .and_then(|d| serde_json::from_str(d).ok()),
+            created_at: entry.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditLogPage {
+    pub entries: Vec<AuditLogResponse>,
+    pub total: i64,
+    pub page: u32,
+    pub per_page: u32,
+}
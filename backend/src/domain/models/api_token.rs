@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::team::TeamRole;
+
+/// Every API token starts with this, so `auth_middleware` can tell it apart from a JWT
+pub const API_TOKEN_PREFIX: &str = "labuh_";
+
+/// Role given to the bot users behind team tokens; they can never log in
+pub const BOT_USER_ROLE: &str = "bot";
+
+pub const API_SCOPES: &[&str] = &[
+    "stacks:read",
+    "stacks:write",
+    "stacks:deploy",
+    "env:read",
+    "env:write",
+    "domains:read",
+    "domains:write",
+    "containers:read",
+    "containers:write",
+    "images:read",
+    "images:write",
+    "registries:read",
+    "registries:write",
+    "templates:read",
+    "templates:write",
+    "teams:read",
+    "teams:write",
+    "system:read",
+];
+
+#[derive(Debug, Clone, FromRow)]
+pub struct ApiToken {
+    pub id: String,
+    /// User the token acts as: the owner, or the bot user of a team token
+    pub user_id: String,
+    pub team_id: Option<String>,
+    pub created_by: Option<String>,
+    pub name: String,
+    pub token_prefix: String,
+    pub token_hash: String,
+    /// Space-separated list of scopes
+    pub scopes: String,
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub revoked_at: Option<String>,
+    pub created_at: String,
+}
+
+impl ApiToken {
+    pub fn scope_list(&self) -> Vec<String> {
+        self.scopes.split_whitespace().map(String::from).collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiTokenResponse {
+    pub id: String,
+    pub team_id: Option<String>,
+    pub created_by: Option<String>,
+    pub name: String,
+    pub token_prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub revoked_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<ApiToken> for ApiTokenResponse {
+    fn from(token: ApiToken) -> Self {
+        Self {
+            scopes: token.scope_list(),
+            id: token.id,
+            team_id: token.team_id,
+            created_by: token.created_by,
+            name: token.name,
+            token_prefix: token.token_prefix,
+            expires_at: token.expires_at,
+            last_used_at: token.last_used_at,
+            revoked_at: token.revoked_at,
+            created_at: token.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    pub scopes: Vec<String>,
+    /// Days until expiry; no expiry when omitted
+    pub expires_in_days: Option<i64>,
+    /// Team role of the bot user (team tokens only, defaults to Developer)
+    pub role: Option<TeamRole>,
+}
+
+/// Returned once on creation; the plain token cannot be retrieved again
+#[derive(Debug, Serialize)]
+pub struct CreatedApiToken {
+    pub token: String,
+    #[serde(flatten)]
+    pub info: ApiTokenResponse,
+}
+
+/// What a request needs when authenticated with an API token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeRequirement {
+    /// Any valid token may call it
+    Any,
+    Scope(&'static str),
+    /// Interactive or credential-management endpoints, never reachable with a token
+    Forbidden,
+}
+
+/// Actions that count as deployments rather than configuration changes
+const DEPLOY_ACTIONS: &[&str] = &[
+    "start",
+    "stop",
+    "redeploy",
+    "build",
+    "rollback",
+    "sync",
+    "scale",
+    "maintenance",
+];
+
+/// Map a protected API route (with or without the `/api` prefix) to the scope it requires
+pub fn required_scope(method: &str, path: &str) -> ScopeRequirement {
+    use ScopeRequirement::*;
+
+    let segments: Vec<&str> = path
+        .trim_start_matches("/api")
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    let read = method == "GET" || method == "HEAD";
+    let rw = |r: &'static str, w: &'static str| Scope(if read { r } else { w });
+
+    match segments.as_slice() {
+        ["me"] => Any,
+        ["auth", ..] | ["tokens", ..] | ["teams", _, "tokens", ..] => Forbidden,
+        ["nodes", "terminal", ..] | ["nodes", "swarm", "tokens"] | ["containers", _, "exec"] => {
+            Forbidden
+        }
+        ["stacks", _, "env" | "env-groups", ..] | ["env-groups", ..] => rw("env:read", "env:write"),
+        ["stacks", "domains", ..]
+        | ["stacks", _, "domains", ..]
+        | ["teams", _, "dns-configs", ..] => rw("domains:read", "domains:write"),
+        ["stacks", _, .., action] if !read && DEPLOY_ACTIONS.contains(action) => {
+            Scope("stacks:deploy")
+        }
+        ["stacks", ..] => rw("stacks:read", "stacks:write"),
+        ["containers", ..] => rw("containers:read", "containers:write"),
+        ["images", ..] => rw("images:read", "images:write"),
+        ["registries", ..] => rw("registries:read", "registries:write"),
+        ["templates", ..] => rw("templates:read", "templates:write"),
+        ["teams", ..] => rw("teams:read", "teams:write"),
+        ["nodes", ..] | ["networks", ..] | ["metrics", ..] if read => Scope("system:read"),
+        _ => Forbidden,
+    }
+}
+
+/// Whether `granted` satisfies `required`; a `:write` scope also grants `:read`
+pub fn scope_granted(granted: &[String], required: &str) -> bool {
+    granted.iter().any(|scope| {
+        scope == required
+            || required
+                .strip_suffix(":read")
+                .is_some_and(|area| scope.strip_suffix(":write") == Some(area))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_scope() {
+        use ScopeRequirement::*;
+
+        assert_eq!(required_scope("GET", "/api/stacks"), Scope("stacks:read"));
+        assert_eq!(required_scope("POST", "/stacks"), Scope("stacks:write"));
+        assert_eq!(
+            required_scope("POST", "/api/stacks/abc/redeploy"),
+            Scope("stacks:deploy")
+        );
+        assert_eq!(
+            required_scope("POST", "/api/stacks/abc/services/web/redeploy"),
+            Scope("stacks:deploy")
+        );
+        assert_eq!(
+            required_scope("PUT", "/api/stacks/abc/maintenance"),
+            Scope("stacks:deploy")
+        );
+        assert_eq!(
+            required_scope("PUT", "/api/stacks/abc/env/bulk"),
+            Scope("env:write")
+        );
+        assert_eq!(required_scope("GET", "/api/me"), Any);
+        assert_eq!(required_scope("POST", "/api/tokens"), Forbidden);
+        assert_eq!(
+            required_scope("DELETE", "/api/teams/t1/tokens/x"),
+            Forbidden
+        );
+        assert_eq!(required_scope("GET", "/api/nodes/terminal"), Forbidden);
+        assert_eq!(required_scope("POST", "/api/nodes/swarm/init"), Forbidden);
+    }
+
+    #[test]
+    fn test_write_scope_grants_read() {
+        let granted = vec!["env:write".to_string()];
+        assert!(scope_granted(&granted, "env:read"));
+        assert!(scope_granted(&granted, "env:write"));
+        assert!(!scope_granted(&granted, "stacks:read"));
+    }
+}
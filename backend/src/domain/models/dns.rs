@@ -18,7 +18,6 @@ pub struct CloudflareConfig {
     pub zone_id: Option<String>, // Deprecated, but kept for backward compat deserialization
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CPanelConfig {
     pub host: String,
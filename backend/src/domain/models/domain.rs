@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+use super::domain_middleware::DomainMiddleware;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, sqlx::Type)]
 #[sqlx(rename_all = "PascalCase")]
 pub enum DomainProvider {
@@ -16,6 +18,111 @@ pub enum DomainType {
     Tunnel,
 }
 
+/// How Caddy proves control of a hostname to the ACME CA
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum TlsChallenge {
+    /// HTTP-01 / TLS-ALPN-01, which need ports 80 or 443 reachable from the CA
+    #[default]
+    Http,
+    /// DNS-01 through the team's Cloudflare DNS config; required for wildcards
+    Dns,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CertificateState {
+    Valid,
+    /// Within the warning window (`CERTIFICATE_WARNING_DAYS`). Caddy renews ACME
+    /// certificates well before that, so reaching it means renewal keeps failing.
+    Expiring,
+    Expired,
+    /// Caddy has no certificate for the hostname yet, or could not be reached
+    Missing,
+    /// Automatic HTTPS is turned off; the domain is served over plain HTTP
+    Disabled,
+    /// Tunnel domain; Cloudflare terminates TLS
+    External,
+}
+
+impl CertificateState {
+    pub fn from_days_remaining(days: i64, warning_days: i64) -> Self {
+        if days < 0 {
+            CertificateState::Expired
+        } else if days <= warning_days {
+            CertificateState::Expiring
+        } else {
+            CertificateState::Valid
+        }
+    }
+}
+
+/// The certificate served for a domain
+#[derive(Debug, Clone, Serialize)]
+pub struct CertificateStatus {
+    pub domain: String,
+    pub state: CertificateState,
+    /// Uploaded certificate rather than one obtained over ACME
+    pub custom: bool,
+    pub subject: Option<String>,
+    pub issuer: Option<String>,
+    pub sans: Vec<String>,
+    pub not_before: Option<String>,
+    pub not_after: Option<String>,
+    pub days_remaining: Option<i64>,
+    /// Why the certificate could not be read
+    pub error: Option<String>,
+    /// Latest error Caddy logged while obtaining or renewing the certificate
+    pub last_error: Option<String>,
+    /// Upcoming expiry, or a certificate that does not cover the hostname
+    pub warning: Option<String>,
+}
+
+impl CertificateStatus {
+    pub fn without_certificate(domain: &str, state: CertificateState) -> Self {
+        Self {
+            domain: domain.to_string(),
+            state,
+            custom: false,
+            subject: None,
+            issuer: None,
+            sans: Vec::new(),
+            not_before: None,
+            not_after: None,
+            days_remaining: None,
+            error: None,
+            last_error: None,
+            warning: None,
+        }
+    }
+}
+
+/// How Caddy picks one of several upstreams; values match Caddy's policy names
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum LoadBalancingPolicy {
+    #[default]
+    RoundRobin,
+    LeastConn,
+    Random,
+    IpHash,
+    First,
+}
+
+impl LoadBalancingPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoadBalancingPolicy::RoundRobin => "round_robin",
+            LoadBalancingPolicy::LeastConn => "least_conn",
+            LoadBalancingPolicy::Random => "random",
+            LoadBalancingPolicy::IpHash => "ip_hash",
+            LoadBalancingPolicy::First => "first",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Domain {
     pub id: String,
@@ -32,9 +139,295 @@ pub struct Domain {
     pub dns_record_id: Option<String>,
     pub proxied: bool,
     pub show_branding: bool,
+    /// Space-separated `container:port` targets balanced with the primary container
+    pub extra_upstreams: String,
+    pub lb_policy: LoadBalancingPolicy,
+    pub health_check_path: Option<String>,
+    pub health_check_interval: i64,
+    /// `DomainMiddleware` as JSON
+    pub middleware: String,
+    /// Space-separated `username:bcrypt-hash` pairs
+    pub basic_auth: String,
+    pub tls_challenge: TlsChallenge,
+    /// Uploaded PEM certificate chain, encrypted
+    #[serde(skip)]
+    pub custom_certificate: Option<String>,
+    /// Private key of `custom_certificate`, encrypted
+    #[serde(skip)]
+    pub custom_private_key: Option<String>,
     pub created_at: String,
 }
 
+impl Domain {
+    /// Primary target first, then the extra upstreams, each as `(container, port)`
+    pub fn targets(&self) -> Vec<(String, i32)> {
+        targets(
+            &self.container_name,
+            self.container_port,
+            &self.extra_upstreams,
+        )
+    }
+
+    /// `*.example.com`
+    pub fn is_wildcard(&self) -> bool {
+        self.domain.starts_with("*.")
+    }
+
+    pub fn middleware(&self) -> DomainMiddleware {
+        serde_json::from_str(&self.middleware).unwrap_or_default()
+    }
+
+    /// Basic auth users as `(username, bcrypt hash)`
+    pub fn basic_auth_accounts(&self) -> Vec<(String, String)> {
+        self.basic_auth
+            .split_whitespace()
+            .filter_map(|pair| pair.split_once(':'))
+            .map(|(user, hash)| (user.to_string(), hash.to_string()))
+            .collect()
+    }
+}
+
+/// A path prefix of a domain routed to its own upstreams. Routes are matched
+/// before the domain's primary target, longest prefix first.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DomainRoute {
+    pub id: String,
+    pub domain_id: String,
+    /// Prefix without a trailing slash, e.g. `/api`
+    pub path: String,
+    /// Remove the prefix before proxying, so `/api/users` reaches the upstream as `/users`
+    pub strip_prefix: bool,
+    pub container_name: String,
+    pub container_port: i32,
+    /// Space-separated `container:port` targets balanced with the primary container
+    pub extra_upstreams: String,
+    pub lb_policy: LoadBalancingPolicy,
+    pub health_check_path: Option<String>,
+    pub health_check_interval: i64,
+    pub created_at: String,
+}
+
+impl DomainRoute {
+    pub fn targets(&self) -> Vec<(String, i32)> {
+        targets(
+            &self.container_name,
+            self.container_port,
+            &self.extra_upstreams,
+        )
+    }
+}
+
+fn targets(container_name: &str, container_port: i32, extra: &str) -> Vec<(String, i32)> {
+    std::iter::once((container_name.to_string(), container_port))
+        .chain(
+            extra
+                .split_whitespace()
+                .filter_map(|t| parse_target(t).ok()),
+        )
+        .collect()
+}
+
+/// Check that a hostname only uses `*` as a whole leading label, as in `*.example.com`
+pub fn validate_hostname(domain: &str) -> Result<(), String> {
+    let rest = domain.strip_prefix("*.").unwrap_or(domain);
+    let valid = !rest.is_empty()
+        && rest.split('.').all(|label| {
+            !label.is_empty()
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        });
+    if !valid {
+        return Err(format!("Invalid domain '{}'", domain));
+    }
+    Ok(())
+}
+
+/// Parse a `container:port` target
+pub fn parse_target(target: &str) -> Result<(String, i32), String> {
+    let invalid = || format!("Invalid upstream '{}', expected container:port", target);
+    let (name, port) = target.trim().rsplit_once(':').ok_or_else(invalid)?;
+    let port: u16 = port.parse().map_err(|_| invalid())?;
+    if name.is_empty() || port == 0 || name.contains(char::is_whitespace) {
+        return Err(invalid());
+    }
+    Ok((name.to_string(), port as i32))
+}
+
+/// Normalize a route prefix to `/segment[/segment...]` without a trailing slash.
+/// `/` itself is rejected because it is what the domain's primary target serves.
+pub fn normalize_path_prefix(path: &str) -> Result<String, String> {
+    let trimmed = path.trim().trim_end_matches('/');
+    if !trimmed.starts_with('/') {
+        return Err("Path must start with '/' and must not be '/' itself".to_string());
+    }
+    if trimmed
+        .chars()
+        .any(|c| c.is_whitespace() || matches!(c, '*' | '?' | '#' | '%'))
+    {
+        return Err(format!("Path '{}' contains unsupported characters", path));
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Extra upstreams and how traffic is spread across them and checked
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpstreamSettings {
+    /// `container:port` targets balanced with the primary container
+    #[serde(default)]
+    pub upstreams: Vec<String>,
+    #[serde(default)]
+    pub lb_policy: LoadBalancingPolicy,
+    /// Path Caddy probes on every upstream; no active health checks when unset
+    pub health_check_path: Option<String>,
+    /// Seconds between probes, 10 by default
+    pub health_check_interval: Option<i64>,
+}
+
+impl UpstreamSettings {
+    /// Validate the settings and return the extra upstreams in their stored form
+    pub fn validate(&self) -> Result<String, String> {
+        let mut upstreams = Vec::new();
+        for target in &self.upstreams {
+            let (name, port) = parse_target(target)?;
+            upstreams.push(format!("{}:{}", name, port));
+        }
+        if let Some(path) = self.health_check_path.as_deref()
+            && !path.starts_with('/')
+        {
+            return Err("Health check path must start with '/'".to_string());
+        }
+        if !(1..=3600).contains(&self.interval()) {
+            return Err("Health check interval must be between 1 and 3600 seconds".to_string());
+        }
+        Ok(upstreams.join(" "))
+    }
+
+    pub fn interval(&self) -> i64 {
+        self.health_check_interval.unwrap_or(10)
+    }
+
+    pub fn health_check_path(&self) -> Option<String> {
+        self.health_check_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(String::from)
+    }
+
+    /// True when nothing beyond the defaults is set
+    pub fn is_default(&self) -> bool {
+        self.upstreams.is_empty() && self.health_check_path().is_none()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateDomainRoute {
+    pub path: String,
+    pub strip_prefix: Option<bool>,
+    pub container_name: String,
+    pub container_port: Option<i32>,
+    #[serde(flatten)]
+    pub upstream: UpstreamSettings,
+}
+
+/// Transport of an L4 route
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum L4Protocol {
+    #[default]
+    Tcp,
+    Udp,
+}
+
+impl L4Protocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            L4Protocol::Tcp => "tcp",
+            L4Protocol::Udp => "udp",
+        }
+    }
+}
+
+/// A host port forwarded as raw TCP or UDP to a stack service, for databases,
+/// brokers and other non-HTTP services. TCP routes that set `sni` share their port
+/// with other SNI routes and are picked by the TLS ClientHello, without Caddy
+/// terminating TLS.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct L4Route {
+    pub id: String,
+    pub stack_id: String,
+    pub protocol: L4Protocol,
+    pub listen_port: i32,
+    pub sni: Option<String>,
+    pub container_name: String,
+    pub container_port: i32,
+    pub created_at: String,
+}
+
+impl L4Route {
+    /// Whether both routes would claim the same connections. SNI routes on one TCP
+    /// port coexist as long as their hostnames differ.
+    pub fn conflicts_with(&self, other: &L4Route) -> bool {
+        if self.protocol != other.protocol || self.listen_port != other.listen_port {
+            return false;
+        }
+        match (&self.sni, &other.sni) {
+            (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateL4Route {
+    #[serde(default)]
+    pub protocol: L4Protocol,
+    pub listen_port: i32,
+    /// TLS server name to match; TCP only
+    pub sni: Option<String>,
+    pub container_name: String,
+    pub container_port: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DomainRouteResponse {
+    pub id: String,
+    pub domain_id: String,
+    pub path: String,
+    pub strip_prefix: bool,
+    pub container_name: String,
+    pub container_port: i32,
+    pub upstreams: Vec<String>,
+    pub lb_policy: LoadBalancingPolicy,
+    pub health_check_path: Option<String>,
+    pub health_check_interval: i64,
+    pub created_at: String,
+}
+
+impl From<DomainRoute> for DomainRouteResponse {
+    fn from(r: DomainRoute) -> Self {
+        Self {
+            upstreams: r
+                .extra_upstreams
+                .split_whitespace()
+                .map(String::from)
+                .collect(),
+            id: r.id,
+            domain_id: r.domain_id,
+            path: r.path,
+            strip_prefix: r.strip_prefix,
+            container_name: r.container_name,
+            container_port: r.container_port,
+            lb_policy: r.lb_policy,
+            health_check_path: r.health_check_path,
+            health_check_interval: r.health_check_interval,
+            created_at: r.created_at,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateDomain {
     pub domain: String,
@@ -47,6 +440,24 @@ pub struct CreateDomain {
     pub dns_record_type: Option<String>,
     pub dns_record_content: Option<String>,
     pub proxied: Option<bool>,
+    /// Defaults to `dns` for wildcard domains and `http` otherwise
+    pub tls_challenge: Option<TlsChallenge>,
+    #[serde(flatten)]
+    pub upstream: UpstreamSettings,
+}
+
+/// TLS settings of a domain; omitted fields keep their current value
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateDomainTls {
+    /// `false` opts out of automatic HTTPS and serves the domain over plain HTTP
+    pub ssl_enabled: Option<bool>,
+    pub tls_challenge: Option<TlsChallenge>,
+    /// PEM certificate chain (leaf first), uploaded together with `private_key`
+    pub certificate: Option<String>,
+    pub private_key: Option<String>,
+    /// Drop the uploaded certificate and go back to ACME
+    #[serde(default)]
+    pub remove_certificate: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -64,12 +475,34 @@ pub struct DomainResponse {
     pub dns_record_id: Option<String>,
     pub proxied: bool,
     pub show_branding: bool,
+    pub upstreams: Vec<String>,
+    pub lb_policy: LoadBalancingPolicy,
+    pub health_check_path: Option<String>,
+    pub health_check_interval: i64,
+    pub middleware: DomainMiddleware,
+    /// Usernames only; password hashes are never returned
+    pub basic_auth_users: Vec<String>,
+    pub tls_challenge: TlsChallenge,
+    /// Whether an uploaded certificate is used; the PEM data is never returned
+    pub custom_certificate: bool,
     pub created_at: String,
 }
 
 impl From<Domain> for DomainResponse {
     fn from(d: Domain) -> Self {
         Self {
+            custom_certificate: d.custom_certificate.is_some(),
+            middleware: d.middleware(),
+            basic_auth_users: d
+                .basic_auth_accounts()
+                .into_iter()
+                .map(|(u, _)| u)
+                .collect(),
+            upstreams: d
+                .extra_upstreams
+                .split_whitespace()
+                .map(String::from)
+                .collect(),
             id: d.id,
             stack_id: d.stack_id,
             container_name: d.container_name,
@@ -83,7 +516,81 @@ impl From<Domain> for DomainResponse {
             dns_record_id: d.dns_record_id,
             proxied: d.proxied,
             show_branding: d.show_branding,
+            tls_challenge: d.tls_challenge,
+            lb_policy: d.lb_policy,
+            health_check_path: d.health_check_path,
+            health_check_interval: d.health_check_interval,
             created_at: d.created_at,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_prefix_and_upstream_validation() {
+        assert_eq!(normalize_path_prefix(" /api/ ").unwrap(), "/api");
+        assert_eq!(normalize_path_prefix("/api/v1").unwrap(), "/api/v1");
+        assert!(normalize_path_prefix("/").is_err());
+        assert!(normalize_path_prefix("api").is_err());
+        assert!(normalize_path_prefix("/api/*").is_err());
+
+        assert!(validate_hostname("*.example.com").is_ok());
+        assert!(validate_hostname("app.example.com").is_ok());
+        assert!(validate_hostname("app.*.example.com").is_err());
+        assert!(validate_hostname("*").is_err());
+        assert_eq!(
+            CertificateState::from_days_remaining(60, 14),
+            CertificateState::Valid
+        );
+        assert_eq!(
+            CertificateState::from_days_remaining(3, 14),
+            CertificateState::Expiring
+        );
+        assert_eq!(
+            CertificateState::from_days_remaining(-1, 14),
+            CertificateState::Expired
+        );
+
+        assert_eq!(parse_target("web:8080").unwrap(), ("web".to_string(), 8080));
+        assert!(parse_target("web").is_err());
+        assert!(parse_target("web:0").is_err());
+        assert!(parse_target(":80").is_err());
+
+        let settings = UpstreamSettings {
+            upstreams: vec!["web-2:80".to_string(), " web-3:80".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(settings.validate().unwrap(), "web-2:80 web-3:80");
+        let bad_interval = UpstreamSettings {
+            health_check_interval: Some(0),
+            ..Default::default()
+        };
+        assert!(bad_interval.validate().is_err());
+    }
+
+    #[test]
+    fn test_l4_route_conflicts() {
+        let route = |protocol, port, sni: Option<&str>| L4Route {
+            id: String::new(),
+            stack_id: String::new(),
+            protocol,
+            listen_port: port,
+            sni: sni.map(String::from),
+            container_name: "db".to_string(),
+            container_port: 5432,
+            created_at: String::new(),
+        };
+        let plain = route(L4Protocol::Tcp, 5432, None);
+        assert!(plain.conflicts_with(&route(L4Protocol::Tcp, 5432, None)));
+        assert!(plain.conflicts_with(&route(L4Protocol::Tcp, 5432, Some("db.example.com"))));
+        assert!(!plain.conflicts_with(&route(L4Protocol::Udp, 5432, None)));
+        assert!(!plain.conflicts_with(&route(L4Protocol::Tcp, 5433, None)));
+
+        let a = route(L4Protocol::Tcp, 8883, Some("a.example.com"));
+        assert!(!a.conflicts_with(&route(L4Protocol::Tcp, 8883, Some("b.example.com"))));
+        assert!(a.conflicts_with(&route(L4Protocol::Tcp, 8883, Some("A.example.com"))));
+    }
+}
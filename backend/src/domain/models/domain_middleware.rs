@@ -0,0 +1,262 @@
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+/// Per-domain HTTP options applied by Caddy in front of the domain's upstreams.
+/// Stored as JSON on the domain; basic auth credentials are kept separately so
+/// their hashes never reach API responses.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DomainMiddleware {
+    /// Redirect plain HTTP requests to HTTPS
+    pub https_redirect: bool,
+    /// Also answer the `www.` (or bare) counterpart of the hostname and redirect it here
+    pub www_redirect: bool,
+    pub redirects: Vec<RedirectRule>,
+    /// `max-age` of the Strict-Transport-Security header; no header when unset
+    pub hsts_max_age: Option<u64>,
+    /// X-Content-Type-Options, X-Frame-Options and Referrer-Policy
+    pub security_headers: bool,
+    pub cors: Option<CorsPolicy>,
+    pub request_headers: Vec<HeaderRule>,
+    pub response_headers: Vec<HeaderRule>,
+    /// Client IPs or CIDR ranges let through; everyone when empty
+    pub ip_allow: Vec<String>,
+    pub ip_deny: Vec<String>,
+    pub max_body_bytes: Option<u64>,
+    /// gzip/zstd response compression
+    pub compression: bool,
+}
+
+impl Default for DomainMiddleware {
+    fn default() -> Self {
+        Self {
+            https_redirect: true,
+            www_redirect: false,
+            redirects: Vec::new(),
+            hsts_max_age: None,
+            security_headers: false,
+            cors: None,
+            request_headers: Vec::new(),
+            response_headers: Vec::new(),
+            ip_allow: Vec::new(),
+            ip_deny: Vec::new(),
+            max_body_bytes: None,
+            compression: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RedirectRule {
+    /// Request path, `*` allowed as in `/blog/*`
+    pub from: String,
+    /// Absolute URL or path
+    pub to: String,
+    /// 301 when true, 302 otherwise
+    #[serde(default)]
+    pub permanent: bool,
+}
+
+/// Set a header, or delete it when `value` is `None`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HeaderRule {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CorsPolicy {
+    /// Exact origins such as `https://app.example.com`, or `*`
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    pub allow_credentials: bool,
+    pub max_age: Option<u64>,
+}
+
+impl Default for CorsPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_methods: ["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"]
+                .map(String::from)
+                .to_vec(),
+            allowed_headers: ["Content-Type", "Authorization"].map(String::from).to_vec(),
+            allow_credentials: false,
+            max_age: None,
+        }
+    }
+}
+
+// Values end up in Caddy's config, where `{...}` would expand placeholders such as
+// `{env.*}`; line breaks would split headers
+fn check_value(what: &str, value: &str) -> Result<(), String> {
+    if value.is_empty()
+        || value
+            .chars()
+            .any(|c| matches!(c, '{' | '}') || c.is_control())
+    {
+        return Err(format!("Invalid {} '{}'", what, value));
+    }
+    Ok(())
+}
+
+fn check_header_name(name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("Invalid header name '{}'", name));
+    }
+    Ok(())
+}
+
+/// Accept a single IP or a CIDR range
+pub fn check_ip_range(range: &str) -> Result<(), String> {
+    let invalid = || format!("Invalid IP or CIDR range '{}'", range);
+    let (ip, prefix) = match range.split_once('/') {
+        Some((ip, prefix)) => (ip, Some(prefix)),
+        None => (range, None),
+    };
+    let ip: IpAddr = ip.parse().map_err(|_| invalid())?;
+    if let Some(prefix) = prefix {
+        let bits: u8 = prefix.parse().map_err(|_| invalid())?;
+        let max = if ip.is_ipv4() { 32 } else { 128 };
+        if bits > max {
+            return Err(invalid());
+        }
+    }
+    Ok(())
+}
+
+impl DomainMiddleware {
+    pub fn validate(&self) -> Result<(), String> {
+        for redirect in &self.redirects {
+            if !redirect.from.starts_with('/') {
+                return Err(format!(
+                    "Redirect source '{}' must start with '/'",
+                    redirect.from
+                ));
+            }
+            check_value("redirect source", &redirect.from)?;
+            check_value("redirect target", &redirect.to)?;
+            if redirect.from.contains(' ') || redirect.to.contains(' ') {
+                return Err("Redirects must not contain spaces".to_string());
+            }
+        }
+        for rule in self.request_headers.iter().chain(&self.response_headers) {
+            check_header_name(&rule.name)?;
+            if let Some(value) = &rule.value {
+                check_value("header value", value)?;
+            }
+        }
+        for range in self.ip_allow.iter().chain(&self.ip_deny) {
+            check_ip_range(range)?;
+        }
+        if self.max_body_bytes == Some(0) {
+            return Err("Maximum body size must be greater than zero".to_string());
+        }
+        if let Some(cors) = &self.cors {
+            if cors.allowed_origins.is_empty() {
+                return Err("CORS needs at least one allowed origin".to_string());
+            }
+            for origin in &cors.allowed_origins {
+                if origin != "*"
+                    && !origin.starts_with("http://")
+                    && !origin.starts_with("https://")
+                {
+                    return Err(format!("Invalid CORS origin '{}'", origin));
+                }
+                check_value("CORS origin", origin)?;
+            }
+            if cors.allow_credentials && cors.allowed_origins.iter().any(|o| o == "*") {
+                return Err("CORS credentials cannot be combined with origin '*'".to_string());
+            }
+            for method in &cors.allowed_methods {
+                if method.is_empty() || !method.chars().all(|c| c.is_ascii_uppercase()) {
+                    return Err(format!("Invalid CORS method '{}'", method));
+                }
+            }
+            for header in &cors.allowed_headers {
+                if header != "*" {
+                    check_header_name(header)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A basic auth user; `password` may be left out to keep an existing user's password
+#[derive(Debug, Deserialize)]
+pub struct BasicAuthCredential {
+    pub username: String,
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateDomainMiddleware {
+    #[serde(flatten)]
+    pub middleware: DomainMiddleware,
+    /// Replaces the domain's basic auth users; empty turns basic auth off
+    #[serde(default)]
+    pub basic_auth: Vec<BasicAuthCredential>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_middleware() {
+        assert!(DomainMiddleware::default().validate().is_ok());
+
+        let valid = DomainMiddleware {
+            redirects: vec![RedirectRule {
+                from: "/old/*".to_string(),
+                to: "https://example.com/new".to_string(),
+                permanent: true,
+            }],
+            response_headers: vec![HeaderRule {
+                name: "X-Powered-By".to_string(),
+                value: None,
+            }],
+            ip_allow: vec!["10.0.0.0/8".to_string(), "2001:db8::1".to_string()],
+            cors: Some(CorsPolicy {
+                allowed_origins: vec!["https://app.example.com".to_string()],
+                allow_credentials: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(valid.validate().is_ok());
+
+        // Placeholders would let a domain read Caddy's environment
+        let placeholder = DomainMiddleware {
+            response_headers: vec![HeaderRule {
+                name: "X-Leak".to_string(),
+                value: Some("{env.CF_API_TOKEN}".to_string()),
+            }],
+            ..Default::default()
+        };
+        assert!(placeholder.validate().is_err());
+
+        let bad_cidr = DomainMiddleware {
+            ip_deny: vec!["10.0.0.0/33".to_string()],
+            ..Default::default()
+        };
+        assert!(bad_cidr.validate().is_err());
+
+        let wildcard_credentials = DomainMiddleware {
+            cors: Some(CorsPolicy {
+                allowed_origins: vec!["*".to_string()],
+                allow_credentials: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(wildcard_credentials.validate().is_err());
+    }
+}
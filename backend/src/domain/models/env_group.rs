@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::environment::SECRET_MASK;
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct EnvGroup {
+    pub id: String,
+    pub team_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct EnvGroupVar {
+    pub id: String,
+    pub group_id: String,
+    pub key: String,
+    pub value: String,
+    pub is_secret: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvGroupVarResponse {
+    pub id: String,
+    pub group_id: String,
+    pub key: String,
+    pub value: String,
+    pub is_secret: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<EnvGroupVar> for EnvGroupVarResponse {
+    fn from(var: EnvGroupVar) -> Self {
+        Self {
+            id: var.id,
+            group_id: var.group_id,
+            key: var.key,
+            value: if var.is_secret {
+                SECRET_MASK.to_string()
+            } else {
+                var.value
+            },
+            is_secret: var.is_secret,
+            created_at: var.created_at,
+            updated_at: var.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateEnvGroupRequest {
+    pub team_id: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetEnvGroupVarRequest {
+    pub key: String,
+    pub value: String,
+    #[serde(default)]
+    pub is_secret: bool,
+}
+
+/// Groups to attach to a stack, lowest precedence first
+#[derive(Debug, Deserialize)]
+pub struct AttachEnvGroupsRequest {
+    pub group_ids: Vec<String>,
+}
+
+/// A variable as the container will receive it, with the scope it was resolved from
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveEnvVar {
+    pub key: String,
+    pub value: String,
+    pub is_secret: bool,
+    /// `group`, `stack` or `container`
+    pub source: String,
+    /// Set when `source` is `group`
+    pub group_name: Option<String>,
+}
@@ -1,6 +1,14 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+/// Placeholder returned instead of secret values
+pub const SECRET_MASK: &str = "********";
+
+/// `StackEnvVar::source` for values written through the API or dashboard
+pub const ENV_SOURCE_UI: &str = "ui";
+/// `StackEnvVar::source` for values synced from the compose file
+pub const ENV_SOURCE_COMPOSE: &str = "compose";
+
 #[derive(Debug, Clone, FromRow, Serialize)]
 pub struct StackEnvVar {
     pub id: String,
@@ -9,6 +17,7 @@ pub struct StackEnvVar {
     pub key: String,
     pub value: String,
     pub is_secret: bool,
+    pub source: String,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -21,6 +30,7 @@ pub struct StackEnvVarResponse {
     pub key: String,
     pub value: String,
     pub is_secret: bool,
+    pub source: String,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -33,11 +43,12 @@ impl From<StackEnvVar> for StackEnvVarResponse {
             container_name: env.container_name,
             key: env.key,
             value: if env.is_secret {
-                "********".to_string()
+                SECRET_MASK.to_string()
             } else {
                 env.value
             },
             is_secret: env.is_secret,
+            source: env.source,
             created_at: env.created_at,
             updated_at: env.updated_at,
         }
@@ -64,3 +75,33 @@ pub struct EnvVarItem {
     pub value: String,
     pub is_secret: bool,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct DotenvImportRequest {
+    #[serde(default)]
+    pub container_name: String,
+    pub content: String,
+    /// Delete variables of this scope that are not in the file
+    #[serde(default)]
+    pub remove_missing: bool,
+    /// New keys to store as secrets; existing keys keep their current flag
+    #[serde(default)]
+    pub secret_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvDiffEntry {
+    pub key: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub is_secret: bool,
+}
+
+/// Changes a dotenv import makes to one scope; secret values are masked
+#[derive(Debug, Default, Serialize)]
+pub struct EnvDiff {
+    pub added: Vec<EnvDiffEntry>,
+    pub changed: Vec<EnvDiffEntry>,
+    pub removed: Vec<EnvDiffEntry>,
+    pub unchanged: usize,
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::team::TeamRole;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct UserInvite {
+    pub id: String,
+    pub team_id: String,
+    pub email: String,
+    pub name: Option<String>,
+    /// Stored in the same form as `team_members.role`
+    pub role: String,
+    pub token_hash: String,
+    pub invited_by: Option<String>,
+    pub expires_at: String,
+    pub accepted_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserInviteResponse {
+    pub id: String,
+    pub team_id: String,
+    pub email: String,
+    pub name: Option<String>,
+    pub role: TeamRole,
+    pub invited_by: Option<String>,
+    pub expires_at: String,
+    pub created_at: String,
+}
+
+impl From<UserInvite> for UserInviteResponse {
+    fn from(invite: UserInvite) -> Self {
+        Self {
+            id: invite.id,
+            team_id: invite.team_id,
+            email: invite.email,
+            name: invite.name,
+            role: TeamRole::from(invite.role),
+            invited_by: invite.invited_by,
+            expires_at: invite.expires_at,
+            created_at: invite.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateInviteRequest {
+    pub email: String,
+    pub name: Option<String>,
+    pub role: TeamRole,
+}
+
+/// Returned once on creation; `token` is not stored and cannot be shown again
+#[derive(Debug, Serialize)]
+pub struct CreatedInvite {
+    pub token: String,
+    #[serde(flatten)]
+    pub info: UserInviteResponse,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InviteTokenRequest {
+    pub token: String,
+}
+
+/// What the invitee sees before accepting
+#[derive(Debug, Serialize)]
+pub struct InviteDetails {
+    pub email: String,
+    pub name: Option<String>,
+    pub team_name: String,
+    pub role: TeamRole,
+    pub expires_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AcceptInviteRequest {
+    pub token: String,
+    pub name: Option<String>,
+    pub password: String,
+}
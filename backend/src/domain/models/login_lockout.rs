@@ -0,0 +1,10 @@
+use sqlx::FromRow;
+
+/// Failed login counter for an account or client IP
+#[derive(Debug, Clone, FromRow)]
+pub struct LoginLockout {
+    pub key: String,
+    pub failures: i64,
+    pub locked_until: Option<String>,
+    pub last_failure_at: String,
+}
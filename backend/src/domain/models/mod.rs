@@ -1,26 +1,73 @@
+pub mod activity_log;
+pub mod api_token;
 pub mod deployment_log;
 pub mod dns;
 pub mod domain;
+pub mod domain_middleware;
+pub mod env_group;
 pub mod environment;
+pub mod invite;
+pub mod login_lockout;
+pub mod oidc;
+pub mod permission;
 pub mod registry;
 pub mod resource;
+pub mod session;
 pub mod stack;
 pub mod system;
 pub mod team;
 pub mod template;
+pub mod two_factor;
 pub mod user;
 
+pub use activity_log::{ActivityLog, AuditLogEntry, AuditLogPage, AuditLogQuery};
+pub use api_token::{
+    API_SCOPES, API_TOKEN_PREFIX, ApiToken, ApiTokenResponse, BOT_USER_ROLE, CreateApiTokenRequest,
+    CreatedApiToken, ScopeRequirement, required_scope, scope_granted,
+};
 pub use deployment_log::{DeploymentLog, DeploymentLogResponse};
 // dns re-exports removed
-pub use domain::{CreateDomain, Domain, DomainProvider, DomainResponse, DomainType};
-pub use environment::{BulkSetEnvVarRequest, SetEnvVarRequest, StackEnvVar, StackEnvVarResponse};
+pub use domain::{
+    CertificateStatus, CreateDomain, CreateDomainRoute, CreateL4Route, Domain, DomainProvider,
+    DomainResponse, DomainRoute, DomainRouteResponse, DomainType, L4Route, UpdateDomainTls,
+    UpstreamSettings,
+};
+pub use domain_middleware::UpdateDomainMiddleware;
+pub use env_group::{
+    AttachEnvGroupsRequest, CreateEnvGroupRequest, EffectiveEnvVar, EnvGroup, EnvGroupVar,
+    EnvGroupVarResponse, SetEnvGroupVarRequest,
+};
+pub use environment::{
+    BulkSetEnvVarRequest, DotenvImportRequest, ENV_SOURCE_COMPOSE, ENV_SOURCE_UI, EnvDiff,
+    EnvDiffEntry, SECRET_MASK, SetEnvVarRequest, StackEnvVar, StackEnvVarResponse,
+};
+pub use invite::{
+    AcceptInviteRequest, CreateInviteRequest, CreatedInvite, InviteDetails, InviteTokenRequest,
+    UserInvite, UserInviteResponse,
+};
+pub use login_lockout::LoginLockout;
+pub use oidc::{LoginOptions, OidcCallbackQuery, OidcIdentity, TeamMapping, UserIdentity};
+pub use permission::{
+    AssignCustomRoleRequest, CustomRole, CustomRoleResponse, PermissionSet, SaveCustomRoleRequest,
+    SaveStackGrantRequest, StackGrant, StackGrantEntry, StackGrantResponse,
+};
 pub use registry::{CreateRegistryCredential, RegistryCredential, RegistryCredentialResponse};
 pub use resource::{ContainerResource, ResourceMetric};
+pub use session::{RefreshTokenRequest, Session, SessionResponse, StreamTicket};
 pub use stack::{
-    BuildLogMessage, ContainerHealth, CreateStack, Stack, StackBackup, StackHealth, StackLogEntry,
-    StackResponse,
+    BackupEnvVar, BuildLogMessage, BulkRedeployResult, ContainerHealth, CreateStack,
+    ERROR_PAGE_STATUSES, PushedImage, RedeployFailure, RestoreSecret, Stack, StackBackup,
+    StackHealth, StackLogEntry, StackResponse,
 };
 pub use system::{LoadAverage, SystemStats};
 pub use team::{CreateTeamRequest, Team, TeamMember, TeamResponse, TeamRole};
 pub use template::{Template, TemplateEnv, TemplateResponse};
-pub use user::{AuthResponse, CreateUser, LoginRequest, User, UserResponse};
+pub use two_factor::{
+    LoginResponse, MfaLoginRequest, RecoveryCode, RecoveryCodes, TeamTwoFactorPolicyRequest,
+    TotpEnrollment, TwoFactorCodeRequest, TwoFactorStatus, UserTotp,
+};
+pub use user::{
+    ADMIN_ROLE, AuthResponse, ChangePasswordRequest, CreateUser, LoginRequest, MIN_PASSWORD_LENGTH,
+    NO_PASSWORD_HASH, PasswordReset, PasswordResetLink, ResetPasswordRequest, USER_ROLE,
+    UpdateProfileRequest, User, UserResponse, requires_system_admin,
+};
@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::team::TeamRole;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct UserIdentity {
+    pub id: String,
+    pub user_id: String,
+    pub issuer: String,
+    pub subject: String,
+    pub created_at: String,
+    pub last_login_at: Option<String>,
+}
+
+/// Verified identity returned by the OIDC provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OidcIdentity {
+    pub issuer: String,
+    pub subject: String,
+    pub email: String,
+    /// The provider vouches for the address (`email_verified: true`); only then may
+    /// it be linked to an existing account
+    pub email_verified: bool,
+    pub name: Option<String>,
+    pub groups: Vec<String>,
+}
+
+/// Maps an IdP group to a Labuh team and role
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TeamMapping {
+    pub group: String,
+    pub team: String,
+    pub role: TeamRole,
+}
+
+impl TeamMapping {
+    /// Parse `group=Team Name:Role` pairs separated by commas
+    pub fn parse_list(input: &str) -> Result<Vec<Self>, String> {
+        input
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (group, target) = entry
+                    .split_once('=')
+                    .ok_or_else(|| format!("Invalid team mapping '{}'", entry))?;
+                let (team, role) = target
+                    .rsplit_once(':')
+                    .ok_or_else(|| format!("Missing role in team mapping '{}'", entry))?;
+                let role = match role.trim().to_ascii_lowercase().as_str() {
+                    "admin" => TeamRole::Admin,
+                    "developer" => TeamRole::Developer,
+                    "viewer" => TeamRole::Viewer,
+                    other => return Err(format!("Unsupported role '{}' in team mapping", other)),
+                };
+                Ok(Self {
+                    group: group.trim().to_string(),
+                    team: team.trim().to_string(),
+                    role,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Login methods offered on the login page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginOptions {
+    pub password_enabled: bool,
+    /// Display name of the SSO provider, when configured
+    pub sso_provider: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OidcCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_team_mapping() {
+        let mappings =
+            TeamMapping::parse_list("platform=Platform Team:Admin, devs = Web:developer,").unwrap();
+        assert_eq!(
+            mappings,
+            vec![
+                TeamMapping {
+                    group: "platform".to_string(),
+                    team: "Platform Team".to_string(),
+                    role: TeamRole::Admin,
+                },
+                TeamMapping {
+                    group: "devs".to_string(),
+                    team: "Web".to_string(),
+                    role: TeamRole::Developer,
+                },
+            ]
+        );
+
+        assert!(TeamMapping::parse_list("ops=Ops:Owner").is_err());
+        assert!(TeamMapping::parse_list("ops").is_err());
+    }
+}
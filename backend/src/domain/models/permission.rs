@@ -0,0 +1,384 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::BTreeSet;
+
+use super::team::TeamRole;
+
+pub const TEAM_VIEW: &str = "team.view";
+pub const TEAM_MEMBERS_MANAGE: &str = "team.members.manage";
+/// Custom roles and per-stack grants
+pub const TEAM_ROLES_MANAGE: &str = "team.roles.manage";
+/// Team settings such as the two-factor policy
+pub const TEAM_SETTINGS_MANAGE: &str = "team.settings.manage";
+pub const TEAM_TOKENS_MANAGE: &str = "team.tokens.manage";
+pub const TEAM_DELETE: &str = "team.delete";
+pub const AUDIT_READ: &str = "audit.read";
+
+pub const STACK_VIEW: &str = "stack.view";
+pub const STACK_CREATE: &str = "stack.create";
+/// Compose, automation, registry push and webhook settings
+pub const STACK_EDIT: &str = "stack.edit";
+/// Start, stop, build, redeploy, rollback and scale
+pub const STACK_DEPLOY: &str = "stack.deploy";
+pub const STACK_DELETE: &str = "stack.delete";
+/// Open a terminal inside the stack's containers
+pub const STACK_EXEC: &str = "stack.exec";
+
+/// Read variables; secret values stay masked
+pub const ENV_READ: &str = "env.read";
+pub const ENV_WRITE: &str = "env.write";
+/// Reveal and export secret values
+pub const ENV_SECRET_READ: &str = "env.secret.read";
+/// Create and delete team environment groups
+pub const ENV_GROUP_MANAGE: &str = "env_group.manage";
+
+pub const DOMAIN_MANAGE: &str = "domain.manage";
+pub const DNS_MANAGE: &str = "dns.manage";
+pub const RESOURCE_MANAGE: &str = "resource.manage";
+pub const REGISTRY_MANAGE: &str = "registry.manage";
+
+pub const PERMISSIONS: &[&str] = &[
+    TEAM_VIEW,
+    TEAM_MEMBERS_MANAGE,
+    TEAM_ROLES_MANAGE,
+    TEAM_SETTINGS_MANAGE,
+    TEAM_TOKENS_MANAGE,
+    TEAM_DELETE,
+    AUDIT_READ,
+    STACK_VIEW,
+    STACK_CREATE,
+    STACK_EDIT,
+    STACK_DEPLOY,
+    STACK_DELETE,
+    STACK_EXEC,
+    ENV_READ,
+    ENV_WRITE,
+    ENV_SECRET_READ,
+    ENV_GROUP_MANAGE,
+    DOMAIN_MANAGE,
+    DNS_MANAGE,
+    RESOURCE_MANAGE,
+    REGISTRY_MANAGE,
+];
+
+/// Permissions that make sense on a single stack and may be given as a stack grant
+pub const STACK_PERMISSIONS: &[&str] = &[
+    STACK_VIEW,
+    STACK_EDIT,
+    STACK_DEPLOY,
+    STACK_DELETE,
+    STACK_EXEC,
+    ENV_READ,
+    ENV_WRITE,
+    ENV_SECRET_READ,
+    DOMAIN_MANAGE,
+    RESOURCE_MANAGE,
+];
+
+const VIEWER_PERMISSIONS: &[&str] = &[TEAM_VIEW, STACK_VIEW, ENV_READ];
+
+const DEVELOPER_PERMISSIONS: &[&str] = &[
+    STACK_CREATE,
+    STACK_EDIT,
+    STACK_DEPLOY,
+    STACK_DELETE,
+    STACK_EXEC,
+    ENV_WRITE,
+    DOMAIN_MANAGE,
+    RESOURCE_MANAGE,
+];
+
+const ADMIN_PERMISSIONS: &[&str] = &[
+    TEAM_MEMBERS_MANAGE,
+    TEAM_ROLES_MANAGE,
+    TEAM_SETTINGS_MANAGE,
+    TEAM_TOKENS_MANAGE,
+    AUDIT_READ,
+    ENV_SECRET_READ,
+    ENV_GROUP_MANAGE,
+    DNS_MANAGE,
+    REGISTRY_MANAGE,
+];
+
+const OWNER_PERMISSIONS: &[&str] = &[TEAM_DELETE];
+
+impl TeamRole {
+    /// Ordering used when one member acts on another: Owner > Admin > Developer > Viewer
+    pub fn rank(&self) -> u8 {
+        match self {
+            TeamRole::Owner => 4,
+            TeamRole::Admin => 3,
+            TeamRole::Developer => 2,
+            TeamRole::Viewer => 1,
+        }
+    }
+
+    /// Permissions of a built-in role; each role includes everything below it
+    pub fn permissions(&self) -> Vec<&'static str> {
+        let tiers = [
+            VIEWER_PERMISSIONS,
+            DEVELOPER_PERMISSIONS,
+            ADMIN_PERMISSIONS,
+            OWNER_PERMISSIONS,
+        ];
+        tiers[..self.rank() as usize]
+            .iter()
+            .flat_map(|tier| tier.iter().copied())
+            .collect()
+    }
+}
+
+/// Reject unknown permission strings and return the list without duplicates
+pub fn validate_permissions(
+    permissions: &[String],
+    allowed: &[&str],
+) -> Result<Vec<String>, String> {
+    let mut seen = BTreeSet::new();
+    for permission in permissions {
+        let permission = permission.trim();
+        if !allowed.contains(&permission) {
+            return Err(format!("Unknown permission '{}'", permission));
+        }
+        seen.insert(permission.to_string());
+    }
+    Ok(seen.into_iter().collect())
+}
+
+/// A named set of permissions defined by a team and assigned to members on top of
+/// their built-in role
+#[derive(Debug, Clone, FromRow)]
+pub struct CustomRole {
+    pub id: String,
+    pub team_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Space-separated list of permissions
+    pub permissions: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl CustomRole {
+    pub fn permission_list(&self) -> Vec<String> {
+        self.permissions
+            .split_whitespace()
+            .map(String::from)
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CustomRoleResponse {
+    pub id: String,
+    pub team_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub permissions: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<CustomRole> for CustomRoleResponse {
+    fn from(role: CustomRole) -> Self {
+        Self {
+            permissions: role.permission_list(),
+            id: role.id,
+            team_id: role.team_id,
+            name: role.name,
+            description: role.description,
+            created_at: role.created_at,
+            updated_at: role.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveCustomRoleRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssignCustomRoleRequest {
+    /// `None` removes the member's custom role
+    pub role_id: Option<String>,
+}
+
+/// Extra permissions for one user on one stack. The user does not need to be a
+/// member of the stack's team.
+#[derive(Debug, Clone, FromRow)]
+pub struct StackGrant {
+    pub stack_id: String,
+    pub user_id: String,
+    /// Space-separated list of permissions
+    pub permissions: String,
+    pub granted_by: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl StackGrant {
+    pub fn permission_list(&self) -> Vec<String> {
+        self.permissions
+            .split_whitespace()
+            .map(String::from)
+            .collect()
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct StackGrantEntry {
+    pub stack_id: String,
+    pub user_id: String,
+    pub user_email: String,
+    pub user_name: Option<String>,
+    pub permissions: String,
+    pub granted_by: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StackGrantResponse {
+    pub stack_id: String,
+    pub user_id: String,
+    pub user_email: String,
+    pub user_name: Option<String>,
+    pub permissions: Vec<String>,
+    pub granted_by: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<StackGrantEntry> for StackGrantResponse {
+    fn from(entry: StackGrantEntry) -> Self {
+        Self {
+            permissions: entry
+                .permissions
+                .split_whitespace()
+                .map(String::from)
+                .collect(),
+            stack_id: entry.stack_id,
+            user_id: entry.user_id,
+            user_email: entry.user_email,
+            user_name: entry.user_name,
+            granted_by: entry.granted_by,
+            created_at: entry.created_at,
+            updated_at: entry.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveStackGrantRequest {
+    pub email: String,
+    pub permissions: Vec<String>,
+}
+
+/// What a user may do in a team, or on one stack
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionSet {
+    /// Built-in role, `None` when access comes only from a stack grant
+    pub role: Option<TeamRole>,
+    pub custom_role: Option<String>,
+    pub permissions: BTreeSet<String>,
+}
+
+impl PermissionSet {
+    /// Union of the built-in role, the custom role and the stack grant
+    pub fn resolve(
+        role: Option<TeamRole>,
+        custom_role: Option<&CustomRole>,
+        grant: Option<&StackGrant>,
+    ) -> Self {
+        let mut permissions: BTreeSet<String> = role
+            .iter()
+            .flat_map(|r| r.permissions())
+            .map(String::from)
+            .collect();
+        if role.is_some() {
+            permissions.extend(custom_role.iter().flat_map(|c| c.permission_list()));
+        }
+        if let Some(grant) = grant {
+            permissions.insert(STACK_VIEW.to_string());
+            permissions.extend(grant.permission_list());
+        }
+        Self {
+            role,
+            custom_role: custom_role.map(|c| c.name.clone()),
+            permissions,
+        }
+    }
+
+    pub fn contains(&self, permission: &str) -> bool {
+        self.permissions.contains(permission)
+    }
+
+    /// True when every permission in `others` is also in this set
+    pub fn covers<'a>(&self, others: impl IntoIterator<Item = &'a str>) -> bool {
+        others.into_iter().all(|p| self.contains(p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grant(permissions: &str) -> StackGrant {
+        StackGrant {
+            stack_id: "s1".to_string(),
+            user_id: "u1".to_string(),
+            permissions: permissions.to_string(),
+            granted_by: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn resolves_roles_custom_roles_and_grants() {
+        let viewer = PermissionSet::resolve(Some(TeamRole::Viewer), None, None);
+        assert!(viewer.contains(STACK_VIEW));
+        assert!(!viewer.contains(STACK_DEPLOY));
+
+        let admin = PermissionSet::resolve(Some(TeamRole::Admin), None, None);
+        assert!(admin.contains(STACK_DEPLOY) && admin.contains(ENV_SECRET_READ));
+        assert!(!admin.contains(TEAM_DELETE));
+        assert!(PermissionSet::resolve(Some(TeamRole::Owner), None, None).contains(TEAM_DELETE));
+
+        let deployer = CustomRole {
+            id: "r1".to_string(),
+            team_id: "t1".to_string(),
+            name: "Deployer".to_string(),
+            description: None,
+            permissions: format!("{} {}", STACK_DEPLOY, AUDIT_READ),
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let custom = PermissionSet::resolve(Some(TeamRole::Viewer), Some(&deployer), None);
+        assert!(custom.contains(STACK_DEPLOY) && custom.contains(AUDIT_READ));
+        assert!(!custom.contains(STACK_EXEC));
+
+        // A grant alone gives access to that stack, and always includes viewing it
+        let contractor = PermissionSet::resolve(None, None, Some(&grant(STACK_DEPLOY)));
+        assert!(contractor.contains(STACK_VIEW) && contractor.contains(STACK_DEPLOY));
+        assert!(!contractor.contains(ENV_READ));
+        assert!(
+            PermissionSet::resolve(None, None, None)
+                .permissions
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn validate_rejects_unknown_permissions() {
+        let ok = validate_permissions(
+            &[STACK_DEPLOY.to_string(), STACK_DEPLOY.to_string()],
+            PERMISSIONS,
+        );
+        assert_eq!(ok.unwrap(), vec![STACK_DEPLOY.to_string()]);
+        assert!(validate_permissions(&["stack.everything".to_string()], PERMISSIONS).is_err());
+        assert!(validate_permissions(&[TEAM_DELETE.to_string()], STACK_PERMISSIONS).is_err());
+    }
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Session {
+    pub id: String,
+    pub user_id: String,
+    pub refresh_token_hash: String,
+    pub user_agent: Option<String>,
+    pub expires_at: String,
+    pub last_used_at: Option<String>,
+    pub revoked_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionResponse {
+    pub id: String,
+    pub user_agent: Option<String>,
+    pub expires_at: String,
+    pub last_used_at: Option<String>,
+    pub created_at: String,
+    /// Whether this is the session making the request
+    pub current: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+/// Short-lived single-use credential for WebSocket and SSE connections, which
+/// cannot send an `Authorization` header from the browser
+#[derive(Debug, Serialize)]
+pub struct StreamTicket {
+    pub ticket: String,
+    pub expires_in: u64,
+}
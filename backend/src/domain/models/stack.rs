@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Stack {
@@ -17,10 +18,29 @@ pub struct Stack {
     pub git_url: Option<String>,
     pub git_branch: Option<String>,
     pub last_commit_hash: Option<String>,
+    pub push_registry_id: Option<String>,
+    pub push_namespace: Option<String>,
+    pub push_retention: i32,
+    pub env_conflict_policy: String,
+    /// Serve the maintenance page instead of proxying to the stack's containers
+    pub maintenance_mode: bool,
+    /// Custom maintenance page HTML; the built-in page when `None`
+    pub maintenance_page: Option<String>,
+    /// JSON object of status code to custom error page HTML
+    pub error_pages: String,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// Upstream failures that can be answered with a custom error page
+pub const ERROR_PAGE_STATUSES: [u16; 3] = [502, 503, 504];
+
+impl Stack {
+    pub fn error_pages(&self) -> BTreeMap<u16, String> {
+        serde_json::from_str(&self.error_pages).unwrap_or_default()
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateStack {
     pub name: String,
@@ -45,6 +65,13 @@ pub struct StackResponse {
     pub git_url: Option<String>,
     pub git_branch: Option<String>,
     pub last_commit_hash: Option<String>,
+    pub push_registry_id: Option<String>,
+    pub push_namespace: Option<String>,
+    pub push_retention: i32,
+    pub env_conflict_policy: String,
+    pub maintenance_mode: bool,
+    pub maintenance_page: Option<String>,
+    pub error_pages: BTreeMap<u16, String>,
     pub container_count: i64,
     pub created_at: String,
     pub updated_at: String,
@@ -52,6 +79,7 @@ pub struct StackResponse {
 
 impl From<Stack> for StackResponse {
     fn from(s: Stack) -> Self {
+        let error_pages = s.error_pages();
         Self {
             id: s.id,
             name: s.name,
@@ -67,6 +95,13 @@ impl From<Stack> for StackResponse {
             git_url: s.git_url,
             git_branch: s.git_branch,
             last_commit_hash: s.last_commit_hash,
+            push_registry_id: s.push_registry_id,
+            push_namespace: s.push_namespace,
+            push_retention: s.push_retention,
+            env_conflict_policy: s.env_conflict_policy,
+            maintenance_mode: s.maintenance_mode,
+            maintenance_page: s.maintenance_page,
+            error_pages,
             container_count: 0, // Will be populated by service
             created_at: s.created_at,
             updated_at: s.updated_at,
@@ -92,6 +127,19 @@ pub struct ContainerHealth {
     pub status: String,
 }
 
+/// Outcome of redeploying several stacks at once
+#[derive(Debug, Default, Serialize)]
+pub struct BulkRedeployResult {
+    pub redeployed: Vec<String>,
+    pub failed: Vec<RedeployFailure>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RedeployFailure {
+    pub stack_id: String,
+    pub error: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct StackLogEntry {
     pub container: String,
@@ -103,6 +151,9 @@ pub struct StackBackup {
     pub name: String,
     pub compose_content: String,
     pub env_vars: Vec<BackupEnvVar>,
+    /// Set when secret values are encrypted with a backup passphrase
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secrets_salt: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -111,7 +162,27 @@ pub struct BackupEnvVar {
     pub key: String,
     pub value: String,
     pub is_secret: bool,
+    /// The secret value was left out and must be supplied on restore
+    #[serde(default)]
+    pub omitted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RestoreSecret {
+    pub container_name: String,
+    pub key: String,
+    pub value: String,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PushedImage {
+    pub id: String,
+    pub stack_id: String,
+    pub service_name: String,
+    pub image_ref: String,
+    pub pushed_at: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BuildLogMessage {
     pub stack_id: String,
@@ -6,6 +6,8 @@ use std::fmt;
 pub struct Team {
     pub id: String,
     pub name: String,
+    /// Owners and Admins must have two-factor authentication enabled
+    pub require_2fa: bool,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -50,6 +52,7 @@ pub struct TeamMember {
     pub user_name: Option<String>,
     pub user_email: String,
     pub role: String,
+    pub custom_role_id: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -63,4 +66,7 @@ pub struct CreateTeamRequest {
 pub struct TeamResponse {
     pub team: Team,
     pub role: TeamRole,
+    pub custom_role: Option<String>,
+    /// Effective permissions of the requesting user in this team
+    pub permissions: Vec<String>,
 }
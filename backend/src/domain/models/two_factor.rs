@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::user::AuthResponse;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct UserTotp {
+    pub user_id: String,
+    /// Encrypted base32 secret
+    pub secret: String,
+    /// Set once the user confirmed a first code; until then enrollment is pending
+    pub enabled_at: Option<String>,
+    pub last_used_step: Option<i64>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct RecoveryCode {
+    pub id: String,
+    pub user_id: String,
+    pub code_hash: String,
+    pub used_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TwoFactorStatus {
+    pub enabled: bool,
+    pub recovery_codes_remaining: i64,
+    /// A team the user administers requires 2FA
+    pub required: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TwoFactorCodeRequest {
+    /// TOTP code or an unused recovery code
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MfaLoginRequest {
+    pub mfa_token: String,
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TeamTwoFactorPolicyRequest {
+    pub require_2fa: bool,
+}
+
+/// Result of a first-factor login: either a session, or a challenge for the second factor
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Authenticated(AuthResponse),
+    MfaRequired {
+        mfa_required: bool,
+        mfa_token: String,
+    },
+}
@@ -1,6 +1,19 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+/// Password hash of accounts that cannot log in with a password (SSO and bot users)
+pub const NO_PASSWORD_HASH: &str = "!";
+
+/// Minimum length for passwords chosen through invites, resets and password changes
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Account role of Labuh administrators. Unlike team roles it applies to the whole
+/// installation: user management and operations on the host itself.
+pub const ADMIN_ROLE: &str = "admin";
+
+/// Role of regular accounts; what they can do comes from their team roles
+pub const USER_ROLE: &str = "user";
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct User {
     pub id: String,
@@ -11,6 +24,38 @@ pub struct User {
     pub role: String,
     pub created_at: String,
     pub updated_at: String,
+    /// Set while the account is disabled; deactivated users cannot sign in
+    pub deactivated_at: Option<String>,
+}
+
+impl User {
+    pub fn is_active(&self) -> bool {
+        self.deactivated_at.is_none()
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.role == ADMIN_ROLE
+    }
+}
+
+/// Whether a protected API route (with or without the `/api` prefix) acts on the
+/// host rather than on a team's resources, and is therefore reserved for administrators
+pub fn requires_system_admin(method: &str, path: &str) -> bool {
+    let segments: Vec<&str> = path
+        .trim_start_matches("/api")
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    let read = method == "GET" || method == "HEAD";
+
+    match segments.as_slice() {
+        // Listing and inspecting images is harmless; pulling and removing affect every team
+        ["images", ..] => !read,
+        ["nodes", ..] | ["networks", ..] | ["metrics", "nodes", ..] => true,
+        // Templates are a catalog shared by all teams
+        ["templates", ..] => !read,
+        _ => false,
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -29,6 +74,9 @@ pub struct LoginRequest {
 #[derive(Debug, Serialize)]
 pub struct AuthResponse {
     pub token: String,
+    pub refresh_token: String,
+    /// Seconds until `token` expires
+    pub expires_in: i64,
     pub user: UserResponse,
 }
 
@@ -39,6 +87,7 @@ pub struct UserResponse {
     pub name: Option<String>,
     pub role: String,
     pub created_at: String,
+    pub deactivated_at: Option<String>,
 }
 
 impl From<User> for UserResponse {
@@ -49,6 +98,83 @@ impl From<User> for UserResponse {
             name: user.name,
             role: user.role,
             created_at: user.created_at,
+            deactivated_at: user.deactivated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateProfileRequest {
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct PasswordReset {
+    pub id: String,
+    pub user_id: String,
+    pub token_hash: String,
+    pub created_by: Option<String>,
+    pub expires_at: String,
+    pub used_at: Option<String>,
+    pub created_at: String,
+}
+
+/// Reset link handed to the administrator to pass on; the token is shown only once
+#[derive(Debug, Serialize)]
+pub struct PasswordResetLink {
+    pub token: String,
+    pub expires_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub password: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requires_system_admin() {
+        let host_routes = [
+            ("POST", "/api/images/pull"),
+            ("DELETE", "/api/images/sha256:abc"),
+            ("GET", "/api/nodes"),
+            ("GET", "/api/nodes/n1"),
+            ("GET", "/api/nodes/swarm"),
+            ("POST", "/api/nodes/swarm/init"),
+            ("POST", "/api/nodes/swarm/join"),
+            ("GET", "/api/nodes/swarm/tokens"),
+            ("GET", "/api/nodes/terminal"),
+            ("GET", "/api/networks/topology"),
+            ("GET", "/api/metrics/nodes/metrics"),
+            ("POST", "/api/templates"),
+            ("POST", "/api/templates/import"),
+            ("DELETE", "/api/templates/t1"),
+        ];
+        for (method, path) in host_routes {
+            assert!(requires_system_admin(method, path), "{} {}", method, path);
+        }
+
+        let team_routes = [
+            ("GET", "/api/images"),
+            ("GET", "/api/images/nginx/inspect"),
+            ("GET", "/api/templates"),
+            ("GET", "/api/metrics/stacks/s1/containers/c1/metrics"),
+            ("POST", "/api/stacks/s1/redeploy"),
+            ("GET", "/api/containers/c1/exec"),
+            ("GET", "/api/users"),
+        ];
+        for (method, path) in team_routes {
+            assert!(!requires_system_admin(method, path), "{} {}", method, path);
         }
     }
 }
@@ -0,0 +1,25 @@
+use crate::domain::models::OidcIdentity;
+use crate::error::Result;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait OidcProvider: Send + Sync {
+    /// Issuer identifier, used to key linked identities
+    fn issuer(&self) -> &str;
+
+    /// URL of the provider's authorization endpoint for an authorization code + PKCE request
+    async fn authorization_url(
+        &self,
+        state: &str,
+        nonce: &str,
+        code_challenge: &str,
+    ) -> Result<String>;
+
+    /// Exchange an authorization code and return the verified identity from the ID token
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<OidcIdentity>;
+}
@@ -0,0 +1,20 @@
+use crate::domain::models::CustomRole;
+use crate::error::Result;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait RoleRepository: Send + Sync {
+    async fn list_by_team(&self, team_id: &str) -> Result<Vec<CustomRole>>;
+    async fn find_by_id(&self, id: &str) -> Result<Option<CustomRole>>;
+    async fn save(&self, role: &CustomRole) -> Result<()>;
+    async fn delete(&self, id: &str) -> Result<()>;
+
+    /// Custom role assigned to a team member, if any
+    async fn find_member_role(&self, team_id: &str, user_id: &str) -> Result<Option<CustomRole>>;
+    async fn set_member_role(
+        &self,
+        team_id: &str,
+        user_id: &str,
+        role_id: Option<&str>,
+    ) -> Result<()>;
+}
@@ -4,6 +4,8 @@ use async_trait::async_trait;
 #[async_trait]
 pub trait RuntimePort: Send + Sync {
     async fn pull_image(&self, image: &str, credentials: Option<(String, String)>) -> Result<()>;
+    async fn tag_image(&self, source: &str, target: &str) -> Result<()>;
+    async fn push_image(&self, image: &str, credentials: Option<(String, String)>) -> Result<()>;
     async fn create_container(&self, config: ContainerConfig) -> Result<String>;
     async fn start_container(&self, id: &str) -> Result<()>;
     async fn stop_container(&self, id: &str) -> Result<()>;
@@ -51,6 +53,24 @@ pub trait RuntimePort: Send + Sync {
     async fn update_service(&self, config: ServiceConfig) -> Result<()>;
     async fn inspect_service(&self, name: &str) -> Result<Option<ServiceInfo>>;
     async fn update_service_scale(&self, service_name: &str, replicas: u64) -> Result<()>;
+
+    // Swarm Secrets & Configs
+    async fn create_secret(
+        &self,
+        name: &str,
+        data: &[u8],
+        labels: std::collections::HashMap<String, String>,
+    ) -> Result<String>;
+    async fn list_secrets(&self) -> Result<Vec<SwarmObjectInfo>>;
+    async fn remove_secret(&self, id: &str) -> Result<()>;
+    async fn create_config(
+        &self,
+        name: &str,
+        data: &[u8],
+        labels: std::collections::HashMap<String, String>,
+    ) -> Result<String>;
+    async fn list_configs(&self) -> Result<Vec<SwarmObjectInfo>>;
+    async fn remove_config(&self, id: &str) -> Result<()>;
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -65,6 +85,31 @@ pub struct ServiceConfig {
     pub cpu_limit: Option<f64>,
     pub memory_limit: Option<i64>,
     pub constraints: Vec<String>,
+    /// Registry credentials forwarded to the swarm so worker nodes can pull `image`
+    #[serde(skip)]
+    pub registry_auth: Option<(String, String)>,
+    #[serde(default)]
+    pub secrets: Vec<ServiceFileMount>,
+    #[serde(default)]
+    pub configs: Vec<ServiceFileMount>,
+}
+
+/// A swarm secret or config attached to a service's tasks
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ServiceFileMount {
+    pub object_id: String,
+    pub object_name: String,
+    pub target: String,
+    pub uid: Option<String>,
+    pub gid: Option<String>,
+    pub mode: Option<u32>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SwarmObjectInfo {
+    pub id: String,
+    pub name: String,
+    pub labels: std::collections::HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -191,3 +236,163 @@ pub struct EndpointInfo {
     pub ipv4_address: String,
     pub ipv6_address: String,
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::error::AppError;
+    use std::collections::HashMap;
+
+    /// Reports a fixed set of containers; every other call fails as if Docker
+    /// were unreachable
+    #[derive(Default)]
+    pub(crate) struct FakeRuntime {
+        pub containers: Vec<ContainerInfo>,
+    }
+
+    fn unavailable<T>() -> Result<T> {
+        Err(AppError::Internal(
+            "No container runtime in tests".to_string(),
+        ))
+    }
+
+    #[async_trait]
+    impl RuntimePort for FakeRuntime {
+        async fn pull_image(&self, _: &str, _: Option<(String, String)>) -> Result<()> {
+            unavailable()
+        }
+        async fn tag_image(&self, _: &str, _: &str) -> Result<()> {
+            unavailable()
+        }
+        async fn push_image(&self, _: &str, _: Option<(String, String)>) -> Result<()> {
+            unavailable()
+        }
+        async fn create_container(&self, _: ContainerConfig) -> Result<String> {
+            unavailable()
+        }
+        async fn start_container(&self, _: &str) -> Result<()> {
+            unavailable()
+        }
+        async fn stop_container(&self, _: &str) -> Result<()> {
+            unavailable()
+        }
+        async fn restart_container(&self, _: &str) -> Result<()> {
+            unavailable()
+        }
+        async fn remove_container(&self, _: &str, _: bool) -> Result<()> {
+            unavailable()
+        }
+        async fn list_containers(&self, _: bool) -> Result<Vec<ContainerInfo>> {
+            Ok(self.containers.clone())
+        }
+        async fn inspect_container(&self, _: &str) -> Result<ContainerInfo> {
+            unavailable()
+        }
+        async fn get_logs(&self, _: &str, _: usize) -> Result<Vec<String>> {
+            unavailable()
+        }
+        async fn get_stats(&self, _: &str) -> Result<ContainerStats> {
+            unavailable()
+        }
+        async fn build_image(
+            &self,
+            _: &str,
+            _: &str,
+            _: &str,
+        ) -> Result<tokio_stream::wrappers::ReceiverStream<Result<String>>> {
+            unavailable()
+        }
+        async fn exec_command(
+            &self,
+            _: &str,
+            _: Vec<String>,
+        ) -> Result<bollard::exec::CreateExecResults> {
+            unavailable()
+        }
+        async fn connect_exec(&self, _: &str) -> Result<bollard::exec::StartExecResults> {
+            unavailable()
+        }
+        async fn list_images(&self) -> Result<Vec<ImageInfo>> {
+            unavailable()
+        }
+        async fn remove_image(&self, _: &str, _: bool) -> Result<()> {
+            unavailable()
+        }
+        async fn inspect_image(&self, _: &str) -> Result<ImageInspect> {
+            unavailable()
+        }
+        async fn ensure_network(&self, _: &str) -> Result<()> {
+            unavailable()
+        }
+        async fn connect_network(&self, _: &str, _: &str) -> Result<()> {
+            unavailable()
+        }
+        async fn list_networks(&self) -> Result<Vec<NetworkInfo>> {
+            unavailable()
+        }
+        async fn migrate_network_to_overlay(&self, _: &str) -> Result<()> {
+            unavailable()
+        }
+        async fn is_swarm_enabled(&self) -> Result<bool> {
+            unavailable()
+        }
+        async fn swarm_init(&self, _: &str) -> Result<String> {
+            unavailable()
+        }
+        async fn swarm_join(&self, _: &str, _: &str, _: &str) -> Result<()> {
+            unavailable()
+        }
+        async fn list_nodes(&self) -> Result<Vec<SwarmNode>> {
+            unavailable()
+        }
+        async fn inspect_node(&self, _: &str) -> Result<SwarmNode> {
+            unavailable()
+        }
+        async fn get_swarm_tokens(&self) -> Result<SwarmTokens> {
+            unavailable()
+        }
+        async fn create_service(&self, _: ServiceConfig) -> Result<String> {
+            unavailable()
+        }
+        async fn remove_service(&self, _: &str) -> Result<()> {
+            unavailable()
+        }
+        async fn update_service(&self, _: ServiceConfig) -> Result<()> {
+            unavailable()
+        }
+        async fn inspect_service(&self, _: &str) -> Result<Option<ServiceInfo>> {
+            unavailable()
+        }
+        async fn update_service_scale(&self, _: &str, _: u64) -> Result<()> {
+            unavailable()
+        }
+        async fn create_secret(
+            &self,
+            _: &str,
+            _: &[u8],
+            _: HashMap<String, String>,
+        ) -> Result<String> {
+            unavailable()
+        }
+        async fn list_secrets(&self) -> Result<Vec<SwarmObjectInfo>> {
+            unavailable()
+        }
+        async fn remove_secret(&self, _: &str) -> Result<()> {
+            unavailable()
+        }
+        async fn create_config(
+            &self,
+            _: &str,
+            _: &[u8],
+            _: HashMap<String, String>,
+        ) -> Result<String> {
+            unavailable()
+        }
+        async fn list_configs(&self) -> Result<Vec<SwarmObjectInfo>> {
+            unavailable()
+        }
+        async fn remove_config(&self, _: &str) -> Result<()> {
+            unavailable()
+        }
+    }
+}
@@ -0,0 +1,8 @@
+use crate::error::Result;
+
+/// Encrypts secrets before they are persisted and decrypts them at use time
+pub trait SecretCipher: Send + Sync {
+    fn encrypt(&self, plaintext: &str) -> Result<String>;
+    fn decrypt(&self, stored: &str) -> Result<String>;
+    fn is_encrypted(&self, stored: &str) -> bool;
+}
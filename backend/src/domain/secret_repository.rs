@@ -0,0 +1,30 @@
+use crate::error::Result;
+use async_trait::async_trait;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretKind {
+    RegistryPassword,
+    DnsConfig,
+    StackEnvVar,
+    EnvGroupVar,
+    /// Keyed by user ID
+    TotpSecret,
+    /// Keyed by domain ID
+    DomainCertificate,
+    DomainPrivateKey,
+}
+
+#[derive(Debug, Clone)]
+pub struct StoredSecret {
+    pub kind: SecretKind,
+    pub id: String,
+    pub value: String,
+}
+
+/// Raw access to every encrypted column, used for migration and key rotation
+#[async_trait]
+pub trait SecretRepository: Send + Sync {
+    async fn list_all(&self) -> Result<Vec<StoredSecret>>;
+    /// Writes all values in a single transaction
+    async fn update_all(&self, secrets: &[StoredSecret]) -> Result<()>;
+}
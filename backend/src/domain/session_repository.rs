@@ -0,0 +1,24 @@
+use crate::domain::models::Session;
+use crate::error::Result;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait SessionRepository: Send + Sync {
+    async fn create(&self, session: Session) -> Result<Session>;
+    async fn find_by_id(&self, id: &str) -> Result<Option<Session>>;
+    async fn find_by_refresh_hash(&self, hash: &str) -> Result<Option<Session>>;
+    /// Active (not revoked, not expired) sessions of a user
+    async fn list_active(&self, user_id: &str, now: &str) -> Result<Vec<Session>>;
+    /// Replace the refresh token of a session (rotation), provided it is still
+    /// `current_hash` and the session is not revoked. Returns whether it was replaced.
+    async fn rotate(
+        &self,
+        id: &str,
+        current_hash: &str,
+        refresh_token_hash: &str,
+        expires_at: &str,
+        used_at: &str,
+    ) -> Result<bool>;
+    async fn revoke(&self, id: &str, revoked_at: &str) -> Result<()>;
+    async fn revoke_all_for_user(&self, user_id: &str, revoked_at: &str) -> Result<()>;
+}
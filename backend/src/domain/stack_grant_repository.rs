@@ -0,0 +1,13 @@
+use crate::domain::models::{StackGrant, StackGrantEntry};
+use crate::error::Result;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait StackGrantRepository: Send + Sync {
+    async fn list_by_stack(&self, stack_id: &str) -> Result<Vec<StackGrantEntry>>;
+    async fn find(&self, stack_id: &str, user_id: &str) -> Result<Option<StackGrant>>;
+    /// Ids of stacks the user was granted access to
+    async fn list_stack_ids_for_user(&self, user_id: &str) -> Result<Vec<String>>;
+    async fn save(&self, grant: &StackGrant) -> Result<()>;
+    async fn delete(&self, stack_id: &str, user_id: &str) -> Result<()>;
+}
@@ -1,4 +1,4 @@
-use crate::domain::models::Stack;
+use crate::domain::models::{PushedImage, Stack};
 use crate::error::Result;
 use async_trait::async_trait;
 
@@ -20,6 +20,28 @@ pub trait StackRepository: Send + Sync {
     ) -> Result<()>;
     async fn update_last_stable_images(&self, id: &str, images: Option<String>) -> Result<()>;
     async fn update_git_info(&self, id: &str, commit_hash: &str) -> Result<()>;
+    async fn update_registry_push(
+        &self,
+        id: &str,
+        registry_id: Option<String>,
+        namespace: Option<String>,
+        retention: i32,
+    ) -> Result<()>;
+    async fn update_env_conflict_policy(&self, id: &str, policy: &str) -> Result<()>;
+    async fn update_maintenance_mode(&self, id: &str, enabled: bool) -> Result<()>;
+    async fn update_pages(
+        &self,
+        id: &str,
+        maintenance_page: Option<&str>,
+        error_pages: &str,
+    ) -> Result<()>;
+    async fn record_pushed_image(&self, image: PushedImage) -> Result<()>;
+    async fn list_pushed_images(
+        &self,
+        stack_id: &str,
+        service_name: Option<&str>,
+    ) -> Result<Vec<PushedImage>>;
+    async fn delete_pushed_image(&self, id: &str) -> Result<()>;
     async fn delete(&self, id: &str) -> Result<()>;
     async fn validate_webhook_token(&self, id: &str, token: &str) -> Result<Stack>;
 }
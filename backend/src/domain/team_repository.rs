@@ -5,8 +5,11 @@ use async_trait::async_trait;
 #[async_trait]
 pub trait TeamRepository: Send + Sync {
     async fn find_by_user_id(&self, user_id: &str) -> Result<Vec<Team>>;
+    async fn find_by_id(&self, id: &str) -> Result<Option<Team>>;
+    async fn find_by_name(&self, name: &str) -> Result<Option<Team>>;
     async fn save(&self, team: &Team) -> Result<()>;
     async fn delete(&self, id: &str) -> Result<()>;
+    async fn set_require_2fa(&self, team_id: &str, required: bool) -> Result<()>;
 
     // Membership
     async fn add_member(&self, team_id: &str, user_id: &str, role: TeamRole) -> Result<()>;
@@ -0,0 +1,25 @@
+use crate::domain::models::{RecoveryCode, UserTotp};
+use crate::error::Result;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait TwoFactorRepository: Send + Sync {
+    async fn find_totp(&self, user_id: &str) -> Result<Option<UserTotp>>;
+    /// Insert or replace a (pending) TOTP secret
+    async fn save_totp(&self, totp: UserTotp) -> Result<()>;
+    async fn enable_totp(&self, user_id: &str, enabled_at: &str) -> Result<()>;
+    /// Record the time step of an accepted code; false if a newer step was already used
+    async fn record_step(&self, user_id: &str, step: i64) -> Result<bool>;
+    /// Remove the TOTP secret and all recovery codes
+    async fn delete(&self, user_id: &str) -> Result<()>;
+
+    async fn replace_recovery_codes(&self, user_id: &str, codes: Vec<RecoveryCode>) -> Result<()>;
+    async fn list_unused_recovery_codes(&self, user_id: &str) -> Result<Vec<RecoveryCode>>;
+    /// Mark a code as used; false if it was used concurrently
+    async fn use_recovery_code(&self, id: &str, used_at: &str) -> Result<bool>;
+
+    /// Whether the user is Owner or Admin of a team requiring 2FA without having it enabled
+    async fn enrollment_required(&self, user_id: &str) -> Result<bool>;
+    /// Whether the user is Owner or Admin of a team requiring 2FA
+    async fn is_required(&self, user_id: &str) -> Result<bool>;
+}
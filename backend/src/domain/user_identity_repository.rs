@@ -0,0 +1,10 @@
+use crate::domain::models::UserIdentity;
+use crate::error::Result;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait UserIdentityRepository: Send + Sync {
+    async fn find(&self, issuer: &str, subject: &str) -> Result<Option<UserIdentity>>;
+    async fn create(&self, identity: UserIdentity) -> Result<UserIdentity>;
+    async fn touch_last_login(&self, id: &str, at: &str) -> Result<()>;
+}
@@ -8,4 +8,9 @@ pub trait UserRepository: Send + Sync {
     async fn find_by_email(&self, email: &str) -> Result<Option<User>>;
     async fn create(&self, user: User) -> Result<User>;
     async fn count_users(&self) -> Result<i64>;
+    /// Every human account (bot users behind team tokens are excluded)
+    async fn list(&self) -> Result<Vec<User>>;
+    async fn update_name(&self, id: &str, name: Option<&str>, updated_at: &str) -> Result<()>;
+    async fn update_password(&self, id: &str, password_hash: &str, updated_at: &str) -> Result<()>;
+    async fn set_deactivated(&self, id: &str, deactivated_at: Option<&str>) -> Result<()>;
 }
@@ -1,4 +1,8 @@
-use axum::{Json, http::StatusCode, response::IntoResponse};
+use axum::{
+    Json,
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
 use serde::Serialize;
 use thiserror::Error;
 
@@ -42,6 +46,10 @@ pub enum AppError {
 
     #[error("Bad request: {0}")]
     BadRequest(String),
+
+    /// Too many attempts; the client may retry after the given number of seconds
+    #[error("Too many requests, retry in {0} seconds")]
+    RateLimited(u64),
 }
 
 #[derive(Serialize)]
@@ -102,6 +110,18 @@ impl IntoResponse for AppError {
             }
             AppError::Auth(msg) => (StatusCode::UNAUTHORIZED, "auth_error", msg.clone()),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "bad_request", msg.clone()),
+            AppError::RateLimited(retry_after) => {
+                let body = Json(ErrorResponse {
+                    error: "rate_limited".to_string(),
+                    message: self.to_string(),
+                });
+                return (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(header::RETRY_AFTER, retry_after.to_string())],
+                    body,
+                )
+                    .into_response();
+            }
         };
 
         let body = Json(ErrorResponse {
@@ -0,0 +1,35 @@
+use rand::distr::{Alphanumeric, SampleString};
+use sha2::{Digest, Sha256};
+
+use crate::domain::models::API_TOKEN_PREFIX;
+
+/// Characters of the token kept in plain text so users can recognise it in the UI
+const DISPLAY_PREFIX_LEN: usize = API_TOKEN_PREFIX.len() + 6;
+
+pub struct GeneratedToken {
+    pub token: String,
+    pub prefix: String,
+    pub hash: String,
+}
+
+pub fn generate() -> GeneratedToken {
+    let token = format!(
+        "{}{}",
+        API_TOKEN_PREFIX,
+        Alphanumeric.sample_string(&mut rand::rng(), 40)
+    );
+    GeneratedToken {
+        prefix: token[..DISPLAY_PREFIX_LEN].to_string(),
+        hash: hash(&token),
+        token,
+    }
+}
+
+/// Opaque tokens (API and refresh tokens) carry enough entropy that a plain SHA-256
+/// is sufficient, and lookup by hash stays O(1)
+pub fn hash(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
@@ -9,31 +9,57 @@ pub struct Claims {
     pub sub: String, // user id
     pub email: String,
     pub role: String,
+    pub sid: String, // session id
     pub exp: i64,
     pub iat: i64,
 }
 
+/// Short-lived proof that the password was checked, exchanged for a session
+/// once the second factor is verified
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaClaims {
+    pub sub: String, // user id
+    pub purpose: String,
+    pub exp: i64,
+    pub iat: i64,
+}
+
+const MFA_PURPOSE: &str = "mfa";
+const MFA_TOKEN_TTL_MINUTES: i64 = 5;
+
 pub struct JwtService {
     secret: String,
-    expiration_hours: u64,
+    access_ttl_minutes: u64,
 }
 
 impl JwtService {
-    pub fn new(secret: String, expiration_hours: u64) -> Self {
+    pub fn new(secret: String, access_ttl_minutes: u64) -> Self {
         Self {
             secret,
-            expiration_hours,
+            access_ttl_minutes,
         }
     }
 
-    pub fn generate_token(&self, user_id: &str, email: &str, role: &str) -> Result<String> {
+    /// Lifetime of an access token in seconds
+    pub fn access_ttl_seconds(&self) -> i64 {
+        self.access_ttl_minutes as i64 * 60
+    }
+
+    pub fn generate_token(
+        &self,
+        user_id: &str,
+        email: &str,
+        role: &str,
+        session_id: &str,
+    ) -> Result<String> {
         let now = Utc::now();
-        let exp = now + Duration::hours(self.expiration_hours as i64);
+        let exp = now + Duration::seconds(self.access_ttl_seconds());
 
         let claims = Claims {
             sub: user_id.to_string(),
             email: email.to_string(),
             role: role.to_string(),
+            sid: session_id.to_string(),
             iat: now.timestamp(),
             exp: exp.timestamp(),
         };
@@ -54,4 +80,33 @@ impl JwtService {
         )?;
         Ok(token_data.claims)
     }
+
+    pub fn generate_mfa_token(&self, user_id: &str) -> Result<String> {
+        let now = Utc::now();
+        let claims = MfaClaims {
+            sub: user_id.to_string(),
+            purpose: MFA_PURPOSE.to_string(),
+            iat: now.timestamp(),
+            exp: (now + Duration::minutes(MFA_TOKEN_TTL_MINUTES)).timestamp(),
+        };
+
+        encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(self.secret.as_bytes()),
+        )
+        .map_err(AppError::Jwt)
+    }
+
+    pub fn verify_mfa_token(&self, token: &str) -> Result<MfaClaims> {
+        let token_data = decode::<MfaClaims>(
+            token,
+            &DecodingKey::from_secret(self.secret.as_bytes()),
+            &Validation::default(),
+        )?;
+        if token_data.claims.purpose != MFA_PURPOSE {
+            return Err(AppError::Auth("Invalid MFA token".to_string()));
+        }
+        Ok(token_data.claims)
+    }
 }
@@ -1,2 +1,4 @@
+pub mod api_token;
 pub mod jwt;
 pub mod password;
+pub mod totp;
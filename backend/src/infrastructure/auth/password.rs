@@ -17,6 +17,11 @@ impl PasswordService {
             .map_err(|_| AppError::Hash)
     }
 
+    /// bcrypt hash for Caddy's basic auth, which does not understand argon2
+    pub fn hash_bcrypt(password: &str) -> Result<String> {
+        bcrypt::hash(password, bcrypt::DEFAULT_COST).map_err(|_| AppError::Hash)
+    }
+
     pub fn verify_password(password: &str, hash: &str) -> Result<()> {
         let parsed_hash = PasswordHash::new(hash).map_err(|_| AppError::Hash)?;
         Argon2::default()
@@ -0,0 +1,276 @@
+use chrono::{DateTime, Utc};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{CryptoProvider, verify_tls12_signature, verify_tls13_signature};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::sign::CertifiedKey;
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use x509_cert::Certificate;
+use x509_cert::der::Decode;
+use x509_cert::ext::pkix::SubjectAltName;
+use x509_cert::ext::pkix::name::GeneralName;
+
+use crate::error::{AppError, Result};
+
+/// Leaf certificate presented for a server name
+#[derive(Debug, Clone)]
+pub struct PeerCertificate {
+    pub subject: String,
+    pub issuer: String,
+    /// DNS names from the subjectAltName extension
+    pub sans: Vec<String>,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+}
+
+impl PeerCertificate {
+    /// Whether the certificate is valid for `host`; a `*.` SAN covers exactly one
+    /// label, and a wildcard host needs the same wildcard SAN
+    pub fn covers(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        self.sans.iter().any(|san| {
+            let san = san.to_ascii_lowercase();
+            if san == host {
+                return true;
+            }
+            match (san.strip_prefix("*."), host.split_once('.')) {
+                (Some(zone), Some((label, rest))) => label != "*" && rest == zone,
+                _ => false,
+            }
+        })
+    }
+}
+
+/// Accepts any certificate: the point is to read what is served, including
+/// expired or self-signed ones, not to trust it
+#[derive(Debug)]
+struct InspectOnly(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for InspectOnly {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Connect to `address` (`host:port`) with `server_name` as SNI and read the leaf
+/// certificate
+pub async fn fetch_certificate(address: &str, server_name: &str) -> Result<PeerCertificate> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = rustls::ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(InspectOnly(provider)))
+        .with_no_client_auth();
+    let name = ServerName::try_from(server_name.to_string())
+        .map_err(|_| AppError::Validation(format!("Invalid server name '{}'", server_name)))?;
+
+    let handshake = async {
+        let stream = TcpStream::connect(address).await?;
+        TlsConnector::from(Arc::new(config))
+            .connect(name, stream)
+            .await
+    };
+    let stream = tokio::time::timeout(Duration::from_secs(5), handshake)
+        .await
+        .map_err(|_| AppError::CaddyApi(format!("TLS handshake with {} timed out", address)))?
+        .map_err(|e| AppError::CaddyApi(format!("TLS handshake failed: {}", e)))?;
+
+    let leaf = stream
+        .get_ref()
+        .1
+        .peer_certificates()
+        .and_then(|certs| certs.first())
+        .ok_or_else(|| AppError::CaddyApi("No certificate presented".to_string()))?;
+    parse_certificate(leaf.as_ref())
+}
+
+fn pem_chain(certificate_pem: &str) -> Result<Vec<CertificateDer<'static>>> {
+    let chain = CertificateDer::pem_slice_iter(certificate_pem.as_bytes())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Validation(format!("Invalid certificate PEM: {}", e)))?;
+    if chain.is_empty() {
+        return Err(AppError::Validation(
+            "No certificate found in PEM".to_string(),
+        ));
+    }
+    Ok(chain)
+}
+
+/// Details of the first (leaf) certificate of a PEM chain
+pub fn parse_pem_certificate(certificate_pem: &str) -> Result<PeerCertificate> {
+    let chain = pem_chain(certificate_pem)?;
+    parse_certificate(chain[0].as_ref())
+}
+
+/// Check an uploaded PEM certificate chain and private key: the key must belong to
+/// the first (leaf) certificate. Returns the leaf's details.
+pub fn parse_pem_pair(certificate_pem: &str, key_pem: &str) -> Result<PeerCertificate> {
+    let chain = pem_chain(certificate_pem)?;
+    let leaf = chain[0].clone();
+    let key = PrivateKeyDer::from_pem_slice(key_pem.as_bytes())
+        .map_err(|e| AppError::Validation(format!("Invalid private key PEM: {}", e)))?;
+
+    CertifiedKey::from_der(chain, key, &rustls::crypto::ring::default_provider()).map_err(|e| {
+        AppError::Validation(format!("Private key does not match the certificate: {}", e))
+    })?;
+    parse_certificate(leaf.as_ref()).map_err(|e| AppError::Validation(e.to_string()))
+}
+
+/// Latest certificate error Caddy logged per hostname, read from its JSON logs.
+/// A later successful obtain or renewal clears the hostname's error.
+pub fn certificate_errors(log_lines: &[String]) -> HashMap<String, String> {
+    let mut errors = HashMap::new();
+    for line in log_lines {
+        // Docker may prefix lines with a timestamp
+        let Some(start) = line.find('{') else {
+            continue;
+        };
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line[start..]) else {
+            continue;
+        };
+        let logger = entry["logger"].as_str().unwrap_or_default();
+        if !logger.starts_with("tls") {
+            continue;
+        }
+        let Some(identifier) = entry["identifier"].as_str() else {
+            continue;
+        };
+        let msg = entry["msg"].as_str().unwrap_or_default();
+        if msg.contains("successfully") {
+            errors.remove(identifier);
+        } else if let Some(error) = entry["error"].as_str() {
+            errors.insert(identifier.to_string(), error.to_string());
+        }
+    }
+    errors
+}
+
+fn parse_certificate(der: &[u8]) -> Result<PeerCertificate> {
+    let cert = Certificate::from_der(der)
+        .map_err(|e| AppError::Internal(format!("Invalid certificate: {}", e)))?;
+    let tbs = &cert.tbs_certificate;
+    let sans = match tbs.get::<SubjectAltName>() {
+        Ok(Some((_, SubjectAltName(names)))) => names
+            .into_iter()
+            .filter_map(|name| match name {
+                GeneralName::DnsName(dns) => Some(dns.to_string()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    let to_utc = |time: x509_cert::time::Time| {
+        DateTime::<Utc>::from_timestamp(time.to_unix_duration().as_secs() as i64, 0)
+            .unwrap_or_default()
+    };
+    Ok(PeerCertificate {
+        subject: tbs.subject.to_string(),
+        issuer: tbs.issuer.to_string(),
+        sans,
+        not_before: to_utc(tbs.validity.not_before),
+        not_after: to_utc(tbs.validity.not_after),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_certificate_errors_from_caddy_logs() {
+        let lines = [
+            r#"{"level":"info","logger":"http","msg":"server running"}"#,
+            r#"2026-10-18T10:00:00Z {"level":"error","logger":"tls.obtain","msg":"could not get certificate from issuer","identifier":"app.example.com","error":"HTTP 403 urn:ietf:params:acme:error:unauthorized"}"#,
+            r#"{"level":"error","logger":"tls.renew","msg":"could not get certificate from issuer","identifier":"api.example.com","error":"timeout"}"#,
+            r#"{"level":"info","logger":"tls.renew","msg":"certificate renewed successfully","identifier":"api.example.com"}"#,
+            "not json",
+        ]
+        .map(String::from);
+
+        let errors = certificate_errors(&lines);
+        assert_eq!(errors.len(), 1);
+        assert!(errors["app.example.com"].contains("unauthorized"));
+    }
+
+    #[test]
+    fn test_certificate_covers_host() {
+        let cert = PeerCertificate {
+            subject: "CN=example.com".to_string(),
+            issuer: "CN=Test CA".to_string(),
+            sans: vec!["example.com".to_string(), "*.example.com".to_string()],
+            not_before: Utc::now(),
+            not_after: Utc::now(),
+        };
+        assert!(cert.covers("example.com"));
+        assert!(cert.covers("App.Example.com"));
+        assert!(cert.covers("*.example.com"));
+        assert!(!cert.covers("a.b.example.com"));
+        assert!(!cert.covers("other.com"));
+    }
+
+    #[test]
+    fn test_wildcard_certificate_covers_one_label() {
+        let cert = |sans: &[&str]| PeerCertificate {
+            subject: "CN=example.com".to_string(),
+            issuer: "CN=Test CA".to_string(),
+            sans: sans.iter().map(|san| san.to_string()).collect(),
+            not_before: Utc::now(),
+            not_after: Utc::now(),
+        };
+        let wildcard = cert(&["*.example.com"]);
+        assert!(wildcard.covers("app.example.com"));
+        assert!(!wildcard.covers("example.com"));
+        assert!(!wildcard.covers("a.app.example.com"));
+        assert!(!wildcard.covers("app.example.org"));
+        assert!(!wildcard.covers("*.app.example.com"));
+
+        // A wildcard host is not covered by certificates for its single names
+        let exact = cert(&["app.example.com", "api.example.com"]);
+        assert!(!exact.covers("*.example.com"));
+    }
+}
@@ -1,14 +1,40 @@
 use reqwest::Client;
+use serde_json::{Map, Value};
 use std::sync::Arc;
 
+use super::certificates::{PeerCertificate, fetch_certificate};
+use super::config::{ACCESS_LOG_FILE, with_apps, with_logs};
 use crate::domain::runtime::{ContainerConfig, RuntimePort};
 use crate::error::{AppError, Result};
 
 /// Version tag for Caddy container - increment to force re-creation
-const CADDY_CONTAINER_VERSION: &str = "v4";
+const CADDY_CONTAINER_VERSION: &str = "v6";
 const LABUH_NETWORK: &str = "labuh-network";
 
-/// Caddy Admin API client for dynamic configuration
+/// Host directory mounted into the Caddy container for its access log, next to
+/// the Caddyfile
+pub fn access_log_dir() -> std::path::PathBuf {
+    std::env::current_dir()
+        .unwrap_or_default()
+        .join("caddy-logs")
+}
+
+/// Access log as Labuh sees it on the host
+pub fn access_log_path() -> std::path::PathBuf {
+    access_log_dir().join(
+        std::path::Path::new(ACCESS_LOG_FILE)
+            .file_name()
+            .unwrap_or_default(),
+    )
+}
+
+enum RequestBody {
+    Json(Value),
+    Caddyfile(String),
+}
+
+/// Caddy Admin API client. Routes are not edited one by one; the reconciler renders
+/// the whole HTTP and TLS apps and hands them to [`CaddyClient::apply_apps`].
 pub struct CaddyClient {
     admin_api_url: String,
     client: Client,
@@ -27,26 +53,17 @@ impl CaddyClient {
         &self,
         method: reqwest::Method,
         path: &str,
-        body: Option<serde_json::Value>,
+        body: Option<RequestBody>,
     ) -> Result<reqwest::Response> {
         let url = format!("{}{}", self.admin_api_url, path);
-        let mut builder = self.client.request(method.clone(), &url);
-        if let Some(ref b) = body {
-            builder = builder.json(b);
-        }
-
-        let resp = builder.send().await;
+        let resp = self.build(method.clone(), &url, body.as_ref()).send().await;
 
         match resp {
             Ok(r) => Ok(r),
             Err(e) if e.is_connect() && self.admin_api_url.contains("localhost") => {
                 let fallback_url = self.admin_api_url.replace("localhost", "caddy");
                 let fallback_full = format!("{}{}", fallback_url, path);
-                let mut fallback_builder = self.client.request(method, &fallback_full);
-                if let Some(ref b) = body {
-                    fallback_builder = fallback_builder.json(b);
-                }
-                fallback_builder
+                self.build(method, &fallback_full, body.as_ref())
                     .send()
                     .await
                     .map_err(|e| AppError::CaddyApi(e.to_string()))
@@ -55,8 +72,31 @@ impl CaddyClient {
         }
     }
 
-    /// Ensure Caddy container is running with correct configuration
-    pub async fn bootstrap(&self, runtime: &Arc<dyn RuntimePort>) -> Result<()> {
+    fn build(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        body: Option<&RequestBody>,
+    ) -> reqwest::RequestBuilder {
+        let builder = self.client.request(method, url);
+        match body {
+            Some(RequestBody::Json(json)) => builder.json(json),
+            Some(RequestBody::Caddyfile(text)) => builder
+                .header(reqwest::header::CONTENT_TYPE, "text/caddyfile")
+                .body(text.clone()),
+            None => builder,
+        }
+    }
+
+    /// Ensure Caddy container is running with correct configuration. `l4_ports`
+    /// (`tcp/5432`, `udp/27015`, ...) are published next to 80 and 443; a different
+    /// set recreates the container, which drops its config until the next reconcile.
+    pub async fn bootstrap(
+        &self,
+        runtime: &Arc<dyn RuntimePort>,
+        image: &str,
+        l4_ports: &[String],
+    ) -> Result<()> {
         let container_name = "labuh-caddy";
 
         // Ensure labuh-network exists
@@ -74,7 +114,13 @@ impl CaddyClient {
                 .labels
                 .get("labuh.caddy.version")
                 .map(|v| v == CADDY_CONTAINER_VERSION)
-                .unwrap_or(false);
+                .unwrap_or(false)
+                || c.image != image
+                || c.labels
+                    .get("labuh.caddy.l4_ports")
+                    .map(String::as_str)
+                    .unwrap_or_default()
+                    != l4_ports.join(",");
 
             if needs_upgrade {
                 tracing::info!(
@@ -85,24 +131,18 @@ impl CaddyClient {
                 let _ = runtime.remove_container(&c.id, true).await;
                 // Fall through to create new container
             } else if c.state == "running" {
-                // Ensure HTTPS is configured even for existing containers
-                self.ensure_srv0().await?;
                 return Ok(());
             } else {
                 tracing::info!("Starting existing Caddy container...");
                 runtime.start_container(&c.id).await?;
                 // Wait for Caddy to be ready
                 tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                self.ensure_srv0().await?;
                 return Ok(());
             }
         }
 
         tracing::info!("Creating Caddy container...");
 
-        // Define Caddy container config
-        let image = "caddy:2-alpine";
-
         // Ensure image exists
         runtime.pull_image(image, None).await?;
 
@@ -129,17 +169,34 @@ impl CaddyClient {
         }
 
         // Create container config
-        let port_bindings = vec![
+        let mut port_bindings = vec![
             "80:80".to_string(),
             "443:443".to_string(),
             "127.0.0.1:2019:2019".to_string(),
         ];
+        for port in l4_ports {
+            if let Some((protocol, number)) = port.split_once('/') {
+                port_bindings.push(format!("{}:{}/{}", number, number, protocol));
+            }
+        }
+
+        // Access logs are read back by Labuh from the host side of this mount
+        let log_dir = access_log_dir();
+        std::fs::create_dir_all(&log_dir).map_err(|e| {
+            AppError::Internal(format!("Failed to create Caddy log directory: {}", e))
+        })?;
+        let log_mount = std::path::Path::new(ACCESS_LOG_FILE)
+            .parent()
+            .unwrap_or(std::path::Path::new("/var/log"))
+            .to_string_lossy()
+            .to_string();
 
         let volumes = vec![
             format!(
                 "{}/Caddyfile:/etc/caddy/Caddyfile",
                 std::env::current_dir().unwrap().to_string_lossy()
             ),
+            format!("{}:{}", log_dir.to_string_lossy(), log_mount),
             "caddy_data:/data".to_string(),
             "caddy_config:/config".to_string(),
         ];
@@ -151,6 +208,7 @@ impl CaddyClient {
             "labuh.caddy.version".to_string(),
             CADDY_CONTAINER_VERSION.to_string(),
         );
+        labels.insert("labuh.caddy.l4_ports".to_string(), l4_ports.join(","));
 
         let config = ContainerConfig {
             name: container_name.to_string(),
@@ -164,7 +222,8 @@ impl CaddyClient {
             memory_limit: None,
             network_mode: Some(LABUH_NETWORK.to_string()),
             networks: None,
-            extra_hosts: None,
+            // Lets the dashboard route reach a Labuh running directly on the host
+            extra_hosts: Some(vec!["host.docker.internal:host-gateway".to_string()]),
             restart_policy: Some("always".to_string()),
         };
 
@@ -173,181 +232,288 @@ impl CaddyClient {
         tracing::info!("Starting Caddy container...");
         runtime.start_container(&id).await?;
 
-        // Wait for Caddy to be ready before initializing srv0
+        // Wait for Caddy to be ready before the first reconcile
         tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
 
-        // Initialize HTTPS server
-        tracing::info!("Initializing Caddy HTTPS server...");
-        self.ensure_srv0().await?;
-
         Ok(())
     }
 
-    /// Initialize a new route
-    #[allow(unused_variables)] // show_branding kept for API compatibility
-    pub async fn add_route(&self, domain: &str, upstream: &str, show_branding: bool) -> Result<()> {
-        // Ensure srv0 structure exists first
-        self.ensure_srv0().await?;
-
-        // Remove existing route for this domain if it exists to prevent duplicates
-        let _ = self.remove_route(domain).await;
-
-        // Simple reverse_proxy handler
-        // Note: Badge injection disabled - requires custom Caddy build with replace-response module
-        let route_config = serde_json::json!({
-            "match": [{ "host": [domain] }],
-            "handle": [{
-                "handler": "reverse_proxy",
-                "upstreams": [{ "dial": upstream }]
-            }]
-        });
+    /// Current full config as reported by `GET /config/` (`null` when Caddy is empty)
+    pub async fn get_config(&self) -> Result<serde_json::Value> {
+        let response = self
+            .request_with_fallback(reqwest::Method::GET, "/config/", None)
+            .await?;
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::CaddyApi(format!(
+                "Failed to read config: {}",
+                error_text
+            )));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| AppError::CaddyApi(e.to_string()))
+    }
 
-        // Insert domain route at the BEGINNING (index 0) so it takes priority
+    /// Replace the whole config in one step; Caddy keeps the old config if the new
+    /// one fails to load
+    pub async fn load(&self, config: &serde_json::Value) -> Result<()> {
         let response = self
             .request_with_fallback(
                 reqwest::Method::POST,
-                "/config/apps/http/servers/srv0/routes/0",
-                Some(route_config.clone()),
+                "/load",
+                Some(RequestBody::Json(config.clone())),
             )
             .await?;
-
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
             return Err(AppError::CaddyApi(format!(
-                "Failed to add route: {}",
+                "Failed to load config: {}",
                 error_text
             )));
         }
-
-        tracing::info!("Added route: {} -> {}", domain, upstream);
         Ok(())
     }
 
-    /// Ensure srv0 exists and is configured for HTTPS
-    async fn ensure_srv0(&self) -> Result<()> {
-        // Always set srv0 to listen on :443 for HTTPS
-        let base_config = serde_json::json!({
-            "listen": [":443"],
-            "routes": []
+    /// Make each of `apps` (keyed by app name, e.g. `http`) equal to the given
+    /// config (`null` removes it), leaving the rest alone. Returns `false` when Caddy already had
+    /// exactly this config.
+    pub async fn apply_apps(&self, apps: &Map<String, Value>) -> Result<bool> {
+        let current = self.get_config().await?;
+        let unchanged = apps.iter().all(|(name, app)| {
+            let existing = current.pointer(&format!("/apps/{}", name));
+            existing == Some(app) || (existing.is_none() && app.is_null())
         });
+        if unchanged {
+            return Ok(false);
+        }
+        self.load(&with_apps(&current, apps)).await?;
+        Ok(true)
+    }
 
-        let resp = self
-            .request_with_fallback(reqwest::Method::GET, "/config/apps/http/servers/srv0", None)
-            .await;
-
-        match resp {
-            Ok(r) if r.status().is_success() => {
-                // Check if already listening on 443
-                let body: serde_json::Value = r.json().await.unwrap_or_default();
-                let listen = body.get("listen").and_then(|l| l.as_array());
-                let has_443 = listen
-                    .map(|arr| {
-                        arr.iter()
-                            .any(|v| v.as_str().map(|s| s.contains("443")).unwrap_or(false))
-                    })
-                    .unwrap_or(false);
-
-                if !has_443 {
-                    tracing::info!("Upgrading srv0 to HTTPS (port 443)...");
-                    // Get existing routes to preserve them
-                    let routes = body.get("routes").cloned().unwrap_or(serde_json::json!([]));
-                    let updated_config = serde_json::json!({
-                        "listen": [":443"],
-                        "routes": routes
-                    });
-                    self.request_with_fallback(
-                        reqwest::Method::PUT,
-                        "/config/apps/http/servers/srv0",
-                        Some(updated_config),
-                    )
-                    .await?;
-                }
-                return Ok(());
-            }
-            _ => {
-                let init_resp = self
-                    .request_with_fallback(
-                        reqwest::Method::PUT,
-                        "/config/apps/http/servers/srv0",
-                        Some(base_config.clone()),
-                    )
-                    .await?;
-
-                if !init_resp.status().is_success() {
-                    // If PUT fails, try PUT to apps/http first
-                    let http_config = serde_json::json!({
-                        "servers": {
-                            "srv0": base_config
-                        }
-                    });
-                    self.request_with_fallback(
-                        reqwest::Method::PUT,
-                        "/config/apps/http",
-                        Some(http_config),
-                    )
-                    .await?;
-                }
-            }
+    /// Make each of `logs` (keyed by logger name) in `logging.logs` equal to the
+    /// given config. Returns `false` when Caddy already had exactly these loggers.
+    pub async fn apply_logs(&self, logs: &Map<String, Value>) -> Result<bool> {
+        let current = self.get_config().await?;
+        let unchanged = logs
+            .iter()
+            .all(|(name, log)| current.pointer(&format!("/logging/logs/{}", name)) == Some(log));
+        if unchanged {
+            return Ok(false);
         }
-        Ok(())
+        self.load(&with_logs(&current, logs)).await?;
+        Ok(true)
     }
 
-    /// Remove all routes matching a domain
-    pub async fn remove_route(&self, domain: &str) -> Result<()> {
-        let mut found_any = false;
-
-        loop {
-            let response = self
-                .request_with_fallback(
-                    reqwest::Method::GET,
-                    "/config/apps/http/servers/srv0/routes",
-                    None,
-                )
-                .await?;
-
-            if !response.status().is_success() {
-                break;
-            }
+    /// Whether Caddy accepts a Caddyfile. The admin API cannot list modules, but
+    /// adapting a Caddyfile that uses a plugin's directive fails without the
+    /// plugin, and adapting loads nothing.
+    async fn adapts(&self, caddyfile: &str) -> Result<bool> {
+        let response = self
+            .request_with_fallback(
+                reqwest::Method::POST,
+                "/adapt",
+                Some(RequestBody::Caddyfile(caddyfile.to_string())),
+            )
+            .await?;
+        Ok(response.status().is_success())
+    }
 
-            let routes: Vec<serde_json::Value> = response
-                .json()
-                .await
-                .map_err(|e| AppError::CaddyApi(e.to_string()))?;
-
-            let mut index_to_remove = None;
-            for (index, route) in routes.iter().enumerate() {
-                if let Some(matches) = route.get("match")
-                    && let Some(hosts) = matches.get(0).and_then(|m| m.get("host"))
-                    && hosts
-                        .as_array()
-                        .map(|arr| arr.iter().any(|h| h.as_str() == Some(domain)))
-                        .unwrap_or(false)
-                {
-                    index_to_remove = Some(index);
-                    break;
-                }
-            }
+    /// Whether Caddy was built with the `replace-response` module the branding badge needs
+    pub async fn supports_replace_response(&self) -> Result<bool> {
+        self.adapts(":80 {\n\troute {\n\t\treplace \"</body>\" \"</body>\"\n\t}\n}\n")
+            .await
+    }
 
-            if let Some(index) = index_to_remove {
-                self.request_with_fallback(
-                    reqwest::Method::DELETE,
-                    &format!("/config/apps/http/servers/srv0/routes/{}", index),
-                    None,
-                )
-                .await?;
-                found_any = true;
-            } else {
-                break;
-            }
+    /// Whether Caddy was built with the `layer4` app (caddy-l4) that L4 routes need
+    pub async fn supports_layer4(&self) -> Result<bool> {
+        self.adapts("{\n\tlayer4 {\n\t\t:1 {\n\t\t\troute {\n\t\t\t\tproxy 127.0.0.1:1\n\t\t\t}\n\t\t}\n\t}\n}\n")
+            .await
+    }
+
+    /// Certificate Caddy currently serves for `server_name`, read from its HTTPS
+    /// listener on the admin API's host
+    pub async fn certificate(&self, server_name: &str) -> Result<PeerCertificate> {
+        let host = reqwest::Url::parse(&self.admin_api_url)
+            .ok()
+            .and_then(|url| url.host_str().map(String::from))
+            .unwrap_or_else(|| "localhost".to_string());
+        match fetch_certificate(&format!("{}:443", host), server_name).await {
+            Err(e) if host == "localhost" => fetch_certificate("caddy:443", server_name)
+                .await
+                .map_err(|_| e),
+            result => result,
         }
+    }
+}
 
-        if found_any {
-            Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::caddy::config::{
+        DnsChallenge, ProxyRoute, Site, render_http_app, render_tls_app,
+    };
+    use axum::http::StatusCode;
+    use axum::{Json, Router, extract::State, routing::get, routing::post};
+    use std::sync::Mutex;
+
+    /// Stand-in for Caddy's admin API holding a single config document
+    #[derive(Clone, Default)]
+    struct MockCaddy {
+        config: Arc<Mutex<serde_json::Value>>,
+        loads: Arc<Mutex<usize>>,
+        /// Caddyfile directives this build knows besides the standard ones
+        plugin_directives: Vec<&'static str>,
+    }
+
+    async fn get_config(State(caddy): State<MockCaddy>) -> Json<serde_json::Value> {
+        Json(caddy.config.lock().unwrap().clone())
+    }
+
+    async fn load(State(caddy): State<MockCaddy>, Json(config): Json<serde_json::Value>) {
+        *caddy.config.lock().unwrap() = config;
+        *caddy.loads.lock().unwrap() += 1;
+    }
+
+    /// Caddy answers 400 for a Caddyfile using a directive it does not know
+    async fn adapt(State(caddy): State<MockCaddy>, caddyfile: String) -> StatusCode {
+        let unknown = caddyfile
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .any(|directive| {
+                !matches!(
+                    directive,
+                    "{" | "}" | ":80" | ":1" | "route" | "reverse_proxy"
+                ) && !caddy.plugin_directives.contains(&directive)
+            });
+        if unknown {
+            StatusCode::BAD_REQUEST
         } else {
-            Err(AppError::NotFound(format!(
-                "Route for domain {} not found",
-                domain
-            )))
+            StatusCode::OK
         }
     }
+
+    async fn start_mock_caddy_with(
+        initial: serde_json::Value,
+        plugin_directives: Vec<&'static str>,
+    ) -> (MockCaddy, String) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let caddy = MockCaddy {
+            config: Arc::new(Mutex::new(initial)),
+            plugin_directives,
+            ..Default::default()
+        };
+        let app = Router::new()
+            .route("/config/", get(get_config))
+            .route("/load", post(load))
+            .route("/adapt", post(adapt))
+            .with_state(caddy.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (caddy, url)
+    }
+
+    async fn start_mock_caddy(initial: serde_json::Value) -> (MockCaddy, String) {
+        start_mock_caddy_with(initial, Vec::new()).await
+    }
+
+    #[tokio::test]
+    async fn test_detects_replace_response_module() {
+        let (_, stock) = start_mock_caddy(serde_json::Value::Null).await;
+        assert!(
+            !CaddyClient::new(stock)
+                .supports_replace_response()
+                .await
+                .unwrap()
+        );
+
+        let (caddy, custom) = start_mock_caddy_with(serde_json::Value::Null, vec!["replace"]).await;
+        assert!(
+            CaddyClient::new(custom)
+                .supports_replace_response()
+                .await
+                .unwrap()
+        );
+        // Probing must not touch the running config
+        assert_eq!(*caddy.loads.lock().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_detects_layer4_app() {
+        let (_, stock) = start_mock_caddy(serde_json::Value::Null).await;
+        assert!(!CaddyClient::new(stock).supports_layer4().await.unwrap());
+
+        let (_, l4) = start_mock_caddy_with(serde_json::Value::Null, vec!["layer4", "proxy"]).await;
+        assert!(CaddyClient::new(l4).supports_layer4().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_apply_apps_removes_null_app() {
+        let (caddy, url) = start_mock_caddy(serde_json::json!({
+            "apps": { "http": { "servers": {} }, "layer4": { "servers": {} } }
+        }))
+        .await;
+        let client = CaddyClient::new(url);
+
+        let mut apps = serde_json::Map::new();
+        apps.insert("layer4".to_string(), serde_json::Value::Null);
+        assert!(client.apply_apps(&apps).await.unwrap());
+        assert!(
+            caddy
+                .config
+                .lock()
+                .unwrap()
+                .pointer("/apps/layer4")
+                .is_none()
+        );
+        // Already gone, nothing to load
+        assert!(!client.apply_apps(&apps).await.unwrap());
+        assert_eq!(*caddy.loads.lock().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_apply_apps_loads_only_on_drift() {
+        let (caddy, url) =
+            start_mock_caddy(serde_json::json!({ "admin": { "listen": "0.0.0.0:2019" } })).await;
+        let client = CaddyClient::new(url);
+        let http = render_http_app(&[Site::new(
+            "app.example.com",
+            vec![ProxyRoute::new("web:80")],
+        )]);
+        let tls = render_tls_app(
+            &[DnsChallenge {
+                subjects: vec!["*.example.com".to_string()],
+                api_token: "token".to_string(),
+            }],
+            &[],
+        );
+        let mut apps = Map::new();
+        apps.insert("http".to_string(), http.clone());
+        apps.insert("tls".to_string(), tls.clone());
+
+        assert!(client.apply_apps(&apps).await.unwrap());
+        assert!(!client.apply_apps(&apps).await.unwrap());
+        assert_eq!(*caddy.loads.lock().unwrap(), 1);
+
+        let config = caddy.config.lock().unwrap().clone();
+        assert_eq!(config["admin"]["listen"], "0.0.0.0:2019");
+        assert_eq!(config["apps"]["http"], http);
+        assert_eq!(
+            config["apps"]["tls"]["automation"]["policies"][0]["issuers"][0]["challenges"]["dns"]["provider"]
+                ["name"],
+            "cloudflare"
+        );
+
+        // A route added behind Labuh's back is drift and gets replaced
+        caddy.config.lock().unwrap()["apps"]["http"]["servers"]["srv0"]["routes"]
+            .as_array_mut()
+            .unwrap()
+            .insert(
+                0,
+                serde_json::json!({ "match": [{ "host": ["stale.example.com"] }] }),
+            );
+        assert!(client.apply_apps(&apps).await.unwrap());
+        assert_eq!(caddy.config.lock().unwrap()["apps"]["http"], http);
+    }
 }
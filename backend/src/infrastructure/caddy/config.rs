@@ -0,0 +1,973 @@
+use serde_json::{Map, Value, json};
+use std::collections::BTreeMap;
+
+use crate::domain::models::domain_middleware::{CorsPolicy, DomainMiddleware, HeaderRule};
+
+/// Name of the HTTPS server Labuh owns in Caddy's `apps.http`
+pub const SERVER_NAME: &str = "srv0";
+/// Plain HTTP server: redirects to HTTPS, or serves sites that opted out of it
+pub const HTTP_SERVER_NAME: &str = "srv1";
+
+/// Logger Labuh's servers write access logs to, as `http.log.access.<name>`
+pub const ACCESS_LOGGER: &str = "labuh_access";
+/// Access log inside the Caddy container; the directory is mounted from the host
+pub const ACCESS_LOG_FILE: &str = "/var/log/labuh/access.log";
+
+/// Active health check Caddy runs against every upstream of a route
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealthCheck {
+    pub path: String,
+    pub interval_seconds: i64,
+}
+
+/// A path prefix, or every path, proxied to its upstreams
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProxyRoute {
+    /// Prefix such as `/api`; `None` matches every path
+    pub path_prefix: Option<String>,
+    pub strip_prefix: bool,
+    /// `host:port` dial addresses
+    pub upstreams: Vec<String>,
+    /// Caddy selection policy, used when there is more than one upstream
+    pub lb_policy: String,
+    pub health_check: Option<HealthCheck>,
+}
+
+impl ProxyRoute {
+    pub fn new(upstream: &str) -> Self {
+        Self {
+            path_prefix: None,
+            strip_prefix: false,
+            upstreams: vec![upstream.to_string()],
+            lb_policy: "round_robin".to_string(),
+            health_check: None,
+        }
+    }
+
+    fn render(&self) -> Value {
+        let upstreams: Vec<Value> = self
+            .upstreams
+            .iter()
+            .map(|dial| json!({ "dial": dial }))
+            .collect();
+        let mut proxy = json!({
+            "handler": "reverse_proxy",
+            "upstreams": upstreams
+        });
+        if self.upstreams.len() > 1 {
+            proxy["load_balancing"] = json!({
+                "selection_policy": { "policy": self.lb_policy }
+            });
+        }
+        if let Some(check) = &self.health_check {
+            proxy["health_checks"] = json!({
+                "active": {
+                    "uri": check.path,
+                    "interval": format!("{}s", check.interval_seconds),
+                    "timeout": "5s"
+                }
+            });
+        }
+
+        let mut route = json!({ "terminal": true });
+        let mut handle = Vec::new();
+        if let Some(prefix) = &self.path_prefix {
+            route["match"] = json!([{ "path": [prefix, format!("{}/*", prefix)] }]);
+            if self.strip_prefix {
+                handle.push(json!({ "handler": "rewrite", "strip_path_prefix": prefix }));
+            }
+        }
+        handle.push(proxy);
+        route["handle"] = json!(handle);
+        route
+    }
+}
+
+/// "Deployed with Labuh" link spliced in before `</body>` of branded sites. Must
+/// stay free of `{`/`}`, which Caddy would read as placeholders.
+pub const BRANDING_BADGE: &str = concat!(
+    r#"<a href="https://github.com/gmedia/labuh" target="_blank" rel="noopener" "#,
+    r#"style="position:fixed;right:12px;bottom:12px;z-index:2147483647;padding:4px 10px;"#,
+    r#"border-radius:9999px;background:#0f172a;color:#fff;font:500 12px/1.6 system-ui,sans-serif;"#,
+    r#"text-decoration:none;opacity:.85">Deployed with Labuh</a>"#,
+);
+
+/// Served with a 503 while a stack is in maintenance and has no page of its own
+pub const MAINTENANCE_PAGE: &str = concat!(
+    r#"<!DOCTYPE html><html lang="en"><head><meta charset="utf-8">"#,
+    r#"<meta name="viewport" content="width=device-width, initial-scale=1">"#,
+    r#"<title>Under maintenance</title></head>"#,
+    r#"<body style="margin:0;min-height:100vh;display:flex;align-items:center;justify-content:center;"#,
+    r#"background:#f8fafc;color:#0f172a;font:16px/1.6 system-ui,sans-serif;text-align:center">"#,
+    r#"<main><h1 style="font-size:24px;margin:0 0 8px">We'll be back shortly</h1>"#,
+    r#"<p style="margin:0;color:#475569">This site is being updated. Please try again in a few minutes.</p>"#,
+    r#"</main></body></html>"#,
+);
+
+/// Where a site's certificate comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SiteTls {
+    /// Obtained and renewed by Caddy over ACME
+    #[default]
+    Automatic,
+    /// Uploaded and loaded through `tls.certificates.load_pem`; Caddy does not manage it
+    Custom,
+    /// No HTTPS; the site is served on the HTTP server only
+    Disabled,
+}
+
+/// Everything served under one hostname: its middleware, then its proxy routes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Site {
+    pub hosts: Vec<String>,
+    pub tls: SiteTls,
+    /// Hosts that only redirect to the first of `hosts` (the www/apex counterpart)
+    pub redirect_hosts: Vec<String>,
+    pub middleware: DomainMiddleware,
+    /// `(username, bcrypt hash)`; basic auth is off when empty
+    pub basic_auth: Vec<(String, String)>,
+    /// Path routes first, longest prefix first, then the catch-all
+    pub routes: Vec<ProxyRoute>,
+    /// Inject [`BRANDING_BADGE`] into HTML responses; needs Caddy's `replace-response` module
+    pub branding: bool,
+    /// HTML answered with a 503 in place of the proxy routes
+    pub maintenance: Option<String>,
+    /// HTML per status code, served when proxying fails with that status
+    pub error_pages: BTreeMap<u16, String>,
+}
+
+impl Site {
+    pub fn new(host: &str, routes: Vec<ProxyRoute>) -> Self {
+        Self {
+            hosts: vec![host.to_string()],
+            tls: SiteTls::Automatic,
+            redirect_hosts: Vec::new(),
+            middleware: DomainMiddleware::default(),
+            basic_auth: Vec::new(),
+            routes,
+            branding: false,
+            maintenance: None,
+            error_pages: BTreeMap::new(),
+        }
+    }
+
+    /// Routes of the site itself, wrapped in a subroute so the middleware runs once
+    /// ahead of every proxy route
+    fn render_site(&self) -> Value {
+        let m = &self.middleware;
+        let mut routes = Vec::new();
+
+        if !m.ip_allow.is_empty() {
+            routes.push(json!({
+                "match": [{ "not": [{ "client_ip": { "ranges": m.ip_allow } }] }],
+                "handle": [forbidden()]
+            }));
+        }
+        if !m.ip_deny.is_empty() {
+            routes.push(json!({
+                "match": [{ "client_ip": { "ranges": m.ip_deny } }],
+                "handle": [forbidden()]
+            }));
+        }
+        for redirect in &m.redirects {
+            let status = if redirect.permanent { 301 } else { 302 };
+            routes.push(json!({
+                "match": [{ "path": [redirect.from] }],
+                "handle": [redirect_to(&redirect.to, status)]
+            }));
+        }
+        // CORS goes before basic auth: browsers send preflights without credentials
+        if let Some(cors) = &m.cors {
+            routes.extend(render_cors(cors));
+        }
+
+        let mut handlers = Vec::new();
+        if !self.basic_auth.is_empty() {
+            let accounts: Vec<Value> = self
+                .basic_auth
+                .iter()
+                .map(|(username, hash)| json!({ "username": username, "password": hash }))
+                .collect();
+            handlers.push(json!({
+                "handler": "authentication",
+                "providers": {
+                    "http_basic": {
+                        "accounts": accounts,
+                        "hash": { "algorithm": "bcrypt" },
+                        "realm": "restricted"
+                    }
+                }
+            }));
+        }
+        if let Some(max_size) = m.max_body_bytes {
+            handlers.push(json!({ "handler": "request_body", "max_size": max_size }));
+        }
+        if let Some(headers) = render_headers(m) {
+            handlers.push(headers);
+        }
+        if m.compression {
+            handlers.push(json!({
+                "handler": "encode",
+                "encodings": { "gzip": {}, "zstd": {} },
+                "prefer": ["zstd", "gzip"]
+            }));
+        }
+        // After `encode` in the chain, so the badge goes in before compression
+        if self.branding {
+            handlers.push(json!({
+                "handler": "replace_response",
+                "replacements": [{
+                    "search": "</body>",
+                    "replace": format!("{}</body>", BRANDING_BADGE)
+                }]
+            }));
+        }
+        if !handlers.is_empty() {
+            routes.push(json!({ "handle": handlers }));
+        }
+
+        if let Some(page) = &self.maintenance {
+            routes.push(json!({
+                "handle": [html_response("503", page, &[("Retry-After", "60")])]
+            }));
+            return self.wrap(routes);
+        }
+        for proxy_route in &self.routes {
+            let mut route = proxy_route.render();
+            // Bodies can only be rewritten when upstreams answer uncompressed
+            if self.branding
+                && let Some(proxy) = route["handle"].as_array_mut().and_then(|h| h.last_mut())
+            {
+                proxy["headers"] = json!({ "request": { "delete": ["Accept-Encoding"] } });
+            }
+            routes.push(route);
+        }
+        self.wrap(routes)
+    }
+
+    fn wrap(&self, routes: Vec<Value>) -> Value {
+        json!({
+            "match": [{ "host": self.hosts }],
+            "handle": [{ "handler": "subroute", "routes": routes }],
+            "terminal": true
+        })
+    }
+
+    /// `handle_errors` routes answering failed requests with the custom pages,
+    /// keeping the original status code
+    fn render_errors(&self) -> Vec<Value> {
+        self.error_pages
+            .iter()
+            .map(|(status, page)| {
+                json!({
+                    "match": [{
+                        "host": self.hosts,
+                        "expression": format!("{{http.error.status_code}} == {}", status)
+                    }],
+                    "handle": [html_response("{http.error.status_code}", page, &[])],
+                    "terminal": true
+                })
+            })
+            .collect()
+    }
+
+    fn render_counterpart(&self) -> Option<Value> {
+        if self.redirect_hosts.is_empty() {
+            return None;
+        }
+        let target = format!(
+            "{{http.request.scheme}}://{}{{http.request.uri}}",
+            self.hosts[0]
+        );
+        Some(json!({
+            "match": [{ "host": self.redirect_hosts }],
+            "handle": [redirect_to(&target, 308)],
+            "terminal": true
+        }))
+    }
+
+    fn render_https(&self) -> Vec<Value> {
+        if self.tls == SiteTls::Disabled {
+            return Vec::new();
+        }
+        self.render_counterpart()
+            .into_iter()
+            .chain(std::iter::once(self.render_site()))
+            .collect()
+    }
+
+    fn render_http(&self) -> Vec<Value> {
+        let mut routes: Vec<Value> = self.render_counterpart().into_iter().collect();
+        if self.middleware.https_redirect && self.tls != SiteTls::Disabled {
+            routes.push(json!({
+                "match": [{ "host": self.hosts }],
+                "handle": [redirect_to("https://{http.request.host}{http.request.uri}", 308)],
+                "terminal": true
+            }));
+        } else {
+            routes.push(self.render_site());
+        }
+        routes
+    }
+}
+
+fn forbidden() -> Value {
+    json!({ "handler": "static_response", "status_code": 403, "body": "Forbidden" })
+}
+
+fn html_response(status: &str, body: &str, headers: &[(&str, &str)]) -> Value {
+    let mut set = Map::new();
+    set.insert(
+        "Content-Type".to_string(),
+        json!(["text/html; charset=utf-8"]),
+    );
+    for (name, value) in headers {
+        set.insert(name.to_string(), json!([value]));
+    }
+    json!({
+        "handler": "static_response",
+        "status_code": status,
+        "headers": set,
+        "body": escape_placeholders(body)
+    })
+}
+
+/// User-supplied text made literal for Caddy, which would otherwise expand
+/// `{env.*}`, `{file.*}` and other placeholders in it
+fn escape_placeholders(text: &str) -> String {
+    text.replace('{', "\\{").replace('}', "\\}")
+}
+
+fn redirect_to(location: &str, status: u16) -> Value {
+    json!({
+        "handler": "static_response",
+        "status_code": status,
+        "headers": { "Location": [location] }
+    })
+}
+
+fn header_ops(rules: &[HeaderRule], set: &mut Map<String, Value>, delete: &mut Vec<String>) {
+    for rule in rules {
+        match &rule.value {
+            Some(value) => {
+                set.insert(rule.name.clone(), json!([value]));
+            }
+            None => delete.push(rule.name.clone()),
+        }
+    }
+}
+
+fn render_headers(m: &DomainMiddleware) -> Option<Value> {
+    let mut request_set = Map::new();
+    let mut request_delete = Vec::new();
+    header_ops(&m.request_headers, &mut request_set, &mut request_delete);
+
+    let mut response_set = Map::new();
+    let mut response_delete = Vec::new();
+    if let Some(max_age) = m.hsts_max_age {
+        response_set.insert(
+            "Strict-Transport-Security".to_string(),
+            json!([format!("max-age={}; includeSubDomains", max_age)]),
+        );
+    }
+    if m.security_headers {
+        response_set.insert("X-Content-Type-Options".to_string(), json!(["nosniff"]));
+        response_set.insert("X-Frame-Options".to_string(), json!(["SAMEORIGIN"]));
+        response_set.insert(
+            "Referrer-Policy".to_string(),
+            json!(["strict-origin-when-cross-origin"]),
+        );
+    }
+    // Explicit rules win over the presets above
+    header_ops(&m.response_headers, &mut response_set, &mut response_delete);
+
+    if request_set.is_empty()
+        && request_delete.is_empty()
+        && response_set.is_empty()
+        && response_delete.is_empty()
+    {
+        return None;
+    }
+    let mut handler = json!({ "handler": "headers" });
+    if !request_set.is_empty() || !request_delete.is_empty() {
+        handler["request"] = header_block(request_set, request_delete);
+    }
+    if !response_set.is_empty() || !response_delete.is_empty() {
+        handler["response"] = header_block(response_set, response_delete);
+        // Applied when the response is written, so upstream headers are overridden
+        handler["response"]["deferred"] = json!(true);
+    }
+    Some(handler)
+}
+
+fn header_block(set: Map<String, Value>, delete: Vec<String>) -> Value {
+    let mut block = json!({});
+    if !set.is_empty() {
+        block["set"] = Value::Object(set);
+    }
+    if !delete.is_empty() {
+        block["delete"] = json!(delete);
+    }
+    block
+}
+
+/// One header route per allowed origin, echoing it back, then a route answering
+/// preflight requests
+fn render_cors(cors: &CorsPolicy) -> Vec<Value> {
+    let mut routes = Vec::new();
+    let wildcard = cors.allowed_origins.iter().any(|o| o == "*");
+    let origins: Vec<&str> = if wildcard {
+        vec!["*"]
+    } else {
+        cors.allowed_origins.iter().map(String::as_str).collect()
+    };
+
+    for origin in origins {
+        let mut set = Map::new();
+        set.insert("Access-Control-Allow-Origin".to_string(), json!([origin]));
+        set.insert(
+            "Access-Control-Allow-Methods".to_string(),
+            json!([cors.allowed_methods.join(", ")]),
+        );
+        set.insert(
+            "Access-Control-Allow-Headers".to_string(),
+            json!([cors.allowed_headers.join(", ")]),
+        );
+        if cors.allow_credentials {
+            set.insert(
+                "Access-Control-Allow-Credentials".to_string(),
+                json!(["true"]),
+            );
+        }
+        if let Some(max_age) = cors.max_age {
+            set.insert(
+                "Access-Control-Max-Age".to_string(),
+                json!([max_age.to_string()]),
+            );
+        }
+        if !wildcard {
+            set.insert("Vary".to_string(), json!(["Origin"]));
+        }
+        let mut route = json!({
+            "handle": [{
+                "handler": "headers",
+                "response": { "set": set, "deferred": true }
+            }]
+        });
+        if !wildcard {
+            route["match"] = json!([{ "header": { "Origin": [origin] } }]);
+        }
+        routes.push(route);
+    }
+
+    routes.push(json!({
+        "match": [{
+            "method": ["OPTIONS"],
+            "header": { "Access-Control-Request-Method": ["*"] }
+        }],
+        "handle": [{ "handler": "static_response", "status_code": 204 }]
+    }));
+    routes
+}
+
+/// Render the complete `apps.http` config. Sites keep the given order, so callers
+/// decide precedence; the result depends only on the input, never on what Caddy
+/// currently holds.
+pub fn render_http_app(sites: &[Site]) -> Value {
+    let https: Vec<Value> = sites.iter().flat_map(Site::render_https).collect();
+    let http: Vec<Value> = sites.iter().flat_map(Site::render_http).collect();
+    let errors: Vec<Value> = sites.iter().flat_map(Site::render_errors).collect();
+
+    // Redirects are rendered per site on the HTTP server instead
+    let mut automatic_https = json!({ "disable_redirects": true });
+    let custom: Vec<&String> = sites
+        .iter()
+        .filter(|site| site.tls == SiteTls::Custom)
+        .flat_map(|site| &site.hosts)
+        .collect();
+    if !custom.is_empty() {
+        automatic_https["skip_certificates"] = json!(custom);
+    }
+
+    let mut app = json!({
+        "servers": {
+            SERVER_NAME: {
+                "listen": [":443"],
+                "routes": https,
+                "automatic_https": automatic_https,
+                "logs": { "default_logger_name": ACCESS_LOGGER }
+            },
+            HTTP_SERVER_NAME: {
+                "listen": [":80"],
+                "routes": http,
+                "logs": { "default_logger_name": ACCESS_LOGGER }
+            }
+        }
+    });
+    if !errors.is_empty() {
+        for server in [SERVER_NAME, HTTP_SERVER_NAME] {
+            app["servers"][server]["errors"] = json!({ "routes": errors });
+        }
+    }
+    app
+}
+
+/// Hostnames whose certificates are obtained with the ACME DNS-01 challenge
+/// through one Cloudflare API token
+#[derive(Debug, Clone, PartialEq)]
+pub struct DnsChallenge {
+    pub subjects: Vec<String>,
+    pub api_token: String,
+}
+
+/// An uploaded certificate chain and its private key, both PEM
+#[derive(Debug, Clone, PartialEq)]
+pub struct PemCertificate {
+    pub host: String,
+    pub certificate: String,
+    pub key: String,
+}
+
+/// Render the complete `apps.tls` config. Hostnames without a DNS-01 policy fall
+/// back to Caddy's default automation (HTTP-01/TLS-ALPN-01); uploaded certificates
+/// are loaded as they are.
+pub fn render_tls_app(challenges: &[DnsChallenge], certificates: &[PemCertificate]) -> Value {
+    let policies: Vec<Value> = challenges
+        .iter()
+        .map(|challenge| {
+            json!({
+                "subjects": challenge.subjects,
+                "issuers": [{
+                    "module": "acme",
+                    "challenges": {
+                        "dns": {
+                            "provider": { "name": "cloudflare", "api_token": challenge.api_token }
+                        }
+                    }
+                }]
+            })
+        })
+        .collect();
+    let mut tls = json!({ "automation": { "policies": policies } });
+    if !certificates.is_empty() {
+        let load_pem: Vec<Value> = certificates
+            .iter()
+            .map(|cert| {
+                json!({
+                    "certificate": cert.certificate,
+                    "key": cert.key,
+                    "tags": [format!("labuh:{}", cert.host)]
+                })
+            })
+            .collect();
+        tls["certificates"] = json!({ "load_pem": load_pem });
+    }
+    tls
+}
+
+/// A host port forwarded as raw TCP/UDP by the `layer4` app (caddy-l4)
+#[derive(Debug, Clone, PartialEq)]
+pub struct L4Proxy {
+    /// `tcp` or `udp`
+    pub protocol: String,
+    pub listen_port: u16,
+    /// TLS server name picking this upstream among others on the same port
+    pub sni: Option<String>,
+    /// `host:port`
+    pub upstream: String,
+}
+
+/// Render the complete `apps.layer4` config, one server per protocol and port.
+/// SNI routes of a port are tried before its catch-all route.
+pub fn render_layer4_app(proxies: &[L4Proxy]) -> Value {
+    let mut servers: BTreeMap<String, (String, Vec<&L4Proxy>)> = BTreeMap::new();
+    for proxy in proxies {
+        let name = format!("l4-{}-{}", proxy.protocol, proxy.listen_port);
+        let listen = format!("{}/:{}", proxy.protocol, proxy.listen_port);
+        servers
+            .entry(name)
+            .or_insert_with(|| (listen, Vec::new()))
+            .1
+            .push(proxy);
+    }
+
+    let servers: Map<String, Value> = servers
+        .into_iter()
+        .map(|(name, (listen, mut proxies))| {
+            proxies.sort_by_key(|p| p.sni.is_none());
+            let routes: Vec<Value> = proxies
+                .iter()
+                .map(|p| {
+                    let dial = match p.protocol.as_str() {
+                        "udp" => format!("udp/{}", p.upstream),
+                        _ => p.upstream.clone(),
+                    };
+                    let mut route = json!({
+                        "handle": [{ "handler": "proxy", "upstreams": [{ "dial": [dial] }] }]
+                    });
+                    if let Some(sni) = &p.sni {
+                        route["match"] = json!([{ "tls": { "sni": [sni] } }]);
+                    }
+                    route
+                })
+                .collect();
+            (name, json!({ "listen": [listen], "routes": routes }))
+        })
+        .collect();
+    json!({ "servers": servers })
+}
+
+/// Loggers for `logging.logs`: access logs go as JSON to [`ACCESS_LOG_FILE`] and
+/// are kept out of the default log, which stays on stderr
+pub fn render_logs() -> Map<String, Value> {
+    let access = format!("http.log.access.{}", ACCESS_LOGGER);
+    let mut logs = Map::new();
+    logs.insert("default".to_string(), json!({ "exclude": [access] }));
+    logs.insert(
+        ACCESS_LOGGER.to_string(),
+        json!({
+            "writer": {
+                "output": "file",
+                "filename": ACCESS_LOG_FILE,
+                "roll_size_mb": 20,
+                "roll_keep": 1,
+                // Labuh may run as another user than Caddy
+                "mode": "0644"
+            },
+            "encoder": { "format": "json" },
+            "include": [access]
+        }),
+    );
+    logs
+}
+
+/// Replace the given loggers in `logging.logs` of a full Caddy config, keeping
+/// everything else as it is
+pub fn with_logs(current: &Value, logs: &Map<String, Value>) -> Value {
+    let mut config = match current {
+        Value::Object(_) => current.clone(),
+        _ => json!({}),
+    };
+    let root = config.as_object_mut().expect("config is an object");
+    let logging = root.entry("logging").or_insert_with(|| json!({}));
+    if !logging.is_object() {
+        *logging = json!({});
+    }
+    let existing = logging
+        .as_object_mut()
+        .expect("logging is an object")
+        .entry("logs")
+        .or_insert_with(|| json!({}));
+    if !existing.is_object() {
+        *existing = json!({});
+    }
+    let existing = existing.as_object_mut().expect("logs is an object");
+    for (name, log) in logs {
+        existing.insert(name.clone(), log.clone());
+    }
+    config
+}
+
+/// Replace the given apps in a full Caddy config, keeping everything else (the
+/// admin endpoint, logging, other apps) as it is. A `null` app is removed.
+pub fn with_apps(current: &Value, apps: &Map<String, Value>) -> Value {
+    let mut config = match current {
+        Value::Object(_) => current.clone(),
+        _ => json!({}),
+    };
+    let root = config.as_object_mut().expect("config is an object");
+    let existing = root.entry("apps").or_insert_with(|| json!({}));
+    if !existing.is_object() {
+        *existing = json!({});
+    }
+    let existing = existing.as_object_mut().expect("apps is an object");
+    for (name, app) in apps {
+        if app.is_null() {
+            existing.remove(name);
+        } else {
+            existing.insert(name.clone(), app.clone());
+        }
+    }
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::domain_middleware::RedirectRule;
+
+    #[test]
+    fn test_render_is_deterministic_and_keeps_other_config() {
+        let sites = vec![
+            Site::new("a.example.com", vec![ProxyRoute::new("web:80")]),
+            Site::new("b.example.com", vec![ProxyRoute::new("api:8080")]),
+        ];
+        let http = render_http_app(&sites);
+        assert_eq!(http, render_http_app(&sites));
+
+        let rendered = &http["servers"][SERVER_NAME]["routes"];
+        assert_eq!(rendered.as_array().unwrap().len(), 2);
+        assert_eq!(rendered[0]["match"][0]["host"][0], "a.example.com");
+        assert_eq!(
+            rendered[1]["handle"][0]["routes"][0]["handle"][0]["upstreams"][0]["dial"],
+            "api:8080"
+        );
+        // Plain HTTP redirects to HTTPS by default
+        let plain = &http["servers"][HTTP_SERVER_NAME]["routes"][0];
+        assert_eq!(plain["handle"][0]["status_code"], 308);
+
+        let current = json!({
+            "admin": { "listen": "0.0.0.0:2019" },
+            "apps": { "http": { "servers": {} }, "tls": { "automation": {} } }
+        });
+        let mut apps = Map::new();
+        apps.insert("http".to_string(), http.clone());
+        let merged = with_apps(&current, &apps);
+        assert_eq!(merged["admin"]["listen"], "0.0.0.0:2019");
+        assert!(merged["apps"]["tls"].is_object());
+        assert_eq!(merged["apps"]["http"], http);
+
+        // A fresh Caddy reports `null` as its config
+        assert_eq!(with_apps(&Value::Null, &apps)["apps"]["http"], http);
+    }
+
+    #[test]
+    fn test_render_path_route_with_load_balancing() {
+        let route = ProxyRoute {
+            path_prefix: Some("/api".to_string()),
+            strip_prefix: true,
+            upstreams: vec!["api-1:8080".to_string(), "api-2:8080".to_string()],
+            lb_policy: "least_conn".to_string(),
+            health_check: Some(HealthCheck {
+                path: "/health".to_string(),
+                interval_seconds: 15,
+            }),
+        };
+        let rendered = route.render();
+        assert_eq!(rendered["match"][0]["path"], json!(["/api", "/api/*"]));
+        assert_eq!(rendered["handle"][0]["strip_path_prefix"], "/api");
+
+        let proxy = &rendered["handle"][1];
+        assert_eq!(proxy["upstreams"][1]["dial"], "api-2:8080");
+        assert_eq!(
+            proxy["load_balancing"]["selection_policy"]["policy"],
+            "least_conn"
+        );
+        assert_eq!(proxy["health_checks"]["active"]["uri"], "/health");
+        assert_eq!(proxy["health_checks"]["active"]["interval"], "15s");
+
+        // A plain catch-all route stays minimal
+        let plain = ProxyRoute::new("web:80").render();
+        assert!(plain.get("match").is_none());
+        assert!(plain["handle"][0].get("load_balancing").is_none());
+    }
+
+    #[test]
+    fn test_render_middleware_ahead_of_proxy() {
+        let site = Site {
+            hosts: vec!["example.com".to_string()],
+            tls: SiteTls::Automatic,
+            redirect_hosts: vec!["www.example.com".to_string()],
+            middleware: DomainMiddleware {
+                https_redirect: false,
+                redirects: vec![RedirectRule {
+                    from: "/old".to_string(),
+                    to: "/new".to_string(),
+                    permanent: true,
+                }],
+                hsts_max_age: Some(31536000),
+                ip_deny: vec!["203.0.113.0/24".to_string()],
+                max_body_bytes: Some(1024),
+                compression: true,
+                cors: Some(CorsPolicy {
+                    allowed_origins: vec!["https://app.example.com".to_string()],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            basic_auth: vec![("admin".to_string(), "$2b$12$hash".to_string())],
+            routes: vec![ProxyRoute::new("web:80")],
+            branding: false,
+            maintenance: None,
+            error_pages: BTreeMap::new(),
+        };
+        let https = site.render_https();
+        assert_eq!(https[0]["match"][0]["host"][0], "www.example.com");
+        assert_eq!(
+            https[0]["handle"][0]["headers"]["Location"][0],
+            "{http.request.scheme}://example.com{http.request.uri}"
+        );
+
+        let inner = https[1]["handle"][0]["routes"].as_array().unwrap();
+        assert_eq!(inner[0]["handle"][0]["status_code"], 403);
+        assert_eq!(inner[1]["handle"][0]["status_code"], 301);
+        assert_eq!(
+            inner[2]["match"][0]["header"]["Origin"][0],
+            "https://app.example.com"
+        );
+        assert_eq!(inner[3]["match"][0]["method"][0], "OPTIONS");
+
+        let handlers: Vec<&str> = inner[4]["handle"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|h| h["handler"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            handlers,
+            vec!["authentication", "request_body", "headers", "encode"]
+        );
+        assert_eq!(inner[5]["handle"][0]["handler"], "reverse_proxy");
+
+        // Opting out of the HTTPS redirect serves the same site over plain HTTP
+        let http = site.render_http();
+        assert_eq!(http[1], https[1]);
+    }
+
+    #[test]
+    fn test_render_branding_badge() {
+        let mut site = Site::new("app.example.com", vec![ProxyRoute::new("web:80")]);
+        assert!(!site.render_site().to_string().contains("replace_response"));
+
+        site.branding = true;
+        site.middleware.compression = true;
+        let rendered = site.render_site();
+        let inner = rendered["handle"][0]["routes"].as_array().unwrap();
+        let handlers = inner[0]["handle"].as_array().unwrap();
+        assert_eq!(handlers[0]["handler"], "encode");
+        assert_eq!(handlers[1]["handler"], "replace_response");
+        let replacement = &handlers[1]["replacements"][0];
+        assert_eq!(replacement["search"], "</body>");
+        assert!(
+            replacement["replace"]
+                .as_str()
+                .unwrap()
+                .ends_with("Deployed with Labuh</a></body>")
+        );
+        assert_eq!(
+            inner[1]["handle"][0]["headers"]["request"]["delete"][0],
+            "Accept-Encoding"
+        );
+        assert!(!BRANDING_BADGE.contains(['{', '}']));
+    }
+
+    #[test]
+    fn test_render_maintenance_and_error_pages() {
+        let mut site = Site::new("app.example.com", vec![ProxyRoute::new("web:80")]);
+        site.middleware.ip_deny = vec!["203.0.113.7".to_string()];
+        site.error_pages.insert(502, "<h1>Down</h1>".to_string());
+        site.maintenance = Some(MAINTENANCE_PAGE.to_string());
+
+        let rendered = site.render_site();
+        let inner = rendered["handle"][0]["routes"].as_array().unwrap();
+        // Middleware still applies, the proxy routes are gone
+        assert_eq!(inner.len(), 2);
+        let page = &inner[1]["handle"][0];
+        assert_eq!(page["handler"], "static_response");
+        assert_eq!(page["status_code"], "503");
+        assert_eq!(page["headers"]["Retry-After"][0], "60");
+        assert!(!rendered.to_string().contains("reverse_proxy"));
+
+        let http = render_http_app(&[site, Site::new("b.example.com", Vec::new())]);
+        for server in [SERVER_NAME, HTTP_SERVER_NAME] {
+            let errors = http["servers"][server]["errors"]["routes"]
+                .as_array()
+                .unwrap();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0]["match"][0]["host"][0], "app.example.com");
+            assert_eq!(
+                errors[0]["match"][0]["expression"],
+                "{http.error.status_code} == 502"
+            );
+            assert_eq!(errors[0]["handle"][0]["body"], "<h1>Down</h1>");
+        }
+        assert!(
+            render_http_app(&[Site::new("b.example.com", Vec::new())])["servers"][SERVER_NAME]
+                .get("errors")
+                .is_none()
+        );
+
+        // Page content never reaches Caddy's placeholder expansion
+        let mut site = Site::new("app.example.com", Vec::new());
+        site.maintenance = Some("<style>p { color: red }</style>{env.HOME}".to_string());
+        let rendered = site.render_site();
+        assert_eq!(
+            rendered["handle"][0]["routes"][0]["handle"][0]["body"],
+            "<style>p \\{ color: red \\}</style>\\{env.HOME\\}"
+        );
+    }
+
+    #[test]
+    fn test_render_layer4_app() {
+        let proxy = |protocol: &str, port, sni: Option<&str>, upstream: &str| L4Proxy {
+            protocol: protocol.to_string(),
+            listen_port: port,
+            sni: sni.map(String::from),
+            upstream: upstream.to_string(),
+        };
+        let app = render_layer4_app(&[
+            proxy("tcp", 5432, Some("a.example.com"), "db-a:5432"),
+            proxy("udp", 27015, None, "game:27015"),
+            proxy("tcp", 5432, Some("b.example.com"), "db-b:5432"),
+        ]);
+
+        let tcp = &app["servers"]["l4-tcp-5432"];
+        assert_eq!(tcp["listen"][0], "tcp/:5432");
+        assert_eq!(
+            tcp["routes"][0]["match"][0]["tls"]["sni"][0],
+            "a.example.com"
+        );
+        assert_eq!(
+            tcp["routes"][1]["handle"][0]["upstreams"][0]["dial"][0],
+            "db-b:5432"
+        );
+
+        let udp = &app["servers"]["l4-udp-27015"];
+        assert_eq!(udp["listen"][0], "udp/:27015");
+        assert!(udp["routes"][0].get("match").is_none());
+        assert_eq!(
+            udp["routes"][0]["handle"][0]["upstreams"][0]["dial"][0],
+            "udp/game:27015"
+        );
+
+        // A null app drops what Caddy had
+        let current = json!({ "apps": { "layer4": app, "http": {} } });
+        let mut apps = Map::new();
+        apps.insert("layer4".to_string(), Value::Null);
+        assert_eq!(
+            with_apps(&current, &apps),
+            json!({ "apps": { "http": {} } })
+        );
+    }
+
+    #[test]
+    fn test_render_custom_and_disabled_tls() {
+        let mut custom = Site::new("shop.example.com", vec![ProxyRoute::new("shop:80")]);
+        custom.tls = SiteTls::Custom;
+        let mut plain = Site::new("intranet.example.com", vec![ProxyRoute::new("wiki:80")]);
+        plain.tls = SiteTls::Disabled;
+
+        let http_app = render_http_app(&[custom, plain]);
+        let srv0 = &http_app["servers"][SERVER_NAME];
+        assert_eq!(srv0["routes"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            srv0["automatic_https"]["skip_certificates"],
+            json!(["shop.example.com"])
+        );
+        // Without HTTPS the site is served on :80 despite the default redirect
+        let srv1 = &http_app["servers"][HTTP_SERVER_NAME]["routes"];
+        assert_eq!(srv1[0]["handle"][0]["status_code"], 308);
+        assert_eq!(srv1[1]["handle"][0]["handler"], "subroute");
+
+        let tls = render_tls_app(
+            &[],
+            &[PemCertificate {
+                host: "shop.example.com".to_string(),
+                certificate: "CERT".to_string(),
+                key: "KEY".to_string(),
+            }],
+        );
+        assert_eq!(tls["certificates"]["load_pem"][0]["key"], "KEY");
+        assert_eq!(tls["automation"]["policies"], json!([]));
+    }
+}
@@ -1 +1,3 @@
+pub mod certificates;
 pub mod client;
+pub mod config;
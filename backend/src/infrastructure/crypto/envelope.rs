@@ -0,0 +1,219 @@
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+use crate::config::Config;
+use crate::domain::secret_cipher::SecretCipher;
+use crate::error::{AppError, Result};
+
+const PREFIX: &str = "enc:v1";
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+
+/// Envelope encryption: every value gets its own random data key, which is
+/// sealed with the master key and stored next to the ciphertext.
+///
+/// Stored format: `enc:v1:<key id>:<sealed data key>:<sealed value>` (base64 parts)
+pub struct EnvelopeCipher {
+    master_key: [u8; KEY_LEN],
+    key_id: String,
+}
+
+impl EnvelopeCipher {
+    pub fn new(master_key: [u8; KEY_LEN]) -> Self {
+        let digest = Sha256::digest(master_key);
+        let key_id = digest[..4].iter().map(|b| format!("{:02x}", b)).collect();
+        Self { master_key, key_id }
+    }
+
+    pub fn generate() -> Self {
+        let mut key = [0u8; KEY_LEN];
+        rand::rng().fill_bytes(&mut key);
+        Self::new(key)
+    }
+
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        let bytes = BASE64
+            .decode(encoded.trim())
+            .map_err(|_| AppError::Internal("Master key is not valid base64".to_string()))?;
+        let key: [u8; KEY_LEN] = bytes
+            .try_into()
+            .map_err(|_| AppError::Internal(format!("Master key must be {} bytes", KEY_LEN)))?;
+        Ok(Self::new(key))
+    }
+
+    /// Load the master key from `LABUH_MASTER_KEY`, or from the key file.
+    /// A missing key file is generated with owner-only permissions.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        if let Some(key) = &config.master_key {
+            return Self::from_base64(key);
+        }
+
+        let path = Path::new(&config.master_key_file);
+        if path.exists() {
+            let encoded = std::fs::read_to_string(path).map_err(|e| {
+                AppError::Internal(format!("Failed to read master key file: {}", e))
+            })?;
+            return Self::from_base64(&encoded);
+        }
+
+        let cipher = Self::generate();
+        cipher.write_key_file(path)?;
+        tracing::warn!(
+            "Generated a new master key at {}. Back it up: secrets cannot be decrypted without it",
+            path.display()
+        );
+        Ok(cipher)
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    pub fn to_base64(&self) -> String {
+        BASE64.encode(self.master_key)
+    }
+
+    pub fn write_key_file(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                AppError::Internal(format!("Failed to create key directory: {}", e))
+            })?;
+        }
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options
+            .open(path)
+            .map_err(|e| AppError::Internal(format!("Failed to write master key file: {}", e)))?;
+        std::io::Write::write_all(&mut file, self.to_base64().as_bytes())
+            .map_err(|e| AppError::Internal(format!("Failed to write master key file: {}", e)))
+    }
+
+    fn seal(key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        let cipher = XChaCha20Poly1305::new_from_slice(key)
+            .map_err(|_| AppError::Internal("Invalid encryption key".to_string()))?;
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::rng().fill_bytes(&mut nonce);
+
+        let ciphertext = cipher
+            .encrypt(XNonce::from_slice(&nonce), plaintext)
+            .map_err(|_| AppError::Internal("Failed to encrypt secret".to_string()))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    fn open(key: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+        if sealed.len() < NONCE_LEN {
+            return Err(AppError::Internal(
+                "Encrypted secret is truncated".to_string(),
+            ));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let cipher = XChaCha20Poly1305::new_from_slice(key)
+            .map_err(|_| AppError::Internal("Invalid encryption key".to_string()))?;
+
+        cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| AppError::Internal("Failed to decrypt secret".to_string()))
+    }
+}
+
+impl SecretCipher for EnvelopeCipher {
+    fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let mut data_key = [0u8; KEY_LEN];
+        rand::rng().fill_bytes(&mut data_key);
+
+        let sealed_key = Self::seal(&self.master_key, &data_key)?;
+        let sealed_value = Self::seal(&data_key, plaintext.as_bytes())?;
+
+        Ok(format!(
+            "{}:{}:{}:{}",
+            PREFIX,
+            self.key_id,
+            BASE64.encode(sealed_key),
+            BASE64.encode(sealed_value)
+        ))
+    }
+
+    fn decrypt(&self, stored: &str) -> Result<String> {
+        let rest = stored
+            .strip_prefix(PREFIX)
+            .and_then(|r| r.strip_prefix(':'))
+            .ok_or_else(|| AppError::Internal("Secret is not encrypted".to_string()))?;
+
+        let mut parts = rest.splitn(3, ':');
+        let (Some(key_id), Some(sealed_key), Some(sealed_value)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(AppError::Internal("Malformed encrypted secret".to_string()));
+        };
+
+        if key_id != self.key_id {
+            return Err(AppError::Internal(format!(
+                "Secret was encrypted with master key {}, but the current key is {}",
+                key_id, self.key_id
+            )));
+        }
+
+        let decode = |part: &str| {
+            BASE64
+                .decode(part)
+                .map_err(|_| AppError::Internal("Malformed encrypted secret".to_string()))
+        };
+
+        let data_key = Self::open(&self.master_key, &decode(sealed_key)?)?;
+        let plaintext = Self::open(&data_key, &decode(sealed_value)?)?;
+
+        String::from_utf8(plaintext)
+            .map_err(|_| AppError::Internal("Decrypted secret is not valid UTF-8".to_string()))
+    }
+
+    fn is_encrypted(&self, stored: &str) -> bool {
+        stored.starts_with(PREFIX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let cipher = EnvelopeCipher::generate();
+        let stored = cipher.encrypt("s3cr3t").unwrap();
+
+        assert!(cipher.is_encrypted(&stored));
+        assert!(!stored.contains("s3cr3t"));
+        assert_ne!(stored, cipher.encrypt("s3cr3t").unwrap());
+        assert_eq!(cipher.decrypt(&stored).unwrap(), "s3cr3t");
+    }
+
+    #[test]
+    fn test_wrong_key_is_rejected() {
+        let stored = EnvelopeCipher::generate().encrypt("s3cr3t").unwrap();
+        assert!(EnvelopeCipher::generate().decrypt(&stored).is_err());
+    }
+
+    #[test]
+    fn test_tampered_value_is_rejected() {
+        let cipher = EnvelopeCipher::generate();
+        let stored = cipher.encrypt("s3cr3t").unwrap();
+        let (head, value) = stored.rsplit_once(':').unwrap();
+        let mut bytes = BASE64.decode(value).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        let tampered = format!("{}:{}", head, BASE64.encode(bytes));
+
+        assert!(cipher.decrypt(&tampered).is_err());
+    }
+}
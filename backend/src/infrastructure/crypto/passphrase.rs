@@ -0,0 +1,114 @@
+use argon2::Argon2;
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+
+use crate::domain::secret_cipher::SecretCipher;
+use crate::error::{AppError, Result};
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Encrypts values with a key derived from a user-supplied passphrase (Argon2id),
+/// used for portable backups that must not depend on the server master key
+pub struct PassphraseCipher {
+    cipher: XChaCha20Poly1305,
+    salt: Vec<u8>,
+}
+
+impl PassphraseCipher {
+    pub fn new(passphrase: &str) -> Result<Self> {
+        let mut salt = vec![0u8; SALT_LEN];
+        rand::rng().fill_bytes(&mut salt);
+        Self::derive(passphrase, salt)
+    }
+
+    /// Rebuild the cipher for a backup, given the salt stored in it
+    pub fn with_salt(passphrase: &str, salt: &str) -> Result<Self> {
+        let salt = BASE64
+            .decode(salt)
+            .map_err(|_| AppError::BadRequest("Invalid backup salt".to_string()))?;
+        Self::derive(passphrase, salt)
+    }
+
+    fn derive(passphrase: &str, salt: Vec<u8>) -> Result<Self> {
+        if passphrase.is_empty() {
+            return Err(AppError::Validation(
+                "Backup passphrase must not be empty".to_string(),
+            ));
+        }
+
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|e| AppError::Internal(format!("Failed to derive backup key: {}", e)))?;
+
+        let cipher = XChaCha20Poly1305::new_from_slice(&key)
+            .map_err(|_| AppError::Internal("Invalid backup key".to_string()))?;
+        Ok(Self { cipher, salt })
+    }
+
+    pub fn salt(&self) -> String {
+        BASE64.encode(&self.salt)
+    }
+}
+
+impl SecretCipher for PassphraseCipher {
+    fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::rng().fill_bytes(&mut nonce);
+
+        let ciphertext = self
+            .cipher
+            .encrypt(XNonce::from_slice(&nonce), plaintext.as_bytes())
+            .map_err(|_| AppError::Internal("Failed to encrypt secret".to_string()))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(BASE64.encode(sealed))
+    }
+
+    fn decrypt(&self, stored: &str) -> Result<String> {
+        let sealed = BASE64
+            .decode(stored)
+            .map_err(|_| AppError::BadRequest("Malformed encrypted secret".to_string()))?;
+        if sealed.len() < NONCE_LEN {
+            return Err(AppError::BadRequest(
+                "Malformed encrypted secret".to_string(),
+            ));
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| AppError::BadRequest("Wrong backup passphrase".to_string()))?;
+
+        String::from_utf8(plaintext)
+            .map_err(|_| AppError::BadRequest("Decrypted secret is not valid UTF-8".to_string()))
+    }
+
+    fn is_encrypted(&self, stored: &str) -> bool {
+        BASE64
+            .decode(stored)
+            .is_ok_and(|sealed| sealed.len() > NONCE_LEN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_with_stored_salt() {
+        let cipher = PassphraseCipher::new("correct horse").unwrap();
+        let sealed = cipher.encrypt("s3cr3t").unwrap();
+
+        let restored = PassphraseCipher::with_salt("correct horse", &cipher.salt()).unwrap();
+        assert_eq!(restored.decrypt(&sealed).unwrap(), "s3cr3t");
+
+        let wrong = PassphraseCipher::with_salt("battery staple", &cipher.salt()).unwrap();
+        assert!(wrong.decrypt(&sealed).is_err());
+    }
+}
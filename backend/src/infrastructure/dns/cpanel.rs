@@ -1,58 +1,589 @@
 use crate::domain::dns_provider::DnsProvider;
-use crate::domain::models::dns::RemoteDnsRecord;
+use crate::domain::models::dns::{CPanelConfig, RemoteDnsRecord};
 use crate::error::{AppError, Result};
 use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::{Value, json};
 
+/// Record types Labuh creates and lists; everything else in the zone is left alone
+const MANAGED_TYPES: [&str; 4] = ["A", "AAAA", "CNAME", "TXT"];
+/// Types that point a name somewhere and replace one another on update
+const ADDRESS_TYPES: [&str; 3] = ["A", "AAAA", "CNAME"];
+const RECORD_TTL: u32 = 300;
+
+/// Manages zones through cPanel's UAPI (`DomainInfo` and `DNS`), authenticated with
+/// an API token. Edits go through `DNS::mass_edit_zone`, which refuses them when the
+/// zone's SOA serial changed since it was read.
+///
+/// Zone line numbers shift whenever a record is removed, so a record ID is
+/// `"<type> <name>"` and the line is looked up again on every change.
 pub struct CPanelProvider {
-    // Basic implementation details for CPanel
+    base_url: String,
+    user: String,
+    token: String,
+    client: reqwest::Client,
+}
+
+/// A record as read from `DNS::parse_zone`
+#[derive(Debug, Clone, PartialEq)]
+struct ZoneRecord {
+    line_index: u64,
+    /// Fully qualified, without the trailing dot
+    name: String,
+    record_type: String,
+    data: Vec<String>,
+}
+
+struct Zone {
+    serial: String,
+    records: Vec<ZoneRecord>,
 }
 
 impl CPanelProvider {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(config: CPanelConfig) -> Self {
+        Self {
+            base_url: base_url(&config.host),
+            user: config.user,
+            token: config.token,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn uapi(&self, module: &str, function: &str, params: &[(&str, String)]) -> Result<Value> {
+        let url = reqwest::Url::parse_with_params(
+            &format!("{}/execute/{}/{}", self.base_url, module, function),
+            params,
+        )
+        .map_err(|e| AppError::Validation(format!("Invalid cPanel host: {}", e)))?;
+
+        let response = self
+            .client
+            .get(url)
+            .header(
+                reqwest::header::AUTHORIZATION,
+                format!("cpanel {}:{}", self.user, self.token),
+            )
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("cPanel API error: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Internal(format!(
+                "cPanel API error ({}): {}",
+                status, error_text
+            )));
+        }
+
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to parse cPanel response: {}", e)))?;
+        if body["status"].as_i64() != Some(1) {
+            let errors: Vec<&str> = body["errors"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            return Err(AppError::Internal(format!(
+                "cPanel {}::{} failed: {}",
+                module,
+                function,
+                errors.join("; ")
+            )));
+        }
+        Ok(body["data"].clone())
+    }
+
+    /// The account's main, addon and parked domains; subdomains live in their
+    /// parent's zone
+    async fn fetch_zones(&self) -> Result<Vec<String>> {
+        let data = self.uapi("DomainInfo", "list_domains", &[]).await?;
+        let mut zones: Vec<String> = data["main_domain"]
+            .as_str()
+            .map(String::from)
+            .into_iter()
+            .collect();
+        for key in ["addon_domains", "parked_domains"] {
+            zones.extend(
+                data[key]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|d| d.as_str().map(String::from)),
+            );
+        }
+        Ok(zones)
+    }
+
+    /// The zone holding `domain`: the longest zone name it ends with
+    async fn zone_for(&self, domain: &str) -> Result<String> {
+        let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+        self.fetch_zones()
+            .await?
+            .into_iter()
+            .filter(|zone| domain == *zone || domain.ends_with(&format!(".{}", zone)))
+            .max_by_key(|zone| zone.len())
+            .ok_or_else(|| {
+                AppError::Validation(format!("No cPanel zone found for domain: {}", domain))
+            })
+    }
+
+    async fn parse_zone(&self, zone: &str) -> Result<Zone> {
+        let data = self
+            .uapi("DNS", "parse_zone", &[("zone", zone.to_string())])
+            .await?;
+        parse_zone_records(zone, &data)
+    }
+
+    /// Apply one change with the serial it was based on. A serial mismatch means
+    /// someone else edited the zone meanwhile, so the change is worked out again
+    /// against a fresh copy, once.
+    async fn edit_zone<F>(&self, zone: &str, change: F) -> Result<()>
+    where
+        F: Fn(&Zone) -> Result<(&'static str, String)>,
+    {
+        let mut attempt = 0;
+        loop {
+            let current = self.parse_zone(zone).await?;
+            let (operation, value) = change(&current)?;
+            let result = self
+                .uapi(
+                    "DNS",
+                    "mass_edit_zone",
+                    &[
+                        ("zone", zone.to_string()),
+                        ("serial", current.serial.clone()),
+                        (operation, value),
+                    ],
+                )
+                .await;
+            match result {
+                Err(AppError::Internal(message))
+                    if attempt == 0 && message.to_ascii_lowercase().contains("serial") =>
+                {
+                    tracing::debug!("cPanel zone {} changed meanwhile, retrying", zone);
+                    attempt += 1;
+                }
+                result => return result.map(|_| ()),
+            }
+        }
+    }
+}
+
+/// `https://host:2083` unless the configured host names a scheme or port itself
+fn base_url(host: &str) -> String {
+    let host = host.trim().trim_end_matches('/');
+    if host.starts_with("http://") || host.starts_with("https://") {
+        return host.to_string();
+    }
+    if host.contains(':') {
+        format!("https://{}", host)
+    } else {
+        format!("https://{}:2083", host)
+    }
+}
+
+fn decode_b64(value: &Value) -> Option<String> {
+    let bytes = STANDARD.decode(value.as_str()?).ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// Records and SOA serial from a `DNS::parse_zone` result. Names are relative
+/// to the zone unless they end with a dot.
+fn parse_zone_records(zone: &str, data: &Value) -> Result<Zone> {
+    let mut serial = None;
+    let mut records = Vec::new();
+    for entry in data.as_array().into_iter().flatten() {
+        if entry["type"].as_str() != Some("record") {
+            continue;
+        }
+        let record_type = entry["record_type"].as_str().unwrap_or_default();
+        let data: Vec<String> = entry["data_b64"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(decode_b64)
+            .collect();
+        if record_type == "SOA" {
+            serial = data.get(2).cloned();
+            continue;
+        }
+        let Some(dname) = decode_b64(&entry["dname_b64"]) else {
+            continue;
+        };
+        let name = match dname.strip_suffix('.') {
+            Some(absolute) => absolute.to_string(),
+            None if dname == "@" || dname.is_empty() => zone.to_string(),
+            None => format!("{}.{}", dname, zone),
+        };
+        records.push(ZoneRecord {
+            line_index: entry["line_index"].as_u64().unwrap_or_default(),
+            name: name.to_ascii_lowercase(),
+            record_type: record_type.to_string(),
+            data,
+        });
+    }
+    let serial = serial
+        .ok_or_else(|| AppError::Internal(format!("cPanel zone {} has no SOA serial", zone)))?;
+    Ok(Zone { serial, records })
+}
+
+fn record_id(record_type: &str, name: &str) -> String {
+    format!(
+        "{} {}",
+        record_type,
+        name.trim_end_matches('.').to_ascii_lowercase()
+    )
+}
+
+/// The record an ID refers to: same name and type, or, when the type was changed
+/// by an update, another address record of that name
+fn find_record<'a>(zone: &'a Zone, record_id: &str) -> Option<&'a ZoneRecord> {
+    let (record_type, name) = record_id.split_once(' ')?;
+    zone.records
+        .iter()
+        .find(|r| r.name == name && r.record_type == record_type)
+        .or_else(|| {
+            zone.records.iter().find(|r| {
+                r.name == name
+                    && ADDRESS_TYPES.contains(&record_type)
+                    && ADDRESS_TYPES.contains(&r.record_type.as_str())
+            })
+        })
+}
+
+/// Record as `mass_edit_zone` takes it: an absolute name and the type's data
+fn zone_record(domain: &str, record_type: &str, content: &str) -> Result<Value> {
+    let record_type = record_type.to_ascii_uppercase();
+    if !MANAGED_TYPES.contains(&record_type.as_str()) {
+        return Err(AppError::Validation(format!(
+            "cPanel provider does not manage {} records",
+            record_type
+        )));
     }
+    let content = match record_type.as_str() {
+        "CNAME" if !content.ends_with('.') => format!("{}.", content),
+        _ => content.to_string(),
+    };
+    Ok(json!({
+        "dname": format!("{}.", domain.trim_end_matches('.')),
+        "ttl": RECORD_TTL,
+        "record_type": record_type,
+        "data": [content]
+    }))
 }
 
 #[async_trait]
 impl DnsProvider for CPanelProvider {
     async fn create_record(
         &self,
-        _domain: &str,
-        _record_type: &str,
-        _content: &str,
+        domain: &str,
+        record_type: &str,
+        content: &str,
         _proxied: bool,
     ) -> Result<String> {
-        Err(AppError::Internal(
-            "cPanel provider not yet implemented".to_string(),
+        let zone = self.zone_for(domain).await?;
+        let record = zone_record(domain, record_type, content)?;
+        self.edit_zone(&zone, |_| Ok(("add", record.to_string())))
+            .await?;
+        Ok(record_id(
+            record["record_type"].as_str().unwrap_or_default(),
+            domain,
         ))
     }
 
-    async fn delete_record(&self, _domain: &str, _record_id: &str) -> Result<()> {
-        Err(AppError::Internal(
-            "cPanel provider not yet implemented".to_string(),
-        ))
+    async fn delete_record(&self, domain: &str, record_id: &str) -> Result<()> {
+        let zone = self.zone_for(domain).await?;
+        self.edit_zone(&zone, |current| {
+            let record = find_record(current, record_id).ok_or_else(|| {
+                AppError::NotFound(format!("cPanel record {} not found", record_id))
+            })?;
+            Ok(("remove", record.line_index.to_string()))
+        })
+        .await
     }
 
     async fn list_available_base_domains(&self) -> Result<Vec<String>> {
-        Err(AppError::Internal(
-            "cPanel provider not yet implemented".to_string(),
-        ))
+        self.fetch_zones().await
     }
 
     async fn list_records(&self) -> Result<Vec<RemoteDnsRecord>> {
-        Ok(vec![])
+        let mut all_records = Vec::new();
+        for zone_name in self.fetch_zones().await? {
+            let zone = match self.parse_zone(&zone_name).await {
+                Ok(zone) => zone,
+                Err(e) => {
+                    tracing::warn!("Skipping cPanel zone {}: {}", zone_name, e);
+                    continue;
+                }
+            };
+            for r in zone.records {
+                if !MANAGED_TYPES.contains(&r.record_type.as_str()) {
+                    continue;
+                }
+                all_records.push(RemoteDnsRecord {
+                    id: record_id(&r.record_type, &r.name),
+                    content: r.data.concat(),
+                    name: r.name,
+                    r#type: r.record_type,
+                    zone_id: zone_name.clone(),
+                    zone_name: zone_name.clone(),
+                });
+            }
+        }
+        Ok(all_records)
     }
 
     async fn update_record(
         &self,
-        _domain: &str,
-        _record_id: &str,
-        _record_type: &str,
-        _content: &str,
+        domain: &str,
+        record_id: &str,
+        record_type: &str,
+        content: &str,
         _proxied: bool,
     ) -> Result<()> {
-        Err(AppError::Internal(
-            "cPanel provider not yet implemented".to_string(),
-        ))
+        let zone = self.zone_for(domain).await?;
+        let record = zone_record(domain, record_type, content)?;
+        self.edit_zone(&zone, |current| {
+            let existing = find_record(current, record_id).ok_or_else(|| {
+                AppError::NotFound(format!("cPanel record {} not found", record_id))
+            })?;
+            let mut record = record.clone();
+            record["line_index"] = json!(existing.line_index);
+            Ok(("edit", record.to_string()))
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Path, Query, State};
+    use axum::http::HeaderMap;
+    use axum::{Json, Router, routing::get};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    fn b64(value: &str) -> String {
+        STANDARD.encode(value)
+    }
+
+    /// Stand-in for cPanel's UAPI holding one zone. Every edit bumps the serial,
+    /// and `conflicts` edits are refused as if someone else changed the zone first.
+    #[derive(Clone)]
+    struct MockCPanel {
+        serial: Arc<Mutex<u64>>,
+        /// `(dname, type, data)` by line
+        lines: Arc<Mutex<Vec<(String, String, String)>>>,
+        conflicts: Arc<Mutex<usize>>,
+        edits: Arc<Mutex<Vec<HashMap<String, String>>>>,
+    }
+
+    fn uapi_error(message: &str) -> Json<Value> {
+        Json(json!({ "status": 0, "errors": [message], "data": null }))
+    }
+
+    async fn execute(
+        State(cpanel): State<MockCPanel>,
+        Path((module, function)): Path<(String, String)>,
+        Query(params): Query<HashMap<String, String>>,
+        headers: HeaderMap,
+    ) -> Json<Value> {
+        if headers.get("authorization").and_then(|v| v.to_str().ok()) != Some("cpanel deploy:TOKEN")
+        {
+            return uapi_error("Access denied");
+        }
+        match (module.as_str(), function.as_str()) {
+            ("DomainInfo", "list_domains") => Json(json!({
+                "status": 1,
+                "data": {
+                    "main_domain": "example.com",
+                    "addon_domains": ["shop.example.com"],
+                    "parked_domains": [],
+                    "sub_domains": ["blog.example.com"]
+                }
+            })),
+            ("DNS", "parse_zone") => {
+                let serial = cpanel.serial.lock().unwrap().to_string();
+                let mut data = vec![
+                    json!({ "type": "control", "line_index": 0, "text_b64": b64("$TTL 14400") }),
+                    json!({
+                        "type": "record", "line_index": 1, "record_type": "SOA", "ttl": 86400,
+                        "dname_b64": b64("example.com."),
+                        "data_b64": [b64("ns1.example.com."), b64("admin.example.com."), b64(&serial),
+                                     b64("3600"), b64("1800"), b64("1209600"), b64("86400")]
+                    }),
+                ];
+                for (i, (dname, record_type, value)) in
+                    cpanel.lines.lock().unwrap().iter().enumerate()
+                {
+                    data.push(json!({
+                        "type": "record", "line_index": i + 2, "record_type": record_type, "ttl": 300,
+                        "dname_b64": b64(dname), "data_b64": [b64(value)]
+                    }));
+                }
+                Json(json!({ "status": 1, "data": data }))
+            }
+            ("DNS", "mass_edit_zone") => {
+                cpanel.edits.lock().unwrap().push(params.clone());
+                let mut serial = cpanel.serial.lock().unwrap();
+                {
+                    let mut conflicts = cpanel.conflicts.lock().unwrap();
+                    if *conflicts > 0 {
+                        *conflicts -= 1;
+                        *serial += 1;
+                    }
+                }
+                if params.get("serial") != Some(&serial.to_string()) {
+                    return uapi_error(
+                        "The given serial number does not match the DNS zone's serial number.",
+                    );
+                }
+                let mut lines = cpanel.lines.lock().unwrap();
+                let record = |value: &str| -> (String, String, String) {
+                    let r: Value = serde_json::from_str(value).unwrap();
+                    (
+                        r["dname"].as_str().unwrap().to_string(),
+                        r["record_type"].as_str().unwrap().to_string(),
+                        r["data"][0].as_str().unwrap().to_string(),
+                    )
+                };
+                if let Some(add) = params.get("add") {
+                    lines.push(record(add));
+                }
+                if let Some(edit) = params.get("edit") {
+                    let line = serde_json::from_str::<Value>(edit).unwrap()["line_index"]
+                        .as_u64()
+                        .unwrap() as usize;
+                    lines[line - 2] = record(edit);
+                }
+                if let Some(remove) = params.get("remove") {
+                    lines.remove(remove.parse::<usize>().unwrap() - 2);
+                }
+                *serial += 1;
+                Json(json!({ "status": 1, "data": { "new_serial": serial.to_string() } }))
+            }
+            _ => uapi_error("Unknown function"),
+        }
+    }
+
+    async fn start_mock_cpanel() -> (MockCPanel, CPanelProvider) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("http://{}", listener.local_addr().unwrap());
+        let cpanel = MockCPanel {
+            serial: Arc::new(Mutex::new(2026101801)),
+            lines: Arc::new(Mutex::new(vec![
+                ("@".to_string(), "A".to_string(), "198.51.100.1".to_string()),
+                (
+                    "example.com.".to_string(),
+                    "TXT".to_string(),
+                    "v=spf1 -all".to_string(),
+                ),
+                (
+                    "mail".to_string(),
+                    "MX".to_string(),
+                    "10 mail.example.com.".to_string(),
+                ),
+            ])),
+            conflicts: Arc::new(Mutex::new(0)),
+            edits: Arc::new(Mutex::new(Vec::new())),
+        };
+        let app = Router::new()
+            .route("/execute/{module}/{function}", get(execute))
+            .with_state(cpanel.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let provider = CPanelProvider::new(CPanelConfig {
+            host,
+            token: "TOKEN".to_string(),
+            user: "deploy".to_string(),
+        });
+        (cpanel, provider)
+    }
+
+    #[test]
+    fn test_base_url() {
+        assert_eq!(
+            base_url("cpanel.example.com"),
+            "https://cpanel.example.com:2083"
+        );
+        assert_eq!(
+            base_url("cpanel.example.com:2083/"),
+            "https://cpanel.example.com:2083"
+        );
+        assert_eq!(base_url("http://127.0.0.1:8080"), "http://127.0.0.1:8080");
+    }
+
+    #[tokio::test]
+    async fn test_lists_zones_and_records() {
+        let (_, provider) = start_mock_cpanel().await;
+        assert_eq!(
+            provider.list_available_base_domains().await.unwrap(),
+            vec!["example.com", "shop.example.com"]
+        );
+
+        let records = provider.list_records().await.unwrap();
+        // Both zones answer with the same mock data; MX is not managed
+        let example: Vec<_> = records
+            .iter()
+            .filter(|r| r.zone_name == "example.com")
+            .collect();
+        assert_eq!(example.len(), 2);
+        assert_eq!(example[0].name, "example.com");
+        assert_eq!(example[0].r#type, "A");
+        assert_eq!(example[0].id, "A example.com");
+        assert_eq!(example[1].content, "v=spf1 -all");
+    }
+
+    #[tokio::test]
+    async fn test_record_lifecycle_with_serial() {
+        let (cpanel, provider) = start_mock_cpanel().await;
+
+        let id = provider
+            .create_record("app.example.com", "A", "203.0.113.9", false)
+            .await
+            .unwrap();
+        assert_eq!(id, "A app.example.com");
+        let edit = cpanel.edits.lock().unwrap()[0].clone();
+        assert_eq!(edit["zone"], "example.com");
+        assert_eq!(edit["serial"], "2026101801");
+
+        // Changing the type keeps the same record
+        provider
+            .update_record("app.example.com", &id, "CNAME", "lb.example.net", false)
+            .await
+            .unwrap();
+        let lines = cpanel.lines.lock().unwrap().clone();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[3],
+            (
+                "app.example.com.".to_string(),
+                "CNAME".to_string(),
+                "lb.example.net.".to_string()
+            )
+        );
+
+        // Someone else edits the zone first; the removal is retried on a fresh serial
+        *cpanel.conflicts.lock().unwrap() = 1;
+        provider
+            .delete_record("app.example.com", &id)
+            .await
+            .unwrap();
+        assert_eq!(cpanel.lines.lock().unwrap().len(), 3);
+        assert_eq!(cpanel.edits.lock().unwrap().len(), 4);
+
+        let missing = provider.delete_record("app.example.com", &id).await;
+        assert!(matches!(missing, Err(AppError::NotFound(_))));
+        assert!(
+            provider
+                .create_record("x.other.org", "A", "203.0.113.9", false)
+                .await
+                .is_err()
+        );
     }
 }
@@ -3,16 +3,19 @@ use bollard::Docker;
 use bollard::container::LogOutput;
 use bollard::exec::{CreateExecOptions, StartExecOptions};
 use bollard::models::{
-    ContainerCreateBody, EndpointPortConfig, EndpointSettings, EndpointSpec, HostConfig, Limit,
-    LocalNodeState, NetworkAttachmentConfig, NetworkConnectRequest, NetworkCreateRequest,
-    NetworkingConfig, PortBinding, RestartPolicy, RestartPolicyNameEnum, ServiceSpec,
-    ServiceSpecMode, ServiceSpecModeReplicated, SwarmInitRequest, SwarmJoinRequest, TaskSpec,
-    TaskSpecContainerSpec, TaskSpecResources,
+    ConfigSpec, ContainerCreateBody, EndpointPortConfig, EndpointSettings, EndpointSpec,
+    HostConfig, Limit, LocalNodeState, NetworkAttachmentConfig, NetworkConnectRequest,
+    NetworkCreateRequest, NetworkingConfig, PortBinding, RestartPolicy, RestartPolicyNameEnum,
+    SecretSpec, ServiceSpec, ServiceSpecMode, ServiceSpecModeReplicated, SwarmInitRequest,
+    SwarmJoinRequest, TaskSpec, TaskSpecContainerSpec, TaskSpecContainerSpecConfigs,
+    TaskSpecContainerSpecFile, TaskSpecContainerSpecFile1, TaskSpecContainerSpecSecrets,
+    TaskSpecResources,
 };
 use bollard::query_parameters::{
-    BuildImageOptions, CreateContainerOptions, CreateImageOptions, ListContainersOptions,
-    ListImagesOptions, ListNetworksOptions, ListNodesOptions, LogsOptions, RemoveContainerOptions,
-    RemoveImageOptions, StartContainerOptions, StatsOptions, StopContainerOptions,
+    BuildImageOptions, CreateContainerOptions, CreateImageOptions, ListConfigsOptions,
+    ListContainersOptions, ListImagesOptions, ListNetworksOptions, ListNodesOptions,
+    ListSecretsOptions, LogsOptions, PushImageOptions, RemoveContainerOptions, RemoveImageOptions,
+    StartContainerOptions, StatsOptions, StopContainerOptions, TagImageOptions,
     UpdateServiceOptions,
 };
 use futures::StreamExt;
@@ -24,7 +27,8 @@ use tokio::sync::mpsc;
 
 use crate::domain::runtime::{
     ContainerConfig, ContainerInfo, ContainerPort, EndpointInfo, NetworkInfo, NodeResources,
-    RuntimePort, ServiceConfig, ServiceInfo, SwarmNode, SwarmTokens,
+    RuntimePort, ServiceConfig, ServiceFileMount, ServiceInfo, SwarmNode, SwarmObjectInfo,
+    SwarmTokens,
 };
 use crate::error::{AppError, Result};
 
@@ -48,6 +52,79 @@ impl DockerRuntimeAdapter {
     }
 }
 
+fn to_docker_credentials(
+    credentials: Option<(String, String)>,
+) -> Option<bollard::auth::DockerCredentials> {
+    credentials.map(|(u, p)| bollard::auth::DockerCredentials {
+        username: Some(u),
+        password: Some(p),
+        ..Default::default()
+    })
+}
+
+/// Split `registry/repo:tag` into repository and tag, ignoring a registry port
+fn split_image_ref(image: &str) -> (&str, Option<&str>) {
+    match image.rfind(':') {
+        Some(pos) if !image[pos..].contains('/') => (&image[..pos], Some(&image[pos + 1..])),
+        _ => (image, None),
+    }
+}
+
+fn secret_references(mounts: &[ServiceFileMount]) -> Option<Vec<TaskSpecContainerSpecSecrets>> {
+    if mounts.is_empty() {
+        return None;
+    }
+    Some(
+        mounts
+            .iter()
+            .map(|m| TaskSpecContainerSpecSecrets {
+                file: Some(TaskSpecContainerSpecFile {
+                    name: Some(m.target.clone()),
+                    uid: Some(m.uid.clone().unwrap_or_else(|| "0".to_string())),
+                    gid: Some(m.gid.clone().unwrap_or_else(|| "0".to_string())),
+                    mode: Some(m.mode.unwrap_or(0o444)),
+                }),
+                secret_id: Some(m.object_id.clone()),
+                secret_name: Some(m.object_name.clone()),
+            })
+            .collect(),
+    )
+}
+
+fn config_references(mounts: &[ServiceFileMount]) -> Option<Vec<TaskSpecContainerSpecConfigs>> {
+    if mounts.is_empty() {
+        return None;
+    }
+    Some(
+        mounts
+            .iter()
+            .map(|m| TaskSpecContainerSpecConfigs {
+                file: Some(TaskSpecContainerSpecFile1 {
+                    name: Some(m.target.clone()),
+                    uid: Some(m.uid.clone().unwrap_or_else(|| "0".to_string())),
+                    gid: Some(m.gid.clone().unwrap_or_else(|| "0".to_string())),
+                    mode: Some(m.mode.unwrap_or(0o444)),
+                }),
+                config_id: Some(m.object_id.clone()),
+                config_name: Some(m.object_name.clone()),
+                ..Default::default()
+            })
+            .collect(),
+    )
+}
+
+fn to_swarm_object(
+    id: Option<String>,
+    name: Option<String>,
+    labels: Option<HashMap<String, String>>,
+) -> SwarmObjectInfo {
+    SwarmObjectInfo {
+        id: id.unwrap_or_default(),
+        name: name.unwrap_or_default(),
+        labels: labels.unwrap_or_default(),
+    }
+}
+
 #[async_trait]
 impl RuntimePort for DockerRuntimeAdapter {
     async fn pull_image(&self, image: &str, credentials: Option<(String, String)>) -> Result<()> {
@@ -56,11 +133,7 @@ impl RuntimePort for DockerRuntimeAdapter {
             ..Default::default()
         };
 
-        let auth = credentials.map(|(u, p)| bollard::auth::DockerCredentials {
-            username: Some(u),
-            password: Some(p),
-            ..Default::default()
-        });
+        let auth = to_docker_credentials(credentials);
 
         let mut stream = self.docker.create_image(Some(options), None, auth);
 
@@ -83,6 +156,58 @@ impl RuntimePort for DockerRuntimeAdapter {
         Ok(())
     }
 
+    async fn tag_image(&self, source: &str, target: &str) -> Result<()> {
+        let (repo, tag) = split_image_ref(target);
+        let options = TagImageOptions {
+            repo: Some(repo.to_string()),
+            tag: tag.map(|t| t.to_string()),
+        };
+
+        self.docker
+            .tag_image(source, Some(options))
+            .await
+            .map_err(|e| AppError::ContainerRuntime(format!("Failed to tag image: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn push_image(&self, image: &str, credentials: Option<(String, String)>) -> Result<()> {
+        let (repo, tag) = split_image_ref(image);
+        let options = PushImageOptions {
+            tag: tag.map(|t| t.to_string()),
+            ..Default::default()
+        };
+
+        let auth = to_docker_credentials(credentials);
+
+        let mut stream = self.docker.push_image(repo, Some(options), auth);
+
+        while let Some(result) = stream.next().await {
+            match result {
+                Ok(info) => {
+                    // Registry errors are reported inside the stream, not as HTTP errors
+                    if let Some(detail) = info.error_detail {
+                        return Err(AppError::ContainerRuntime(format!(
+                            "Failed to push image: {}",
+                            detail.message.unwrap_or_default()
+                        )));
+                    }
+                    if let Some(status) = info.status {
+                        tracing::debug!("Push status: {}", status);
+                    }
+                }
+                Err(e) => {
+                    return Err(AppError::ContainerRuntime(format!(
+                        "Failed to push image: {}",
+                        e
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     async fn create_container(&self, config: ContainerConfig) -> Result<String> {
         // Build exposed ports and port bindings
         let mut exposed_ports: Vec<String> = Vec::new();
@@ -1045,6 +1170,8 @@ impl RuntimePort for DockerRuntimeAdapter {
             image: Some(config.image),
             env: Some(config.env),
             labels: Some(labels.clone()),
+            secrets: secret_references(&config.secrets),
+            configs: config_references(&config.configs),
             ..Default::default()
         };
 
@@ -1132,7 +1259,7 @@ impl RuntimePort for DockerRuntimeAdapter {
 
         let response = self
             .docker
-            .create_service(spec, None)
+            .create_service(spec, to_docker_credentials(config.registry_auth))
             .await
             .map_err(|e| AppError::ContainerRuntime(e.to_string()))?;
 
@@ -1245,6 +1372,8 @@ impl RuntimePort for DockerRuntimeAdapter {
                     Some(config.env)
                 },
                 labels: Some(labels.clone()),
+                secrets: secret_references(&config.secrets),
+                configs: config_references(&config.configs),
                 ..Default::default()
             }),
             networks: if networks.is_empty() {
@@ -1317,7 +1446,12 @@ impl RuntimePort for DockerRuntimeAdapter {
         };
 
         self.docker
-            .update_service(&service_id, spec, options, None)
+            .update_service(
+                &service_id,
+                spec,
+                options,
+                to_docker_credentials(config.registry_auth),
+            )
             .await
             .map_err(|e: bollard::errors::Error| AppError::ContainerRuntime(e.to_string()))?;
 
@@ -1364,4 +1498,121 @@ impl RuntimePort for DockerRuntimeAdapter {
 
         Ok(())
     }
+
+    async fn create_secret(
+        &self,
+        name: &str,
+        data: &[u8],
+        labels: HashMap<String, String>,
+    ) -> Result<String> {
+        use base64::Engine;
+        let spec = SecretSpec {
+            name: Some(name.to_string()),
+            labels: Some(labels),
+            data: Some(base64::engine::general_purpose::STANDARD.encode(data)),
+            ..Default::default()
+        };
+
+        let response =
+            self.docker.create_secret(spec).await.map_err(|e| {
+                AppError::ContainerRuntime(format!("Failed to create secret: {}", e))
+            })?;
+
+        Ok(response.id)
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<SwarmObjectInfo>> {
+        let secrets = self
+            .docker
+            .list_secrets(None::<ListSecretsOptions>)
+            .await
+            .map_err(|e| AppError::ContainerRuntime(e.to_string()))?;
+
+        Ok(secrets
+            .into_iter()
+            .map(|s| {
+                let spec = s.spec.unwrap_or_default();
+                to_swarm_object(s.id, spec.name, spec.labels)
+            })
+            .collect())
+    }
+
+    async fn remove_secret(&self, id: &str) -> Result<()> {
+        self.docker
+            .delete_secret(id)
+            .await
+            .map_err(|e| AppError::ContainerRuntime(e.to_string()))
+    }
+
+    async fn create_config(
+        &self,
+        name: &str,
+        data: &[u8],
+        labels: HashMap<String, String>,
+    ) -> Result<String> {
+        use base64::Engine;
+        let spec = ConfigSpec {
+            name: Some(name.to_string()),
+            labels: Some(labels),
+            data: Some(base64::engine::general_purpose::STANDARD.encode(data)),
+            ..Default::default()
+        };
+
+        let response =
+            self.docker.create_config(spec).await.map_err(|e| {
+                AppError::ContainerRuntime(format!("Failed to create config: {}", e))
+            })?;
+
+        Ok(response.id)
+    }
+
+    async fn list_configs(&self) -> Result<Vec<SwarmObjectInfo>> {
+        let configs = self
+            .docker
+            .list_configs(None::<ListConfigsOptions>)
+            .await
+            .map_err(|e| AppError::ContainerRuntime(e.to_string()))?;
+
+        Ok(configs
+            .into_iter()
+            .map(|c| {
+                let spec = c.spec.unwrap_or_default();
+                to_swarm_object(c.id, spec.name, spec.labels)
+            })
+            .collect())
+    }
+
+    async fn remove_config(&self, id: &str) -> Result<()> {
+        self.docker
+            .delete_config(id)
+            .await
+            .map_err(|e| AppError::ContainerRuntime(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_image_ref() {
+        for (image, expected) in [
+            ("nginx", ("nginx", None)),
+            ("nginx:1.27", ("nginx", Some("1.27"))),
+            (
+                "ghcr.io/acme/web:sha-1a2b",
+                ("ghcr.io/acme/web", Some("sha-1a2b")),
+            ),
+            (
+                "registry.local:5000/acme/web",
+                ("registry.local:5000/acme/web", None),
+            ),
+            (
+                "registry.local:5000/acme/web:v2",
+                ("registry.local:5000/acme/web", Some("v2")),
+            ),
+        ] {
+            assert_eq!(split_image_ref(image), expected, "{}", image);
+        }
+    }
 }
@@ -1,8 +1,12 @@
 pub mod auth;
 pub mod caddy;
+pub mod crypto;
 pub mod dns;
 pub mod docker;
 pub mod git;
 pub mod linux_system;
+pub mod oidc;
+pub mod rate_limit;
+pub mod secret_files;
 pub mod sqlite;
 pub mod tunnel;
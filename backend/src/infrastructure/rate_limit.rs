@@ -0,0 +1,74 @@
+//! In-process fixed-window request limiter keyed by client
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::{AppError, Result};
+
+/// Drop idle buckets once the map grows past this size
+const PRUNE_THRESHOLD: usize = 10_000;
+
+pub struct RateLimiter {
+    limit: u32,
+    window: Duration,
+    buckets: Mutex<HashMap<String, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    /// `limit` requests per `window`; a limit of 0 disables the limiter
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn per_minute(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(60))
+    }
+
+    /// Count a request for `key`, failing with `RateLimited` once the window is used up
+    pub fn check(&self, key: &str) -> Result<()> {
+        if self.limit == 0 {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() > PRUNE_THRESHOLD {
+            buckets.retain(|_, (start, _)| now.duration_since(*start) < self.window);
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert((now, 0));
+        if now.duration_since(bucket.0) >= self.window {
+            *bucket = (now, 0);
+        }
+        if bucket.1 >= self.limit {
+            let retry_after = self.window.saturating_sub(now.duration_since(bucket.0));
+            return Err(AppError::RateLimited(retry_after.as_secs().max(1)));
+        }
+        bucket.1 += 1;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_per_key_and_resets_after_window() {
+        let limiter = RateLimiter::new(2, Duration::from_millis(50));
+        assert!(limiter.check("a").is_ok());
+        assert!(limiter.check("a").is_ok());
+        assert!(matches!(limiter.check("a"), Err(AppError::RateLimited(_))));
+        assert!(limiter.check("b").is_ok());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(limiter.check("a").is_ok());
+
+        assert!(RateLimiter::per_minute(0).check("a").is_ok());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::error::{AppError, Result};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+const SECRET_FILES_DIR: &str = "backend/data/secrets";
+
+/// Materialises compose secrets and configs as files for standalone containers.
+/// Each stack gets a 0700 directory that is bind-mounted read-only into its containers.
+pub struct SecretFileStore;
+
+impl SecretFileStore {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn stack_dir(stack_id: &str) -> PathBuf {
+        Path::new(SECRET_FILES_DIR).join(stack_id)
+    }
+
+    /// Directory of one service; refuses names that would leave the stack directory
+    fn service_dir(stack_id: &str, service: &str) -> Result<PathBuf> {
+        if service.is_empty() || service == "." || service == ".." || service.contains(['/', '\\'])
+        {
+            return Err(AppError::Validation(format!(
+                "Invalid service name '{}'",
+                service
+            )));
+        }
+        Ok(Self::stack_dir(stack_id).join(service))
+    }
+
+    async fn create_private_dir(path: &Path) -> Result<()> {
+        tokio::fs::create_dir_all(path)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to create secret directory: {}", e)))?;
+        tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(0o700))
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to secure secret directory: {}", e)))
+    }
+
+    /// Drop the files previously written for a service so removed secrets don't linger
+    pub async fn clear_service(&self, stack_id: &str, service: &str) -> Result<()> {
+        let dir = Self::service_dir(stack_id, service)?;
+        match tokio::fs::remove_dir_all(&dir).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::Internal(format!(
+                "Failed to clear secret files: {}",
+                e
+            ))),
+        }
+    }
+
+    /// Write one file for a service and return its absolute host path for bind-mounting.
+    /// `target` is the path inside the container and only used to derive a unique file name.
+    pub async fn write_file(
+        &self,
+        stack_id: &str,
+        service: &str,
+        target: &str,
+        data: &[u8],
+        mode: u32,
+        owner: Option<(u32, u32)>,
+    ) -> Result<String> {
+        let service_dir = Self::service_dir(stack_id, service)?;
+        Self::create_private_dir(&Self::stack_dir(stack_id)).await?;
+        Self::create_private_dir(&service_dir).await?;
+
+        let file_name = target.trim_start_matches('/').replace('/', "_");
+        let path = service_dir.join(file_name);
+
+        // Write with owner-only permissions first, then relax to the requested mode
+        let _ = tokio::fs::remove_file(&path).await;
+        tokio::fs::write(&path, data)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to write secret file: {}", e)))?;
+        tokio::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to secure secret file: {}", e)))?;
+
+        if let Some((uid, gid)) = owner
+            && let Err(e) = std::os::unix::fs::chown(&path, Some(uid), Some(gid))
+        {
+            tracing::warn!("Failed to change owner of {}: {}", path.display(), e);
+        }
+
+        tokio::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode))
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to secure secret file: {}", e)))?;
+
+        let absolute = tokio::fs::canonicalize(&path)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to resolve secret file: {}", e)))?;
+        Ok(absolute.to_string_lossy().into_owned())
+    }
+
+    pub async fn remove_stack(&self, stack_id: &str) -> Result<()> {
+        match tokio::fs::remove_dir_all(Self::stack_dir(stack_id)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::Internal(format!(
+                "Failed to remove secret files: {}",
+                e
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_refuses_service_names_outside_stack_directory() {
+        let store = SecretFileStore::new();
+        for service in ["../../git", "..", "a/b", "a\\b", ""] {
+            assert!(
+                matches!(
+                    store.clear_service("s1", service).await,
+                    Err(AppError::Validation(_))
+                ),
+                "{}",
+                service
+            );
+            assert!(
+                store
+                    .write_file("s1", service, "/run/secrets/db", b"x", 0o400, None)
+                    .await
+                    .is_err(),
+                "{}",
+                service
+            );
+        }
+    }
+}
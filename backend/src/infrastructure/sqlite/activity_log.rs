@@ -0,0 +1,200 @@
+use crate::domain::activity_log_repository::ActivityLogRepository;
+use crate::domain::models::{ActivityLog, AuditLogEntry, AuditLogQuery};
+use crate::error::Result;
+use async_trait::async_trait;
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+pub struct SqliteActivityLogRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteActivityLogRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Append the team scope and optional filters as a WHERE clause
+fn push_filters<'a>(
+    builder: &mut QueryBuilder<'a, Sqlite>,
+    team_id: &'a str,
+    query: &'a AuditLogQuery,
+) {
+    builder.push(" WHERE a.team_id = ").push_bind(team_id);
+
+    if let Some(action) = query.action.as_deref() {
+        if action.ends_with('.') {
+            builder
+                .push(" AND substr(a.action, 1, ")
+                .push_bind(action.len() as i64)
+                .push(") = ")
+                .push_bind(action);
+        } else {
+            builder.push(" AND a.action = ").push_bind(action);
+        }
+    }
+    if let Some(user_id) = query.user_id.as_deref() {
+        builder.push(" AND a.user_id = ").push_bind(user_id);
+    }
+    if let Some(resource_type) = query.resource_type.as_deref() {
+        builder
+            .push(" AND a.resource_type = ")
+            .push_bind(resource_type);
+    }
+    if let Some(resource_id) = query.resource_id.as_deref() {
+        builder.push(" AND a.resource_id = ").push_bind(resource_id);
+    }
+    if let Some(since) = query.since.as_deref() {
+        builder.push(" AND a.created_at >= ").push_bind(since);
+    }
+    if let Some(until) = query.until.as_deref() {
+        builder.push(" AND a.created_at < ").push_bind(until);
+    }
+}
+
+#[async_trait]
+impl ActivityLogRepository for SqliteActivityLogRepository {
+    async fn create(&self, log: ActivityLog) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO activity_logs (id, user_id, team_id, action, resource_type, resource_id, details, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&log.id)
+        .bind(&log.user_id)
+        .bind(&log.team_id)
+        .bind(&log.action)
+        .bind(&log.resource_type)
+        .bind(&log.resource_id)
+        .bind(&log.details)
+        .bind(&log.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_by_team(
+        &self,
+        team_id: &str,
+        query: &AuditLogQuery,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<AuditLogEntry>> {
+        let mut builder = QueryBuilder::new(
+            r#"
+            SELECT a.id, a.user_id, u.email AS user_email, u.name AS user_name, a.action,
+                   a.resource_type, a.resource_id, a.details, a.created_at
+            FROM activity_logs a
+            LEFT JOIN users u ON u.id = a.user_id
+            "#,
+        );
+        push_filters(&mut builder, team_id, query);
+        builder
+            .push(" ORDER BY a.created_at DESC, a.id DESC LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        let entries = builder
+            .build_query_as::<AuditLogEntry>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(entries)
+    }
+
+    async fn count_by_team(&self, team_id: &str, query: &AuditLogQuery) -> Result<i64> {
+        let mut builder = QueryBuilder::new("SELECT COUNT(*) FROM activity_logs a");
+        push_filters(&mut builder, team_id, query);
+
+        let count: i64 = builder.build_query_scalar().fetch_one(&self.pool).await?;
+
+        Ok(count)
+    }
+
+    async fn delete_before(&self, cutoff: &str) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM activity_logs WHERE created_at < ?")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    fn entry(id: &str, team_id: &str, action: &str, created_at: &str) -> ActivityLog {
+        ActivityLog {
+            id: id.to_string(),
+            user_id: None,
+            team_id: Some(team_id.to_string()),
+            action: action.to_string(),
+            resource_type: Some("stack".to_string()),
+            resource_id: Some("s1".to_string()),
+            details: Some("{}".to_string()),
+            created_at: created_at.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_team_filters_pagination_and_pruning() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        crate::db::run_migrations(&pool).await.unwrap();
+        for team in ["t1", "t2"] {
+            sqlx::query(
+                "INSERT INTO teams (id, name, created_at, updated_at) VALUES (?, ?, '', '')",
+            )
+            .bind(team)
+            .bind(team)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let repo = SqliteActivityLogRepository::new(pool);
+        for log in [
+            entry("1", "t1", "stack.create", "2026-01-01T00:00:00+00:00"),
+            entry("2", "t1", "stack.deploy", "2026-01-02T00:00:00+00:00"),
+            entry("3", "t1", "stacks.other", "2026-01-03T00:00:00+00:00"),
+            entry("4", "t1", "team.member_role", "2026-01-04T00:00:00+00:00"),
+            entry("5", "t2", "stack.create", "2026-01-05T00:00:00+00:00"),
+        ] {
+            repo.create(log).await.unwrap();
+        }
+
+        let stack_actions = AuditLogQuery {
+            action: Some("stack.".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(repo.count_by_team("t1", &stack_actions).await.unwrap(), 2);
+        let page = repo.list_by_team("t1", &stack_actions, 1, 0).await.unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].id, "2");
+
+        let since = AuditLogQuery {
+            since: Some("2026-01-03".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(repo.count_by_team("t1", &since).await.unwrap(), 2);
+
+        assert_eq!(
+            repo.delete_before("2026-01-03T00:00:00+00:00")
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            repo.count_by_team("t1", &AuditLogQuery::default())
+                .await
+                .unwrap(),
+            2
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use crate::domain::api_token_repository::ApiTokenRepository;
+use crate::domain::models::ApiToken;
+use crate::error::{AppError, Result};
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+
+pub struct SqliteApiTokenRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteApiTokenRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ApiTokenRepository for SqliteApiTokenRepository {
+    async fn create(&self, token: ApiToken) -> Result<ApiToken> {
+        sqlx::query(
+            r#"
+            INSERT INTO api_tokens (id, user_id, team_id, created_by, name, token_prefix, token_hash, scopes, expires_at, last_used_at, revoked_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&token.id)
+        .bind(&token.user_id)
+        .bind(&token.team_id)
+        .bind(&token.created_by)
+        .bind(&token.name)
+        .bind(&token.token_prefix)
+        .bind(&token.token_hash)
+        .bind(&token.scopes)
+        .bind(&token.expires_at)
+        .bind(&token.last_used_at)
+        .bind(&token.revoked_at)
+        .bind(&token.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<ApiToken> {
+        sqlx::query_as::<_, ApiToken>("SELECT * FROM api_tokens WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("API token not found".to_string()))
+    }
+
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<ApiToken>> {
+        let token = sqlx::query_as::<_, ApiToken>("SELECT * FROM api_tokens WHERE token_hash = ?")
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(token)
+    }
+
+    async fn list_personal(&self, user_id: &str) -> Result<Vec<ApiToken>> {
+        let tokens = sqlx::query_as::<_, ApiToken>(
+            "SELECT * FROM api_tokens WHERE user_id = ? AND team_id IS NULL ORDER BY created_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tokens)
+    }
+
+    async fn list_by_team(&self, team_id: &str) -> Result<Vec<ApiToken>> {
+        let tokens = sqlx::query_as::<_, ApiToken>(
+            "SELECT * FROM api_tokens WHERE team_id = ? ORDER BY created_at DESC",
+        )
+        .bind(team_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tokens)
+    }
+
+    async fn revoke(&self, id: &str, revoked_at: &str) -> Result<()> {
+        sqlx::query("UPDATE api_tokens SET revoked_at = ? WHERE id = ? AND revoked_at IS NULL")
+            .bind(revoked_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn touch_last_used(&self, id: &str, used_at: &str) -> Result<()> {
+        sqlx::query("UPDATE api_tokens SET last_used_at = ? WHERE id = ?")
+            .bind(used_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use crate::domain::domain_repository::DomainRepository;
-use crate::domain::models::Domain;
+use crate::domain::models::{Domain, DomainRoute, L4Route};
 use crate::error::Result;
 use async_trait::async_trait;
 use sqlx::SqlitePool;
@@ -56,7 +56,7 @@ impl DomainRepository for SqliteDomainRepository {
 
     async fn create(&self, domain: Domain) -> Result<Domain> {
         sqlx::query(
-            "INSERT INTO domains (id, stack_id, container_name, container_port, domain, ssl_enabled, verified, provider, type, tunnel_id, dns_record_id, proxied, show_branding, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            "INSERT INTO domains (id, stack_id, container_name, container_port, domain, ssl_enabled, verified, provider, type, tunnel_id, dns_record_id, proxied, show_branding, extra_upstreams, lb_policy, health_check_path, health_check_interval, middleware, basic_auth, tls_challenge, custom_certificate, custom_private_key, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )
         .bind(&domain.id)
         .bind(&domain.stack_id)
@@ -71,6 +71,15 @@ impl DomainRepository for SqliteDomainRepository {
         .bind(&domain.dns_record_id)
         .bind(domain.proxied)
         .bind(domain.show_branding)
+        .bind(&domain.extra_upstreams)
+        .bind(domain.lb_policy)
+        .bind(&domain.health_check_path)
+        .bind(domain.health_check_interval)
+        .bind(&domain.middleware)
+        .bind(&domain.basic_auth)
+        .bind(domain.tls_challenge)
+        .bind(&domain.custom_certificate)
+        .bind(&domain.custom_private_key)
         .bind(&domain.created_at)
         .execute(&self.pool)
         .await?;
@@ -112,4 +121,132 @@ impl DomainRepository for SqliteDomainRepository {
             .await?;
         Ok(())
     }
+
+    async fn update_upstreams(&self, domain: &Domain) -> Result<()> {
+        sqlx::query(
+            "UPDATE domains SET extra_upstreams = ?, lb_policy = ?, health_check_path = ?, health_check_interval = ? WHERE id = ?",
+        )
+        .bind(&domain.extra_upstreams)
+        .bind(domain.lb_policy)
+        .bind(&domain.health_check_path)
+        .bind(domain.health_check_interval)
+        .bind(&domain.id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn update_middleware(&self, domain: &Domain) -> Result<()> {
+        sqlx::query("UPDATE domains SET middleware = ?, basic_auth = ? WHERE id = ?")
+            .bind(&domain.middleware)
+            .bind(&domain.basic_auth)
+            .bind(&domain.id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn update_tls(&self, domain: &Domain) -> Result<()> {
+        sqlx::query(
+            "UPDATE domains SET ssl_enabled = ?, tls_challenge = ?, custom_certificate = ?, custom_private_key = ? WHERE id = ?",
+        )
+        .bind(domain.ssl_enabled)
+        .bind(domain.tls_challenge)
+        .bind(&domain.custom_certificate)
+        .bind(&domain.custom_private_key)
+        .bind(&domain.id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn list_routes(&self, domain_id: &str) -> Result<Vec<DomainRoute>> {
+        let routes = sqlx::query_as::<_, DomainRoute>(
+            "SELECT * FROM domain_routes WHERE domain_id = ? ORDER BY length(path) DESC, path",
+        )
+        .bind(domain_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(routes)
+    }
+
+    async fn list_all_routes(&self) -> Result<Vec<DomainRoute>> {
+        let routes = sqlx::query_as::<_, DomainRoute>(
+            "SELECT * FROM domain_routes ORDER BY length(path) DESC, path",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(routes)
+    }
+
+    async fn create_route(&self, route: DomainRoute) -> Result<DomainRoute> {
+        sqlx::query(
+            "INSERT INTO domain_routes (id, domain_id, path, strip_prefix, container_name, container_port, extra_upstreams, lb_policy, health_check_path, health_check_interval, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&route.id)
+        .bind(&route.domain_id)
+        .bind(&route.path)
+        .bind(route.strip_prefix)
+        .bind(&route.container_name)
+        .bind(route.container_port)
+        .bind(&route.extra_upstreams)
+        .bind(route.lb_policy)
+        .bind(&route.health_check_path)
+        .bind(route.health_check_interval)
+        .bind(&route.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(route)
+    }
+
+    async fn delete_route(&self, id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM domain_routes WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn list_l4_routes(&self, stack_id: &str) -> Result<Vec<L4Route>> {
+        let routes = sqlx::query_as::<_, L4Route>(
+            "SELECT * FROM l4_routes WHERE stack_id = ? ORDER BY listen_port, sni",
+        )
+        .bind(stack_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(routes)
+    }
+
+    async fn list_all_l4_routes(&self) -> Result<Vec<L4Route>> {
+        let routes =
+            sqlx::query_as::<_, L4Route>("SELECT * FROM l4_routes ORDER BY listen_port, sni")
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(routes)
+    }
+
+    async fn create_l4_route(&self, route: L4Route) -> Result<L4Route> {
+        sqlx::query(
+            "INSERT INTO l4_routes (id, stack_id, protocol, listen_port, sni, container_name, container_port, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&route.id)
+        .bind(&route.stack_id)
+        .bind(route.protocol)
+        .bind(route.listen_port)
+        .bind(&route.sni)
+        .bind(&route.container_name)
+        .bind(route.container_port)
+        .bind(&route.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(route)
+    }
+
+    async fn delete_l4_route(&self, id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM l4_routes WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
 }
@@ -0,0 +1,189 @@
+use crate::domain::env_group_repository::EnvGroupRepository;
+use crate::domain::models::{EnvGroup, EnvGroupVar};
+use crate::error::{AppError, Result};
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+
+pub struct SqliteEnvGroupRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteEnvGroupRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EnvGroupRepository for SqliteEnvGroupRepository {
+    async fn list_by_team(&self, team_id: &str) -> Result<Vec<EnvGroup>> {
+        let groups = sqlx::query_as::<_, EnvGroup>(
+            "SELECT * FROM env_groups WHERE team_id = ? ORDER BY name",
+        )
+        .bind(team_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(groups)
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<EnvGroup> {
+        sqlx::query_as::<_, EnvGroup>("SELECT * FROM env_groups WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Environment group not found".to_string()))
+    }
+
+    async fn create(&self, group: EnvGroup) -> Result<EnvGroup> {
+        sqlx::query(
+            "INSERT INTO env_groups (id, team_id, name, description, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&group.id)
+        .bind(&group.team_id)
+        .bind(&group.name)
+        .bind(&group.description)
+        .bind(&group.created_at)
+        .bind(&group.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(group)
+    }
+
+    async fn delete(&self, id: &str) -> Result<()> {
+        let result = sqlx::query("DELETE FROM env_groups WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(
+                "Environment group not found".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn list_vars(&self, group_id: &str) -> Result<Vec<EnvGroupVar>> {
+        let vars = sqlx::query_as::<_, EnvGroupVar>(
+            "SELECT * FROM env_group_vars WHERE group_id = ? ORDER BY key",
+        )
+        .bind(group_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(vars)
+    }
+
+    async fn find_var(&self, group_id: &str, key: &str) -> Result<Option<EnvGroupVar>> {
+        let var = sqlx::query_as::<_, EnvGroupVar>(
+            "SELECT * FROM env_group_vars WHERE group_id = ? AND key = ?",
+        )
+        .bind(group_id)
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(var)
+    }
+
+    async fn save_var(&self, var: EnvGroupVar) -> Result<EnvGroupVar> {
+        sqlx::query(
+            r#"
+            INSERT INTO env_group_vars (id, group_id, key, value, is_secret, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(group_id, key) DO UPDATE SET
+                value = excluded.value,
+                is_secret = excluded.is_secret,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&var.id)
+        .bind(&var.group_id)
+        .bind(&var.key)
+        .bind(&var.value)
+        .bind(var.is_secret)
+        .bind(&var.created_at)
+        .bind(&var.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query("UPDATE env_groups SET updated_at = ? WHERE id = ?")
+            .bind(&var.updated_at)
+            .bind(&var.group_id)
+            .execute(&self.pool)
+            .await?;
+
+        self.find_var(&var.group_id, &var.key)
+            .await?
+            .ok_or_else(|| AppError::Internal("Failed to save environment variable".to_string()))
+    }
+
+    async fn delete_var(&self, group_id: &str, key: &str) -> Result<()> {
+        let result = sqlx::query("DELETE FROM env_group_vars WHERE group_id = ? AND key = ?")
+            .bind(group_id)
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(
+                "Environment variable not found".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn list_for_stack(&self, stack_id: &str) -> Result<Vec<EnvGroup>> {
+        let groups = sqlx::query_as::<_, EnvGroup>(
+            r#"
+            SELECT g.* FROM env_groups g
+            INNER JOIN stack_env_groups sg ON sg.group_id = g.id
+            WHERE sg.stack_id = ?
+            ORDER BY sg.position
+            "#,
+        )
+        .bind(stack_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(groups)
+    }
+
+    async fn set_for_stack(&self, stack_id: &str, group_ids: &[String]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM stack_env_groups WHERE stack_id = ?")
+            .bind(stack_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for (position, group_id) in group_ids.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO stack_env_groups (stack_id, group_id, position) VALUES (?, ?, ?)",
+            )
+            .bind(stack_id)
+            .bind(group_id)
+            .bind(position as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn list_stack_ids(&self, group_id: &str) -> Result<Vec<String>> {
+        let ids = sqlx::query_scalar::<_, String>(
+            "SELECT stack_id FROM stack_env_groups WHERE group_id = ?",
+        )
+        .bind(group_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+}
@@ -62,10 +62,11 @@ impl EnvironmentRepository for SqliteEnvironmentRepository {
 
         if let Some(existing) = existing {
             sqlx::query(
-                "UPDATE stack_env_vars SET value = ?, is_secret = ?, updated_at = ? WHERE id = ?",
+                "UPDATE stack_env_vars SET value = ?, is_secret = ?, source = ?, updated_at = ? WHERE id = ?",
             )
             .bind(&var.value)
             .bind(var.is_secret)
+            .bind(&var.source)
             .bind(&var.updated_at)
             .bind(&existing.id)
             .execute(&self.pool)
@@ -74,7 +75,7 @@ impl EnvironmentRepository for SqliteEnvironmentRepository {
             self.find_by_id(&existing.id).await
         } else {
             sqlx::query(
-                "INSERT INTO stack_env_vars (id, stack_id, container_name, key, value, is_secret, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+                "INSERT INTO stack_env_vars (id, stack_id, container_name, key, value, is_secret, source, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
             )
             .bind(&var.id)
             .bind(&var.stack_id)
@@ -82,6 +83,7 @@ impl EnvironmentRepository for SqliteEnvironmentRepository {
             .bind(&var.key)
             .bind(&var.value)
             .bind(var.is_secret)
+            .bind(&var.source)
             .bind(&var.created_at)
             .bind(&var.updated_at)
             .execute(&self.pool)
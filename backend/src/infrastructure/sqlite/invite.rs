@@ -0,0 +1,128 @@
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+
+use crate::domain::invite_repository::InviteRepository;
+use crate::domain::models::{PasswordReset, UserInvite};
+use crate::error::Result;
+
+pub struct SqliteInviteRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteInviteRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl InviteRepository for SqliteInviteRepository {
+    async fn create(&self, invite: UserInvite) -> Result<UserInvite> {
+        sqlx::query(
+            r#"
+            INSERT INTO user_invites (id, team_id, email, name, role, token_hash, invited_by, expires_at, accepted_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&invite.id)
+        .bind(&invite.team_id)
+        .bind(&invite.email)
+        .bind(&invite.name)
+        .bind(&invite.role)
+        .bind(&invite.token_hash)
+        .bind(&invite.invited_by)
+        .bind(&invite.expires_at)
+        .bind(&invite.accepted_at)
+        .bind(&invite.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(invite)
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<UserInvite>> {
+        let invite = sqlx::query_as::<_, UserInvite>("SELECT * FROM user_invites WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(invite)
+    }
+
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<UserInvite>> {
+        let invite =
+            sqlx::query_as::<_, UserInvite>("SELECT * FROM user_invites WHERE token_hash = ?")
+                .bind(token_hash)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(invite)
+    }
+
+    async fn list_pending(&self, team_id: &str, now: &str) -> Result<Vec<UserInvite>> {
+        let invites = sqlx::query_as::<_, UserInvite>(
+            "SELECT * FROM user_invites WHERE team_id = ? AND accepted_at IS NULL AND expires_at > ? ORDER BY created_at DESC",
+        )
+        .bind(team_id)
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(invites)
+    }
+
+    async fn mark_accepted(&self, id: &str, accepted_at: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE user_invites SET accepted_at = ? WHERE id = ? AND accepted_at IS NULL",
+        )
+        .bind(accepted_at)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete(&self, id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM user_invites WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn create_password_reset(&self, reset: PasswordReset) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO password_resets (id, user_id, token_hash, created_by, expires_at, used_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&reset.id)
+        .bind(&reset.user_id)
+        .bind(&reset.token_hash)
+        .bind(&reset.created_by)
+        .bind(&reset.expires_at)
+        .bind(&reset.used_at)
+        .bind(&reset.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn find_password_reset(&self, token_hash: &str) -> Result<Option<PasswordReset>> {
+        let reset = sqlx::query_as::<_, PasswordReset>(
+            "SELECT * FROM password_resets WHERE token_hash = ?",
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(reset)
+    }
+
+    async fn use_password_reset(&self, id: &str, used_at: &str) -> Result<bool> {
+        let result =
+            sqlx::query("UPDATE password_resets SET used_at = ? WHERE id = ? AND used_at IS NULL")
+                .bind(used_at)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
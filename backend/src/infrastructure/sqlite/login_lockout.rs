@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+
+use crate::domain::login_lockout_repository::LoginLockoutRepository;
+use crate::domain::models::LoginLockout;
+use crate::error::Result;
+
+pub struct SqliteLoginLockoutRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteLoginLockoutRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl LoginLockoutRepository for SqliteLoginLockoutRepository {
+    async fn list_recent(&self, since: &str) -> Result<Vec<LoginLockout>> {
+        let lockouts = sqlx::query_as::<_, LoginLockout>(
+            "SELECT * FROM login_lockouts WHERE last_failure_at > ? OR locked_until > ?",
+        )
+        .bind(since)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(lockouts)
+    }
+
+    async fn upsert(&self, lockout: LoginLockout) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO login_lockouts (key, failures, locked_until, last_failure_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(key) DO UPDATE SET
+                failures = excluded.failures,
+                locked_until = excluded.locked_until,
+                last_failure_at = excluded.last_failure_at
+            "#,
+        )
+        .bind(&lockout.key)
+        .bind(lockout.failures)
+        .bind(&lockout.locked_until)
+        .bind(&lockout.last_failure_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        sqlx::query("DELETE FROM login_lockouts WHERE key = ?")
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
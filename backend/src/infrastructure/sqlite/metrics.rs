@@ -1,5 +1,5 @@
 use crate::domain::metrics_repository::{
-    HistoricalContainerMetrics, HistoricalNodeMetrics, MetricsRepository,
+    DomainRequestMetrics, HistoricalContainerMetrics, HistoricalNodeMetrics, MetricsRepository,
 };
 use crate::error::Result;
 use async_trait::async_trait;
@@ -102,6 +102,119 @@ impl MetricsRepository for SqliteMetricsRepository {
             .map(HistoricalContainerMetrics::from)
             .collect())
     }
+
+    async fn save_domain_requests(&self, metrics: &[DomainRequestMetrics]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for bucket in metrics {
+            let existing = sqlx::query_as::<_, DomainRequestRow>(
+                "SELECT * FROM domain_request_metrics WHERE domain = ? AND stack_id = ? AND bucket = ?",
+            )
+            .bind(&bucket.domain)
+            .bind(&bucket.stack_id)
+            .bind(&bucket.bucket)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            let (id, merged) = match existing {
+                Some(row) => {
+                    let id = row.id.clone();
+                    let mut merged = DomainRequestMetrics::from(row);
+                    merged.merge(bucket);
+                    (id, merged)
+                }
+                None => (Uuid::new_v4().to_string(), bucket.clone()),
+            };
+            sqlx::query(
+                "INSERT OR REPLACE INTO domain_request_metrics (id, domain, stack_id, bucket, requests, status_1xx, status_2xx, status_3xx, status_4xx, status_5xx, bytes_sent, latency_sum_ms, latency_histogram)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(id)
+            .bind(&merged.domain)
+            .bind(&merged.stack_id)
+            .bind(&merged.bucket)
+            .bind(merged.requests as i64)
+            .bind(merged.status[0] as i64)
+            .bind(merged.status[1] as i64)
+            .bind(merged.status[2] as i64)
+            .bind(merged.status[3] as i64)
+            .bind(merged.status[4] as i64)
+            .bind(merged.bytes_sent as i64)
+            .bind(merged.latency_sum_ms)
+            .bind(serde_json::to_string(&merged.latency_histogram).unwrap_or_default())
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        // Cleanup
+        let _ = sqlx::query(
+            "DELETE FROM domain_request_metrics WHERE bucket < datetime('now', '-30 days')",
+        )
+        .execute(&self.pool)
+        .await;
+
+        Ok(())
+    }
+
+    async fn get_domain_requests(
+        &self,
+        stack_id: &str,
+        domain: Option<&str>,
+        last_hours: i32,
+    ) -> Result<Vec<DomainRequestMetrics>> {
+        let rows = sqlx::query_as::<_, DomainRequestRow>(
+            "SELECT * FROM domain_request_metrics
+             WHERE stack_id = ? AND (? IS NULL OR domain = ?)
+               AND bucket > datetime('now', '-' || ? || ' hours')
+             ORDER BY bucket ASC",
+        )
+        .bind(stack_id)
+        .bind(domain)
+        .bind(domain)
+        .bind(last_hours)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(DomainRequestMetrics::from).collect())
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct DomainRequestRow {
+    id: String,
+    domain: String,
+    stack_id: String,
+    bucket: String,
+    requests: i64,
+    status_1xx: i64,
+    status_2xx: i64,
+    status_3xx: i64,
+    status_4xx: i64,
+    status_5xx: i64,
+    bytes_sent: i64,
+    latency_sum_ms: f64,
+    latency_histogram: String,
+}
+
+impl From<DomainRequestRow> for DomainRequestMetrics {
+    fn from(row: DomainRequestRow) -> Self {
+        Self {
+            domain: row.domain,
+            stack_id: row.stack_id,
+            bucket: row.bucket,
+            requests: row.requests as u64,
+            status: [
+                row.status_1xx as u64,
+                row.status_2xx as u64,
+                row.status_3xx as u64,
+                row.status_4xx as u64,
+                row.status_5xx as u64,
+            ],
+            bytes_sent: row.bytes_sent as u64,
+            latency_sum_ms: row.latency_sum_ms,
+            latency_histogram: serde_json::from_str(&row.latency_histogram).unwrap_or_default(),
+        }
+    }
 }
 
 #[derive(sqlx::FromRow)]
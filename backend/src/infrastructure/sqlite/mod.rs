@@ -1,11 +1,22 @@
+pub mod activity_log;
+pub mod api_token;
 pub mod deployment_log;
 pub mod dns;
 pub mod domain;
+pub mod env_group;
 pub mod environment;
+pub mod invite;
+pub mod login_lockout;
 pub mod metrics;
 pub mod registry;
 pub mod resource;
+pub mod role;
+pub mod secret;
+pub mod session;
 pub mod stack;
+pub mod stack_grant;
 pub mod team;
 pub mod template;
+pub mod two_factor;
 pub mod user;
+pub mod user_identity;
@@ -27,6 +27,18 @@ impl RegistryRepository for SqliteRegistryRepository {
         Ok(credentials)
     }
 
+    async fn find_by_id(&self, id: &str, team_id: &str) -> Result<Option<RegistryCredential>> {
+        let credential = sqlx::query_as::<_, RegistryCredential>(
+            "SELECT * FROM registry_credentials WHERE id = ? AND team_id = ?",
+        )
+        .bind(id)
+        .bind(team_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(credential)
+    }
+
     async fn find_by_url(&self, team_id: &str, url: &str) -> Result<Option<RegistryCredential>> {
         let credential = sqlx::query_as::<_, RegistryCredential>(
             "SELECT * FROM registry_credentials WHERE team_id = ? AND registry_url = ? LIMIT 1",
@@ -0,0 +1,104 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::domain::models::CustomRole;
+use crate::domain::role_repository::RoleRepository;
+use crate::error::Result;
+
+pub struct SqliteRoleRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteRoleRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RoleRepository for SqliteRoleRepository {
+    async fn list_by_team(&self, team_id: &str) -> Result<Vec<CustomRole>> {
+        let roles = sqlx::query_as::<_, CustomRole>(
+            "SELECT * FROM team_roles WHERE team_id = ? ORDER BY name",
+        )
+        .bind(team_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(roles)
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<CustomRole>> {
+        let role = sqlx::query_as::<_, CustomRole>("SELECT * FROM team_roles WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(role)
+    }
+
+    async fn save(&self, role: &CustomRole) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO team_roles (id, team_id, name, description, permissions, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                name = excluded.name,
+                description = excluded.description,
+                permissions = excluded.permissions,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&role.id)
+        .bind(&role.team_id)
+        .bind(&role.name)
+        .bind(&role.description)
+        .bind(&role.permissions)
+        .bind(&role.created_at)
+        .bind(&role.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM team_roles WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn find_member_role(&self, team_id: &str, user_id: &str) -> Result<Option<CustomRole>> {
+        let role = sqlx::query_as::<_, CustomRole>(
+            r#"
+            SELECT r.* FROM team_roles r
+            JOIN team_members tm ON tm.custom_role_id = r.id
+            WHERE tm.team_id = ? AND tm.user_id = ?
+            "#,
+        )
+        .bind(team_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(role)
+    }
+
+    async fn set_member_role(
+        &self,
+        team_id: &str,
+        user_id: &str,
+        role_id: Option<&str>,
+    ) -> Result<()> {
+        let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        sqlx::query(
+            "UPDATE team_members SET custom_role_id = ?, updated_at = ? WHERE team_id = ? AND user_id = ?",
+        )
+        .bind(role_id)
+        .bind(now)
+        .bind(team_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
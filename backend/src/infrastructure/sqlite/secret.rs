@@ -0,0 +1,89 @@
+use crate::domain::secret_repository::{SecretKind, SecretRepository, StoredSecret};
+use crate::error::Result;
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+
+pub struct SqliteSecretRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteSecretRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+fn update_query(kind: SecretKind) -> &'static str {
+    match kind {
+        SecretKind::RegistryPassword => {
+            "UPDATE registry_credentials SET password_encrypted = ? WHERE id = ?"
+        }
+        SecretKind::DnsConfig => "UPDATE dns_configs SET config = ? WHERE id = ?",
+        SecretKind::StackEnvVar => "UPDATE stack_env_vars SET value = ? WHERE id = ?",
+        SecretKind::EnvGroupVar => "UPDATE env_group_vars SET value = ? WHERE id = ?",
+        SecretKind::TotpSecret => "UPDATE user_totp SET secret = ? WHERE user_id = ?",
+        SecretKind::DomainCertificate => "UPDATE domains SET custom_certificate = ? WHERE id = ?",
+        SecretKind::DomainPrivateKey => "UPDATE domains SET custom_private_key = ? WHERE id = ?",
+    }
+}
+
+#[async_trait]
+impl SecretRepository for SqliteSecretRepository {
+    async fn list_all(&self) -> Result<Vec<StoredSecret>> {
+        let sources = [
+            (
+                SecretKind::RegistryPassword,
+                "SELECT id, password_encrypted FROM registry_credentials",
+            ),
+            (SecretKind::DnsConfig, "SELECT id, config FROM dns_configs"),
+            (
+                SecretKind::StackEnvVar,
+                "SELECT id, value FROM stack_env_vars WHERE is_secret = 1",
+            ),
+            (
+                SecretKind::EnvGroupVar,
+                "SELECT id, value FROM env_group_vars WHERE is_secret = 1",
+            ),
+            (
+                SecretKind::TotpSecret,
+                "SELECT user_id, secret FROM user_totp",
+            ),
+            (
+                SecretKind::DomainCertificate,
+                "SELECT id, custom_certificate FROM domains WHERE custom_certificate IS NOT NULL",
+            ),
+            (
+                SecretKind::DomainPrivateKey,
+                "SELECT id, custom_private_key FROM domains WHERE custom_private_key IS NOT NULL",
+            ),
+        ];
+
+        let mut secrets = Vec::new();
+        for (kind, query) in sources {
+            let rows = sqlx::query_as::<_, (String, String)>(query)
+                .fetch_all(&self.pool)
+                .await?;
+            secrets.extend(
+                rows.into_iter()
+                    .map(|(id, value)| StoredSecret { kind, id, value }),
+            );
+        }
+
+        Ok(secrets)
+    }
+
+    async fn update_all(&self, secrets: &[StoredSecret]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for secret in secrets {
+            sqlx::query(update_query(secret.kind))
+                .bind(&secret.value)
+                .bind(&secret.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use crate::domain::models::Session;
+use crate::domain::session_repository::SessionRepository;
+use crate::error::Result;
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+
+pub struct SqliteSessionRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteSessionRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SessionRepository for SqliteSessionRepository {
+    async fn create(&self, session: Session) -> Result<Session> {
+        sqlx::query(
+            r#"
+            INSERT INTO sessions (id, user_id, refresh_token_hash, user_agent, expires_at, last_used_at, revoked_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&session.id)
+        .bind(&session.user_id)
+        .bind(&session.refresh_token_hash)
+        .bind(&session.user_agent)
+        .bind(&session.expires_at)
+        .bind(&session.last_used_at)
+        .bind(&session.revoked_at)
+        .bind(&session.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(session)
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<Session>> {
+        let session = sqlx::query_as::<_, Session>("SELECT * FROM sessions WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(session)
+    }
+
+    async fn find_by_refresh_hash(&self, hash: &str) -> Result<Option<Session>> {
+        let session =
+            sqlx::query_as::<_, Session>("SELECT * FROM sessions WHERE refresh_token_hash = ?")
+                .bind(hash)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(session)
+    }
+
+    async fn list_active(&self, user_id: &str, now: &str) -> Result<Vec<Session>> {
+        let sessions = sqlx::query_as::<_, Session>(
+            "SELECT * FROM sessions WHERE user_id = ? AND revoked_at IS NULL AND expires_at > ? ORDER BY created_at DESC",
+        )
+        .bind(user_id)
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    async fn rotate(
+        &self,
+        id: &str,
+        current_hash: &str,
+        refresh_token_hash: &str,
+        expires_at: &str,
+        used_at: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE sessions SET refresh_token_hash = ?, expires_at = ?, last_used_at = ? WHERE id = ? AND refresh_token_hash = ? AND revoked_at IS NULL",
+        )
+        .bind(refresh_token_hash)
+        .bind(expires_at)
+        .bind(used_at)
+        .bind(id)
+        .bind(current_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn revoke(&self, id: &str, revoked_at: &str) -> Result<()> {
+        sqlx::query("UPDATE sessions SET revoked_at = ? WHERE id = ? AND revoked_at IS NULL")
+            .bind(revoked_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_all_for_user(&self, user_id: &str, revoked_at: &str) -> Result<()> {
+        sqlx::query("UPDATE sessions SET revoked_at = ? WHERE user_id = ? AND revoked_at IS NULL")
+            .bind(revoked_at)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
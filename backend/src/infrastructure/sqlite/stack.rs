@@ -1,4 +1,4 @@
-use crate::domain::models::Stack;
+use crate::domain::models::{PushedImage, Stack};
 use crate::domain::stack_repository::StackRepository;
 use crate::error::{AppError, Result};
 use async_trait::async_trait;
@@ -48,7 +48,7 @@ impl StackRepository for SqliteStackRepository {
 
     async fn create(&self, stack: Stack) -> Result<Stack> {
         sqlx::query(
-            "INSERT INTO stacks (id, name, user_id, team_id, compose_content, status, webhook_token, cron_schedule, health_check_path, health_check_interval, last_stable_images, git_url, git_branch, last_commit_hash, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            "INSERT INTO stacks (id, name, user_id, team_id, compose_content, status, webhook_token, cron_schedule, health_check_path, health_check_interval, last_stable_images, git_url, git_branch, last_commit_hash, push_registry_id, push_namespace, push_retention, env_conflict_policy, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )
         .bind(&stack.id)
         .bind(&stack.name)
@@ -64,6 +64,10 @@ impl StackRepository for SqliteStackRepository {
         .bind(&stack.git_url)
         .bind(&stack.git_branch)
         .bind(&stack.last_commit_hash)
+        .bind(&stack.push_registry_id)
+        .bind(&stack.push_namespace)
+        .bind(stack.push_retention)
+        .bind(&stack.env_conflict_policy)
         .bind(&stack.created_at)
         .bind(&stack.updated_at)
         .execute(&self.pool)
@@ -146,6 +150,118 @@ impl StackRepository for SqliteStackRepository {
         Ok(())
     }
 
+    async fn update_registry_push(
+        &self,
+        id: &str,
+        registry_id: Option<String>,
+        namespace: Option<String>,
+        retention: i32,
+    ) -> Result<()> {
+        sqlx::query("UPDATE stacks SET push_registry_id = ?, push_namespace = ?, push_retention = ?, updated_at = ? WHERE id = ?")
+            .bind(registry_id)
+            .bind(namespace)
+            .bind(retention)
+            .bind(Utc::now().to_rfc3339())
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update_env_conflict_policy(&self, id: &str, policy: &str) -> Result<()> {
+        sqlx::query("UPDATE stacks SET env_conflict_policy = ?, updated_at = ? WHERE id = ?")
+            .bind(policy)
+            .bind(Utc::now().to_rfc3339())
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update_maintenance_mode(&self, id: &str, enabled: bool) -> Result<()> {
+        sqlx::query("UPDATE stacks SET maintenance_mode = ?, updated_at = ? WHERE id = ?")
+            .bind(enabled)
+            .bind(Utc::now().to_rfc3339())
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update_pages(
+        &self,
+        id: &str,
+        maintenance_page: Option<&str>,
+        error_pages: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE stacks SET maintenance_page = ?, error_pages = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(maintenance_page)
+        .bind(error_pages)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn record_pushed_image(&self, image: PushedImage) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO stack_pushed_images (id, stack_id, service_name, image_ref, pushed_at) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(&image.id)
+        .bind(&image.stack_id)
+        .bind(&image.service_name)
+        .bind(&image.image_ref)
+        .bind(&image.pushed_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_pushed_images(
+        &self,
+        stack_id: &str,
+        service_name: Option<&str>,
+    ) -> Result<Vec<PushedImage>> {
+        let images = match service_name {
+            Some(service) => {
+                sqlx::query_as::<_, PushedImage>(
+                    "SELECT * FROM stack_pushed_images WHERE stack_id = ? AND service_name = ? ORDER BY pushed_at DESC",
+                )
+                .bind(stack_id)
+                .bind(service)
+                .fetch_all(&self.pool)
+                .await?
+            }
+            None => {
+                sqlx::query_as::<_, PushedImage>(
+                    "SELECT * FROM stack_pushed_images WHERE stack_id = ? ORDER BY pushed_at DESC",
+                )
+                .bind(stack_id)
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+
+        Ok(images)
+    }
+
+    async fn delete_pushed_image(&self, id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM stack_pushed_images WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
     async fn delete(&self, id: &str) -> Result<()> {
         sqlx::query("DELETE FROM stacks WHERE id = ?")
             .bind(id)
@@ -0,0 +1,94 @@
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+
+use crate::domain::models::{StackGrant, StackGrantEntry};
+use crate::domain::stack_grant_repository::StackGrantRepository;
+use crate::error::Result;
+
+pub struct SqliteStackGrantRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteStackGrantRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl StackGrantRepository for SqliteStackGrantRepository {
+    async fn list_by_stack(&self, stack_id: &str) -> Result<Vec<StackGrantEntry>> {
+        let grants = sqlx::query_as::<_, StackGrantEntry>(
+            r#"
+            SELECT
+                g.stack_id,
+                g.user_id,
+                u.email as user_email,
+                u.name as user_name,
+                g.permissions,
+                g.granted_by,
+                g.created_at,
+                g.updated_at
+            FROM stack_grants g
+            JOIN users u ON g.user_id = u.id
+            WHERE g.stack_id = ?
+            ORDER BY u.email
+            "#,
+        )
+        .bind(stack_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(grants)
+    }
+
+    async fn find(&self, stack_id: &str, user_id: &str) -> Result<Option<StackGrant>> {
+        let grant = sqlx::query_as::<_, StackGrant>(
+            "SELECT * FROM stack_grants WHERE stack_id = ? AND user_id = ?",
+        )
+        .bind(stack_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(grant)
+    }
+
+    async fn list_stack_ids_for_user(&self, user_id: &str) -> Result<Vec<String>> {
+        let ids =
+            sqlx::query_scalar::<_, String>("SELECT stack_id FROM stack_grants WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(ids)
+    }
+
+    async fn save(&self, grant: &StackGrant) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO stack_grants (stack_id, user_id, permissions, granted_by, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(stack_id, user_id) DO UPDATE SET
+                permissions = excluded.permissions,
+                granted_by = excluded.granted_by,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&grant.stack_id)
+        .bind(&grant.user_id)
+        .bind(&grant.permissions)
+        .bind(&grant.granted_by)
+        .bind(&grant.created_at)
+        .bind(&grant.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, stack_id: &str, user_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM stack_grants WHERE stack_id = ? AND user_id = ?")
+            .bind(stack_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -32,19 +32,37 @@ impl TeamRepository for SqliteTeamRepository {
         Ok(teams)
     }
 
+    async fn find_by_id(&self, id: &str) -> Result<Option<Team>> {
+        let team = sqlx::query_as::<_, Team>("SELECT * FROM teams WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(team)
+    }
+
+    async fn find_by_name(&self, name: &str) -> Result<Option<Team>> {
+        let team = sqlx::query_as::<_, Team>("SELECT * FROM teams WHERE name = ?")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(team)
+    }
+
     async fn save(&self, team: &Team) -> Result<()> {
         let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
         sqlx::query(
             r#"
-            INSERT INTO teams (id, name, created_at, updated_at)
-            VALUES (?, ?, ?, ?)
+            INSERT INTO teams (id, name, require_2fa, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?)
             ON CONFLICT(id) DO UPDATE SET
                 name = excluded.name,
+                require_2fa = excluded.require_2fa,
                 updated_at = excluded.updated_at
             "#,
         )
         .bind(&team.id)
         .bind(&team.name)
+        .bind(team.require_2fa)
         .bind(&team.created_at)
         .bind(&now)
         .execute(&self.pool)
@@ -78,6 +96,17 @@ impl TeamRepository for SqliteTeamRepository {
         Ok(())
     }
 
+    async fn set_require_2fa(&self, team_id: &str, required: bool) -> Result<()> {
+        let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        sqlx::query("UPDATE teams SET require_2fa = ?, updated_at = ? WHERE id = ?")
+            .bind(required)
+            .bind(&now)
+            .bind(team_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     async fn remove_member(&self, team_id: &str, user_id: &str) -> Result<()> {
         sqlx::query("DELETE FROM team_members WHERE team_id = ? AND user_id = ?")
             .bind(team_id)
@@ -113,6 +142,7 @@ impl TeamRepository for SqliteTeamRepository {
                 u.name as user_name,
                 u.email as user_email,
                 tm.role,
+                tm.custom_role_id,
                 tm.created_at,
                 tm.updated_at
             FROM team_members tm
@@ -0,0 +1,164 @@
+use crate::domain::models::{RecoveryCode, UserTotp};
+use crate::domain::two_factor_repository::TwoFactorRepository;
+use crate::error::Result;
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+
+pub struct SqliteTwoFactorRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteTwoFactorRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+const REQUIRING_TEAMS: &str = r#"
+    SELECT 1 FROM team_members tm
+    JOIN teams t ON t.id = tm.team_id
+    WHERE tm.user_id = ? AND t.require_2fa = 1 AND tm.role IN ('OWNER', 'ADMIN')
+"#;
+
+#[async_trait]
+impl TwoFactorRepository for SqliteTwoFactorRepository {
+    async fn find_totp(&self, user_id: &str) -> Result<Option<UserTotp>> {
+        let totp = sqlx::query_as::<_, UserTotp>("SELECT * FROM user_totp WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(totp)
+    }
+
+    async fn save_totp(&self, totp: UserTotp) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO user_totp (user_id, secret, enabled_at, last_used_step, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(user_id) DO UPDATE SET
+                secret = excluded.secret,
+                enabled_at = excluded.enabled_at,
+                last_used_step = excluded.last_used_step,
+                created_at = excluded.created_at
+            "#,
+        )
+        .bind(&totp.user_id)
+        .bind(&totp.secret)
+        .bind(&totp.enabled_at)
+        .bind(totp.last_used_step)
+        .bind(&totp.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn enable_totp(&self, user_id: &str, enabled_at: &str) -> Result<()> {
+        sqlx::query("UPDATE user_totp SET enabled_at = ? WHERE user_id = ?")
+            .bind(enabled_at)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn record_step(&self, user_id: &str, step: i64) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE user_totp SET last_used_step = ? WHERE user_id = ? AND (last_used_step IS NULL OR last_used_step < ?)",
+        )
+        .bind(step)
+        .bind(user_id)
+        .bind(step)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete(&self, user_id: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM user_recovery_codes WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM user_totp WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn replace_recovery_codes(&self, user_id: &str, codes: Vec<RecoveryCode>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM user_recovery_codes WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        for code in codes {
+            sqlx::query(
+                "INSERT INTO user_recovery_codes (id, user_id, code_hash, used_at, created_at) VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(&code.id)
+            .bind(&code.user_id)
+            .bind(&code.code_hash)
+            .bind(&code.used_at)
+            .bind(&code.created_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn list_unused_recovery_codes(&self, user_id: &str) -> Result<Vec<RecoveryCode>> {
+        let codes = sqlx::query_as::<_, RecoveryCode>(
+            "SELECT * FROM user_recovery_codes WHERE user_id = ? AND used_at IS NULL",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(codes)
+    }
+
+    async fn use_recovery_code(&self, id: &str, used_at: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE user_recovery_codes SET used_at = ? WHERE id = ? AND used_at IS NULL",
+        )
+        .bind(used_at)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn enrollment_required(&self, user_id: &str) -> Result<bool> {
+        let query = format!(
+            "SELECT EXISTS({}) AND NOT EXISTS(SELECT 1 FROM user_totp WHERE user_id = ? AND enabled_at IS NOT NULL)",
+            REQUIRING_TEAMS
+        );
+        let row: (bool,) = sqlx::query_as(&query)
+            .bind(user_id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.0)
+    }
+
+    async fn is_required(&self, user_id: &str) -> Result<bool> {
+        let query = format!("SELECT EXISTS({})", REQUIRING_TEAMS);
+        let row: (bool,) = sqlx::query_as(&query)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.0)
+    }
+}
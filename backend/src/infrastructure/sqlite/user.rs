@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use sqlx::SqlitePool;
 
-use crate::domain::models::User;
+use crate::domain::models::{BOT_USER_ROLE, User};
 use crate::domain::user_repository::UserRepository;
 use crate::error::Result;
 
@@ -59,4 +59,42 @@ impl UserRepository for SqliteUserRepository {
             .await?;
         Ok(row.0)
     }
+
+    async fn list(&self) -> Result<Vec<User>> {
+        let users =
+            sqlx::query_as::<_, User>("SELECT * FROM users WHERE role != ? ORDER BY email ASC")
+                .bind(BOT_USER_ROLE)
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(users)
+    }
+
+    async fn update_name(&self, id: &str, name: Option<&str>, updated_at: &str) -> Result<()> {
+        sqlx::query("UPDATE users SET name = ?, updated_at = ? WHERE id = ?")
+            .bind(name)
+            .bind(updated_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn update_password(&self, id: &str, password_hash: &str, updated_at: &str) -> Result<()> {
+        sqlx::query("UPDATE users SET password_hash = ?, updated_at = ? WHERE id = ?")
+            .bind(password_hash)
+            .bind(updated_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn set_deactivated(&self, id: &str, deactivated_at: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE users SET deactivated_at = ? WHERE id = ?")
+            .bind(deactivated_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
 }
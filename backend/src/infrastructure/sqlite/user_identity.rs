@@ -0,0 +1,56 @@
+use crate::domain::models::UserIdentity;
+use crate::domain::user_identity_repository::UserIdentityRepository;
+use crate::error::Result;
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+
+pub struct SqliteUserIdentityRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteUserIdentityRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UserIdentityRepository for SqliteUserIdentityRepository {
+    async fn find(&self, issuer: &str, subject: &str) -> Result<Option<UserIdentity>> {
+        let identity = sqlx::query_as::<_, UserIdentity>(
+            "SELECT * FROM user_identities WHERE issuer = ? AND subject = ?",
+        )
+        .bind(issuer)
+        .bind(subject)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(identity)
+    }
+
+    async fn create(&self, identity: UserIdentity) -> Result<UserIdentity> {
+        sqlx::query(
+            "INSERT INTO user_identities (id, user_id, issuer, subject, created_at, last_login_at) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&identity.id)
+        .bind(&identity.user_id)
+        .bind(&identity.issuer)
+        .bind(&identity.subject)
+        .bind(&identity.created_at)
+        .bind(&identity.last_login_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(identity)
+    }
+
+    async fn touch_last_login(&self, id: &str, at: &str) -> Result<()> {
+        sqlx::query("UPDATE user_identities SET last_login_at = ? WHERE id = ?")
+            .bind(at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 mod api;
 mod app_state;
+mod cli;
 mod config;
 mod db;
 mod domain;
@@ -7,6 +8,7 @@ mod error;
 mod infrastructure;
 mod usecase;
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -40,6 +42,10 @@ async fn main() -> anyhow::Result<()> {
     db::run_migrations(&pool).await?;
     tracing::info!("Database migrations completed");
 
+    if std::env::args().nth(1).as_deref() == Some("rotate-master-key") {
+        return cli::rotate_master_key(&config, pool).await;
+    }
+
     // 6. Initialize Application State (Dependency Injection Container)
     let state = Arc::new(AppState::new(config.clone(), pool).await?);
     tracing::info!("Application state initialized");
@@ -52,7 +58,12 @@ async fn main() -> anyhow::Result<()> {
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     tracing::info!("🚀 Labuh server listening on http://{}", addr);
 
-    axum::serve(listener, app).await?;
+    // Connection info gives the rate limiter the client address
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
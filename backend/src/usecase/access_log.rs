@@ -0,0 +1,330 @@
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::Mutex;
+use tokio::time::{Duration, sleep};
+
+use crate::domain::domain_repository::DomainRepository;
+use crate::domain::metrics_repository::{DomainRequestMetrics, MetricsRepository};
+use crate::domain::models::domain::{Domain, DomainType};
+use crate::error::{AppError, Result};
+
+/// Most bytes read from the access log per pass; the rest waits for the next one
+const MAX_READ_BYTES: u64 = 8 * 1024 * 1024;
+
+/// One request from Caddy's access log, without headers
+#[derive(Debug, Clone, serde::Serialize, PartialEq)]
+pub struct AccessLogEntry {
+    pub timestamp: DateTime<Utc>,
+    /// Registered domain the request was attributed to
+    pub domain: String,
+    #[serde(skip)]
+    pub stack_id: String,
+    pub host: String,
+    pub method: String,
+    pub uri: String,
+    pub proto: String,
+    pub status: u16,
+    pub bytes: u64,
+    pub duration_ms: f64,
+    pub client_ip: String,
+}
+
+/// Parse a line of Caddy's JSON access log; anything else yields `None`
+pub fn parse_access_log(line: &str) -> Option<AccessLogEntry> {
+    let entry: serde_json::Value = serde_json::from_str(line.trim()).ok()?;
+    if !entry["logger"].as_str()?.starts_with("http.log.access") {
+        return None;
+    }
+    let request = &entry["request"];
+    let ts = entry["ts"].as_f64()?;
+    let text = |value: &serde_json::Value| value.as_str().unwrap_or_default().to_string();
+    Some(AccessLogEntry {
+        timestamp: DateTime::from_timestamp_millis((ts * 1000.0) as i64)?,
+        domain: String::new(),
+        stack_id: String::new(),
+        host: text(&request["host"]),
+        method: text(&request["method"]),
+        uri: text(&request["uri"]),
+        proto: text(&request["proto"]),
+        status: entry["status"].as_u64()? as u16,
+        bytes: entry["size"].as_u64().unwrap_or(0),
+        duration_ms: entry["duration"].as_f64().unwrap_or(0.0) * 1000.0,
+        client_ip: request["client_ip"]
+            .as_str()
+            .or(request["remote_ip"].as_str())
+            .unwrap_or_default()
+            .to_string(),
+    })
+}
+
+/// The registered Caddy domain serving `host`: an exact match, the domain whose
+/// `www.` counterpart it is, or a wildcard one label up
+fn match_domain<'a>(host: &str, domains: &'a [Domain]) -> Option<&'a Domain> {
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    }
+    .to_ascii_lowercase();
+    let caddy = || {
+        domains
+            .iter()
+            .filter(|d| matches!(d.r#type, DomainType::Caddy))
+    };
+    let counterpart = match host.strip_prefix("www.") {
+        Some(apex) => apex.to_string(),
+        None => format!("www.{}", host),
+    };
+    caddy()
+        .find(|d| d.domain == host)
+        .or_else(|| caddy().find(|d| d.domain == counterpart && !d.is_wildcard()))
+        .or_else(|| {
+            let (_, zone) = host.split_once('.')?;
+            let wildcard = format!("*.{}", zone);
+            caddy().find(|d| d.domain == wildcard)
+        })
+}
+
+/// Bucket start for a request: the minute it was handled in
+fn minute_bucket(timestamp: &DateTime<Utc>) -> String {
+    timestamp.format("%Y-%m-%d %H:%M:00").to_string()
+}
+
+/// Latest requests across all domains, kept in memory for the live tail
+pub struct RecentRequests {
+    capacity: usize,
+    entries: std::sync::Mutex<VecDeque<AccessLogEntry>>,
+}
+
+impl RecentRequests {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: std::sync::Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    fn push(&self, entry: AccessLogEntry) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Newest first
+    pub fn for_domain(&self, stack_id: &str, domain: &str, limit: usize) -> Vec<AccessLogEntry> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|e| e.stack_id == stack_id && e.domain == domain)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Where the collector stopped reading; a new inode or a shorter file means
+/// Caddy rolled the log over
+struct LogCursor {
+    inode: u64,
+    offset: u64,
+}
+
+/// Tails Caddy's access log and stores per-domain request counts, status
+/// classes, bytes and latency per minute
+pub struct AccessLogCollector {
+    path: PathBuf,
+    domain_repo: Arc<dyn DomainRepository>,
+    metrics_repo: Arc<dyn MetricsRepository>,
+    recent: Arc<RecentRequests>,
+    cursor: Mutex<Option<LogCursor>>,
+}
+
+impl AccessLogCollector {
+    pub fn new(
+        path: PathBuf,
+        domain_repo: Arc<dyn DomainRepository>,
+        metrics_repo: Arc<dyn MetricsRepository>,
+        recent: Arc<RecentRequests>,
+    ) -> Self {
+        Self {
+            path,
+            domain_repo,
+            metrics_repo,
+            recent,
+            cursor: Mutex::new(None),
+        }
+    }
+
+    pub async fn start(&self, period: Duration) {
+        tracing::info!("Starting access log collector ({})...", self.path.display());
+        loop {
+            if let Err(e) = self.collect().await {
+                tracing::warn!("Error collecting access logs: {}", e);
+            }
+            sleep(period).await;
+        }
+    }
+
+    pub async fn collect(&self) -> Result<()> {
+        let lines = self.read_new_lines().await?;
+        if lines.is_empty() {
+            return Ok(());
+        }
+
+        let domains = self.domain_repo.list_all().await?;
+        let mut buckets: HashMap<(String, String, String), DomainRequestMetrics> = HashMap::new();
+        for line in &lines {
+            let Some(mut entry) = parse_access_log(line) else {
+                continue;
+            };
+            // The dashboard and unknown hosts belong to no stack
+            let Some(domain) = match_domain(&entry.host, &domains) else {
+                continue;
+            };
+            entry.domain = domain.domain.clone();
+            entry.stack_id = domain.stack_id.clone();
+
+            let bucket = minute_bucket(&entry.timestamp);
+            buckets
+                .entry((entry.domain.clone(), entry.stack_id.clone(), bucket.clone()))
+                .or_insert_with(|| {
+                    DomainRequestMetrics::new(&entry.domain, &entry.stack_id, &bucket)
+                })
+                .record(entry.status, entry.bytes, entry.duration_ms);
+            self.recent.push(entry);
+        }
+
+        if !buckets.is_empty() {
+            let metrics: Vec<DomainRequestMetrics> = buckets.into_values().collect();
+            self.metrics_repo.save_domain_requests(&metrics).await?;
+        }
+        Ok(())
+    }
+
+    /// Complete lines appended since the last pass. The first pass only notes the
+    /// end of the file, so a restart does not count old requests twice.
+    async fn read_new_lines(&self) -> Result<Vec<String>> {
+        let metadata = match tokio::fs::metadata(&self.path).await {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(AppError::Internal(format!(
+                    "Failed to read access log: {}",
+                    e
+                )));
+            }
+        };
+        let (inode, len) = (metadata.ino(), metadata.len());
+
+        let mut cursor = self.cursor.lock().await;
+        let start = match cursor.as_ref() {
+            None => {
+                *cursor = Some(LogCursor { inode, offset: len });
+                return Ok(Vec::new());
+            }
+            Some(c) if c.inode != inode || len < c.offset => 0,
+            Some(c) => c.offset,
+        };
+        if start == len {
+            *cursor = Some(LogCursor { inode, offset: len });
+            return Ok(Vec::new());
+        }
+
+        let read = async {
+            let mut file = tokio::fs::File::open(&self.path).await?;
+            file.seek(std::io::SeekFrom::Start(start)).await?;
+            let mut buf = Vec::new();
+            file.take(MAX_READ_BYTES.min(len - start))
+                .read_to_end(&mut buf)
+                .await?;
+            Ok::<_, std::io::Error>(buf)
+        };
+        let buf = read
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to read access log: {}", e)))?;
+
+        // Leave a line Caddy is still writing for the next pass, unless it alone
+        // fills the read limit
+        let consumed = match buf.iter().rposition(|b| *b == b'\n') {
+            Some(end) => end + 1,
+            None if buf.len() as u64 >= MAX_READ_BYTES => buf.len(),
+            None => 0,
+        };
+        *cursor = Some(LogCursor {
+            inode,
+            offset: start + consumed as u64,
+        });
+        Ok(String::from_utf8_lossy(&buf[..consumed])
+            .lines()
+            .map(String::from)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::domain::DomainProvider;
+
+    #[test]
+    fn test_parse_caddy_access_log() {
+        let line = r#"{"level":"info","ts":1792317600.5,"logger":"http.log.access.labuh_access","msg":"handled request","request":{"remote_ip":"172.18.0.1","client_ip":"203.0.113.9","proto":"HTTP/2.0","method":"GET","host":"App.example.com:443","uri":"/api/users?page=2","headers":{"Authorization":["REDACTED"]}},"bytes_read":0,"duration":0.0421,"size":5120,"status":200,"resp_headers":{}}"#;
+        let entry = parse_access_log(line).unwrap();
+        assert_eq!(entry.host, "App.example.com:443");
+        assert_eq!(entry.client_ip, "203.0.113.9");
+        assert_eq!(entry.status, 200);
+        assert_eq!(entry.bytes, 5120);
+        assert!((entry.duration_ms - 42.1).abs() < 1e-9);
+        assert_eq!(minute_bucket(&entry.timestamp), "2026-10-18 10:00:00");
+
+        assert!(parse_access_log(r#"{"logger":"tls.obtain","ts":1.0,"status":200}"#).is_none());
+        assert!(parse_access_log("not json").is_none());
+    }
+
+    #[test]
+    fn test_match_request_host_to_domain() {
+        let domain = |name: &str| Domain {
+            id: name.to_string(),
+            stack_id: "s1".to_string(),
+            container_name: "web".to_string(),
+            container_port: 80,
+            domain: name.to_string(),
+            ssl_enabled: true,
+            verified: true,
+            provider: DomainProvider::Custom,
+            r#type: DomainType::Caddy,
+            tunnel_id: None,
+            dns_record_id: None,
+            proxied: false,
+            show_branding: false,
+            extra_upstreams: String::new(),
+            lb_policy: Default::default(),
+            health_check_path: None,
+            health_check_interval: 10,
+            middleware: String::new(),
+            basic_auth: String::new(),
+            tls_challenge: Default::default(),
+            custom_certificate: None,
+            custom_private_key: None,
+            created_at: String::new(),
+        };
+        let domains = vec![domain("app.example.com"), domain("*.preview.example.com")];
+
+        let matched = |host| match_domain(host, &domains).map(|d| d.domain.as_str());
+        assert_eq!(matched("App.Example.com:443"), Some("app.example.com"));
+        assert_eq!(matched("www.app.example.com"), Some("app.example.com"));
+        assert_eq!(
+            matched("pr-7.preview.example.com"),
+            Some("*.preview.example.com")
+        );
+        assert_eq!(matched("a.pr-7.preview.example.com"), None);
+        assert_eq!(matched("other.com"), None);
+    }
+}
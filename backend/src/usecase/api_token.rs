@@ -0,0 +1,202 @@
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::domain::TeamRepository;
+use crate::domain::api_token_repository::ApiTokenRepository;
+use crate::domain::models::permission;
+use crate::domain::models::{
+    API_SCOPES, ApiToken, ApiTokenResponse, BOT_USER_ROLE, CreateApiTokenRequest, CreatedApiToken,
+    NO_PASSWORD_HASH, TeamRole, User,
+};
+use crate::domain::user_repository::UserRepository;
+use crate::error::{AppError, Result};
+use crate::infrastructure::auth::api_token;
+use crate::usecase::authorization::AuthorizationUsecase;
+
+pub struct ApiTokenUsecase {
+    repo: Arc<dyn ApiTokenRepository>,
+    user_repo: Arc<dyn UserRepository>,
+    team_repo: Arc<dyn TeamRepository>,
+    authz: Arc<AuthorizationUsecase>,
+}
+
+impl ApiTokenUsecase {
+    pub fn new(
+        repo: Arc<dyn ApiTokenRepository>,
+        user_repo: Arc<dyn UserRepository>,
+        team_repo: Arc<dyn TeamRepository>,
+        authz: Arc<AuthorizationUsecase>,
+    ) -> Self {
+        Self {
+            repo,
+            user_repo,
+            team_repo,
+            authz,
+        }
+    }
+
+    fn new_token(
+        &self,
+        user_id: &str,
+        team_id: Option<&str>,
+        created_by: &str,
+        request: &CreateApiTokenRequest,
+    ) -> Result<(ApiToken, String)> {
+        let name = request.name.trim();
+        if name.is_empty() {
+            return Err(AppError::Validation("Token name is required".to_string()));
+        }
+        if request.scopes.is_empty() {
+            return Err(AppError::Validation(
+                "At least one scope is required".to_string(),
+            ));
+        }
+        if let Some(scope) = request
+            .scopes
+            .iter()
+            .find(|s| !API_SCOPES.contains(&s.as_str()))
+        {
+            return Err(AppError::Validation(format!("Unknown scope '{}'", scope)));
+        }
+
+        let now = Utc::now();
+        let expires_at = match request.expires_in_days {
+            Some(days) if days <= 0 => {
+                return Err(AppError::Validation(
+                    "expires_in_days must be positive".to_string(),
+                ));
+            }
+            Some(days) => Some(
+                (now + Duration::days(days))
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string(),
+            ),
+            None => None,
+        };
+
+        let mut scopes = request.scopes.clone();
+        scopes.sort();
+        scopes.dedup();
+
+        let generated = api_token::generate();
+        let token = ApiToken {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            team_id: team_id.map(String::from),
+            created_by: Some(created_by.to_string()),
+            name: name.to_string(),
+            token_prefix: generated.prefix,
+            token_hash: generated.hash,
+            scopes: scopes.join(" "),
+            expires_at,
+            last_used_at: None,
+            revoked_at: None,
+            created_at: now.format("%Y-%m-%d %H:%M:%S").to_string(),
+        };
+
+        Ok((token, generated.token))
+    }
+
+    pub async fn create_personal(
+        &self,
+        user_id: &str,
+        request: CreateApiTokenRequest,
+    ) -> Result<CreatedApiToken> {
+        let (token, raw) = self.new_token(user_id, None, user_id, &request)?;
+        let token = self.repo.create(token).await?;
+
+        Ok(CreatedApiToken {
+            token: raw,
+            info: token.into(),
+        })
+    }
+
+    pub async fn list_personal(&self, user_id: &str) -> Result<Vec<ApiTokenResponse>> {
+        let tokens = self.repo.list_personal(user_id).await?;
+        Ok(tokens.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn revoke_personal(&self, id: &str, user_id: &str) -> Result<()> {
+        let token = self.repo.find_by_id(id).await?;
+        if token.user_id != user_id || token.team_id.is_some() {
+            return Err(AppError::NotFound("API token not found".to_string()));
+        }
+
+        let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        self.repo.revoke(id, &now).await
+    }
+
+    /// Team tokens act as a dedicated bot member of the team, so every existing
+    /// team permission check applies to them unchanged
+    pub async fn create_team_token(
+        &self,
+        team_id: &str,
+        user_id: &str,
+        request: CreateApiTokenRequest,
+    ) -> Result<CreatedApiToken> {
+        let creator_role = self
+            .authz
+            .require(team_id, user_id, permission::TEAM_TOKENS_MANAGE)
+            .await?;
+
+        let role = request.role.clone().unwrap_or(TeamRole::Developer);
+        if role == TeamRole::Owner || role.rank() > creator_role.rank() {
+            return Err(AppError::Forbidden(
+                "Cannot create a token with a higher role than your own".to_string(),
+            ));
+        }
+
+        let bot_id = Uuid::new_v4().to_string();
+        let (token, raw) = self.new_token(&bot_id, Some(team_id), user_id, &request)?;
+
+        let bot = User {
+            id: bot_id.clone(),
+            email: format!("token-{}@tokens.labuh.local", token.id),
+            password_hash: NO_PASSWORD_HASH.to_string(),
+            name: Some(format!("{} (API token)", token.name)),
+            role: BOT_USER_ROLE.to_string(),
+            created_at: token.created_at.clone(),
+            updated_at: token.created_at.clone(),
+            deactivated_at: None,
+        };
+        self.user_repo.create(bot).await?;
+        self.team_repo.add_member(team_id, &bot_id, role).await?;
+
+        let token = self.repo.create(token).await?;
+
+        Ok(CreatedApiToken {
+            token: raw,
+            info: token.into(),
+        })
+    }
+
+    pub async fn list_team_tokens(
+        &self,
+        team_id: &str,
+        user_id: &str,
+    ) -> Result<Vec<ApiTokenResponse>> {
+        self.authz
+            .require(team_id, user_id, permission::TEAM_TOKENS_MANAGE)
+            .await?;
+        let tokens = self.repo.list_by_team(team_id).await?;
+        Ok(tokens.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn revoke_team_token(&self, team_id: &str, id: &str, user_id: &str) -> Result<()> {
+        self.authz
+            .require(team_id, user_id, permission::TEAM_TOKENS_MANAGE)
+            .await?;
+
+        let token = self.repo.find_by_id(id).await?;
+        if token.team_id.as_deref() != Some(team_id) {
+            return Err(AppError::NotFound("API token not found".to_string()));
+        }
+
+        let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        self.repo.revoke(id, &now).await?;
+
+        // The bot user is kept so anything it created stays attributed
+        self.team_repo.remove_member(team_id, &token.user_id).await
+    }
+}
@@ -0,0 +1,123 @@
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::domain::activity_log_repository::ActivityLogRepository;
+use crate::domain::models::permission;
+use crate::domain::models::{ActivityLog, AuditLogPage, AuditLogQuery};
+use crate::domain::stack_repository::StackRepository;
+use crate::error::Result;
+use crate::usecase::authorization::AuthorizationUsecase;
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 200;
+
+/// Records mutating actions to `activity_logs` and exposes them per team
+pub struct AuditUsecase {
+    repo: Arc<dyn ActivityLogRepository>,
+    authz: Arc<AuthorizationUsecase>,
+    stack_repo: Arc<dyn StackRepository>,
+}
+
+impl AuditUsecase {
+    pub fn new(
+        repo: Arc<dyn ActivityLogRepository>,
+        authz: Arc<AuthorizationUsecase>,
+        stack_repo: Arc<dyn StackRepository>,
+    ) -> Self {
+        Self {
+            repo,
+            authz,
+            stack_repo,
+        }
+    }
+
+    /// Write an audit entry. `actor_id` is `None` for webhook and scheduled actions;
+    /// `team_id` is `None` for account-level events.
+    pub async fn record(
+        &self,
+        actor_id: Option<&str>,
+        team_id: Option<&str>,
+        action: &str,
+        resource_type: &str,
+        resource_id: &str,
+        details: serde_json::Value,
+    ) -> Result<()> {
+        self.repo
+            .create(ActivityLog {
+                id: Uuid::new_v4().to_string(),
+                user_id: actor_id.map(str::to_string),
+                team_id: team_id.map(str::to_string),
+                action: action.to_string(),
+                resource_type: Some(resource_type.to_string()),
+                resource_id: Some(resource_id.to_string()),
+                details: Some(details.to_string()),
+                created_at: Utc::now().to_rfc3339(),
+            })
+            .await
+    }
+
+    /// Record an action on a stack, attributed to the stack's team
+    pub async fn record_stack(
+        &self,
+        actor_id: Option<&str>,
+        stack_id: &str,
+        action: &str,
+        details: serde_json::Value,
+    ) -> Result<()> {
+        let team_id = self
+            .stack_repo
+            .find_by_id_internal(stack_id)
+            .await
+            .ok()
+            .map(|stack| stack.team_id);
+
+        self.record(
+            actor_id,
+            team_id.as_deref(),
+            action,
+            "stack",
+            stack_id,
+            details,
+        )
+        .await
+    }
+
+    /// Team audit log, newest first. Requires `audit.read` (owners and admins by default).
+    pub async fn list(
+        &self,
+        team_id: &str,
+        user_id: &str,
+        query: AuditLogQuery,
+    ) -> Result<AuditLogPage> {
+        self.authz
+            .require(team_id, user_id, permission::AUDIT_READ)
+            .await?;
+
+        let page = query.page.unwrap_or(1).max(1);
+        let per_page = query
+            .per_page
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+        let offset = (page as i64 - 1) * per_page as i64;
+
+        let total = self.repo.count_by_team(team_id, &query).await?;
+        let entries = self
+            .repo
+            .list_by_team(team_id, &query, per_page as i64, offset)
+            .await?;
+
+        Ok(AuditLogPage {
+            entries: entries.into_iter().map(Into::into).collect(),
+            total,
+            page,
+            per_page,
+        })
+    }
+
+    /// Delete entries older than `retention_days`
+    pub async fn prune(&self, retention_days: u64) -> Result<u64> {
+        let cutoff = Utc::now() - Duration::days(retention_days as i64);
+        self.repo.delete_before(&cutoff.to_rfc3339()).await
+    }
+}
@@ -1,24 +1,131 @@
-use chrono::Utc;
-use std::sync::Arc;
+use chrono::{DateTime, Duration, Utc};
+use rand::distr::{Alphanumeric, SampleString};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
-use crate::domain::models::{AuthResponse, CreateUser, LoginRequest, User};
+use crate::domain::api_token_repository::ApiTokenRepository;
+use crate::domain::models::{
+    ADMIN_ROLE, ApiToken, AuthResponse, BOT_USER_ROLE, CreateUser, LoginOptions, LoginRequest,
+    LoginResponse, NO_PASSWORD_HASH, Session, SessionResponse, StreamTicket, User,
+};
+use crate::domain::session_repository::SessionRepository;
 use crate::domain::user_repository::UserRepository;
 use crate::error::{AppError, Result};
-use crate::infrastructure::auth::jwt::JwtService;
+use crate::infrastructure::auth::api_token;
+use crate::infrastructure::auth::jwt::{Claims, JwtService};
 use crate::infrastructure::auth::password::PasswordService;
+use crate::usecase::login_throttle::LoginThrottle;
+use crate::usecase::two_factor::TwoFactorUsecase;
 
 pub struct AuthUsecase {
     repo: Arc<dyn UserRepository>,
+    token_repo: Arc<dyn ApiTokenRepository>,
+    session_repo: Arc<dyn SessionRepository>,
     jwt_service: Arc<JwtService>,
+    two_factor: Arc<TwoFactorUsecase>,
+    throttle: Arc<LoginThrottle>,
+    refresh_ttl_days: i64,
+    login_options: LoginOptions,
+    stream_tickets: Mutex<HashMap<String, StreamGrant>>,
+}
+
+/// Login session a stream ticket stands in for
+struct StreamGrant {
+    user_id: String,
+    session_id: String,
+    expires_at: DateTime<Utc>,
+}
+
+const STREAM_TICKET_TTL_SECONDS: i64 = 30;
+
+fn timestamp(at: chrono::DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M:%S").to_string()
 }
 
 impl AuthUsecase {
-    pub fn new(repo: Arc<dyn UserRepository>, jwt_service: Arc<JwtService>) -> Self {
-        Self { repo, jwt_service }
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        repo: Arc<dyn UserRepository>,
+        token_repo: Arc<dyn ApiTokenRepository>,
+        session_repo: Arc<dyn SessionRepository>,
+        jwt_service: Arc<JwtService>,
+        two_factor: Arc<TwoFactorUsecase>,
+        throttle: Arc<LoginThrottle>,
+        refresh_ttl_days: u64,
+        login_options: LoginOptions,
+    ) -> Self {
+        Self {
+            repo,
+            token_repo,
+            session_repo,
+            jwt_service,
+            two_factor,
+            throttle,
+            refresh_ttl_days: refresh_ttl_days as i64,
+            login_options,
+            stream_tickets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn login_options(&self) -> &LoginOptions {
+        &self.login_options
+    }
+
+    /// Password credentials can only be created or used while password login is enabled
+    pub(crate) fn ensure_password_login(&self) -> Result<()> {
+        if !self.login_options.password_enabled {
+            return Err(AppError::Forbidden(
+                "Password login is disabled. Please sign in with SSO.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Open a new session and issue its access and refresh tokens
+    pub async fn start_session(
+        &self,
+        user: User,
+        user_agent: Option<String>,
+    ) -> Result<AuthResponse> {
+        if !user.is_active() {
+            return Err(AppError::Forbidden("Account is deactivated".to_string()));
+        }
+
+        let now = Utc::now();
+        let refresh_token = Alphanumeric.sample_string(&mut rand::rng(), 48);
+
+        let session = Session {
+            id: Uuid::new_v4().to_string(),
+            user_id: user.id.clone(),
+            refresh_token_hash: api_token::hash(&refresh_token),
+            user_agent,
+            expires_at: timestamp(now + Duration::days(self.refresh_ttl_days)),
+            last_used_at: None,
+            revoked_at: None,
+            created_at: timestamp(now),
+        };
+        let session = self.session_repo.create(session).await?;
+
+        let token =
+            self.jwt_service
+                .generate_token(&user.id, &user.email, &user.role, &session.id)?;
+
+        Ok(AuthResponse {
+            token,
+            refresh_token,
+            expires_in: self.jwt_service.access_ttl_seconds(),
+            user: user.into(),
+        })
     }
 
-    pub async fn register(&self, input: CreateUser) -> Result<AuthResponse> {
+    pub async fn register(
+        &self,
+        input: CreateUser,
+        user_agent: Option<String>,
+    ) -> Result<AuthResponse> {
+        self.ensure_password_login()?;
+
         // Only allow registration if no users exist (initial setup)
         let user_count = self.repo.count_users().await?;
         if user_count > 0 {
@@ -44,44 +151,251 @@ impl AuthUsecase {
             email: input.email,
             password_hash,
             name: input.name,
-            role: "admin".to_string(), // First user is always admin
+            role: ADMIN_ROLE.to_string(), // First user is always admin
             created_at: now.clone(),
             updated_at: now,
+            deactivated_at: None,
         };
 
         let created_user = self.repo.create(user).await?;
-        let token = self.jwt_service.generate_token(
-            &created_user.id,
-            &created_user.email,
-            &created_user.role,
-        )?;
+        self.start_session(created_user, user_agent).await
+    }
 
-        Ok(AuthResponse {
-            token,
-            user: created_user.into(),
-        })
+    /// Open a session once the first factor is checked, or ask for the second
+    /// factor when the user has 2FA enabled
+    pub async fn finish_login(
+        &self,
+        user: User,
+        user_agent: Option<String>,
+    ) -> Result<LoginResponse> {
+        if !user.is_active() {
+            return Err(AppError::Forbidden("Account is deactivated".to_string()));
+        }
+        if self.two_factor.is_enabled(&user.id).await? {
+            return Ok(LoginResponse::MfaRequired {
+                mfa_required: true,
+                mfa_token: self.jwt_service.generate_mfa_token(&user.id)?,
+            });
+        }
+
+        Ok(LoginResponse::Authenticated(
+            self.start_session(user, user_agent).await?,
+        ))
+    }
+
+    /// Exchange the MFA token from `finish_login` and a TOTP or recovery code for a session
+    pub async fn complete_mfa_login(
+        &self,
+        mfa_token: &str,
+        code: &str,
+        client_ip: &str,
+        user_agent: Option<String>,
+    ) -> Result<AuthResponse> {
+        let claims = self.jwt_service.verify_mfa_token(mfa_token)?;
+        let user = self.get_user_by_id(&claims.sub).await?;
+
+        // The MFA token is valid for minutes, so second factor guesses count
+        // against the same account lockout as passwords
+        self.throttle.check(&user.email, client_ip)?;
+        match self.two_factor.verify_second_factor(&user.id, code).await {
+            Err(AppError::InvalidCredentials) => {
+                self.throttle.record_failure(&user.email, client_ip).await?;
+                return Err(AppError::InvalidCredentials);
+            }
+            result => result?,
+        }
+        self.throttle.record_success(&user.email).await?;
+
+        self.start_session(user, user_agent).await
     }
 
-    pub async fn login(&self, input: LoginRequest) -> Result<AuthResponse> {
+    pub async fn login(
+        &self,
+        input: LoginRequest,
+        client_ip: &str,
+        user_agent: Option<String>,
+    ) -> Result<LoginResponse> {
+        self.ensure_password_login()?;
+        self.throttle.check(&input.email, client_ip)?;
+
+        // Unknown accounts count as failures too, so probing emails is throttled
+        let user = match self.check_password(&input).await {
+            Err(AppError::InvalidCredentials) => {
+                self.throttle
+                    .record_failure(&input.email, client_ip)
+                    .await?;
+                return Err(AppError::InvalidCredentials);
+            }
+            result => result?,
+        };
+        self.throttle.record_success(&input.email).await?;
+
+        self.finish_login(user, user_agent).await
+    }
+
+    async fn check_password(&self, input: &LoginRequest) -> Result<User> {
         let user = self
             .repo
             .find_by_email(&input.email)
             .await?
             .ok_or(AppError::InvalidCredentials)?;
 
-        // Verify password
+        // Bot users back team API tokens; SSO users have no password
+        if user.role == BOT_USER_ROLE || user.password_hash == NO_PASSWORD_HASH {
+            return Err(AppError::InvalidCredentials);
+        }
+
         PasswordService::verify_password(&input.password, &user.password_hash)?;
+        Ok(user)
+    }
 
-        let token = self
-            .jwt_service
-            .generate_token(&user.id, &user.email, &user.role)?;
+    /// Exchange a refresh token for a new access token, rotating the refresh token
+    /// so each one can be used only once
+    pub async fn refresh(&self, refresh_token: &str) -> Result<AuthResponse> {
+        let invalid = || AppError::Auth("Invalid or expired refresh token".to_string());
+        let now = Utc::now();
+        let now_str = timestamp(now);
+
+        let current_hash = api_token::hash(refresh_token);
+        let session = self
+            .session_repo
+            .find_by_refresh_hash(&current_hash)
+            .await?
+            .ok_or_else(invalid)?;
+
+        if session.revoked_at.is_some() || session.expires_at <= now_str {
+            return Err(invalid());
+        }
+
+        let user = self
+            .repo
+            .find_by_id(&session.user_id)
+            .await?
+            .filter(User::is_active)
+            .ok_or_else(invalid)?;
+
+        let next_refresh = Alphanumeric.sample_string(&mut rand::rng(), 48);
+        let rotated = self
+            .session_repo
+            .rotate(
+                &session.id,
+                &current_hash,
+                &api_token::hash(&next_refresh),
+                &timestamp(now + Duration::days(self.refresh_ttl_days)),
+                &now_str,
+            )
+            .await?;
+        // Another request used the same token first: treat it as a stolen token
+        // and end the session for both
+        if !rotated {
+            self.session_repo.revoke(&session.id, &now_str).await?;
+            return Err(invalid());
+        }
+
+        let token =
+            self.jwt_service
+                .generate_token(&user.id, &user.email, &user.role, &session.id)?;
 
         Ok(AuthResponse {
             token,
+            refresh_token: next_refresh,
+            expires_in: self.jwt_service.access_ttl_seconds(),
             user: user.into(),
         })
     }
 
+    pub async fn logout(&self, session_id: &str) -> Result<()> {
+        self.session_repo
+            .revoke(session_id, &timestamp(Utc::now()))
+            .await
+    }
+
+    /// Revoke every session of a user; their access tokens stop working immediately
+    pub async fn revoke_all_sessions(&self, user_id: &str) -> Result<()> {
+        self.session_repo
+            .revoke_all_for_user(user_id, &timestamp(Utc::now()))
+            .await
+    }
+
+    /// Issue a ticket that authenticates one WebSocket or SSE connection for the
+    /// given login session
+    pub fn issue_stream_ticket(&self, user_id: &str, session_id: &str) -> StreamTicket {
+        let now = Utc::now();
+        let ticket = Alphanumeric.sample_string(&mut rand::rng(), 48);
+
+        let mut tickets = self
+            .stream_tickets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        tickets.retain(|_, grant| grant.expires_at > now);
+        tickets.insert(
+            ticket.clone(),
+            StreamGrant {
+                user_id: user_id.to_string(),
+                session_id: session_id.to_string(),
+                expires_at: now + Duration::seconds(STREAM_TICKET_TTL_SECONDS),
+            },
+        );
+
+        StreamTicket {
+            ticket,
+            expires_in: STREAM_TICKET_TTL_SECONDS as u64,
+        }
+    }
+
+    /// Consume a stream ticket, returning the user and the session it was issued for
+    pub async fn redeem_stream_ticket(&self, ticket: &str) -> Result<(User, String)> {
+        let invalid = || AppError::Auth("Invalid or expired stream ticket".to_string());
+
+        let StreamGrant {
+            user_id,
+            session_id,
+            expires_at,
+        } = self
+            .stream_tickets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(ticket)
+            .ok_or_else(invalid)?;
+        if expires_at <= Utc::now() {
+            return Err(invalid());
+        }
+
+        let active = self
+            .session_repo
+            .find_by_id(&session_id)
+            .await?
+            .is_some_and(|s| s.user_id == user_id && s.revoked_at.is_none());
+        if !active {
+            return Err(AppError::Auth("Session has been revoked".to_string()));
+        }
+
+        Ok((self.get_user_by_id(&user_id).await?, session_id))
+    }
+
+    pub async fn list_sessions(
+        &self,
+        user_id: &str,
+        current_session: &str,
+    ) -> Result<Vec<SessionResponse>> {
+        let sessions = self
+            .session_repo
+            .list_active(user_id, &timestamp(Utc::now()))
+            .await?;
+
+        Ok(sessions
+            .into_iter()
+            .map(|s| SessionResponse {
+                current: s.id == current_session,
+                id: s.id,
+                user_agent: s.user_agent,
+                expires_at: s.expires_at,
+                last_used_at: s.last_used_at,
+                created_at: s.created_at,
+            })
+            .collect())
+    }
+
     pub async fn get_user_by_id(&self, id: &str) -> Result<User> {
         self.repo
             .find_by_id(id)
@@ -89,8 +403,55 @@ impl AuthUsecase {
             .ok_or(AppError::NotFound("User not found".to_string()))
     }
 
-    pub fn verify_token(&self, token: &str) -> Result<crate::infrastructure::auth::jwt::Claims> {
-        self.jwt_service.verify_token(token)
+    /// Verify an access token and that its session has not been revoked
+    pub async fn verify_token(&self, token: &str) -> Result<Claims> {
+        let claims = self.jwt_service.verify_token(token)?;
+
+        let active = self
+            .session_repo
+            .find_by_id(&claims.sid)
+            .await?
+            .is_some_and(|s| s.user_id == claims.sub && s.revoked_at.is_none());
+        if !active {
+            return Err(AppError::Auth("Session has been revoked".to_string()));
+        }
+
+        Ok(claims)
+    }
+
+    /// Resolve an API token to the user it acts as, rejecting revoked or expired tokens
+    pub async fn verify_api_token(&self, raw: &str) -> Result<(User, ApiToken)> {
+        let invalid = || AppError::Auth("Invalid or expired API token".to_string());
+
+        let token = self
+            .token_repo
+            .find_by_hash(&api_token::hash(raw))
+            .await?
+            .ok_or_else(invalid)?;
+
+        let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        if token.revoked_at.is_some() || token.expires_at.as_ref().is_some_and(|exp| *exp <= now) {
+            return Err(invalid());
+        }
+
+        let user = self
+            .repo
+            .find_by_id(&token.user_id)
+            .await?
+            .filter(User::is_active)
+            .ok_or_else(invalid)?;
+        self.token_repo.touch_last_used(&token.id, &now).await?;
+
+        Ok((user, token))
+    }
+
+    /// Whether a team policy forces the user to enroll in 2FA before using the API.
+    /// Bot users behind team tokens are exempt.
+    pub async fn mfa_enrollment_required(&self, user: &User) -> Result<bool> {
+        if user.role == BOT_USER_ROLE {
+            return Ok(false);
+        }
+        self.two_factor.enrollment_required(&user.id).await
     }
 
     pub async fn is_setup_required(&self) -> Result<bool> {
@@ -98,3 +459,171 @@ impl AuthUsecase {
         Ok(count == 0)
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::domain::models::USER_ROLE;
+    use crate::infrastructure::crypto::envelope::EnvelopeCipher;
+    use crate::infrastructure::sqlite::{
+        activity_log::SqliteActivityLogRepository, api_token::SqliteApiTokenRepository,
+        role::SqliteRoleRepository, session::SqliteSessionRepository, stack::SqliteStackRepository,
+        stack_grant::SqliteStackGrantRepository, team::SqliteTeamRepository,
+        two_factor::SqliteTwoFactorRepository, user::SqliteUserRepository,
+    };
+    use crate::usecase::audit::AuditUsecase;
+    use crate::usecase::authorization::AuthorizationUsecase;
+    use sqlx::SqlitePool;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    pub(crate) async fn memory_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        crate::db::run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    /// `AuthUsecase` over `pool` with the repositories the app uses
+    pub(crate) fn auth_usecase(pool: &SqlitePool, password_enabled: bool) -> AuthUsecase {
+        let team_repo = Arc::new(SqliteTeamRepository::new(pool.clone()));
+        let authz = Arc::new(AuthorizationUsecase::new(
+            team_repo.clone(),
+            Arc::new(SqliteRoleRepository::new(pool.clone())),
+            Arc::new(SqliteStackGrantRepository::new(pool.clone())),
+        ));
+        let audit = Arc::new(AuditUsecase::new(
+            Arc::new(SqliteActivityLogRepository::new(pool.clone())),
+            authz.clone(),
+            Arc::new(SqliteStackRepository::new(pool.clone())),
+        ));
+        let two_factor = Arc::new(TwoFactorUsecase::new(
+            Arc::new(SqliteTwoFactorRepository::new(pool.clone())),
+            team_repo,
+            authz,
+            audit,
+            Arc::new(EnvelopeCipher::generate()),
+        ));
+
+        AuthUsecase::new(
+            Arc::new(SqliteUserRepository::new(pool.clone())),
+            Arc::new(SqliteApiTokenRepository::new(pool.clone())),
+            Arc::new(SqliteSessionRepository::new(pool.clone())),
+            Arc::new(JwtService::new("test-secret".to_string(), 15)),
+            two_factor,
+            Arc::new(LoginThrottle::new(None, 5, 60)),
+            30,
+            LoginOptions {
+                password_enabled,
+                sso_provider: (!password_enabled).then(|| "Mock".to_string()),
+            },
+        )
+    }
+
+    /// A user with `password`, or an SSO-only one without
+    pub(crate) async fn create_user(
+        pool: &SqlitePool,
+        email: &str,
+        password: Option<&str>,
+        role: &str,
+    ) -> User {
+        let now = timestamp(Utc::now());
+        SqliteUserRepository::new(pool.clone())
+            .create(User {
+                id: Uuid::new_v4().to_string(),
+                email: email.to_string(),
+                password_hash: match password {
+                    Some(password) => PasswordService::hash_password(password).unwrap(),
+                    None => NO_PASSWORD_HASH.to_string(),
+                },
+                name: None,
+                role: role.to_string(),
+                created_at: now.clone(),
+                updated_at: now,
+                deactivated_at: None,
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_refresh_tokens_are_single_use() {
+        let pool = memory_pool().await;
+        let auth = auth_usecase(&pool, true);
+        let user = create_user(&pool, "dev@example.com", Some("hunter22"), USER_ROLE).await;
+        let first = auth.start_session(user, None).await.unwrap();
+
+        let second = auth.refresh(&first.refresh_token).await.unwrap();
+        assert_ne!(second.refresh_token, first.refresh_token);
+        assert!(matches!(
+            auth.refresh(&first.refresh_token).await,
+            Err(AppError::Auth(_))
+        ));
+
+        // Two requests racing with the same token: only one gets a new one
+        let (a, b) = tokio::join!(
+            auth.refresh(&second.refresh_token),
+            auth.refresh(&second.refresh_token)
+        );
+        assert_eq!([a.is_ok(), b.is_ok()].iter().filter(|ok| **ok).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rotate_requires_current_token_and_live_session() {
+        let pool = memory_pool().await;
+        let auth = auth_usecase(&pool, true);
+        let user = create_user(&pool, "dev@example.com", Some("hunter22"), USER_ROLE).await;
+        let response = auth.start_session(user, None).await.unwrap();
+        let sid = auth.verify_token(&response.token).await.unwrap().sid;
+
+        let repo = SqliteSessionRepository::new(pool);
+        let current = api_token::hash(&response.refresh_token);
+        assert!(
+            repo.rotate(&sid, &current, "next", "9999", "now")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !repo
+                .rotate(&sid, &current, "other", "9999", "now")
+                .await
+                .unwrap()
+        );
+        repo.revoke(&sid, "now").await.unwrap();
+        assert!(
+            !repo
+                .rotate(&sid, "next", "other", "9999", "now")
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_logout_and_revoke_all_end_sessions() {
+        let pool = memory_pool().await;
+        let auth = auth_usecase(&pool, true);
+        let user = create_user(&pool, "dev@example.com", Some("hunter22"), USER_ROLE).await;
+        let laptop = auth.start_session(user.clone(), None).await.unwrap();
+        let phone = auth.start_session(user.clone(), None).await.unwrap();
+        let laptop_sid = auth.verify_token(&laptop.token).await.unwrap().sid;
+        assert_eq!(
+            auth.list_sessions(&user.id, &laptop_sid)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        auth.logout(&laptop_sid).await.unwrap();
+        assert!(auth.verify_token(&laptop.token).await.is_err());
+        assert!(auth.refresh(&laptop.refresh_token).await.is_err());
+        assert!(auth.verify_token(&phone.token).await.is_ok());
+
+        auth.revoke_all_sessions(&user.id).await.unwrap();
+        assert!(auth.verify_token(&phone.token).await.is_err());
+        assert!(auth.refresh(&phone.refresh_token).await.is_err());
+        assert!(auth.list_sessions(&user.id, "").await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,238 @@
+use std::sync::Arc;
+
+use crate::domain::TeamRepository;
+use crate::domain::models::{PermissionSet, Stack, TeamRole};
+use crate::domain::role_repository::RoleRepository;
+use crate::domain::stack_grant_repository::StackGrantRepository;
+use crate::error::{AppError, Result};
+
+/// Single place that decides what a user may do in a team or on a stack.
+/// Effective permissions are the union of the member's built-in role, their
+/// custom role and, for stacks, any grant they hold on that stack.
+pub struct AuthorizationUsecase {
+    team_repo: Arc<dyn TeamRepository>,
+    role_repo: Arc<dyn RoleRepository>,
+    grant_repo: Arc<dyn StackGrantRepository>,
+}
+
+fn denied(permission: &str) -> AppError {
+    AppError::Forbidden(format!(
+        "Insufficient permissions for this operation (requires {})",
+        permission
+    ))
+}
+
+impl AuthorizationUsecase {
+    pub fn new(
+        team_repo: Arc<dyn TeamRepository>,
+        role_repo: Arc<dyn RoleRepository>,
+        grant_repo: Arc<dyn StackGrantRepository>,
+    ) -> Self {
+        Self {
+            team_repo,
+            role_repo,
+            grant_repo,
+        }
+    }
+
+    async fn member_permissions(&self, team_id: &str, user_id: &str) -> Result<PermissionSet> {
+        let role = self.team_repo.get_user_role(team_id, user_id).await?;
+        let custom_role = match role {
+            Some(_) => self.role_repo.find_member_role(team_id, user_id).await?,
+            None => None,
+        };
+        Ok(PermissionSet::resolve(role, custom_role.as_ref(), None))
+    }
+
+    /// Permissions of a team member; non-members are denied
+    pub async fn team_permissions(&self, team_id: &str, user_id: &str) -> Result<PermissionSet> {
+        let permissions = self.member_permissions(team_id, user_id).await?;
+        if permissions.role.is_none() {
+            return Err(AppError::Forbidden("Access denied".to_string()));
+        }
+        Ok(permissions)
+    }
+
+    /// Check a team-wide permission and return the member's built-in role
+    pub async fn require(
+        &self,
+        team_id: &str,
+        user_id: &str,
+        permission: &str,
+    ) -> Result<TeamRole> {
+        let permissions = self.team_permissions(team_id, user_id).await?;
+        if !permissions.contains(permission) {
+            return Err(denied(permission));
+        }
+        Ok(permissions.role.unwrap_or(TeamRole::Viewer))
+    }
+
+    /// Permissions on one stack: team permissions plus the user's grant on it.
+    /// Users with neither are denied.
+    pub async fn stack_permissions(&self, stack: &Stack, user_id: &str) -> Result<PermissionSet> {
+        let member = self.member_permissions(&stack.team_id, user_id).await?;
+        let grant = self.grant_repo.find(&stack.id, user_id).await?;
+        if member.role.is_none() && grant.is_none() {
+            return Err(AppError::Forbidden("Access denied".to_string()));
+        }
+
+        let mut permissions = member;
+        if let Some(grant) = grant {
+            permissions
+                .permissions
+                .extend(PermissionSet::resolve(None, None, Some(&grant)).permissions);
+        }
+        Ok(permissions)
+    }
+
+    pub async fn require_stack(
+        &self,
+        stack: &Stack,
+        user_id: &str,
+        permission: &str,
+    ) -> Result<()> {
+        if !self
+            .stack_permissions(stack, user_id)
+            .await?
+            .contains(permission)
+        {
+            return Err(denied(permission));
+        }
+        Ok(())
+    }
+
+    /// Stacks outside the user's teams that they can reach through a grant
+    pub async fn granted_stack_ids(&self, user_id: &str) -> Result<Vec<String>> {
+        self.grant_repo.list_stack_ids_for_user(user_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::permission::{STACK_DEPLOY, STACK_EXEC, STACK_VIEW};
+    use crate::domain::models::{CustomRole, StackGrant};
+    use crate::domain::stack_repository::StackRepository;
+    use crate::infrastructure::sqlite::role::SqliteRoleRepository;
+    use crate::infrastructure::sqlite::stack::SqliteStackRepository;
+    use crate::infrastructure::sqlite::stack_grant::SqliteStackGrantRepository;
+    use crate::infrastructure::sqlite::team::SqliteTeamRepository;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[tokio::test]
+    async fn test_custom_roles_and_stack_grants() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        crate::db::run_migrations(&pool).await.unwrap();
+        for user in ["viewer", "contractor", "outsider"] {
+            sqlx::query("INSERT INTO users (id, email, password_hash) VALUES (?, ?, '')")
+                .bind(user)
+                .bind(format!("{}@example.com", user))
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO teams (id, name, created_at, updated_at) VALUES ('t1', 't1', '', '')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        for stack in ["s1", "s2"] {
+            sqlx::query(
+                "INSERT INTO stacks (id, name, user_id, team_id, created_at, updated_at) VALUES (?, ?, 'viewer', 't1', '', '')",
+            )
+            .bind(stack)
+            .bind(stack)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let team_repo = Arc::new(SqliteTeamRepository::new(pool.clone()));
+        let role_repo = Arc::new(SqliteRoleRepository::new(pool.clone()));
+        let grant_repo = Arc::new(SqliteStackGrantRepository::new(pool.clone()));
+        team_repo
+            .add_member("t1", "viewer", TeamRole::Viewer)
+            .await
+            .unwrap();
+        role_repo
+            .save(&CustomRole {
+                id: "r1".to_string(),
+                team_id: "t1".to_string(),
+                name: "Deployer".to_string(),
+                description: None,
+                permissions: STACK_DEPLOY.to_string(),
+                created_at: String::new(),
+                updated_at: String::new(),
+            })
+            .await
+            .unwrap();
+        grant_repo
+            .save(&StackGrant {
+                stack_id: "s1".to_string(),
+                user_id: "contractor".to_string(),
+                permissions: STACK_DEPLOY.to_string(),
+                granted_by: None,
+                created_at: String::new(),
+                updated_at: String::new(),
+            })
+            .await
+            .unwrap();
+
+        let authz = AuthorizationUsecase::new(team_repo, role_repo.clone(), grant_repo);
+        let stacks = SqliteStackRepository::new(pool);
+        let s1 = stacks.find_by_id_internal("s1").await.unwrap();
+        let s2 = stacks.find_by_id_internal("s2").await.unwrap();
+
+        // Built-in Viewer can look but not deploy until given the custom role
+        assert!(authz.require("t1", "viewer", STACK_VIEW).await.is_ok());
+        assert!(authz.require("t1", "viewer", STACK_DEPLOY).await.is_err());
+        role_repo
+            .set_member_role("t1", "viewer", Some("r1"))
+            .await
+            .unwrap();
+        assert!(authz.require("t1", "viewer", STACK_DEPLOY).await.is_ok());
+        assert!(
+            authz
+                .require_stack(&s2, "viewer", STACK_EXEC)
+                .await
+                .is_err()
+        );
+
+        // The contractor reaches exactly one stack through their grant
+        assert!(
+            authz
+                .require_stack(&s1, "contractor", STACK_DEPLOY)
+                .await
+                .is_ok()
+        );
+        assert!(
+            authz
+                .require_stack(&s1, "contractor", STACK_EXEC)
+                .await
+                .is_err()
+        );
+        assert!(
+            authz
+                .require_stack(&s2, "contractor", STACK_VIEW)
+                .await
+                .is_err()
+        );
+        assert!(authz.require("t1", "contractor", STACK_VIEW).await.is_err());
+        assert_eq!(
+            authz.granted_stack_ids("contractor").await.unwrap(),
+            vec!["s1"]
+        );
+
+        assert!(
+            authz
+                .require_stack(&s1, "outsider", STACK_VIEW)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,899 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, OnceCell};
+
+use serde_json::{Map, Value};
+
+use crate::domain::compose::parse_compose;
+use crate::domain::domain_repository::DomainRepository;
+use crate::domain::models::Stack;
+use crate::domain::models::domain::{
+    Domain, DomainRoute, DomainType, L4Protocol, L4Route, TlsChallenge,
+};
+use crate::domain::runtime::{ContainerInfo, RuntimePort};
+use crate::domain::secret_cipher::SecretCipher;
+use crate::domain::stack_repository::StackRepository;
+use crate::error::{AppError, Result};
+use crate::infrastructure::caddy::certificates::{PeerCertificate, certificate_errors};
+use crate::infrastructure::caddy::client::CaddyClient;
+use crate::infrastructure::caddy::config::{
+    DnsChallenge, HealthCheck, L4Proxy, MAINTENANCE_PAGE, PemCertificate, ProxyRoute, Site,
+    SiteTls, render_http_app, render_layer4_app, render_logs, render_tls_app,
+};
+use crate::usecase::dns::DnsUsecase;
+
+/// Host ports Caddy itself listens on, never available to L4 routes
+const CADDY_PORTS: [(L4Protocol, u16); 3] = [
+    (L4Protocol::Tcp, 80),
+    (L4Protocol::Tcp, 443),
+    (L4Protocol::Tcp, 2019),
+];
+
+/// Keeps Caddy's HTTP, TLS and layer4 config equal to what the `domains` and
+/// `l4_routes` tables describe.
+/// Every run renders the complete config from scratch, so the result never depends
+/// on the order of earlier edits, and a restarted Caddy is repopulated on the next run.
+pub struct CaddyReconciler {
+    domain_repo: Arc<dyn DomainRepository>,
+    stack_repo: Arc<dyn StackRepository>,
+    dns_usecase: Arc<DnsUsecase>,
+    cipher: Arc<dyn SecretCipher>,
+    runtime: Arc<dyn RuntimePort>,
+    caddy_client: Arc<CaddyClient>,
+    caddy_image: String,
+    /// Site for Labuh's own dashboard, when `LABUH_DOMAIN` is set
+    dashboard: Option<Site>,
+    /// Whether Caddy has the `replace-response` module, probed once
+    branding: OnceCell<bool>,
+    /// Whether Caddy has the `layer4` app, probed once
+    layer4: OnceCell<bool>,
+    /// L4 ports the Caddy container was last created with
+    published_ports: Mutex<Option<Vec<String>>>,
+    /// Stacks being redeployed or rolled back, served the maintenance page meanwhile
+    deploying: std::sync::Mutex<HashSet<String>>,
+    // Serializes runs so a periodic sync cannot interleave with one from a domain change
+    lock: Mutex<()>,
+}
+
+impl CaddyReconciler {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        domain_repo: Arc<dyn DomainRepository>,
+        stack_repo: Arc<dyn StackRepository>,
+        dns_usecase: Arc<DnsUsecase>,
+        cipher: Arc<dyn SecretCipher>,
+        runtime: Arc<dyn RuntimePort>,
+        caddy_client: Arc<CaddyClient>,
+        caddy_image: String,
+        dashboard: Option<Site>,
+    ) -> Self {
+        Self {
+            domain_repo,
+            stack_repo,
+            dns_usecase,
+            cipher,
+            runtime,
+            caddy_client,
+            caddy_image,
+            dashboard,
+            branding: OnceCell::new(),
+            layer4: OnceCell::new(),
+            published_ports: Mutex::new(None),
+            deploying: std::sync::Mutex::new(HashSet::new()),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Caddy domains ordered by hostname, wildcards last so that an exact hostname
+    /// is never shadowed by a wildcard covering it
+    async fn caddy_domains(&self) -> Result<Vec<Domain>> {
+        let mut domains: Vec<Domain> = self
+            .domain_repo
+            .list_all()
+            .await?
+            .into_iter()
+            .filter(|d| matches!(d.r#type, DomainType::Caddy))
+            .collect();
+        domains.sort_by(|a, b| (a.is_wildcard(), &a.domain).cmp(&(b.is_wildcard(), &b.domain)));
+        Ok(domains)
+    }
+
+    /// A site for every Caddy domain, followed by the dashboard. A domain's path
+    /// routes come before its catch-all route, longest prefix first, so the
+    /// catch-all never shadows them.
+    async fn desired_sites(
+        &self,
+        domains: &[Domain],
+        certificates: &[PemCertificate],
+    ) -> Result<Vec<Site>> {
+        // Already ordered longest prefix first
+        let mut path_routes: HashMap<String, Vec<DomainRoute>> = HashMap::new();
+        if !domains.is_empty() {
+            for route in self.domain_repo.list_all_routes().await? {
+                path_routes
+                    .entry(route.domain_id.clone())
+                    .or_default()
+                    .push(route);
+            }
+        }
+
+        // Swarm service lookups only matter when there are routes to resolve
+        let containers = if domains.is_empty() {
+            Vec::new()
+        } else {
+            self.runtime.list_containers(true).await.unwrap_or_default()
+        };
+
+        let branding = domains.iter().any(|d| d.show_branding) && self.branding_supported().await;
+
+        let stacks: HashMap<String, Stack> = if domains.is_empty() {
+            HashMap::new()
+        } else {
+            self.stack_repo
+                .list_all()
+                .await?
+                .into_iter()
+                .map(|s| (s.id.clone(), s))
+                .collect()
+        };
+        let deploying = self.deploying.lock().unwrap().clone();
+
+        let registered: HashSet<&str> = domains.iter().map(|d| d.domain.as_str()).collect();
+        let mut sites = Vec::new();
+        for d in domains {
+            let mut routes: Vec<ProxyRoute> = path_routes
+                .get(&d.id)
+                .into_iter()
+                .flatten()
+                .map(|r| ProxyRoute {
+                    path_prefix: Some(r.path.clone()),
+                    strip_prefix: r.strip_prefix,
+                    upstreams: dial_addresses(&r.targets(), &containers),
+                    lb_policy: r.lb_policy.as_str().to_string(),
+                    health_check: health_check(&r.health_check_path, r.health_check_interval),
+                })
+                .collect();
+            routes.push(ProxyRoute {
+                path_prefix: None,
+                strip_prefix: false,
+                upstreams: dial_addresses(&d.targets(), &containers),
+                lb_policy: d.lb_policy.as_str().to_string(),
+                health_check: health_check(&d.health_check_path, d.health_check_interval),
+            });
+
+            let middleware = d.middleware();
+            // A counterpart registered as its own domain keeps its own site
+            let redirect_hosts = if middleware.www_redirect && !d.is_wildcard() {
+                let counterpart = www_counterpart(&d.domain);
+                if registered.contains(counterpart.as_str()) {
+                    Vec::new()
+                } else {
+                    vec![counterpart]
+                }
+            } else {
+                Vec::new()
+            };
+
+            let tls = if !d.ssl_enabled {
+                SiteTls::Disabled
+            } else if certificates.iter().any(|c| c.host == d.domain) {
+                SiteTls::Custom
+            } else {
+                SiteTls::Automatic
+            };
+
+            let stack = stacks.get(&d.stack_id);
+            let maintenance = stack
+                .filter(|s| s.maintenance_mode || deploying.contains(&s.id))
+                .map(|s| {
+                    s.maintenance_page
+                        .clone()
+                        .unwrap_or_else(|| MAINTENANCE_PAGE.to_string())
+                });
+
+            sites.push(Site {
+                hosts: vec![d.domain.clone()],
+                tls,
+                redirect_hosts,
+                middleware,
+                basic_auth: d.basic_auth_accounts(),
+                branding: branding && d.show_branding,
+                maintenance,
+                error_pages: stack.map(Stack::error_pages).unwrap_or_default(),
+                routes,
+            });
+        }
+        sites.extend(self.dashboard.clone());
+        Ok(sites)
+    }
+
+    /// Uploaded certificates of domains serving HTTPS. A pair that fails to decrypt
+    /// is left out, so the domain falls back to ACME instead of going without one.
+    fn custom_certificates(&self, domains: &[Domain]) -> Vec<PemCertificate> {
+        domains
+            .iter()
+            .filter(|d| d.ssl_enabled)
+            .filter_map(|d| {
+                let (certificate, key) = d
+                    .custom_certificate
+                    .as_ref()
+                    .zip(d.custom_private_key.as_ref())?;
+                match (self.cipher.decrypt(certificate), self.cipher.decrypt(key)) {
+                    (Ok(certificate), Ok(key)) => Some(PemCertificate {
+                        host: d.domain.clone(),
+                        certificate,
+                        key,
+                    }),
+                    _ => {
+                        tracing::warn!("Could not decrypt the certificate of {}", d.domain);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// DNS-01 policies for domains that ask for them, one per team Cloudflare token.
+    /// Domains whose team has no Cloudflare config are left to HTTP-01.
+    async fn dns_challenges(
+        &self,
+        domains: &[Domain],
+        certificates: &[PemCertificate],
+    ) -> Result<Vec<DnsChallenge>> {
+        let dns_domains: Vec<&Domain> = domains
+            .iter()
+            .filter(|d| d.ssl_enabled && d.tls_challenge == TlsChallenge::Dns)
+            .filter(|d| !certificates.iter().any(|c| c.host == d.domain))
+            .collect();
+        if dns_domains.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let teams: HashMap<String, String> = self
+            .stack_repo
+            .list_all()
+            .await?
+            .into_iter()
+            .map(|s| (s.id, s.team_id))
+            .collect();
+        let mut tokens: HashMap<String, Option<String>> = HashMap::new();
+        let mut challenges: Vec<DnsChallenge> = Vec::new();
+        for d in dns_domains {
+            let Some(team_id) = teams.get(&d.stack_id) else {
+                continue;
+            };
+            if !tokens.contains_key(team_id) {
+                let token = self.dns_usecase.cloudflare_api_token(team_id).await?;
+                tokens.insert(team_id.clone(), token);
+            }
+            let Some(api_token) = tokens[team_id].clone() else {
+                tracing::warn!(
+                    "No Cloudflare config for {}; falling back to HTTP-01",
+                    d.domain
+                );
+                continue;
+            };
+
+            let mut subjects = vec![d.domain.clone()];
+            if d.middleware().www_redirect && !d.is_wildcard() {
+                subjects.push(www_counterpart(&d.domain));
+            }
+            match challenges.iter_mut().find(|c| c.api_token == api_token) {
+                Some(challenge) => challenge.subjects.extend(subjects),
+                None => challenges.push(DnsChallenge {
+                    subjects,
+                    api_token,
+                }),
+            }
+        }
+        Ok(challenges)
+    }
+
+    /// L4 routes as Caddy proxies, and the host ports they need published
+    async fn desired_l4(&self) -> Result<(Vec<L4Proxy>, Vec<String>)> {
+        let routes = self.domain_repo.list_all_l4_routes().await?;
+        if routes.is_empty() {
+            return Ok((Vec::new(), Vec::new()));
+        }
+        let containers = self.runtime.list_containers(true).await.unwrap_or_default();
+        let proxies: Vec<L4Proxy> = routes
+            .iter()
+            .map(|r| L4Proxy {
+                protocol: r.protocol.as_str().to_string(),
+                listen_port: r.listen_port as u16,
+                sni: r.sni.clone(),
+                upstream: format!(
+                    "{}:{}",
+                    upstream_host(
+                        &r.container_name,
+                        &stack_containers(&r.stack_id, &containers)
+                    ),
+                    r.container_port
+                ),
+            })
+            .collect();
+        Ok((proxies, published_ports(&routes)))
+    }
+
+    /// Start Caddy, publishing the ports of the stored L4 routes
+    pub async fn bootstrap(&self) -> Result<()> {
+        let routes = self.domain_repo.list_all_l4_routes().await?;
+        self.publish(published_ports(&routes)).await
+    }
+
+    async fn publish(&self, ports: Vec<String>) -> Result<()> {
+        let mut published = self.published_ports.lock().await;
+        // Nothing to release when Caddy was never started with L4 ports, e.g. when
+        // it is not a container Labuh manages
+        if published.as_ref() == Some(&ports) || (published.is_none() && ports.is_empty()) {
+            return Ok(());
+        }
+        self.caddy_client
+            .bootstrap(&self.runtime, &self.caddy_image, &ports)
+            .await?;
+        *published = Some(ports);
+        Ok(())
+    }
+
+    /// Render the desired config and load it if Caddy differs. Returns whether
+    /// anything was applied.
+    pub async fn reconcile(&self) -> Result<bool> {
+        let _guard = self.lock.lock().await;
+        let domains = self.caddy_domains().await?;
+        let certificates = self.custom_certificates(&domains);
+        let http_app = render_http_app(&self.desired_sites(&domains, &certificates).await?);
+        let challenges = self.dns_challenges(&domains, &certificates).await?;
+
+        let (proxies, ports) = self.desired_l4().await?;
+        let layer4_app = if proxies.is_empty() {
+            // Release the ports of removed routes
+            self.publish(ports).await?;
+            Value::Null
+        } else if self.layer4_supported().await {
+            self.publish(ports).await?;
+            render_layer4_app(&proxies)
+        } else {
+            tracing::warn!(
+                "{} L4 route(s) not applied; CADDY_IMAGE needs the mholt/caddy-l4 module",
+                proxies.len()
+            );
+            Value::Null
+        };
+
+        let applied = match self
+            .caddy_client
+            .apply_apps(&apps(&http_app, &challenges, &certificates, &layer4_app))
+            .await
+        {
+            // Stock Caddy images lack the Cloudflare DNS module and refuse the whole
+            // config; keep routing up and let those hosts use HTTP-01 meanwhile
+            Err(e) if !challenges.is_empty() => {
+                tracing::warn!(
+                    "Caddy rejected DNS-01 config ({}); CADDY_IMAGE needs the caddy-dns/cloudflare module",
+                    e
+                );
+                self.caddy_client
+                    .apply_apps(&apps(&http_app, &[], &certificates, &layer4_app))
+                    .await?
+            }
+            result => result?,
+        };
+        let applied = self.caddy_client.apply_logs(&render_logs()).await? || applied;
+        if applied {
+            tracing::info!("Caddy config reconciled");
+        }
+        Ok(applied)
+    }
+
+    /// Serve the maintenance page for a stack's domains while it is redeployed or
+    /// rolled back, whatever its maintenance mode. Failing to apply is only logged,
+    /// since the deploy itself should go ahead.
+    pub async fn set_deploying(&self, stack_id: &str, deploying: bool) {
+        let changed = {
+            let mut stacks = self.deploying.lock().unwrap();
+            if deploying {
+                stacks.insert(stack_id.to_string())
+            } else {
+                stacks.remove(stack_id)
+            }
+        };
+        if changed && let Err(e) = self.reconcile().await {
+            tracing::warn!("Could not switch maintenance page for {}: {}", stack_id, e);
+        }
+    }
+
+    /// Whether L4 routes can be served; stock Caddy images lack the `layer4` app
+    pub async fn layer4_supported(&self) -> bool {
+        let probe = self
+            .layer4
+            .get_or_try_init(|| self.caddy_client.supports_layer4())
+            .await;
+        match probe {
+            Ok(supported) => *supported,
+            Err(e) => {
+                tracing::debug!("Could not probe Caddy modules: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Refuse a host port that Caddy's own listeners or another container already
+    /// publishes. Ports of existing L4 routes are Caddy's and are checked against
+    /// the routes themselves.
+    ///
+    /// Labuh runs in its own network namespace, so a port held by a plain process
+    /// on the host is not visible here; Caddy then fails to bind it and the route
+    /// shows up in its logs instead.
+    pub async fn check_host_port(&self, protocol: L4Protocol, port: u16) -> Result<()> {
+        let taken = |by: &str| {
+            AppError::Conflict(format!(
+                "Port {}/{} is already used by {}",
+                port,
+                protocol.as_str(),
+                by
+            ))
+        };
+        if CADDY_PORTS.contains(&(protocol, port)) {
+            return Err(taken("Caddy"));
+        }
+
+        let containers = self.runtime.list_containers(true).await?;
+        for container in &containers {
+            if container.names.iter().any(|n| n.contains("labuh-caddy")) {
+                continue;
+            }
+            let publishes = container.ports.iter().flatten().any(|p| {
+                p.public_port == Some(port) && p.port_type.eq_ignore_ascii_case(protocol.as_str())
+            });
+            if publishes {
+                let name = container.names.first().map(String::as_str).unwrap_or("");
+                return Err(taken(&format!(
+                    "container {}",
+                    name.trim_start_matches('/')
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Refuse a route target that is not a container or Swarm service of `stack`,
+    /// so a route cannot expose another team's services or Labuh's own containers.
+    pub async fn check_stack_target(&self, stack: &Stack, name: &str) -> Result<()> {
+        let containers = self.runtime.list_containers(true).await?;
+        if stack_targets(stack, &containers).contains(name) {
+            Ok(())
+        } else {
+            Err(AppError::Validation(format!(
+                "{} is not a container or service of stack {}",
+                name, stack.name
+            )))
+        }
+    }
+
+    /// Whether the branding badge can be injected. Stock Caddy images lack the
+    /// `replace-response` module; a failed probe is retried on the next run.
+    pub async fn branding_supported(&self) -> bool {
+        let probe = self
+            .branding
+            .get_or_try_init(|| async {
+                let supported = self.caddy_client.supports_replace_response().await?;
+                if !supported {
+                    tracing::info!(
+                        "Caddy lacks the replace-response module; the branding badge is not injected"
+                    );
+                }
+                Ok::<_, crate::error::AppError>(supported)
+            })
+            .await;
+        match probe {
+            Ok(supported) => *supported,
+            Err(e) => {
+                tracing::debug!("Could not probe Caddy modules: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Certificate Caddy currently serves for `server_name`
+    pub async fn certificate(&self, server_name: &str) -> Result<PeerCertificate> {
+        self.caddy_client.certificate(server_name).await
+    }
+
+    /// Latest obtain/renewal error per hostname from the Caddy container's logs
+    pub async fn certificate_errors(&self) -> HashMap<String, String> {
+        match self.runtime.get_logs("labuh-caddy", 5000).await {
+            Ok(lines) => certificate_errors(&lines),
+            Err(e) => {
+                tracing::debug!("Could not read Caddy logs: {}", e);
+                HashMap::new()
+            }
+        }
+    }
+
+    /// Periodic reconcile that repairs drift and repopulates Caddy after a restart
+    pub async fn start(self: Arc<Self>, period: Duration) {
+        tracing::info!("Starting Caddy reconciler (every {:?})...", period);
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.reconcile().await {
+                tracing::warn!("Caddy reconcile failed: {}", e);
+            }
+        }
+    }
+}
+
+fn apps(
+    http_app: &Value,
+    challenges: &[DnsChallenge],
+    certificates: &[PemCertificate],
+    layer4_app: &Value,
+) -> Map<String, Value> {
+    let mut apps = Map::new();
+    apps.insert("http".to_string(), http_app.clone());
+    apps.insert("tls".to_string(), render_tls_app(challenges, certificates));
+    apps.insert("layer4".to_string(), layer4_app.clone());
+    apps
+}
+
+/// `tcp/5432`-style ports to publish on the Caddy container, sorted and unique
+fn published_ports(routes: &[L4Route]) -> Vec<String> {
+    let mut ports: Vec<String> = routes
+        .iter()
+        .map(|r| format!("{}/{}", r.protocol.as_str(), r.listen_port))
+        .collect();
+    ports.sort();
+    ports.dedup();
+    ports
+}
+
+fn dial_addresses(targets: &[(String, i32)], containers: &[ContainerInfo]) -> Vec<String> {
+    let mut upstreams: Vec<String> = Vec::new();
+    for (name, port) in targets {
+        let dial = format!("{}:{}", upstream_host(name, containers), port);
+        // Replicas of one Swarm service collapse into its VIP
+        if !upstreams.contains(&dial) {
+            upstreams.push(dial);
+        }
+    }
+    upstreams
+}
+
+fn health_check(path: &Option<String>, interval_seconds: i64) -> Option<HealthCheck> {
+    path.as_ref().map(|path| HealthCheck {
+        path: path.clone(),
+        interval_seconds,
+    })
+}
+
+/// `www.example.com` for `example.com` and the other way round
+fn www_counterpart(host: &str) -> String {
+    match host.strip_prefix("www.") {
+        Some(apex) => apex.to_string(),
+        None => format!("www.{}", host),
+    }
+}
+
+/// Names a stack's services are reachable under on `labuh-network`: container
+/// names, and the Swarm service names of replicated services. Services declared
+/// in the compose file count even while they are not running.
+fn stack_targets(stack: &Stack, containers: &[ContainerInfo]) -> HashSet<String> {
+    let mut targets = HashSet::new();
+    if let Some(parsed) = stack
+        .compose_content
+        .as_deref()
+        .and_then(|content| parse_compose(content).ok())
+    {
+        for service in &parsed.services {
+            targets.insert(format!("{}-{}", stack.name, service.name));
+            targets.insert(format!("{}_{}", stack.name, service.name));
+        }
+    }
+    for container in stack_containers(&stack.id, containers) {
+        targets.extend(
+            container
+                .names
+                .iter()
+                .map(|n| n.trim_start_matches('/').to_string()),
+        );
+        targets.extend(
+            container
+                .labels
+                .get("com.docker.swarm.service.name")
+                .cloned(),
+        );
+    }
+    targets
+}
+
+fn stack_containers(stack_id: &str, containers: &[ContainerInfo]) -> Vec<ContainerInfo> {
+    containers
+        .iter()
+        .filter(|c| c.labels.get("labuh.stack.id").map(String::as_str) == Some(stack_id))
+        .cloned()
+        .collect()
+}
+
+/// Containers that belong to a Swarm service are routed through the service VIP
+fn upstream_host(container_name: &str, containers: &[ContainerInfo]) -> String {
+    containers
+        .iter()
+        .find(|c| {
+            c.names.iter().any(|n| n.contains(container_name)) || c.id.starts_with(container_name)
+        })
+        .and_then(|c| c.labels.get("com.docker.swarm.service.name"))
+        .cloned()
+        .unwrap_or_else(|| container_name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::runtime::tests::FakeRuntime;
+    use crate::infrastructure::crypto::envelope::EnvelopeCipher;
+    use crate::infrastructure::sqlite::dns::SqliteDnsConfigRepository;
+    use crate::infrastructure::sqlite::domain::SqliteDomainRepository;
+    use crate::infrastructure::sqlite::stack::SqliteStackRepository;
+    use crate::usecase::auth::tests::memory_pool;
+    use axum::http::StatusCode;
+    use axum::{Json, Router, extract::State, routing::get, routing::post};
+    use sqlx::SqlitePool;
+
+    fn container(name: &str, labels: &[(&str, &str)]) -> ContainerInfo {
+        ContainerInfo {
+            id: format!("{}-id", name),
+            names: vec![format!("/{}", name)],
+            image: "postgres:16".to_string(),
+            state: "running".to_string(),
+            status: String::new(),
+            labels: labels
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            networks: HashMap::new(),
+            ports: None,
+            created: 0,
+        }
+    }
+
+    #[test]
+    fn test_stack_targets_only_cover_own_services() {
+        let stack = Stack {
+            id: "s1".to_string(),
+            name: "shop".to_string(),
+            user_id: "u1".to_string(),
+            team_id: "t1".to_string(),
+            compose_content: Some(
+                "services:\n  db:\n    image: postgres:16\n  cache:\n    image: redis:7\n"
+                    .to_string(),
+            ),
+            status: "running".to_string(),
+            webhook_token: None,
+            cron_schedule: None,
+            health_check_path: None,
+            health_check_interval: 30,
+            last_stable_images: None,
+            git_url: None,
+            git_branch: None,
+            last_commit_hash: None,
+            push_registry_id: None,
+            push_namespace: None,
+            push_retention: 0,
+            env_conflict_policy: String::new(),
+            maintenance_mode: false,
+            maintenance_page: None,
+            error_pages: "{}".to_string(),
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let containers = [
+            container("shop-db", &[("labuh.stack.id", "s1")]),
+            container(
+                "shop_cache.1.abc",
+                &[
+                    ("labuh.stack.id", "s1"),
+                    ("com.docker.swarm.service.name", "shop_cache"),
+                ],
+            ),
+            container("billing-db", &[("labuh.stack.id", "s2")]),
+            container("labuh-caddy", &[]),
+        ];
+
+        let targets = stack_targets(&stack, &containers);
+        for name in [
+            "shop-db",
+            "shop_db",
+            "shop-cache",
+            "shop_cache",
+            "shop_cache.1.abc",
+        ] {
+            assert!(targets.contains(name), "{}", name);
+        }
+        for name in ["billing-db", "labuh-caddy", "db", "shop"] {
+            assert!(!targets.contains(name), "{}", name);
+        }
+    }
+
+    /// Stacks s1 to s4 in teams t1 to t4. Teams t1 and t2 share a Cloudflare
+    /// token, t3 has its own and t4 has none.
+    async fn seed_teams(pool: &SqlitePool, dns_usecase: &DnsUsecase) {
+        sqlx::query(
+            "INSERT INTO users (id, email, password_hash) VALUES ('u1', 'u1@example.com', '')",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+        for n in 1..=4 {
+            sqlx::query(&format!(
+                "INSERT INTO teams (id, name, created_at, updated_at) VALUES ('t{n}', 'Team {n}', '', '')"
+            ))
+            .execute(pool)
+            .await
+            .unwrap();
+            sqlx::query(&format!(
+                "INSERT INTO stacks (id, name, user_id, team_id, created_at, updated_at) VALUES ('s{n}', 'stack{n}', 'u1', 't{n}', '', '')"
+            ))
+            .execute(pool)
+            .await
+            .unwrap();
+        }
+        for (team_id, token) in [("t1", "token-a"), ("t2", "token-a"), ("t3", "token-b")] {
+            dns_usecase
+                .save_config(
+                    team_id,
+                    "Cloudflare",
+                    serde_json::json!({ "api_token": token }),
+                )
+                .await
+                .unwrap();
+        }
+    }
+
+    async fn add_domain(
+        pool: &SqlitePool,
+        stack_id: &str,
+        domain: &str,
+        domain_type: &str,
+        tls: &str,
+    ) {
+        sqlx::query(
+            "INSERT INTO domains (id, stack_id, container_name, domain, type, tls_challenge, show_branding, created_at) VALUES (?, ?, 'web', ?, ?, ?, 0, '')",
+        )
+        .bind(domain)
+        .bind(stack_id)
+        .bind(domain)
+        .bind(domain_type)
+        .bind(tls)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn reconciler(pool: &SqlitePool, caddy_url: &str) -> CaddyReconciler {
+        let cipher: Arc<dyn SecretCipher> = Arc::new(EnvelopeCipher::generate());
+        let dns_usecase = Arc::new(DnsUsecase::new(
+            Arc::new(SqliteDnsConfigRepository::new(pool.clone())),
+            cipher.clone(),
+        ));
+        seed_teams(pool, &dns_usecase).await;
+        CaddyReconciler::new(
+            Arc::new(SqliteDomainRepository::new(pool.clone())),
+            Arc::new(SqliteStackRepository::new(pool.clone())),
+            dns_usecase,
+            cipher,
+            Arc::new(FakeRuntime::default()),
+            Arc::new(CaddyClient::new(caddy_url.to_string())),
+            "caddy:2-alpine".to_string(),
+            None,
+        )
+    }
+
+    /// Stand-in for the admin API of a stock Caddy build, which refuses any
+    /// config asking for the Cloudflare DNS module
+    async fn start_stock_caddy() -> (Arc<std::sync::Mutex<Value>>, String) {
+        async fn get_config(State(config): State<Arc<std::sync::Mutex<Value>>>) -> Json<Value> {
+            Json(config.lock().unwrap().clone())
+        }
+        async fn load(
+            State(config): State<Arc<std::sync::Mutex<Value>>>,
+            Json(body): Json<Value>,
+        ) -> StatusCode {
+            if body.to_string().contains("\"cloudflare\"") {
+                return StatusCode::BAD_REQUEST;
+            }
+            *config.lock().unwrap() = body;
+            StatusCode::OK
+        }
+
+        let config = Arc::new(std::sync::Mutex::new(Value::Null));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let app = Router::new()
+            .route("/config/", get(get_config))
+            .route("/load", post(load))
+            .route("/adapt", post(|| async { StatusCode::BAD_REQUEST }))
+            .with_state(config.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (config, url)
+    }
+
+    #[tokio::test]
+    async fn test_wildcards_follow_exact_hostnames() {
+        let pool = memory_pool().await;
+        let reconciler = reconciler(&pool, "http://127.0.0.1:9").await;
+        add_domain(&pool, "s1", "*.shop.example.com", "Caddy", "dns").await;
+        add_domain(&pool, "s1", "app.shop.example.com", "Caddy", "http").await;
+        add_domain(&pool, "s2", "*.billing.example.com", "Caddy", "dns").await;
+        add_domain(&pool, "s2", "billing.example.com", "Caddy", "http").await;
+        add_domain(&pool, "s3", "tunnel.example.com", "Tunnel", "http").await;
+
+        let domains: Vec<String> = reconciler
+            .caddy_domains()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|d| d.domain)
+            .collect();
+        assert_eq!(
+            domains,
+            [
+                "app.shop.example.com",
+                "billing.example.com",
+                "*.billing.example.com",
+                "*.shop.example.com",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dns_challenges_grouped_by_token() {
+        let pool = memory_pool().await;
+        let reconciler = reconciler(&pool, "http://127.0.0.1:9").await;
+        add_domain(&pool, "s1", "*.shop.example.com", "Caddy", "dns").await;
+        add_domain(&pool, "s1", "app.example.com", "Caddy", "http").await;
+        add_domain(&pool, "s2", "billing.example.com", "Caddy", "dns").await;
+        add_domain(&pool, "s3", "*.blog.example.org", "Caddy", "dns").await;
+        add_domain(&pool, "s4", "docs.example.net", "Caddy", "dns").await;
+
+        let domains = reconciler.caddy_domains().await.unwrap();
+        let challenges = reconciler.dns_challenges(&domains, &[]).await.unwrap();
+        // Teams sharing a token share a policy; a team without a Cloudflare config
+        // is left to HTTP-01
+        assert_eq!(
+            challenges,
+            [
+                DnsChallenge {
+                    subjects: vec![
+                        "billing.example.com".to_string(),
+                        "*.shop.example.com".to_string(),
+                    ],
+                    api_token: "token-a".to_string(),
+                },
+                DnsChallenge {
+                    subjects: vec!["*.blog.example.org".to_string()],
+                    api_token: "token-b".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_without_dns_module_falls_back_to_http01() {
+        let pool = memory_pool().await;
+        let (config, url) = start_stock_caddy().await;
+        let reconciler = reconciler(&pool, &url).await;
+        add_domain(&pool, "s1", "api.example.com", "Caddy", "dns").await;
+        add_domain(&pool, "s1", "app.example.com", "Caddy", "http").await;
+
+        assert!(reconciler.reconcile().await.unwrap());
+        let config = config.lock().unwrap().clone();
+        let hosts = config["apps"]["http"].to_string();
+        assert!(hosts.contains("api.example.com"));
+        assert!(hosts.contains("app.example.com"));
+        assert_eq!(
+            config["apps"]["tls"]["automation"]["policies"],
+            serde_json::json!([])
+        );
+    }
+}
@@ -1,18 +1,20 @@
 use crate::domain::dns_provider::DnsProvider;
 use crate::domain::dns_repository::DnsConfigRepository;
-use crate::domain::models::dns::{CloudflareConfig, DnsConfig};
+use crate::domain::models::dns::{CPanelConfig, CloudflareConfig, DnsConfig};
 use crate::domain::models::domain::DomainProvider;
+use crate::domain::secret_cipher::SecretCipher;
 use crate::error::{AppError, Result};
 use crate::infrastructure::dns::{CPanelProvider, CloudflareProvider};
 use std::sync::Arc;
 
 pub struct DnsUsecase {
     dns_repo: Arc<dyn DnsConfigRepository>,
+    cipher: Arc<dyn SecretCipher>,
 }
 
 impl DnsUsecase {
-    pub fn new(dns_repo: Arc<dyn DnsConfigRepository>) -> Self {
-        Self { dns_repo }
+    pub fn new(dns_repo: Arc<dyn DnsConfigRepository>, cipher: Arc<dyn SecretCipher>) -> Self {
+        Self { dns_repo, cipher }
     }
 
     pub async fn get_provider(
@@ -25,30 +27,69 @@ impl DnsUsecase {
                 "Custom provider does not support automated DNS".to_string(),
             )),
             DomainProvider::Cloudflare => {
-                let config_record = self
-                    .dns_repo
-                    .find_by_team_and_provider(team_id, "Cloudflare")
-                    .await?
-                    .ok_or_else(|| {
-                        AppError::NotFound(
-                            "Cloudflare configuration not found for team".to_string(),
-                        )
-                    })?;
-
-                let config: CloudflareConfig = serde_json::from_str(&config_record.config)
-                    .map_err(|e| AppError::Internal(format!("Invalid Cloudflare config: {}", e)))?;
+                let config = self.cloudflare_config(team_id).await?.ok_or_else(|| {
+                    AppError::NotFound("Cloudflare configuration not found for team".to_string())
+                })?;
 
                 Ok(Box::new(CloudflareProvider::new(
                     config.api_token,
                     config.account_id,
                 )))
             }
-            DomainProvider::CPanel => Ok(Box::new(CPanelProvider::new())),
+            DomainProvider::CPanel => {
+                let config = self.cpanel_config(team_id).await?.ok_or_else(|| {
+                    AppError::NotFound("cPanel configuration not found for team".to_string())
+                })?;
+
+                Ok(Box::new(CPanelProvider::new(config)))
+            }
         }
     }
 
+    async fn cloudflare_config(&self, team_id: &str) -> Result<Option<CloudflareConfig>> {
+        let Some(config_record) = self
+            .dns_repo
+            .find_by_team_and_provider(team_id, "Cloudflare")
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let config_json = self.cipher.decrypt(&config_record.config)?;
+        let config = serde_json::from_str(&config_json)
+            .map_err(|e| AppError::Internal(format!("Invalid Cloudflare config: {}", e)))?;
+        Ok(Some(config))
+    }
+
+    async fn cpanel_config(&self, team_id: &str) -> Result<Option<CPanelConfig>> {
+        let Some(config_record) = self
+            .dns_repo
+            .find_by_team_and_provider(team_id, "CPanel")
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let config_json = self.cipher.decrypt(&config_record.config)?;
+        let config = serde_json::from_str(&config_json)
+            .map_err(|e| AppError::Internal(format!("Invalid cPanel config: {}", e)))?;
+        Ok(Some(config))
+    }
+
+    /// The team's Cloudflare API token, used by Caddy for DNS-01 challenges
+    pub async fn cloudflare_api_token(&self, team_id: &str) -> Result<Option<String>> {
+        Ok(self
+            .cloudflare_config(team_id)
+            .await?
+            .map(|config| config.api_token))
+    }
+
     pub async fn list_configs(&self, team_id: &str) -> Result<Vec<DnsConfig>> {
-        self.dns_repo.find_by_team_id(team_id).await
+        let mut configs = self.dns_repo.find_by_team_id(team_id).await?;
+        for config in &mut configs {
+            config.config = self.cipher.decrypt(&config.config)?;
+        }
+        Ok(configs)
     }
 
     pub async fn save_config(
@@ -66,12 +107,14 @@ impl DnsUsecase {
             id,
             team_id: team_id.to_string(),
             provider: provider.to_string(),
-            config: config_str,
+            config: self.cipher.encrypt(&config_str)?,
             created_at: now.clone(),
             updated_at: now,
         };
 
-        self.dns_repo.save(dns_config).await
+        let mut saved = self.dns_repo.save(dns_config).await?;
+        saved.config = config_str;
+        Ok(saved)
     }
 
     pub async fn list_available_domains(
@@ -1,22 +1,35 @@
 use crate::domain::domain_repository::DomainRepository;
-use crate::domain::models::domain::{Domain, DomainProvider, DomainType};
+use crate::domain::models::domain::{
+    CertificateState, CertificateStatus, CreateDomainRoute, CreateL4Route, Domain, DomainProvider,
+    DomainResponse, DomainRoute, DomainType, L4Protocol, L4Route, TlsChallenge, UpdateDomainTls,
+    UpstreamSettings, normalize_path_prefix, parse_target, validate_hostname,
+};
+use crate::domain::models::domain_middleware::UpdateDomainMiddleware;
+use crate::domain::secret_cipher::SecretCipher;
 use crate::domain::stack_repository::StackRepository;
 use crate::error::{AppError, Result};
-use crate::infrastructure::caddy::client::CaddyClient;
+use crate::infrastructure::auth::password::PasswordService;
+use crate::infrastructure::caddy::certificates::{parse_pem_certificate, parse_pem_pair};
 use crate::infrastructure::tunnel::manager::TunnelManager;
+use crate::usecase::audit::AuditUsecase;
+use crate::usecase::caddy_reconciler::CaddyReconciler;
 use crate::usecase::dns::DnsUsecase;
 use chrono::Utc;
 use hickory_resolver::TokioResolver;
+use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
 pub struct DomainUsecase {
     domain_repo: Arc<dyn DomainRepository>,
     stack_repo: Arc<dyn StackRepository>,
-    caddy_client: Arc<CaddyClient>,
+    reconciler: Arc<CaddyReconciler>,
     dns_usecase: Arc<DnsUsecase>,
-    runtime: Arc<dyn crate::domain::runtime::RuntimePort>,
+    cipher: Arc<dyn SecretCipher>,
     tunnel_manager: Option<Arc<TunnelManager>>,
+    /// Days before expiry at which certificates are reported as expiring
+    certificate_warning_days: i64,
+    audit: Arc<AuditUsecase>,
 }
 
 pub struct AddDomainRequest {
@@ -31,24 +44,64 @@ pub struct AddDomainRequest {
     pub dns_record_type: Option<String>,
     pub dns_record_content: Option<String>,
     pub proxied: bool,
+    pub tls_challenge: Option<TlsChallenge>,
+    pub upstream: UpstreamSettings,
 }
 
 impl DomainUsecase {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         domain_repo: Arc<dyn DomainRepository>,
         stack_repo: Arc<dyn StackRepository>,
-        caddy_client: Arc<CaddyClient>,
+        reconciler: Arc<CaddyReconciler>,
         dns_usecase: Arc<DnsUsecase>,
-        runtime: Arc<dyn crate::domain::runtime::RuntimePort>,
+        cipher: Arc<dyn SecretCipher>,
         tunnel_manager: Option<Arc<TunnelManager>>,
+        certificate_warning_days: i64,
+        audit: Arc<AuditUsecase>,
     ) -> Self {
         Self {
             domain_repo,
             stack_repo,
-            caddy_client,
+            reconciler,
             dns_usecase,
-            runtime,
+            cipher,
             tunnel_manager,
+            certificate_warning_days,
+            audit,
+        }
+    }
+
+    /// Audit a domain change on behalf of the stack's team. The change is already
+    /// applied by then, so a failed write is logged instead of failing the request.
+    async fn record_activity(
+        &self,
+        user_id: &str,
+        stack_id: &str,
+        action: &str,
+        resource_type: &str,
+        resource_id: &str,
+        details: serde_json::Value,
+    ) {
+        let team_id = self
+            .stack_repo
+            .find_by_id_internal(stack_id)
+            .await
+            .ok()
+            .map(|stack| stack.team_id);
+        if let Err(e) = self
+            .audit
+            .record(
+                Some(user_id),
+                team_id.as_deref(),
+                action,
+                resource_type,
+                resource_id,
+                details,
+            )
+            .await
+        {
+            tracing::warn!("Failed to audit {} on {}: {}", action, resource_id, e);
         }
     }
 
@@ -60,7 +113,35 @@ impl DomainUsecase {
         self.domain_repo.find_by_stack_id(stack_id).await
     }
 
-    pub async fn add_domain(&self, request: AddDomainRequest) -> Result<Domain> {
+    pub async fn add_domain(&self, request: AddDomainRequest, user_id: &str) -> Result<Domain> {
+        validate_hostname(&request.domain).map_err(AppError::Validation)?;
+        let is_wildcard = request.domain.starts_with("*.");
+        let tls_challenge = request.tls_challenge.unwrap_or(if is_wildcard {
+            TlsChallenge::Dns
+        } else {
+            TlsChallenge::Http
+        });
+        self.check_tls_challenge(
+            &request.stack_id,
+            is_wildcard,
+            &request.domain_type,
+            tls_challenge,
+        )
+        .await?;
+
+        let extra_upstreams = request.upstream.validate().map_err(AppError::Validation)?;
+        if matches!(request.domain_type, DomainType::Tunnel) && !request.upstream.is_default() {
+            return Err(AppError::Validation(
+                "Load balancing and health checks are only available for Caddy domains".to_string(),
+            ));
+        }
+        self.check_targets(
+            &request.stack_id,
+            Some(&request.container_name),
+            &extra_upstreams,
+        )
+        .await?;
+
         // Check if domain already exists
         if self
             .domain_repo
@@ -190,6 +271,15 @@ impl DomainUsecase {
             dns_record_id: dns_record_id.clone(),
             proxied: request.proxied,
             show_branding: true,
+            extra_upstreams,
+            lb_policy: request.upstream.lb_policy,
+            health_check_path: request.upstream.health_check_path(),
+            health_check_interval: request.upstream.interval(),
+            middleware: "{}".to_string(),
+            basic_auth: String::new(),
+            tls_challenge,
+            custom_certificate: None,
+            custom_private_key: None,
             created_at: now,
         };
 
@@ -200,27 +290,31 @@ impl DomainUsecase {
             return Err(e);
         }
 
-        // Add route to Caddy if it's a Caddy type domain
-        if matches!(request.domain_type, DomainType::Caddy) {
-            let upstream_name = self.resolve_upstream(&request.container_name).await;
-            let container_upstream = format!("{}:{}", upstream_name, request.container_port);
-            if let Err(e) = self
-                .caddy_client
-                .add_route(
-                    &request.domain,
-                    &container_upstream,
-                    domain_record.show_branding,
-                )
-                .await
-            {
-                // Rollback DNS and DB
-                self.rollback_dns(&request.stack_id, &domain_record, dns_record_id.as_deref())
-                    .await;
-                let _ = self.domain_repo.delete(&id).await;
-                return Err(e);
-            }
+        // Route it through Caddy; a failed load leaves Caddy's previous config in place
+        if matches!(request.domain_type, DomainType::Caddy)
+            && let Err(e) = self.reconciler.reconcile().await
+        {
+            // Rollback DNS and DB
+            self.rollback_dns(&request.stack_id, &domain_record, dns_record_id.as_deref())
+                .await;
+            let _ = self.domain_repo.delete(&id).await;
+            return Err(e);
         }
 
+        self.record_activity(
+            user_id,
+            &request.stack_id,
+            "domain.add",
+            "domain",
+            &domain_record.domain,
+            serde_json::json!({
+                "stack_id": request.stack_id,
+                "container_name": domain_record.container_name,
+                "container_port": domain_record.container_port,
+                "tls_challenge": domain_record.tls_challenge,
+            }),
+        )
+        .await;
         Ok(domain_record)
     }
 
@@ -243,7 +337,7 @@ impl DomainUsecase {
         }
     }
 
-    pub async fn remove_domain(&self, stack_id: &str, domain: &str) -> Result<()> {
+    pub async fn remove_domain(&self, stack_id: &str, domain: &str, user_id: &str) -> Result<()> {
         let domain_record = self
             .domain_repo
             .find_by_domain(domain)
@@ -279,13 +373,28 @@ impl DomainUsecase {
             }
         }
 
-        // Remove from Caddy
-        if matches!(domain_record.r#type, DomainType::Caddy) {
-            let _ = self.caddy_client.remove_route(&domain_record.domain).await;
+        // Delete from database, then drop the route from Caddy
+        self.domain_repo.delete(&domain_record.id).await?;
+        if matches!(domain_record.r#type, DomainType::Caddy)
+            && let Err(e) = self.reconciler.reconcile().await
+        {
+            tracing::warn!(
+                "Route for {} stays in Caddy until the next reconcile: {}",
+                domain_record.domain,
+                e
+            );
         }
 
-        // Delete from database
-        self.domain_repo.delete(&domain_record.id).await
+        self.record_activity(
+            user_id,
+            stack_id,
+            "domain.remove",
+            "domain",
+            domain,
+            serde_json::json!({ "stack_id": stack_id }),
+        )
+        .await;
+        Ok(())
     }
 
     pub async fn verify_domain(&self, domain: &str) -> Result<DnsVerificationResult> {
@@ -333,34 +442,12 @@ impl DomainUsecase {
         })
     }
 
-    pub async fn sync_all_routes(&self) -> Result<()> {
-        let domains = self.domain_repo.list_all().await?;
-        for domain in domains {
-            if matches!(domain.r#type, DomainType::Caddy) {
-                let upstream_name = self.resolve_upstream(&domain.container_name).await;
-                let container_upstream = format!("{}:{}", upstream_name, domain.container_port);
-                let _ = self
-                    .caddy_client
-                    .add_route(&domain.domain, &container_upstream, domain.show_branding)
-                    .await;
-            }
-        }
-        Ok(())
-    }
-
     pub async fn sync_infrastructure(&self) -> Result<()> {
+        // 1. Sync Caddy
+        self.reconciler.reconcile().await?;
+
         let domains = self.domain_repo.list_all().await?;
         for domain in domains {
-            // 1. Sync Caddy
-            if matches!(domain.r#type, DomainType::Caddy) {
-                let upstream_name = self.resolve_upstream(&domain.container_name).await;
-                let container_upstream = format!("{}:{}", upstream_name, domain.container_port);
-                let _ = self
-                    .caddy_client
-                    .add_route(&domain.domain, &container_upstream, domain.show_branding)
-                    .await;
-            }
-
             // 2. Sync DNS (Create if missing)
             if !matches!(domain.provider, DomainProvider::Custom)
                 && let Ok(stack) = self.stack_repo.find_by_id_internal(&domain.stack_id).await
@@ -456,34 +543,14 @@ impl DomainUsecase {
         Ok(())
     }
 
-    async fn resolve_upstream(&self, container_name: &str) -> String {
-        let mut upstream_name = container_name.to_string();
-
-        // Try to find if this container is part of a swarm service
-        if let Ok(containers) = self.runtime.list_containers(true).await
-            && let Some(container) = containers.iter().find(|c| {
-                c.names.iter().any(|n| n.contains(container_name))
-                    || c.id.starts_with(container_name)
-            })
-            && let Some(service_name) = container.labels.get("com.docker.swarm.service.name")
-        {
-            tracing::debug!(
-                "Detected Swarm service '{}' for container '{}'. Using service VIP for routing.",
-                service_name,
-                container_name
-            );
-            upstream_name = service_name.clone();
-        }
-
-        upstream_name
-    }
-
+    /// Turn the "Deployed with Labuh" badge on or off. Returns whether the badge is
+    /// served, which needs a Caddy domain and a Caddy with `replace-response`.
     pub async fn toggle_branding(
         &self,
         stack_id: &str,
         domain: &str,
         show_branding: bool,
-    ) -> Result<()> {
+    ) -> Result<bool> {
         // Find domain and verify ownership
         let domain_record = self
             .domain_repo
@@ -502,17 +569,633 @@ impl DomainUsecase {
             .update_branding(domain, show_branding)
             .await?;
 
-        // Re-sync Caddy route with new branding setting
-        if matches!(domain_record.r#type, DomainType::Caddy) {
-            let upstream_name = self.resolve_upstream(&domain_record.container_name).await;
-            let container_upstream = format!("{}:{}", upstream_name, domain_record.container_port);
-            self.caddy_client
-                .add_route(domain, &container_upstream, show_branding)
-                .await?;
+        // Re-render the route with the new branding setting
+        if !matches!(domain_record.r#type, DomainType::Caddy) {
+            return Ok(false);
+        }
+        self.reconciler.reconcile().await?;
+
+        // Whether the badge is actually being served
+        Ok(show_branding && self.reconciler.branding_supported().await)
+    }
+
+    /// Every host a domain or route proxies to must belong to the stack: the
+    /// primary container and each `name:port` extra upstream
+    async fn check_targets(
+        &self,
+        stack_id: &str,
+        container_name: Option<&str>,
+        extra_upstreams: &str,
+    ) -> Result<()> {
+        let stack = self.stack_repo.find_by_id_internal(stack_id).await?;
+        let extras = extra_upstreams
+            .split_whitespace()
+            .map(|upstream| upstream.rsplit_once(':').map_or(upstream, |(name, _)| name));
+        for name in container_name.into_iter().chain(extras) {
+            self.reconciler.check_stack_target(&stack, name).await?;
+        }
+        Ok(())
+    }
+
+    /// A domain of the stack, or an error when it belongs to another stack
+    async fn owned_domain(&self, stack_id: &str, domain: &str) -> Result<Domain> {
+        let domain_record = self
+            .domain_repo
+            .find_by_domain(domain)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Domain not found".to_string()))?;
+
+        if domain_record.stack_id != stack_id {
+            return Err(AppError::Forbidden(
+                "You do not have permission to modify this domain".to_string(),
+            ));
+        }
+        Ok(domain_record)
+    }
+
+    /// A Caddy domain of the stack; path routes and load balancing go through Caddy
+    async fn owned_caddy_domain(&self, stack_id: &str, domain: &str) -> Result<Domain> {
+        let domain_record = self.owned_domain(stack_id, domain).await?;
+        if !matches!(domain_record.r#type, DomainType::Caddy) {
+            return Err(AppError::Validation(
+                "Path routes and load balancing are only available for Caddy domains".to_string(),
+            ));
+        }
+        Ok(domain_record)
+    }
+
+    /// Wildcards can only be validated over DNS, and DNS-01 goes through the
+    /// team's Cloudflare config; tunnel certificates are Cloudflare's business
+    async fn check_tls_challenge(
+        &self,
+        stack_id: &str,
+        is_wildcard: bool,
+        domain_type: &DomainType,
+        challenge: TlsChallenge,
+    ) -> Result<()> {
+        if challenge == TlsChallenge::Http {
+            if is_wildcard {
+                return Err(AppError::Validation(
+                    "Wildcard domains need the DNS-01 challenge".to_string(),
+                ));
+            }
+            return Ok(());
+        }
+        if !matches!(domain_type, DomainType::Caddy) {
+            return Err(AppError::Validation(
+                "DNS-01 challenges are only available for Caddy domains".to_string(),
+            ));
+        }
+        let stack = self.stack_repo.find_by_id_internal(stack_id).await?;
+        if self
+            .dns_usecase
+            .cloudflare_api_token(&stack.team_id)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::Validation(
+                "DNS-01 challenges need a Cloudflare DNS configuration for the team".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub async fn update_tls(
+        &self,
+        stack_id: &str,
+        domain: &str,
+        request: UpdateDomainTls,
+        user_id: &str,
+    ) -> Result<Domain> {
+        let mut domain_record = self.owned_domain(stack_id, domain).await?;
+        if !matches!(domain_record.r#type, DomainType::Caddy) {
+            return Err(AppError::Validation(
+                "TLS settings are only available for Caddy domains".to_string(),
+            ));
+        }
+        let previous = domain_record.clone();
+
+        if let Some(ssl_enabled) = request.ssl_enabled {
+            domain_record.ssl_enabled = ssl_enabled;
+        }
+        if let Some(challenge) = request.tls_challenge {
+            domain_record.tls_challenge = challenge;
+        }
+        if request.remove_certificate {
+            domain_record.custom_certificate = None;
+            domain_record.custom_private_key = None;
+        }
+        match (&request.certificate, &request.private_key) {
+            (Some(certificate), Some(private_key)) => {
+                let cert = parse_pem_pair(certificate, private_key)?;
+                if !cert.covers(&domain_record.domain) {
+                    return Err(AppError::Validation(format!(
+                        "Certificate does not cover {} (SANs: {})",
+                        domain_record.domain,
+                        cert.sans.join(", ")
+                    )));
+                }
+                if cert.not_after < Utc::now() {
+                    return Err(AppError::Validation(
+                        "Certificate has already expired".to_string(),
+                    ));
+                }
+                domain_record.custom_certificate = Some(self.cipher.encrypt(certificate)?);
+                domain_record.custom_private_key = Some(self.cipher.encrypt(private_key)?);
+            }
+            (None, None) => {}
+            _ => {
+                return Err(AppError::Validation(
+                    "Upload the certificate and its private key together".to_string(),
+                ));
+            }
+        }
+
+        // The ACME challenge only matters when Caddy obtains the certificate itself
+        if domain_record.ssl_enabled && domain_record.custom_certificate.is_none() {
+            self.check_tls_challenge(
+                stack_id,
+                domain_record.is_wildcard(),
+                &domain_record.r#type,
+                domain_record.tls_challenge,
+            )
+            .await?;
+        }
+
+        self.domain_repo.update_tls(&domain_record).await?;
+        if let Err(e) = self.reconciler.reconcile().await {
+            let _ = self.domain_repo.update_tls(&previous).await;
+            return Err(e);
+        }
+
+        self.record_activity(
+            user_id,
+            stack_id,
+            "domain.tls.update",
+            "domain",
+            domain,
+            serde_json::json!({
+                "stack_id": stack_id,
+                "ssl_enabled": domain_record.ssl_enabled,
+                "tls_challenge": domain_record.tls_challenge,
+                "custom_certificate": domain_record.custom_certificate.is_some(),
+            }),
+        )
+        .await;
+        Ok(domain_record)
+    }
+
+    pub async fn certificate_status(
+        &self,
+        stack_id: &str,
+        domain: &str,
+    ) -> Result<CertificateStatus> {
+        let domain_record = self.owned_domain(stack_id, domain).await?;
+        let errors = self.reconciler.certificate_errors().await;
+        Ok(self.status_of(&domain_record, &errors).await)
+    }
+
+    /// Certificate status of every domain of the team
+    pub async fn team_certificate_statuses(&self, team_id: &str) -> Result<Vec<CertificateStatus>> {
+        let domains = self.domain_repo.find_by_team_id(team_id).await?;
+        let errors = self.reconciler.certificate_errors().await;
+        Ok(futures::future::join_all(domains.iter().map(|d| self.status_of(d, &errors))).await)
+    }
+
+    /// Uploaded certificates are read from the database; ACME ones from what Caddy
+    /// serves. Wildcards are probed with a name under the wildcard, since `*` is not
+    /// a valid SNI value.
+    async fn status_of(
+        &self,
+        domain_record: &Domain,
+        errors: &HashMap<String, String>,
+    ) -> CertificateStatus {
+        let host = domain_record.domain.as_str();
+        if !matches!(domain_record.r#type, DomainType::Caddy) {
+            return CertificateStatus::without_certificate(host, CertificateState::External);
+        }
+        if !domain_record.ssl_enabled {
+            return CertificateStatus::without_certificate(host, CertificateState::Disabled);
         }
 
+        let custom = domain_record.custom_certificate.is_some();
+        let cert = match &domain_record.custom_certificate {
+            Some(encrypted) => match self.cipher.decrypt(encrypted) {
+                Ok(pem) => parse_pem_certificate(&pem),
+                Err(e) => Err(e),
+            },
+            None => {
+                let server_name = match host.strip_prefix("*.") {
+                    Some(zone) => format!("labuh-probe.{}", zone),
+                    None => host.to_string(),
+                };
+                self.reconciler.certificate(&server_name).await
+            }
+        };
+
+        let mut status = CertificateStatus::without_certificate(host, CertificateState::Missing);
+        status.custom = custom;
+        // Uploaded certificates are not renewed by Caddy
+        status.last_error = if custom {
+            None
+        } else {
+            errors.get(host).cloned()
+        };
+        match cert {
+            Ok(cert) => {
+                let days_remaining = (cert.not_after - Utc::now()).num_days();
+                status.state = CertificateState::from_days_remaining(
+                    days_remaining,
+                    self.certificate_warning_days,
+                );
+                status.warning = match status.state {
+                    CertificateState::Expired => Some("Certificate has expired".to_string()),
+                    CertificateState::Expiring => {
+                        Some(format!("Certificate expires in {} days", days_remaining))
+                    }
+                    _ if !cert.covers(host) => Some(format!("Certificate does not cover {}", host)),
+                    _ => None,
+                };
+                status.subject = Some(cert.subject.clone());
+                status.issuer = Some(cert.issuer.clone());
+                status.not_before = Some(cert.not_before.to_rfc3339());
+                status.not_after = Some(cert.not_after.to_rfc3339());
+                status.days_remaining = Some(days_remaining);
+                status.sans = cert.sans;
+            }
+            Err(e) => status.error = Some(e.to_string()),
+        }
+        status
+    }
+
+    pub async fn update_upstreams(
+        &self,
+        stack_id: &str,
+        domain: &str,
+        settings: UpstreamSettings,
+        user_id: &str,
+    ) -> Result<Domain> {
+        let mut domain_record = self.owned_caddy_domain(stack_id, domain).await?;
+        let previous = domain_record.clone();
+
+        domain_record.extra_upstreams = settings.validate().map_err(AppError::Validation)?;
+        self.check_targets(stack_id, None, &domain_record.extra_upstreams)
+            .await?;
+        domain_record.lb_policy = settings.lb_policy;
+        domain_record.health_check_path = settings.health_check_path();
+        domain_record.health_check_interval = settings.interval();
+        self.domain_repo.update_upstreams(&domain_record).await?;
+
+        // Keep the database in line with what Caddy accepted
+        if let Err(e) = self.reconciler.reconcile().await {
+            let _ = self.domain_repo.update_upstreams(&previous).await;
+            return Err(e);
+        }
+
+        self.record_activity(
+            user_id,
+            stack_id,
+            "domain.upstreams.update",
+            "domain",
+            domain,
+            serde_json::json!({
+                "stack_id": stack_id,
+                "upstreams": domain_record.extra_upstreams,
+                "lb_policy": domain_record.lb_policy,
+            }),
+        )
+        .await;
+        Ok(domain_record)
+    }
+
+    pub async fn update_middleware(
+        &self,
+        stack_id: &str,
+        domain: &str,
+        request: UpdateDomainMiddleware,
+        user_id: &str,
+    ) -> Result<Domain> {
+        let mut domain_record = self.owned_domain(stack_id, domain).await?;
+        if !matches!(domain_record.r#type, DomainType::Caddy) {
+            return Err(AppError::Validation(
+                "HTTP middleware is only available for Caddy domains".to_string(),
+            ));
+        }
+        request
+            .middleware
+            .validate()
+            .map_err(AppError::Validation)?;
+
+        // Keep existing hashes for users sent without a password
+        let existing: HashMap<String, String> =
+            domain_record.basic_auth_accounts().into_iter().collect();
+        let mut accounts: Vec<String> = Vec::new();
+        for credential in &request.basic_auth {
+            let username = credential.username.trim();
+            if username.is_empty()
+                || username.contains(':')
+                || username.contains(char::is_whitespace)
+            {
+                return Err(AppError::Validation(format!(
+                    "Invalid basic auth username '{}'",
+                    credential.username
+                )));
+            }
+            if accounts
+                .iter()
+                .any(|a| a.starts_with(&format!("{}:", username)))
+            {
+                return Err(AppError::Validation(format!(
+                    "Duplicate basic auth username '{}'",
+                    username
+                )));
+            }
+            let hash = match credential.password.as_deref().filter(|p| !p.is_empty()) {
+                Some(password) => PasswordService::hash_bcrypt(password)?,
+                None => existing.get(username).cloned().ok_or_else(|| {
+                    AppError::Validation(format!("Password required for new user '{}'", username))
+                })?,
+            };
+            accounts.push(format!("{}:{}", username, hash));
+        }
+
+        let previous = domain_record.clone();
+        domain_record.middleware = serde_json::to_string(&request.middleware)
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        domain_record.basic_auth = accounts.join(" ");
+        self.domain_repo.update_middleware(&domain_record).await?;
+
+        if let Err(e) = self.reconciler.reconcile().await {
+            let _ = self.domain_repo.update_middleware(&previous).await;
+            return Err(e);
+        }
+
+        let updated = DomainResponse::from(domain_record.clone());
+        self.record_activity(
+            user_id,
+            stack_id,
+            "domain.middleware.update",
+            "domain",
+            domain,
+            serde_json::json!({
+                "stack_id": stack_id,
+                "middleware": updated.middleware,
+                "basic_auth_users": updated.basic_auth_users,
+            }),
+        )
+        .await;
+        Ok(domain_record)
+    }
+
+    pub async fn list_routes(&self, stack_id: &str, domain: &str) -> Result<Vec<DomainRoute>> {
+        let domain_record = self.owned_domain(stack_id, domain).await?;
+        self.domain_repo.list_routes(&domain_record.id).await
+    }
+
+    pub async fn add_route(
+        &self,
+        stack_id: &str,
+        domain: &str,
+        request: CreateDomainRoute,
+        user_id: &str,
+    ) -> Result<DomainRoute> {
+        let domain_record = self.owned_caddy_domain(stack_id, domain).await?;
+        let path = normalize_path_prefix(&request.path).map_err(AppError::Validation)?;
+        let container_port = request.container_port.unwrap_or(80);
+        parse_target(&format!("{}:{}", request.container_name, container_port))
+            .map_err(AppError::Validation)?;
+        let extra_upstreams = request.upstream.validate().map_err(AppError::Validation)?;
+        self.check_targets(stack_id, Some(&request.container_name), &extra_upstreams)
+            .await?;
+
+        let existing = self.domain_repo.list_routes(&domain_record.id).await?;
+        if existing.iter().any(|r| r.path == path) {
+            return Err(AppError::Conflict(format!(
+                "Path '{}' is already routed on {}",
+                path, domain
+            )));
+        }
+
+        let route = DomainRoute {
+            id: Uuid::new_v4().to_string(),
+            domain_id: domain_record.id,
+            path,
+            strip_prefix: request.strip_prefix.unwrap_or(false),
+            container_name: request.container_name,
+            container_port,
+            extra_upstreams,
+            lb_policy: request.upstream.lb_policy,
+            health_check_path: request.upstream.health_check_path(),
+            health_check_interval: request.upstream.interval(),
+            created_at: Utc::now().to_rfc3339(),
+        };
+        self.domain_repo.create_route(route.clone()).await?;
+
+        if let Err(e) = self.reconciler.reconcile().await {
+            let _ = self.domain_repo.delete_route(&route.id).await;
+            return Err(e);
+        }
+
+        self.record_activity(
+            user_id,
+            stack_id,
+            "domain.route.add",
+            "domain",
+            domain,
+            serde_json::json!({
+                "stack_id": stack_id,
+                "path": route.path,
+                "container_name": route.container_name,
+                "container_port": route.container_port,
+            }),
+        )
+        .await;
+        Ok(route)
+    }
+
+    pub async fn remove_route(
+        &self,
+        stack_id: &str,
+        domain: &str,
+        route_id: &str,
+        user_id: &str,
+    ) -> Result<()> {
+        let domain_record = self.owned_domain(stack_id, domain).await?;
+        let route = self
+            .domain_repo
+            .list_routes(&domain_record.id)
+            .await?
+            .into_iter()
+            .find(|r| r.id == route_id)
+            .ok_or_else(|| AppError::NotFound("Route not found".to_string()))?;
+
+        self.domain_repo.delete_route(&route.id).await?;
+        if let Err(e) = self.reconciler.reconcile().await {
+            tracing::warn!(
+                "Route {}{} stays in Caddy until the next reconcile: {}",
+                domain,
+                route.path,
+                e
+            );
+        }
+
+        self.record_activity(
+            user_id,
+            stack_id,
+            "domain.route.remove",
+            "domain",
+            domain,
+            serde_json::json!({ "stack_id": stack_id, "route_id": route_id }),
+        )
+        .await;
         Ok(())
     }
+
+    pub async fn list_l4_routes(&self, stack_id: &str) -> Result<Vec<L4Route>> {
+        self.domain_repo.list_l4_routes(stack_id).await
+    }
+
+    /// Forward a host port to a stack service as raw TCP/UDP through Caddy's
+    /// `layer4` app. The target must be one of the stack's own containers, and the
+    /// port must not be held by Caddy's own listeners, another container, or an L4
+    /// route claiming the same connections.
+    pub async fn add_l4_route(
+        &self,
+        stack_id: &str,
+        request: CreateL4Route,
+        user_id: &str,
+    ) -> Result<L4Route> {
+        let listen_port = u16::try_from(request.listen_port)
+            .ok()
+            .filter(|port| *port > 0)
+            .ok_or_else(|| {
+                AppError::Validation(format!("Invalid listen port {}", request.listen_port))
+            })?;
+        parse_target(&format!(
+            "{}:{}",
+            request.container_name, request.container_port
+        ))
+        .map_err(AppError::Validation)?;
+        self.check_targets(stack_id, Some(&request.container_name), "")
+            .await?;
+        let sni = match request.sni.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(_) if request.protocol == L4Protocol::Udp => {
+                return Err(AppError::Validation(
+                    "SNI routing needs TLS and is only available for TCP".to_string(),
+                ));
+            }
+            Some(sni) => {
+                validate_hostname(sni).map_err(AppError::Validation)?;
+                Some(sni.to_ascii_lowercase())
+            }
+        };
+
+        if !self.reconciler.layer4_supported().await {
+            return Err(AppError::Validation(
+                "Caddy lacks the layer4 app; set CADDY_IMAGE to a build with github.com/mholt/caddy-l4"
+                    .to_string(),
+            ));
+        }
+
+        let route = L4Route {
+            id: Uuid::new_v4().to_string(),
+            stack_id: stack_id.to_string(),
+            protocol: request.protocol,
+            listen_port: listen_port as i32,
+            sni,
+            container_name: request.container_name,
+            container_port: request.container_port,
+            created_at: Utc::now().to_rfc3339(),
+        };
+
+        let existing = self.domain_repo.list_all_l4_routes().await?;
+        if let Some(other) = existing.iter().find(|r| r.conflicts_with(&route)) {
+            return Err(AppError::Conflict(match &other.sni {
+                Some(sni) if route.sni.is_some() => {
+                    format!("{} is already routed on port {}", sni, other.listen_port)
+                }
+                _ => format!(
+                    "Port {}/{} is already routed{}",
+                    other.listen_port,
+                    other.protocol.as_str(),
+                    if other.sni.is_some() || route.sni.is_some() {
+                        "; a port is either shared by SNI routes or used by one plain route"
+                    } else {
+                        ""
+                    }
+                ),
+            }));
+        }
+        self.reconciler
+            .check_host_port(route.protocol, listen_port)
+            .await?;
+
+        self.domain_repo.create_l4_route(route.clone()).await?;
+        if let Err(e) = self.reconciler.reconcile().await {
+            let _ = self.domain_repo.delete_l4_route(&route.id).await;
+            return Err(e);
+        }
+
+        self.record_activity(
+            user_id,
+            stack_id,
+            "domain.l4.add",
+            "l4_route",
+            &route.id,
+            serde_json::json!({
+                "stack_id": stack_id,
+                "protocol": route.protocol,
+                "listen_port": route.listen_port,
+                "sni": route.sni,
+                "container_name": route.container_name,
+                "container_port": route.container_port,
+            }),
+        )
+        .await;
+        Ok(route)
+    }
+
+    pub async fn remove_l4_route(
+        &self,
+        stack_id: &str,
+        route_id: &str,
+        user_id: &str,
+    ) -> Result<L4Route> {
+        let route = self
+            .domain_repo
+            .list_l4_routes(stack_id)
+            .await?
+            .into_iter()
+            .find(|r| r.id == route_id)
+            .ok_or_else(|| AppError::NotFound("L4 route not found".to_string()))?;
+
+        self.domain_repo.delete_l4_route(&route.id).await?;
+        if let Err(e) = self.reconciler.reconcile().await {
+            tracing::warn!(
+                "Port {}/{} stays routed in Caddy until the next reconcile: {}",
+                route.listen_port,
+                route.protocol.as_str(),
+                e
+            );
+        }
+
+        self.record_activity(
+            user_id,
+            stack_id,
+            "domain.l4.remove",
+            "l4_route",
+            &route.id,
+            serde_json::json!({
+                "stack_id": stack_id,
+                "protocol": route.protocol,
+                "listen_port": route.listen_port,
+                "sni": route.sni,
+            }),
+        )
+        .await;
+        Ok(route)
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -522,3 +1205,156 @@ pub struct DnsVerificationResult {
     pub a_records: Vec<String>,
     pub cname_records: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::runtime::ContainerInfo;
+    use crate::domain::runtime::tests::FakeRuntime;
+    use crate::infrastructure::caddy::client::CaddyClient;
+    use crate::infrastructure::crypto::envelope::EnvelopeCipher;
+    use crate::infrastructure::sqlite::activity_log::SqliteActivityLogRepository;
+    use crate::infrastructure::sqlite::dns::SqliteDnsConfigRepository;
+    use crate::infrastructure::sqlite::domain::SqliteDomainRepository;
+    use crate::infrastructure::sqlite::role::SqliteRoleRepository;
+    use crate::infrastructure::sqlite::stack::SqliteStackRepository;
+    use crate::infrastructure::sqlite::stack_grant::SqliteStackGrantRepository;
+    use crate::infrastructure::sqlite::team::SqliteTeamRepository;
+    use crate::usecase::auth::tests::memory_pool;
+    use crate::usecase::authorization::AuthorizationUsecase;
+
+    fn container(name: &str, stack_id: &str) -> ContainerInfo {
+        ContainerInfo {
+            id: format!("{}-id", name),
+            names: vec![format!("/{}", name)],
+            image: "nginx:alpine".to_string(),
+            state: "running".to_string(),
+            status: String::new(),
+            labels: HashMap::from([("labuh.stack.id".to_string(), stack_id.to_string())]),
+            networks: HashMap::new(),
+            ports: None,
+            created: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_route_targets_stay_within_the_stack() {
+        let pool = memory_pool().await;
+        for query in [
+            "INSERT INTO users (id, email, password_hash) VALUES ('u1', 'u1@example.com', '')",
+            "INSERT INTO teams (id, name, created_at, updated_at) VALUES ('t1', 'Web', '', '')",
+            "INSERT INTO teams (id, name, created_at, updated_at) VALUES ('t2', 'Billing', '', '')",
+            "INSERT INTO stacks (id, name, user_id, team_id, created_at, updated_at) VALUES ('s1', 'shop', 'u1', 't1', '', '')",
+            "INSERT INTO stacks (id, name, user_id, team_id, created_at, updated_at) VALUES ('s2', 'billing', 'u1', 't2', '', '')",
+            "INSERT INTO domains (id, stack_id, container_name, domain, created_at) VALUES ('d1', 's1', 'shop-web', 'shop.example.com', '')",
+        ] {
+            sqlx::query(query).execute(&pool).await.unwrap();
+        }
+
+        let cipher: Arc<dyn SecretCipher> = Arc::new(EnvelopeCipher::generate());
+        let domain_repo = Arc::new(SqliteDomainRepository::new(pool.clone()));
+        let stack_repo = Arc::new(SqliteStackRepository::new(pool.clone()));
+        let dns_usecase = Arc::new(DnsUsecase::new(
+            Arc::new(SqliteDnsConfigRepository::new(pool.clone())),
+            cipher.clone(),
+        ));
+        let runtime = Arc::new(FakeRuntime {
+            containers: vec![container("shop-web", "s1"), container("billing-db", "s2")],
+        });
+        let reconciler = Arc::new(CaddyReconciler::new(
+            domain_repo.clone(),
+            stack_repo.clone(),
+            dns_usecase.clone(),
+            cipher.clone(),
+            runtime,
+            // Nothing listens on the discard port, so reconciles fail fast
+            Arc::new(CaddyClient::new("http://127.0.0.1:9".to_string())),
+            "caddy:2-alpine".to_string(),
+            None,
+        ));
+        let authz = Arc::new(AuthorizationUsecase::new(
+            Arc::new(SqliteTeamRepository::new(pool.clone())),
+            Arc::new(SqliteRoleRepository::new(pool.clone())),
+            Arc::new(SqliteStackGrantRepository::new(pool.clone())),
+        ));
+        let usecase = DomainUsecase::new(
+            domain_repo.clone(),
+            stack_repo.clone(),
+            reconciler,
+            dns_usecase,
+            cipher,
+            None,
+            14,
+            Arc::new(AuditUsecase::new(
+                Arc::new(SqliteActivityLogRepository::new(pool.clone())),
+                authz,
+                stack_repo,
+            )),
+        );
+
+        let route = |container_name: &str, upstreams: &[&str]| CreateDomainRoute {
+            path: "/api".to_string(),
+            strip_prefix: None,
+            container_name: container_name.to_string(),
+            container_port: Some(8080),
+            upstream: UpstreamSettings {
+                upstreams: upstreams.iter().map(|u| u.to_string()).collect(),
+                ..Default::default()
+            },
+        };
+        let rejected = |result: Result<DomainRoute>| matches!(result, Err(AppError::Validation(_)));
+
+        // Another team's container, directly or as an extra upstream
+        assert!(rejected(
+            usecase
+                .add_route("s1", "shop.example.com", route("billing-db", &[]), "u1")
+                .await
+        ));
+        assert!(rejected(
+            usecase
+                .add_route(
+                    "s1",
+                    "shop.example.com",
+                    route("shop-web", &["billing-db:5432"]),
+                    "u1"
+                )
+                .await
+        ));
+        // Hosts that are not containers at all
+        assert!(rejected(
+            usecase
+                .add_route(
+                    "s1",
+                    "shop.example.com",
+                    route("shop-web", &["169.254.169.254:80"]),
+                    "u1"
+                )
+                .await
+        ));
+        let settings = UpstreamSettings {
+            upstreams: vec!["billing-db:5432".to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            usecase
+                .update_upstreams("s1", "shop.example.com", settings, "u1")
+                .await,
+            Err(AppError::Validation(_))
+        ));
+
+        // The stack's own container gets past the check and only fails on the absent Caddy
+        let own = usecase
+            .add_route("s1", "shop.example.com", route("shop-web", &[]), "u1")
+            .await;
+        assert!(matches!(own, Err(ref e) if !matches!(e, AppError::Validation(_))));
+        assert!(
+            domain_repo
+                .find_by_domain("shop.example.com")
+                .await
+                .unwrap()
+                .unwrap()
+                .extra_upstreams
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use chrono::Utc;
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::domain::env_group_repository::EnvGroupRepository;
+use crate::domain::models::permission;
+use crate::domain::models::{
+    CreateEnvGroupRequest, EnvGroup, EnvGroupVar, EnvGroupVarResponse, SECRET_MASK, Stack,
+};
+use crate::domain::secret_cipher::SecretCipher;
+use crate::error::{AppError, Result};
+use crate::usecase::authorization::AuthorizationUsecase;
+
+pub struct EnvGroupUsecase {
+    repo: Arc<dyn EnvGroupRepository>,
+    authz: Arc<AuthorizationUsecase>,
+    cipher: Arc<dyn SecretCipher>,
+}
+
+impl EnvGroupUsecase {
+    pub fn new(
+        repo: Arc<dyn EnvGroupRepository>,
+        authz: Arc<AuthorizationUsecase>,
+        cipher: Arc<dyn SecretCipher>,
+    ) -> Self {
+        Self {
+            repo,
+            authz,
+            cipher,
+        }
+    }
+
+    async fn get_group(&self, id: &str, user_id: &str, permission: &str) -> Result<EnvGroup> {
+        let group = self.repo.find_by_id(id).await?;
+        self.authz
+            .require(&group.team_id, user_id, permission)
+            .await?;
+        Ok(group)
+    }
+
+    pub async fn list_groups(&self, team_id: &str, user_id: &str) -> Result<Vec<EnvGroup>> {
+        self.authz
+            .require(team_id, user_id, permission::TEAM_VIEW)
+            .await?;
+        self.repo.list_by_team(team_id).await
+    }
+
+    pub async fn create_group(
+        &self,
+        user_id: &str,
+        request: CreateEnvGroupRequest,
+    ) -> Result<EnvGroup> {
+        self.authz
+            .require(&request.team_id, user_id, permission::ENV_GROUP_MANAGE)
+            .await?;
+
+        let name = request.name.trim();
+        if name.is_empty() {
+            return Err(AppError::Validation("Group name is required".to_string()));
+        }
+        let existing = self.repo.list_by_team(&request.team_id).await?;
+        if existing.iter().any(|g| g.name == name) {
+            return Err(AppError::Validation(format!(
+                "An environment group named '{}' already exists",
+                name
+            )));
+        }
+
+        let now = Utc::now().to_rfc3339();
+        self.repo
+            .create(EnvGroup {
+                id: Uuid::new_v4().to_string(),
+                team_id: request.team_id,
+                name: name.to_string(),
+                description: request.description,
+                created_at: now.clone(),
+                updated_at: now,
+            })
+            .await
+    }
+
+    pub async fn delete_group(&self, id: &str, user_id: &str) -> Result<()> {
+        self.get_group(id, user_id, permission::ENV_GROUP_MANAGE)
+            .await?;
+        self.repo.delete(id).await
+    }
+
+    pub async fn list_vars(&self, id: &str, user_id: &str) -> Result<Vec<EnvGroupVarResponse>> {
+        self.get_group(id, user_id, permission::ENV_READ).await?;
+        let vars = self.repo.list_vars(id).await?;
+        Ok(vars.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn set_var(
+        &self,
+        id: &str,
+        user_id: &str,
+        key: &str,
+        value: &str,
+        is_secret: bool,
+    ) -> Result<EnvGroupVarResponse> {
+        self.get_group(id, user_id, permission::ENV_WRITE).await?;
+
+        if key.trim().is_empty() {
+            return Err(AppError::Validation("Variable key is required".to_string()));
+        }
+
+        // The UI sends the mask back for secrets it never saw; keep the stored value
+        let value = if is_secret && value == SECRET_MASK {
+            match self.repo.find_var(id, key).await? {
+                Some(existing) if existing.is_secret => existing.value,
+                _ => {
+                    return Err(AppError::Validation(format!(
+                        "A value is required for secret '{}'",
+                        key
+                    )));
+                }
+            }
+        } else if is_secret {
+            self.cipher.encrypt(value)?
+        } else {
+            value.to_string()
+        };
+
+        let now = Utc::now().to_rfc3339();
+        let saved = self
+            .repo
+            .save_var(EnvGroupVar {
+                id: Uuid::new_v4().to_string(),
+                group_id: id.to_string(),
+                key: key.to_string(),
+                value,
+                is_secret,
+                created_at: now.clone(),
+                updated_at: now,
+            })
+            .await?;
+        Ok(saved.into())
+    }
+
+    pub async fn delete_var(&self, id: &str, user_id: &str, key: &str) -> Result<()> {
+        self.get_group(id, user_id, permission::ENV_WRITE).await?;
+        self.repo.delete_var(id, key).await
+    }
+
+    pub async fn list_stack_groups(&self, stack_id: &str) -> Result<Vec<EnvGroup>> {
+        self.repo.list_for_stack(stack_id).await
+    }
+
+    /// Attach groups to a stack in precedence order (later groups override earlier ones).
+    /// The caller is responsible for authorizing access to the stack.
+    pub async fn set_stack_groups(
+        &self,
+        stack: &Stack,
+        group_ids: Vec<String>,
+    ) -> Result<Vec<EnvGroup>> {
+        let mut seen = HashSet::new();
+        for group_id in &group_ids {
+            if !seen.insert(group_id) {
+                return Err(AppError::Validation(format!(
+                    "Environment group '{}' is listed more than once",
+                    group_id
+                )));
+            }
+            let group = self.repo.find_by_id(group_id).await?;
+            if group.team_id != stack.team_id {
+                return Err(AppError::Validation(format!(
+                    "Environment group '{}' belongs to another team",
+                    group.name
+                )));
+            }
+        }
+
+        self.repo.set_for_stack(&stack.id, &group_ids).await?;
+        self.repo.list_for_stack(&stack.id).await
+    }
+
+    /// Stacks that pick up changes to this group on their next deploy
+    pub async fn affected_stacks(&self, id: &str, user_id: &str) -> Result<Vec<String>> {
+        self.get_group(id, user_id, permission::ENV_WRITE).await?;
+        self.repo.list_stack_ids(id).await
+    }
+}
@@ -3,17 +3,45 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::domain::dotenv::{parse_dotenv, to_dotenv};
+use crate::domain::env_group_repository::EnvGroupRepository;
 use crate::domain::environment_repository::EnvironmentRepository;
-use crate::domain::models::{StackEnvVar, StackEnvVarResponse};
-use crate::error::Result;
+use crate::domain::models::{
+    BackupEnvVar, DotenvImportRequest, ENV_SOURCE_COMPOSE, ENV_SOURCE_UI, EffectiveEnvVar, EnvDiff,
+    EnvDiffEntry, SECRET_MASK, StackEnvVar, StackEnvVarResponse,
+};
+use crate::domain::secret_cipher::SecretCipher;
+use crate::error::{AppError, Result};
+use crate::usecase::audit::AuditUsecase;
 
 pub struct EnvironmentUsecase {
     repo: Arc<dyn EnvironmentRepository>,
+    group_repo: Arc<dyn EnvGroupRepository>,
+    cipher: Arc<dyn SecretCipher>,
+    audit: Arc<AuditUsecase>,
 }
 
 impl EnvironmentUsecase {
-    pub fn new(repo: Arc<dyn EnvironmentRepository>) -> Self {
-        Self { repo }
+    pub fn new(
+        repo: Arc<dyn EnvironmentRepository>,
+        group_repo: Arc<dyn EnvGroupRepository>,
+        cipher: Arc<dyn SecretCipher>,
+        audit: Arc<AuditUsecase>,
+    ) -> Self {
+        Self {
+            repo,
+            group_repo,
+            cipher,
+            audit,
+        }
+    }
+
+    /// Decrypt the value of a secret variable in place
+    fn reveal(&self, mut var: StackEnvVar) -> Result<StackEnvVar> {
+        if var.is_secret {
+            var.value = self.cipher.decrypt(&var.value)?;
+        }
+        Ok(var)
     }
 
     pub async fn list_vars(&self, stack_id: &str) -> Result<Vec<StackEnvVarResponse>> {
@@ -21,29 +49,92 @@ impl EnvironmentUsecase {
         Ok(vars.into_iter().map(Into::into).collect())
     }
 
+    /// Variables with secret values decrypted
     pub async fn get_raw_vars(&self, stack_id: &str) -> Result<Vec<StackEnvVar>> {
-        self.repo.list_by_stack(stack_id).await
+        self.repo
+            .list_by_stack(stack_id)
+            .await?
+            .into_iter()
+            .map(|v| self.reveal(v))
+            .collect()
     }
 
-    pub async fn get_env_map_for_container(
+    /// Resolve a container's variables in precedence order:
+    /// team groups (in attachment order) < stack global < container specific
+    async fn resolve_env(
         &self,
         stack_id: &str,
         container_name: &str,
-    ) -> Result<HashMap<String, String>> {
-        let vars = self.repo.list_by_stack(stack_id).await?;
-        let mut map = HashMap::new();
+    ) -> Result<Vec<EffectiveEnvVar>> {
+        let mut resolved: Vec<EffectiveEnvVar> = Vec::new();
+        let mut apply = |var: EffectiveEnvVar| match resolved.iter_mut().find(|v| v.key == var.key)
+        {
+            Some(existing) => *existing = var,
+            None => resolved.push(var),
+        };
 
-        // Global vars
-        for v in vars.iter().filter(|v| v.container_name.is_empty()) {
-            map.insert(v.key.clone(), v.value.clone());
+        for group in self.group_repo.list_for_stack(stack_id).await? {
+            for var in self.group_repo.list_vars(&group.id).await? {
+                let value = if var.is_secret {
+                    self.cipher.decrypt(&var.value)?
+                } else {
+                    var.value
+                };
+                apply(EffectiveEnvVar {
+                    key: var.key,
+                    value,
+                    is_secret: var.is_secret,
+                    source: "group".to_string(),
+                    group_name: Some(group.name.clone()),
+                });
+            }
         }
 
-        // Container-specific overrides
-        for v in vars.iter().filter(|v| v.container_name == container_name) {
-            map.insert(v.key.clone(), v.value.clone());
+        let vars = self.get_raw_vars(stack_id).await?;
+        let global = vars.iter().filter(|v| v.container_name.is_empty());
+        let specific = vars.iter().filter(|v| v.container_name == container_name);
+
+        for (v, source) in global
+            .map(|v| (v, "stack"))
+            .chain(specific.map(|v| (v, "container")))
+        {
+            apply(EffectiveEnvVar {
+                key: v.key.clone(),
+                value: v.value.clone(),
+                is_secret: v.is_secret,
+                source: source.to_string(),
+                group_name: None,
+            });
         }
 
-        Ok(map)
+        Ok(resolved)
+    }
+
+    pub async fn get_env_map_for_container(
+        &self,
+        stack_id: &str,
+        container_name: &str,
+    ) -> Result<HashMap<String, String>> {
+        Ok(self
+            .resolve_env(stack_id, container_name)
+            .await?
+            .into_iter()
+            .map(|v| (v.key, v.value))
+            .collect())
+    }
+
+    /// Resolved variables for a service with secret values masked
+    pub async fn effective_env(
+        &self,
+        stack_id: &str,
+        container_name: &str,
+    ) -> Result<Vec<EffectiveEnvVar>> {
+        let mut vars = self.resolve_env(stack_id, container_name).await?;
+        for var in vars.iter_mut().filter(|v| v.is_secret) {
+            var.value = SECRET_MASK.to_string();
+        }
+        vars.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(vars)
     }
 
     pub async fn set_var(
@@ -53,15 +144,73 @@ impl EnvironmentUsecase {
         key: &str,
         value: &str,
         is_secret: bool,
+        user_id: &str,
     ) -> Result<StackEnvVarResponse> {
+        let var = self
+            .write_var(
+                stack_id,
+                container_name,
+                key,
+                value,
+                is_secret,
+                ENV_SOURCE_UI,
+            )
+            .await?;
+
+        self.record_activity(
+            user_id,
+            "env.set",
+            stack_id,
+            serde_json::json!({
+                "container_name": container_name,
+                "key": key,
+                "is_secret": is_secret,
+            }),
+        )
+        .await?;
+
+        Ok(var)
+    }
+
+    async fn write_var(
+        &self,
+        stack_id: &str,
+        container_name: &str,
+        key: &str,
+        value: &str,
+        is_secret: bool,
+        source: &str,
+    ) -> Result<StackEnvVarResponse> {
+        // The UI sends the mask back for secrets it never saw; keep the stored value
+        let value = if is_secret && value == SECRET_MASK {
+            match self
+                .repo
+                .find_existing(stack_id, container_name, key)
+                .await?
+            {
+                Some(existing) if existing.is_secret => existing.value,
+                _ => {
+                    return Err(AppError::Validation(format!(
+                        "A value is required for secret '{}'",
+                        key
+                    )));
+                }
+            }
+        } else if is_secret {
+            self.cipher.encrypt(value)?
+        } else {
+            value.to_string()
+        };
+
         let now = Utc::now().to_rfc3339();
         let var = StackEnvVar {
             id: Uuid::new_v4().to_string(),
             stack_id: stack_id.to_string(),
             container_name: container_name.to_string(),
             key: key.to_string(),
-            value: value.to_string(),
+            value,
             is_secret,
+            source: source.to_string(),
             created_at: now.clone(),
             updated_at: now,
         };
@@ -70,23 +219,493 @@ impl EnvironmentUsecase {
         Ok(saved.into())
     }
 
+    /// Store variables defined in the compose file. Unless `compose_wins`, values last set
+    /// through the UI are kept; values that came from compose are always refreshed.
+    pub async fn sync_compose_vars(
+        &self,
+        stack_id: &str,
+        container_name: &str,
+        vars: Vec<(String, String)>,
+        compose_wins: bool,
+    ) -> Result<()> {
+        for (key, value) in vars {
+            let existing = self
+                .repo
+                .find_existing(stack_id, container_name, &key)
+                .await?;
+
+            if let Some(existing) = &existing
+                && !compose_wins
+                && existing.source == ENV_SOURCE_UI
+            {
+                continue;
+            }
+
+            let is_secret = existing.is_some_and(|v| v.is_secret);
+            self.write_var(
+                stack_id,
+                container_name,
+                &key,
+                &value,
+                is_secret,
+                ENV_SOURCE_COMPOSE,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
     pub async fn bulk_set(
         &self,
         stack_id: &str,
         container_name: &str,
         vars: Vec<(String, String, bool)>,
+        user_id: &str,
     ) -> Result<Vec<StackEnvVarResponse>> {
         let mut results = Vec::new();
         for (key, value, is_secret) in vars {
             results.push(
-                self.set_var(stack_id, container_name, &key, &value, is_secret)
-                    .await?,
+                self.write_var(
+                    stack_id,
+                    container_name,
+                    &key,
+                    &value,
+                    is_secret,
+                    ENV_SOURCE_UI,
+                )
+                .await?,
             );
         }
+
+        if !results.is_empty() {
+            let keys: Vec<&str> = results.iter().map(|v| v.key.as_str()).collect();
+            self.record_activity(
+                user_id,
+                "env.bulk_set",
+                stack_id,
+                serde_json::json!({ "container_name": container_name, "keys": keys }),
+            )
+            .await?;
+        }
+
         Ok(results)
     }
 
-    pub async fn delete_var(&self, stack_id: &str, container_name: &str, key: &str) -> Result<()> {
-        self.repo.delete(stack_id, container_name, key).await
+    /// Variables for a stack backup. Secrets are re-encrypted with `backup_cipher`
+    /// (and the export is audited), or omitted when no cipher is given.
+    pub async fn export_vars(
+        &self,
+        stack_id: &str,
+        user_id: &str,
+        backup_cipher: Option<&dyn SecretCipher>,
+    ) -> Result<Vec<BackupEnvVar>> {
+        let vars = self.get_raw_vars(stack_id).await?;
+        let has_secrets = vars.iter().any(|v| v.is_secret);
+
+        let mut exported = Vec::new();
+        for var in vars {
+            let (value, omitted) = match (var.is_secret, backup_cipher) {
+                (false, _) => (var.value, false),
+                (true, Some(cipher)) => (cipher.encrypt(&var.value)?, false),
+                (true, None) => (SECRET_MASK.to_string(), true),
+            };
+            exported.push(BackupEnvVar {
+                container_name: var.container_name,
+                key: var.key,
+                value,
+                is_secret: var.is_secret,
+                omitted,
+            });
+        }
+
+        if has_secrets && backup_cipher.is_some() {
+            self.record_activity(user_id, "secret.export", stack_id, serde_json::json!({}))
+                .await?;
+        }
+
+        Ok(exported)
+    }
+
+    async fn record_activity(
+        &self,
+        user_id: &str,
+        action: &str,
+        stack_id: &str,
+        details: serde_json::Value,
+    ) -> Result<()> {
+        self.audit
+            .record_stack(Some(user_id), stack_id, action, details)
+            .await
+    }
+
+    /// Return the clear-text value of a variable and record who looked at it
+    pub async fn reveal_var(
+        &self,
+        stack_id: &str,
+        container_name: &str,
+        key: &str,
+        user_id: &str,
+    ) -> Result<String> {
+        let var = self
+            .repo
+            .find_existing(stack_id, container_name, key)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Environment variable not found".to_string()))?;
+        let var = self.reveal(var)?;
+
+        if var.is_secret {
+            self.record_activity(
+                user_id,
+                "secret.reveal",
+                stack_id,
+                serde_json::json!({ "container_name": container_name, "key": key }),
+            )
+            .await?;
+        }
+
+        Ok(var.value)
+    }
+
+    /// Render one scope as a `.env` file. Secrets are masked unless `revealed_by` names
+    /// the (already authorized) user, in which case the export is audited.
+    pub async fn export_dotenv(
+        &self,
+        stack_id: &str,
+        container_name: &str,
+        revealed_by: Option<&str>,
+    ) -> Result<String> {
+        let vars: Vec<StackEnvVar> = self
+            .get_raw_vars(stack_id)
+            .await?
+            .into_iter()
+            .filter(|v| v.container_name == container_name)
+            .collect();
+
+        if let Some(user_id) = revealed_by
+            && vars.iter().any(|v| v.is_secret)
+        {
+            self.record_activity(
+                user_id,
+                "secret.export",
+                stack_id,
+                serde_json::json!({ "format": "dotenv", "container_name": container_name }),
+            )
+            .await?;
+        }
+
+        let pairs: Vec<(String, String)> = vars
+            .into_iter()
+            .map(|v| {
+                let value = if v.is_secret && revealed_by.is_none() {
+                    SECRET_MASK.to_string()
+                } else {
+                    v.value
+                };
+                (v.key, value)
+            })
+            .collect();
+
+        Ok(to_dotenv(&pairs))
+    }
+
+    /// Compare a `.env` file with the stored scope.
+    /// Returns the diff plus the writes and deletions needed to apply it.
+    async fn plan_dotenv_import(
+        &self,
+        stack_id: &str,
+        request: &DotenvImportRequest,
+    ) -> Result<(EnvDiff, Vec<(String, String, bool)>, Vec<String>)> {
+        let incoming = parse_dotenv(&request.content)?;
+        let current: HashMap<String, StackEnvVar> = self
+            .get_raw_vars(stack_id)
+            .await?
+            .into_iter()
+            .filter(|v| v.container_name == request.container_name)
+            .map(|v| (v.key.clone(), v))
+            .collect();
+
+        let mask = |value: &str, is_secret: bool| {
+            Some(if is_secret {
+                SECRET_MASK.to_string()
+            } else {
+                value.to_string()
+            })
+        };
+
+        let mut diff = EnvDiff::default();
+        let mut writes = Vec::new();
+        for (key, value) in &incoming {
+            match current.get(key) {
+                // A masked secret in the file means "keep the stored value"
+                Some(existing) if existing.is_secret && value == SECRET_MASK => diff.unchanged += 1,
+                Some(existing) if &existing.value == value => diff.unchanged += 1,
+                Some(existing) => {
+                    diff.changed.push(EnvDiffEntry {
+                        key: key.clone(),
+                        old_value: mask(&existing.value, existing.is_secret),
+                        new_value: mask(value, existing.is_secret),
+                        is_secret: existing.is_secret,
+                    });
+                    writes.push((key.clone(), value.clone(), existing.is_secret));
+                }
+                None => {
+                    let is_secret = request.secret_keys.contains(key);
+                    if is_secret && value == SECRET_MASK {
+                        return Err(AppError::Validation(format!(
+                            "A value is required for secret '{}'",
+                            key
+                        )));
+                    }
+                    diff.added.push(EnvDiffEntry {
+                        key: key.clone(),
+                        old_value: None,
+                        new_value: mask(value, is_secret),
+                        is_secret,
+                    });
+                    writes.push((key.clone(), value.clone(), is_secret));
+                }
+            }
+        }
+
+        let mut deletes = Vec::new();
+        if request.remove_missing {
+            let mut missing: Vec<&StackEnvVar> = current
+                .values()
+                .filter(|v| !incoming.iter().any(|(k, _)| k == &v.key))
+                .collect();
+            missing.sort_by(|a, b| a.key.cmp(&b.key));
+            for var in missing {
+                diff.removed.push(EnvDiffEntry {
+                    key: var.key.clone(),
+                    old_value: mask(&var.value, var.is_secret),
+                    new_value: None,
+                    is_secret: var.is_secret,
+                });
+                deletes.push(var.key.clone());
+            }
+        }
+
+        Ok((diff, writes, deletes))
+    }
+
+    /// What importing the `.env` file would change, without applying it
+    pub async fn preview_dotenv(
+        &self,
+        stack_id: &str,
+        request: &DotenvImportRequest,
+    ) -> Result<EnvDiff> {
+        let (diff, _, _) = self.plan_dotenv_import(stack_id, request).await?;
+        Ok(diff)
+    }
+
+    pub async fn import_dotenv(
+        &self,
+        stack_id: &str,
+        request: &DotenvImportRequest,
+        user_id: &str,
+    ) -> Result<EnvDiff> {
+        let (diff, writes, deletes) = self.plan_dotenv_import(stack_id, request).await?;
+        let written: Vec<String> = writes.iter().map(|(key, _, _)| key.clone()).collect();
+
+        for (key, value, is_secret) in writes {
+            self.write_var(
+                stack_id,
+                &request.container_name,
+                &key,
+                &value,
+                is_secret,
+                ENV_SOURCE_UI,
+            )
+            .await?;
+        }
+        for key in &deletes {
+            self.repo
+                .delete(stack_id, &request.container_name, key)
+                .await?;
+        }
+
+        self.record_activity(
+            user_id,
+            "env.import",
+            stack_id,
+            serde_json::json!({
+                "container_name": request.container_name,
+                "written": written,
+                "deleted": deletes,
+            }),
+        )
+        .await?;
+
+        Ok(diff)
+    }
+
+    pub async fn delete_var(
+        &self,
+        stack_id: &str,
+        container_name: &str,
+        key: &str,
+        user_id: &str,
+    ) -> Result<()> {
+        self.repo.delete(stack_id, container_name, key).await?;
+
+        self.record_activity(
+            user_id,
+            "env.delete",
+            stack_id,
+            serde_json::json!({ "container_name": container_name, "key": key }),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::{EnvGroup, EnvGroupVar};
+    use crate::infrastructure::crypto::envelope::EnvelopeCipher;
+    use crate::infrastructure::sqlite::activity_log::SqliteActivityLogRepository;
+    use crate::infrastructure::sqlite::env_group::SqliteEnvGroupRepository;
+    use crate::infrastructure::sqlite::environment::SqliteEnvironmentRepository;
+    use crate::infrastructure::sqlite::role::SqliteRoleRepository;
+    use crate::infrastructure::sqlite::stack::SqliteStackRepository;
+    use crate::infrastructure::sqlite::stack_grant::SqliteStackGrantRepository;
+    use crate::infrastructure::sqlite::team::SqliteTeamRepository;
+    use crate::usecase::auth::tests::memory_pool;
+    use crate::usecase::authorization::AuthorizationUsecase;
+
+    async fn create_group(
+        repo: &SqliteEnvGroupRepository,
+        cipher: &EnvelopeCipher,
+        name: &str,
+        vars: &[(&str, &str, bool)],
+    ) -> String {
+        let group = repo
+            .create(EnvGroup {
+                id: Uuid::new_v4().to_string(),
+                team_id: "t1".to_string(),
+                name: name.to_string(),
+                description: None,
+                created_at: String::new(),
+                updated_at: String::new(),
+            })
+            .await
+            .unwrap();
+        for (key, value, is_secret) in vars {
+            repo.save_var(EnvGroupVar {
+                id: Uuid::new_v4().to_string(),
+                group_id: group.id.clone(),
+                key: key.to_string(),
+                value: if *is_secret {
+                    cipher.encrypt(value).unwrap()
+                } else {
+                    value.to_string()
+                },
+                is_secret: *is_secret,
+                created_at: String::new(),
+                updated_at: String::new(),
+            })
+            .await
+            .unwrap();
+        }
+        group.id
+    }
+
+    #[tokio::test]
+    async fn test_stack_variables_override_group_variables() {
+        let pool = memory_pool().await;
+        for query in [
+            "INSERT INTO users (id, email, password_hash) VALUES ('u1', 'u1@example.com', '')",
+            "INSERT INTO teams (id, name, created_at, updated_at) VALUES ('t1', 'Web', '', '')",
+            "INSERT INTO stacks (id, name, user_id, team_id, created_at, updated_at) VALUES ('s1', 'shop', 'u1', 't1', '', '')",
+        ] {
+            sqlx::query(query).execute(&pool).await.unwrap();
+        }
+
+        let cipher = Arc::new(EnvelopeCipher::generate());
+        let group_repo = Arc::new(SqliteEnvGroupRepository::new(pool.clone()));
+        let shared = create_group(
+            &group_repo,
+            &cipher,
+            "Shared",
+            &[
+                ("LOG_LEVEL", "info", false),
+                ("REGION", "eu", false),
+                ("API_KEY", "sk-shared", true),
+            ],
+        )
+        .await;
+        let prod = create_group(
+            &group_repo,
+            &cipher,
+            "Prod",
+            &[("LOG_LEVEL", "warn", false), ("DB_HOST", "prod-db", false)],
+        )
+        .await;
+        group_repo
+            .set_for_stack("s1", &[shared.clone(), prod.clone()])
+            .await
+            .unwrap();
+
+        let authz = Arc::new(AuthorizationUsecase::new(
+            Arc::new(SqliteTeamRepository::new(pool.clone())),
+            Arc::new(SqliteRoleRepository::new(pool.clone())),
+            Arc::new(SqliteStackGrantRepository::new(pool.clone())),
+        ));
+        let usecase = EnvironmentUsecase::new(
+            Arc::new(SqliteEnvironmentRepository::new(pool.clone())),
+            group_repo.clone(),
+            cipher,
+            Arc::new(AuditUsecase::new(
+                Arc::new(SqliteActivityLogRepository::new(pool.clone())),
+                authz,
+                Arc::new(SqliteStackRepository::new(pool)),
+            )),
+        );
+        usecase
+            .set_var("s1", "", "DB_HOST", "stack-db", false, "u1")
+            .await
+            .unwrap();
+        usecase
+            .set_var("s1", "web", "REGION", "us", false, "u1")
+            .await
+            .unwrap();
+
+        // Later groups win over earlier ones, the stack over groups, the container over the stack
+        let web = usecase
+            .get_env_map_for_container("s1", "web")
+            .await
+            .unwrap();
+        assert_eq!(web["LOG_LEVEL"], "warn");
+        assert_eq!(web["DB_HOST"], "stack-db");
+        assert_eq!(web["REGION"], "us");
+        assert_eq!(web["API_KEY"], "sk-shared");
+        let worker = usecase
+            .get_env_map_for_container("s1", "worker")
+            .await
+            .unwrap();
+        assert_eq!(worker["REGION"], "eu");
+
+        let effective = usecase.effective_env("s1", "web").await.unwrap();
+        let source = |key: &str| {
+            let var = effective.iter().find(|v| v.key == key).unwrap();
+            (var.source.as_str(), var.group_name.as_deref())
+        };
+        assert_eq!(source("LOG_LEVEL"), ("group", Some("Prod")));
+        assert_eq!(source("API_KEY"), ("group", Some("Shared")));
+        assert_eq!(source("DB_HOST"), ("stack", None));
+        assert_eq!(source("REGION"), ("container", None));
+        let api_key = effective.iter().find(|v| v.key == "API_KEY").unwrap();
+        assert_eq!(api_key.value, SECRET_MASK);
+
+        // Attachment order decides between groups
+        group_repo
+            .set_for_stack("s1", &[prod, shared])
+            .await
+            .unwrap();
+        let web = usecase
+            .get_env_map_for_container("s1", "web")
+            .await
+            .unwrap();
+        assert_eq!(web["LOG_LEVEL"], "info");
     }
 }
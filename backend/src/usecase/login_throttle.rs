@@ -0,0 +1,222 @@
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::domain::login_lockout_repository::LoginLockoutRepository;
+use crate::domain::models::LoginLockout;
+use crate::error::{AppError, Result};
+
+/// Failures older than this are forgotten
+const FAILURE_WINDOW_HOURS: i64 = 1;
+const MAX_LOCKOUT_SECONDS: i64 = 3600;
+/// Many users may share an IP (NAT, offices), so IPs tolerate more failures
+const IP_ATTEMPT_FACTOR: u32 = 4;
+
+#[derive(Debug, Clone)]
+struct Counter {
+    failures: u32,
+    locked_until: Option<DateTime<Utc>>,
+    last_failure: DateTime<Utc>,
+}
+
+/// Per-account and per-IP failed login tracking with exponential lockout.
+/// State lives in memory; with a repository it is also written to SQLite.
+pub struct LoginThrottle {
+    store: Option<Arc<dyn LoginLockoutRepository>>,
+    max_attempts: u32,
+    base_lockout: Duration,
+    counters: Mutex<HashMap<String, Counter>>,
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+fn account_key(account: &str) -> String {
+    format!("account:{}", account.trim().to_lowercase())
+}
+
+fn ip_key(ip: &str) -> String {
+    format!("ip:{}", ip)
+}
+
+impl LoginThrottle {
+    pub fn new(
+        store: Option<Arc<dyn LoginLockoutRepository>>,
+        max_attempts: u32,
+        base_lockout_seconds: u64,
+    ) -> Self {
+        Self {
+            store,
+            max_attempts: max_attempts.max(1),
+            base_lockout: Duration::seconds(base_lockout_seconds as i64),
+            counters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Restore recent counters from the store after a restart
+    pub async fn load(&self) -> Result<()> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        let since = timestamp(Utc::now() - Duration::hours(FAILURE_WINDOW_HOURS));
+        let lockouts = store.list_recent(&since).await?;
+
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        for lockout in lockouts {
+            let Some(last_failure) = parse_timestamp(&lockout.last_failure_at) else {
+                continue;
+            };
+            counters.insert(
+                lockout.key,
+                Counter {
+                    failures: lockout.failures.max(0) as u32,
+                    locked_until: lockout.locked_until.as_deref().and_then(parse_timestamp),
+                    last_failure,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Reject the attempt while the account or the IP is locked out
+    pub fn check(&self, account: &str, ip: &str) -> Result<()> {
+        let now = Utc::now();
+        let counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+
+        let retry_after = [account_key(account), ip_key(ip)]
+            .iter()
+            .filter_map(|key| counters.get(key)?.locked_until)
+            .filter(|until| *until > now)
+            .map(|until| (until - now).num_seconds().max(1) as u64)
+            .max();
+
+        match retry_after {
+            Some(seconds) => Err(AppError::RateLimited(seconds)),
+            None => Ok(()),
+        }
+    }
+
+    pub async fn record_failure(&self, account: &str, ip: &str) -> Result<()> {
+        let updated = {
+            let now = Utc::now();
+            let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+            counters.retain(|_, c| {
+                c.locked_until.is_some_and(|until| until > now)
+                    || now - c.last_failure < Duration::hours(FAILURE_WINDOW_HOURS)
+            });
+
+            [
+                (account_key(account), self.max_attempts),
+                (ip_key(ip), self.max_attempts * IP_ATTEMPT_FACTOR),
+            ]
+            .into_iter()
+            .map(|(key, threshold)| {
+                let counter = counters.entry(key.clone()).or_insert(Counter {
+                    failures: 0,
+                    locked_until: None,
+                    last_failure: now,
+                });
+                counter.failures += 1;
+                counter.last_failure = now;
+                if counter.failures >= threshold {
+                    counter.locked_until =
+                        Some(now + self.lockout_for(counter.failures - threshold));
+                }
+                (key, counter.clone())
+            })
+            .collect::<Vec<_>>()
+        };
+
+        if let Some(store) = &self.store {
+            for (key, counter) in updated {
+                store
+                    .upsert(LoginLockout {
+                        key,
+                        failures: counter.failures as i64,
+                        locked_until: counter.locked_until.map(timestamp),
+                        last_failure_at: timestamp(counter.last_failure),
+                    })
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Clear the account counter after a successful login. The IP counter is kept
+    /// so one valid account cannot be used to reset guessing against others.
+    pub async fn record_success(&self, account: &str) -> Result<()> {
+        let key = account_key(account);
+        let removed = self
+            .counters
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&key)
+            .is_some();
+
+        if let (true, Some(store)) = (removed, &self.store) {
+            store.delete(&key).await?;
+        }
+        Ok(())
+    }
+
+    /// Base lockout doubled for every failure past the threshold, capped at an hour
+    fn lockout_for(&self, excess_failures: u32) -> Duration {
+        let factor = 2i64.saturating_pow(excess_failures.min(20));
+        let seconds = self
+            .base_lockout
+            .num_seconds()
+            .saturating_mul(factor)
+            .min(MAX_LOCKOUT_SECONDS);
+        Duration::seconds(seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_account_lockout_grows_and_resets_on_success() {
+        let throttle = LoginThrottle::new(None, 3, 30);
+
+        for _ in 0..2 {
+            throttle
+                .record_failure("a@example.com", "10.0.0.1")
+                .await
+                .unwrap();
+            assert!(throttle.check("a@example.com", "10.0.0.1").is_ok());
+        }
+        throttle
+            .record_failure("A@example.com", "10.0.0.1")
+            .await
+            .unwrap();
+        assert!(matches!(
+            throttle.check("a@example.com", "10.0.0.2"),
+            Err(AppError::RateLimited(s)) if s <= 30
+        ));
+
+        // Each further failure doubles the lockout
+        throttle
+            .record_failure("a@example.com", "10.0.0.1")
+            .await
+            .unwrap();
+        assert!(matches!(
+            throttle.check("a@example.com", "10.0.0.2"),
+            Err(AppError::RateLimited(s)) if s > 30 && s <= 60
+        ));
+        assert_eq!(throttle.lockout_for(30).num_seconds(), MAX_LOCKOUT_SECONDS);
+
+        // Other accounts from another IP are unaffected
+        assert!(throttle.check("b@example.com", "10.0.0.2").is_ok());
+
+        throttle.record_success("a@example.com").await.unwrap();
+        assert!(throttle.check("a@example.com", "10.0.0.2").is_ok());
+    }
+}
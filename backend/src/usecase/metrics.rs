@@ -1,16 +1,101 @@
 use crate::domain::metrics_repository::{
-    HistoricalContainerMetrics, HistoricalNodeMetrics, MetricsRepository,
+    DomainRequestMetrics, HistoricalContainerMetrics, HistoricalNodeMetrics, MetricsRepository,
 };
 use crate::error::Result;
+use crate::usecase::access_log::{AccessLogEntry, RecentRequests};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
+/// Request analytics of a domain over a range of buckets. Percentiles are
+/// estimated from the latency histogram, so they are bucket upper bounds.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DomainTrafficStats {
+    pub domain: String,
+    /// Start of the range or step
+    pub timestamp: String,
+    pub requests: u64,
+    pub status_1xx: u64,
+    pub status_2xx: u64,
+    pub status_3xx: u64,
+    pub status_4xx: u64,
+    pub status_5xx: u64,
+    pub bytes_sent: u64,
+    pub avg_latency_ms: Option<f64>,
+    pub p50_latency_ms: Option<f64>,
+    pub p95_latency_ms: Option<f64>,
+    pub p99_latency_ms: Option<f64>,
+}
+
+impl From<&DomainRequestMetrics> for DomainTrafficStats {
+    fn from(m: &DomainRequestMetrics) -> Self {
+        Self {
+            domain: m.domain.clone(),
+            timestamp: m.bucket.clone(),
+            requests: m.requests,
+            status_1xx: m.status[0],
+            status_2xx: m.status[1],
+            status_3xx: m.status[2],
+            status_4xx: m.status[3],
+            status_5xx: m.status[4],
+            bytes_sent: m.bytes_sent,
+            avg_latency_ms: (m.requests > 0).then(|| m.latency_sum_ms / m.requests as f64),
+            p50_latency_ms: m.latency_percentile_ms(0.5),
+            p95_latency_ms: m.latency_percentile_ms(0.95),
+            p99_latency_ms: m.latency_percentile_ms(0.99),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DomainTraffic {
+    pub summary: DomainTrafficStats,
+    pub series: Vec<DomainTrafficStats>,
+}
+
+/// Merge buckets under `key`, keeping the first bucket seen as the timestamp
+fn merge_by<F>(buckets: &[DomainRequestMetrics], key: F) -> Vec<DomainRequestMetrics>
+where
+    F: Fn(&DomainRequestMetrics) -> String,
+{
+    let mut merged: BTreeMap<String, DomainRequestMetrics> = BTreeMap::new();
+    for bucket in buckets {
+        match merged.get_mut(&key(bucket)) {
+            Some(total) => total.merge(bucket),
+            None => {
+                merged.insert(key(bucket), bucket.clone());
+            }
+        }
+    }
+    merged.into_values().collect()
+}
+
+/// Step of a series: minutes for short ranges, then 5 minutes, then hours
+fn series_step(bucket: &str, last_hours: i32) -> String {
+    match last_hours {
+        ..=6 => bucket.to_string(),
+        // `YYYY-MM-DD HH:MM:00`: round the minutes down to a multiple of 5
+        7..=48 => match (bucket.get(..14), bucket.get(14..16)) {
+            (Some(hour), Some(minutes)) => {
+                let minutes: u32 = minutes.parse().unwrap_or(0);
+                format!("{}{:02}:00", hour, minutes / 5 * 5)
+            }
+            _ => bucket.to_string(),
+        },
+        _ => match bucket.get(..13) {
+            Some(hour) => format!("{}:00:00", hour),
+            None => bucket.to_string(),
+        },
+    }
+}
+
 pub struct MetricsUsecase {
     repo: Arc<dyn MetricsRepository>,
+    recent: Arc<RecentRequests>,
 }
 
 impl MetricsUsecase {
-    pub fn new(repo: Arc<dyn MetricsRepository>) -> Self {
-        Self { repo }
+    pub fn new(repo: Arc<dyn MetricsRepository>, recent: Arc<RecentRequests>) -> Self {
+        Self { repo, recent }
     }
 
     pub async fn get_node_metrics(
@@ -29,4 +114,81 @@ impl MetricsUsecase {
         let hours = last_hours.unwrap_or(24);
         self.repo.get_container_metrics(container_id, hours).await
     }
+
+    /// Totals per domain of a stack over the range
+    pub async fn get_domain_traffic(
+        &self,
+        stack_id: &str,
+        last_hours: Option<i32>,
+    ) -> Result<Vec<DomainTrafficStats>> {
+        let hours = last_hours.unwrap_or(24);
+        let buckets = self.repo.get_domain_requests(stack_id, None, hours).await?;
+        Ok(merge_by(&buckets, |b| b.domain.clone())
+            .iter()
+            .map(DomainTrafficStats::from)
+            .collect())
+    }
+
+    /// Totals of one domain plus a series over the range
+    pub async fn get_domain_traffic_series(
+        &self,
+        stack_id: &str,
+        domain: &str,
+        last_hours: Option<i32>,
+    ) -> Result<DomainTraffic> {
+        let hours = last_hours.unwrap_or(24);
+        let buckets = self
+            .repo
+            .get_domain_requests(stack_id, Some(domain), hours)
+            .await?;
+
+        let mut summary = DomainRequestMetrics::new(
+            domain,
+            stack_id,
+            buckets.first().map(|b| b.bucket.as_str()).unwrap_or(""),
+        );
+        for bucket in &buckets {
+            summary.merge(bucket);
+        }
+        let series = merge_by(&buckets, |b| series_step(&b.bucket, hours))
+            .into_iter()
+            .map(|mut b| {
+                b.bucket = series_step(&b.bucket, hours);
+                DomainTrafficStats::from(&b)
+            })
+            .collect();
+        Ok(DomainTraffic {
+            summary: DomainTrafficStats::from(&summary),
+            series,
+        })
+    }
+
+    /// Latest requests to a domain since Labuh started, newest first
+    pub fn get_recent_requests(
+        &self,
+        stack_id: &str,
+        domain: &str,
+        limit: Option<usize>,
+    ) -> Vec<AccessLogEntry> {
+        self.recent
+            .for_domain(stack_id, domain, limit.unwrap_or(100).min(500))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_series_step() {
+        assert_eq!(series_step("2026-10-18 10:37:00", 1), "2026-10-18 10:37:00");
+        assert_eq!(
+            series_step("2026-10-18 10:37:00", 24),
+            "2026-10-18 10:35:00"
+        );
+        assert_eq!(
+            series_step("2026-10-18 10:37:00", 168),
+            "2026-10-18 10:00:00"
+        );
+    }
 }
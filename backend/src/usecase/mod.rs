@@ -1,15 +1,27 @@
+pub mod access_log;
+pub mod api_token;
+pub mod audit;
 pub mod auth;
+pub mod authorization;
+pub mod caddy_reconciler;
 pub mod deployment_log;
 pub mod dns;
 pub mod domain;
+pub mod env_group;
 pub mod environment;
+pub mod login_throttle;
 pub mod metrics;
 pub mod metrics_collector;
 pub mod node;
+pub mod oidc;
 pub mod registry;
 pub mod resource;
+pub mod role;
 pub mod scheduler;
+pub mod secret;
 pub mod stack;
 pub mod system;
 pub mod team;
 pub mod template;
+pub mod two_factor;
+pub mod user;
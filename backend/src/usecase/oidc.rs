@@ -0,0 +1,285 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::Utc;
+use rand::distr::{Alphanumeric, SampleString};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::domain::TeamRepository;
+use crate::domain::models::{
+    ADMIN_ROLE, BOT_USER_ROLE, LoginResponse, NO_PASSWORD_HASH, OidcIdentity, TeamMapping,
+    TeamRole, USER_ROLE, User, UserIdentity,
+};
+use crate::domain::oidc_provider::OidcProvider;
+use crate::domain::user_identity_repository::UserIdentityRepository;
+use crate::domain::user_repository::UserRepository;
+use crate::error::{AppError, Result};
+use crate::usecase::auth::AuthUsecase;
+
+/// How long a user has to complete the login at the provider
+const PENDING_LOGIN_TTL: Duration = Duration::from_secs(600);
+
+struct PendingLogin {
+    code_verifier: String,
+    nonce: String,
+    created_at: Instant,
+}
+
+pub struct OidcUsecase {
+    provider: Arc<dyn OidcProvider>,
+    auth_usecase: Arc<AuthUsecase>,
+    user_repo: Arc<dyn UserRepository>,
+    identity_repo: Arc<dyn UserIdentityRepository>,
+    team_repo: Arc<dyn TeamRepository>,
+    mappings: Vec<TeamMapping>,
+    pending: Mutex<HashMap<String, PendingLogin>>,
+}
+
+impl OidcUsecase {
+    pub fn new(
+        provider: Arc<dyn OidcProvider>,
+        auth_usecase: Arc<AuthUsecase>,
+        user_repo: Arc<dyn UserRepository>,
+        identity_repo: Arc<dyn UserIdentityRepository>,
+        team_repo: Arc<dyn TeamRepository>,
+        mappings: Vec<TeamMapping>,
+    ) -> Self {
+        Self {
+            provider,
+            auth_usecase,
+            user_repo,
+            identity_repo,
+            team_repo,
+            mappings,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Start a login: remember the PKCE verifier and nonce, return the provider URL
+    pub async fn begin(&self) -> Result<String> {
+        let state = Alphanumeric.sample_string(&mut rand::rng(), 32);
+        let nonce = Alphanumeric.sample_string(&mut rand::rng(), 32);
+        let code_verifier = Alphanumeric.sample_string(&mut rand::rng(), 64);
+        let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+
+        let url = self
+            .provider
+            .authorization_url(&state, &nonce, &code_challenge)
+            .await?;
+
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.retain(|_, login| login.created_at.elapsed() < PENDING_LOGIN_TTL);
+        pending.insert(
+            state,
+            PendingLogin {
+                code_verifier,
+                nonce,
+                created_at: Instant::now(),
+            },
+        );
+
+        Ok(url)
+    }
+
+    /// Finish a login from the provider callback and open a Labuh session, unless
+    /// the user still has to pass 2FA
+    pub async fn complete(
+        &self,
+        code: &str,
+        state: &str,
+        user_agent: Option<String>,
+    ) -> Result<LoginResponse> {
+        let login = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(state)
+            .filter(|login| login.created_at.elapsed() < PENDING_LOGIN_TTL)
+            .ok_or_else(|| AppError::Auth("Login request expired, please try again".to_string()))?;
+
+        let identity = self
+            .provider
+            .exchange_code(code, &login.code_verifier, &login.nonce)
+            .await?;
+
+        let user = self.provision_user(&identity).await?;
+        self.sync_teams(&user.id, &identity.groups).await?;
+
+        self.auth_usecase.finish_login(user, user_agent).await
+    }
+
+    /// Find the user linked to the identity, linking by verified email or creating one
+    /// on first login
+    async fn provision_user(&self, identity: &OidcIdentity) -> Result<User> {
+        let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+        if let Some(linked) = self
+            .identity_repo
+            .find(&identity.issuer, &identity.subject)
+            .await?
+        {
+            self.identity_repo
+                .touch_last_login(&linked.id, &now)
+                .await?;
+            return self
+                .user_repo
+                .find_by_id(&linked.user_id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("User not found".to_string()));
+        }
+
+        let user = match self.user_repo.find_by_email(&identity.email).await? {
+            Some(user) if user.role == BOT_USER_ROLE => {
+                return Err(AppError::Forbidden("Access denied".to_string()));
+            }
+            // Whoever controls the address at the IdP would take over the account
+            Some(_) if !identity.email_verified => {
+                return Err(AppError::Forbidden(
+                    "Your identity provider has not verified this email address, so it cannot be linked to an existing account".to_string(),
+                ));
+            }
+            Some(user) => user,
+            None => {
+                // Like password registration, the very first user becomes the admin
+                let role = if self.user_repo.count_users().await? == 0 {
+                    ADMIN_ROLE
+                } else {
+                    USER_ROLE
+                };
+                let user = User {
+                    id: Uuid::new_v4().to_string(),
+                    email: identity.email.clone(),
+                    password_hash: NO_PASSWORD_HASH.to_string(),
+                    name: identity.name.clone(),
+                    role: role.to_string(),
+                    created_at: now.clone(),
+                    updated_at: now.clone(),
+                    deactivated_at: None,
+                };
+                self.user_repo.create(user).await?
+            }
+        };
+
+        self.identity_repo
+            .create(UserIdentity {
+                id: Uuid::new_v4().to_string(),
+                user_id: user.id.clone(),
+                issuer: identity.issuer.clone(),
+                subject: identity.subject.clone(),
+                created_at: now.clone(),
+                last_login_at: Some(now),
+            })
+            .await?;
+
+        Ok(user)
+    }
+
+    /// Apply the group mapping to every mapped team. Mapped teams are managed by the
+    /// IdP: membership follows the groups, except for team owners.
+    async fn sync_teams(&self, user_id: &str, groups: &[String]) -> Result<()> {
+        let mut desired: HashMap<&str, Option<TeamRole>> = HashMap::new();
+        for mapping in &self.mappings {
+            let entry = desired.entry(mapping.team.as_str()).or_insert(None);
+            if groups.contains(&mapping.group)
+                && entry
+                    .as_ref()
+                    .is_none_or(|r| mapping.role.rank() > r.rank())
+            {
+                *entry = Some(mapping.role.clone());
+            }
+        }
+
+        for (team_name, role) in desired {
+            let Some(team) = self.team_repo.find_by_name(team_name).await? else {
+                tracing::warn!("OIDC team mapping refers to unknown team '{}'", team_name);
+                continue;
+            };
+
+            match (self.team_repo.get_user_role(&team.id, user_id).await?, role) {
+                (Some(TeamRole::Owner), _) | (None, None) => {}
+                (None, Some(role)) => self.team_repo.add_member(&team.id, user_id, role).await?,
+                (Some(current), Some(role)) if current != role => {
+                    self.team_repo
+                        .update_member_role(&team.id, user_id, role)
+                        .await?
+                }
+                (Some(_), Some(_)) => {}
+                (Some(_), None) => self.team_repo.remove_member(&team.id, user_id).await?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OidcConfig;
+    use crate::infrastructure::oidc::OidcClient;
+    use crate::infrastructure::sqlite::{
+        team::SqliteTeamRepository, user::SqliteUserRepository,
+        user_identity::SqliteUserIdentityRepository,
+    };
+    use crate::usecase::auth::tests::{auth_usecase, create_user, memory_pool};
+
+    fn identity(subject: &str, email: &str, email_verified: bool) -> OidcIdentity {
+        OidcIdentity {
+            issuer: "https://idp.example.com".to_string(),
+            subject: subject.to_string(),
+            email: email.to_string(),
+            email_verified,
+            name: None,
+            groups: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_links_existing_accounts_by_verified_email_only() {
+        let pool = memory_pool().await;
+        let admin = create_user(&pool, "admin@example.com", Some("hunter22"), ADMIN_ROLE).await;
+        let usecase = OidcUsecase::new(
+            Arc::new(OidcClient::new(OidcConfig {
+                issuer_url: "https://idp.example.com".to_string(),
+                client_id: "labuh".to_string(),
+                client_secret: None,
+                redirect_url: "http://localhost:3000/api/auth/oidc/callback".to_string(),
+                scopes: "openid email".to_string(),
+                provider_name: "Mock".to_string(),
+                groups_claim: "groups".to_string(),
+                team_mapping: String::new(),
+            })),
+            Arc::new(auth_usecase(&pool, true)),
+            Arc::new(SqliteUserRepository::new(pool.clone())),
+            Arc::new(SqliteUserIdentityRepository::new(pool.clone())),
+            Arc::new(SqliteTeamRepository::new(pool.clone())),
+            Vec::new(),
+        );
+
+        let unverified = identity("attacker", "admin@example.com", false);
+        assert!(matches!(
+            usecase.provision_user(&unverified).await,
+            Err(AppError::Forbidden(_))
+        ));
+
+        let verified = identity("admin-sub", "admin@example.com", true);
+        assert_eq!(
+            usecase.provision_user(&verified).await.unwrap().id,
+            admin.id
+        );
+        // Once linked, the subject identifies the user
+        let relogin = identity("admin-sub", "admin@example.com", false);
+        assert_eq!(usecase.provision_user(&relogin).await.unwrap().id, admin.id);
+
+        // A new address gets its own account, verified or not
+        let newcomer = usecase
+            .provision_user(&identity("new-sub", "new@example.com", false))
+            .await
+            .unwrap();
+        assert_ne!(newcomer.id, admin.id);
+        assert_eq!(newcomer.role, USER_ROLE);
+    }
+}
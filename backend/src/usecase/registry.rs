@@ -3,50 +3,36 @@ use chrono::Utc;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::domain::models::{RegistryCredential, RegistryCredentialResponse, TeamRole};
+use crate::domain::models::permission;
+use crate::domain::models::{RegistryCredential, RegistryCredentialResponse};
 use crate::domain::registry_repository::RegistryRepository;
+use crate::domain::secret_cipher::SecretCipher;
 use crate::error::{AppError, Result};
+use crate::usecase::audit::AuditUsecase;
+use crate::usecase::authorization::AuthorizationUsecase;
+
+const MANIFEST_MEDIA_TYPES: &str = "application/vnd.docker.distribution.manifest.v2+json, application/vnd.docker.distribution.manifest.list.v2+json, application/vnd.oci.image.manifest.v1+json, application/vnd.oci.image.index.v1+json";
 
 pub struct RegistryUsecase {
     repo: Arc<dyn RegistryRepository>,
-    team_repo: Arc<dyn crate::domain::TeamRepository>,
+    authz: Arc<AuthorizationUsecase>,
+    cipher: Arc<dyn SecretCipher>,
+    audit: Arc<AuditUsecase>,
 }
 
 impl RegistryUsecase {
     pub fn new(
         repo: Arc<dyn RegistryRepository>,
-        team_repo: Arc<dyn crate::domain::TeamRepository>,
+        authz: Arc<AuthorizationUsecase>,
+        cipher: Arc<dyn SecretCipher>,
+        audit: Arc<AuditUsecase>,
     ) -> Self {
-        Self { repo, team_repo }
-    }
-
-    /// Verify user has required role for the team
-    async fn verify_permission(
-        &self,
-        team_id: &str,
-        user_id: &str,
-        required_role: TeamRole,
-    ) -> Result<()> {
-        let role = self
-            .team_repo
-            .get_user_role(team_id, user_id)
-            .await?
-            .ok_or(AppError::Forbidden("Access denied".to_string()))?;
-
-        let role_priority = |r: TeamRole| match r {
-            TeamRole::Owner => 4,
-            TeamRole::Admin => 3,
-            TeamRole::Developer => 2,
-            TeamRole::Viewer => 1,
-        };
-
-        if role_priority(role) < role_priority(required_role) {
-            return Err(AppError::Forbidden(
-                "Insufficient permissions for this operation".to_string(),
-            ));
+        Self {
+            repo,
+            authz,
+            cipher,
+            audit,
         }
-
-        Ok(())
     }
 
     pub async fn list_credentials(
@@ -54,7 +40,8 @@ impl RegistryUsecase {
         team_id: &str,
         user_id: &str,
     ) -> Result<Vec<RegistryCredentialResponse>> {
-        self.verify_permission(team_id, user_id, TeamRole::Viewer)
+        self.authz
+            .require(team_id, user_id, permission::TEAM_VIEW)
             .await?;
 
         let creds = self.repo.list_by_team(team_id).await?;
@@ -70,12 +57,13 @@ impl RegistryUsecase {
         username: &str,
         password: &str,
     ) -> Result<RegistryCredentialResponse> {
-        self.verify_permission(team_id, user_id, TeamRole::Admin)
+        self.authz
+            .require(team_id, user_id, permission::REGISTRY_MANAGE)
             .await?;
 
         let id = Uuid::new_v4().to_string();
         let now = Utc::now().to_rfc3339();
-        let password_encrypted = BASE64.encode(password.as_bytes());
+        let password_encrypted = self.cipher.encrypt(password)?;
 
         let cred = RegistryCredential {
             id,
@@ -90,14 +78,39 @@ impl RegistryUsecase {
         };
 
         let saved = self.repo.save(cred).await?;
+        self.audit
+            .record(
+                Some(user_id),
+                Some(team_id),
+                "registry.add",
+                "registry",
+                &saved.id,
+                serde_json::json!({
+                    "name": name,
+                    "registry_url": registry_url,
+                    "username": username,
+                }),
+            )
+            .await?;
         Ok(saved.into())
     }
 
     pub async fn remove_credential(&self, id: &str, team_id: &str, user_id: &str) -> Result<()> {
-        self.verify_permission(team_id, user_id, TeamRole::Admin)
+        self.authz
+            .require(team_id, user_id, permission::REGISTRY_MANAGE)
             .await?;
 
-        self.repo.delete(id, team_id).await
+        self.repo.delete(id, team_id).await?;
+        self.audit
+            .record(
+                Some(user_id),
+                Some(team_id),
+                "registry.remove",
+                "registry",
+                id,
+                serde_json::json!({}),
+            )
+            .await
     }
 
     pub async fn get_credentials_for_image(
@@ -119,19 +132,199 @@ impl RegistryUsecase {
         let cred = self.repo.find_by_url(team_id, &url).await?;
 
         if let Some(c) = cred {
-            let password = String::from_utf8(
-                BASE64
-                    .decode(&c.password_encrypted)
-                    .map_err(|_| AppError::Internal("Failed to decode password".to_string()))?,
-            )
-            .map_err(|_| AppError::Internal("Invalid password encoding".to_string()))?;
-
+            let password = self.decode_password(&c)?;
             return Ok(Some((c.username, password)));
         }
 
         Ok(None)
     }
 
+    /// Resolve a team registry used as push target, along with its password
+    pub async fn get_push_target(
+        &self,
+        team_id: &str,
+        registry_id: &str,
+    ) -> Result<(RegistryCredential, String)> {
+        let cred = self
+            .repo
+            .find_by_id(registry_id, team_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Registry credential not found".to_string()))?;
+        let password = self.decode_password(&cred)?;
+        Ok((cred, password))
+    }
+
+    /// Build `<registry>/<namespace>/<repository>:<tag>`, defaulting the namespace to the username
+    pub fn push_reference(
+        &self,
+        cred: &RegistryCredential,
+        namespace: Option<&str>,
+        repository: &str,
+        tag: &str,
+    ) -> String {
+        let namespace = namespace
+            .map(|n| n.trim_matches('/'))
+            .filter(|n| !n.is_empty())
+            .unwrap_or(&cred.username);
+
+        format!(
+            "{}/{}/{}:{}",
+            normalize_registry_host(&cred.registry_url),
+            namespace,
+            repository,
+            tag
+        )
+        .to_lowercase()
+    }
+
+    /// Delete a pushed tag through the Registry HTTP API v2.
+    /// Docker Hub does not allow manifest deletion there, so it is skipped.
+    pub async fn delete_remote_image(
+        &self,
+        cred: &RegistryCredential,
+        password: &str,
+        image_ref: &str,
+    ) -> Result<()> {
+        let host = normalize_registry_host(&cred.registry_url);
+        if host == "docker.io" {
+            tracing::debug!("Skipping remote deletion of {} on Docker Hub", image_ref);
+            return Ok(());
+        }
+
+        let path = image_ref
+            .strip_prefix(&format!("{}/", host))
+            .ok_or_else(|| {
+                AppError::BadRequest(format!("Image {} does not belong to {}", image_ref, host))
+            })?;
+        let (repository, tag) = path.rsplit_once(':').unwrap_or((path, "latest"));
+
+        let client = reqwest::Client::new();
+        let authorization = self
+            .registry_authorization(&client, &host, repository, &cred.username, password)
+            .await?;
+
+        let manifest_url = format!("https://{}/v2/{}/manifests/{}", host, repository, tag);
+        let response = client
+            .head(&manifest_url)
+            .header(reqwest::header::ACCEPT, MANIFEST_MEDIA_TYPES)
+            .header(reqwest::header::AUTHORIZATION, &authorization)
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Registry API error: {}", e)))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+
+        let digest = response
+            .headers()
+            .get("Docker-Content-Digest")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string())
+            .ok_or_else(|| {
+                AppError::Internal(format!(
+                    "Registry did not return a digest for {} ({})",
+                    image_ref,
+                    response.status()
+                ))
+            })?;
+
+        let response = client
+            .delete(format!(
+                "https://{}/v2/{}/manifests/{}",
+                host, repository, digest
+            ))
+            .header(reqwest::header::AUTHORIZATION, &authorization)
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Registry API error: {}", e)))?;
+
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            return Err(AppError::Internal(format!(
+                "Registry refused to delete {} ({})",
+                image_ref,
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Resolve the Authorization header for a registry, following the bearer token
+    /// challenge when the registry uses token auth instead of basic auth
+    async fn registry_authorization(
+        &self,
+        client: &reqwest::Client,
+        host: &str,
+        repository: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<String> {
+        let basic = format!(
+            "Basic {}",
+            BASE64.encode(format!("{}:{}", username, password))
+        );
+
+        let response = client
+            .get(format!("https://{}/v2/", host))
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Registry API error: {}", e)))?;
+
+        let challenge = response
+            .headers()
+            .get(reqwest::header::WWW_AUTHENTICATE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+
+        let Some(params) = challenge.strip_prefix("Bearer ") else {
+            return Ok(basic);
+        };
+
+        let params: std::collections::HashMap<&str, &str> = params
+            .split(',')
+            .filter_map(|p| p.trim().split_once('='))
+            .map(|(k, v)| (k, v.trim_matches('"')))
+            .collect();
+
+        let realm = params
+            .get("realm")
+            .ok_or_else(|| AppError::Internal("Registry challenge missing realm".to_string()))?;
+        let scope = format!("repository:{}:pull,push,delete", repository);
+        let mut query = vec![("scope", scope.as_str())];
+        if let Some(service) = params.get("service") {
+            query.push(("service", service));
+        }
+
+        let token_url = reqwest::Url::parse_with_params(realm, &query)
+            .map_err(|e| AppError::Internal(format!("Invalid registry token realm: {}", e)))?;
+
+        let body: serde_json::Value = client
+            .get(token_url)
+            .header(reqwest::header::AUTHORIZATION, &basic)
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Registry token error: {}", e)))?
+            .error_for_status()
+            .map_err(|e| AppError::Internal(format!("Registry token error: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to parse registry token: {}", e)))?;
+
+        let token = body["token"]
+            .as_str()
+            .or_else(|| body["access_token"].as_str())
+            .ok_or_else(|| {
+                AppError::Internal("Registry token response missing token".to_string())
+            })?;
+
+        Ok(format!("Bearer {}", token))
+    }
+
+    fn decode_password(&self, cred: &RegistryCredential) -> Result<String> {
+        self.cipher.decrypt(&cred.password_encrypted)
+    }
+
     fn extract_registry(&self, image: &str) -> String {
         if !image.contains('/') {
             return "docker.io".to_string();
@@ -148,3 +341,108 @@ impl RegistryUsecase {
         "docker.io".to_string()
     }
 }
+
+/// Strip scheme, path and Docker Hub aliases from a stored registry URL
+fn normalize_registry_host(url: &str) -> String {
+    let host = url
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .split('/')
+        .next()
+        .unwrap_or_default();
+
+    match host {
+        "" | "index.docker.io" | "registry-1.docker.io" | "registry.hub.docker.com" => {
+            "docker.io".to_string()
+        }
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::crypto::envelope::EnvelopeCipher;
+    use crate::infrastructure::sqlite::activity_log::SqliteActivityLogRepository;
+    use crate::infrastructure::sqlite::registry::SqliteRegistryRepository;
+    use crate::infrastructure::sqlite::role::SqliteRoleRepository;
+    use crate::infrastructure::sqlite::stack::SqliteStackRepository;
+    use crate::infrastructure::sqlite::stack_grant::SqliteStackGrantRepository;
+    use crate::infrastructure::sqlite::team::SqliteTeamRepository;
+    use crate::usecase::auth::tests::memory_pool;
+
+    #[test]
+    fn test_normalize_registry_host() {
+        for (url, host) in [
+            ("", "docker.io"),
+            ("docker.io", "docker.io"),
+            ("https://index.docker.io/v1/", "docker.io"),
+            ("registry-1.docker.io", "docker.io"),
+            ("https://registry.hub.docker.com", "docker.io"),
+            ("ghcr.io", "ghcr.io"),
+            ("https://ghcr.io/", "ghcr.io"),
+            ("http://registry.local:5000/v2", "registry.local:5000"),
+        ] {
+            assert_eq!(normalize_registry_host(url), host, "{}", url);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_push_reference() {
+        let pool = memory_pool().await;
+        let authz = Arc::new(AuthorizationUsecase::new(
+            Arc::new(SqliteTeamRepository::new(pool.clone())),
+            Arc::new(SqliteRoleRepository::new(pool.clone())),
+            Arc::new(SqliteStackGrantRepository::new(pool.clone())),
+        ));
+        let usecase = RegistryUsecase::new(
+            Arc::new(SqliteRegistryRepository::new(pool.clone())),
+            authz.clone(),
+            Arc::new(EnvelopeCipher::generate()),
+            Arc::new(AuditUsecase::new(
+                Arc::new(SqliteActivityLogRepository::new(pool.clone())),
+                authz,
+                Arc::new(SqliteStackRepository::new(pool)),
+            )),
+        );
+        let cred = |registry_url: &str| RegistryCredential {
+            id: "r1".to_string(),
+            user_id: "u1".to_string(),
+            team_id: "t1".to_string(),
+            name: "Registry".to_string(),
+            registry_url: registry_url.to_string(),
+            username: "Deployer".to_string(),
+            password_encrypted: String::new(),
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+
+        for (registry_url, namespace, expected) in [
+            (
+                "https://index.docker.io/v1/",
+                None,
+                "docker.io/deployer/shop-web:1a2b3c",
+            ),
+            ("ghcr.io", Some("acme"), "ghcr.io/acme/shop-web:1a2b3c"),
+            (
+                "ghcr.io",
+                Some("/Acme/apps/"),
+                "ghcr.io/acme/apps/shop-web:1a2b3c",
+            ),
+            ("ghcr.io", Some(""), "ghcr.io/deployer/shop-web:1a2b3c"),
+            (
+                "http://registry.local:5000",
+                Some("team"),
+                "registry.local:5000/team/shop-web:1a2b3c",
+            ),
+        ] {
+            assert_eq!(
+                usecase.push_reference(&cred(registry_url), namespace, "shop-web", "1a2b3c"),
+                expected,
+                "{} {:?}",
+                registry_url,
+                namespace
+            );
+        }
+    }
+}
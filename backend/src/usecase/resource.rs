@@ -5,25 +5,25 @@ use crate::error::Result;
 use chrono::{Duration, Utc};
 use std::sync::Arc;
 
-use crate::domain::TeamRepository;
-use crate::error::AppError;
+use crate::domain::models::permission;
+use crate::usecase::authorization::AuthorizationUsecase;
 
 pub struct ResourceUsecase {
     repo: Arc<dyn ResourceRepository>,
     stack_repo: Arc<dyn StackRepository>,
-    team_repo: Arc<dyn TeamRepository>,
+    authz: Arc<AuthorizationUsecase>,
 }
 
 impl ResourceUsecase {
     pub fn new(
         repo: Arc<dyn ResourceRepository>,
         stack_repo: Arc<dyn StackRepository>,
-        team_repo: Arc<dyn TeamRepository>,
+        authz: Arc<AuthorizationUsecase>,
     ) -> Self {
         Self {
             repo,
             stack_repo,
-            team_repo,
+            authz,
         }
     }
 
@@ -35,13 +35,10 @@ impl ResourceUsecase {
         cpu: Option<f64>,
         memory: Option<i64>,
     ) -> Result<()> {
-        // Verify ownership via team
         let stack = self.stack_repo.find_by_id_internal(stack_id).await?;
-        let _role = self
-            .team_repo
-            .get_user_role(&stack.team_id, user_id)
-            .await?
-            .ok_or(AppError::Forbidden("Access denied".to_string()))?;
+        self.authz
+            .require_stack(&stack, user_id, permission::RESOURCE_MANAGE)
+            .await?;
 
         self.repo
             .update_resource_limits(stack_id, service_name, cpu, memory)
@@ -54,13 +51,10 @@ impl ResourceUsecase {
         stack_id: &str,
         user_id: &str,
     ) -> Result<Vec<ContainerResource>> {
-        // Verify ownership
         let stack = self.stack_repo.find_by_id_internal(stack_id).await?;
-        let _role = self
-            .team_repo
-            .get_user_role(&stack.team_id, user_id)
-            .await?
-            .ok_or(AppError::Forbidden("Access denied".to_string()))?;
+        self.authz
+            .require_stack(&stack, user_id, permission::STACK_VIEW)
+            .await?;
 
         self.repo.list_resource_limits_for_stack(stack_id).await
     }
@@ -71,13 +65,10 @@ impl ResourceUsecase {
         user_id: &str,
         range: &str,
     ) -> Result<Vec<ResourceMetric>> {
-        // Verify ownership
         let stack = self.stack_repo.find_by_id_internal(stack_id).await?;
-        let _role = self
-            .team_repo
-            .get_user_role(&stack.team_id, user_id)
-            .await?
-            .ok_or(AppError::Forbidden("Access denied".to_string()))?;
+        self.authz
+            .require_stack(&stack, user_id, permission::STACK_VIEW)
+            .await?;
 
         let duration = match range {
             "1h" => Duration::hours(1),
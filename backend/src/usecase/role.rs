@@ -0,0 +1,399 @@
+use chrono::Utc;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::domain::TeamRepository;
+use crate::domain::models::permission::{
+    self, PERMISSIONS, STACK_PERMISSIONS, validate_permissions,
+};
+use crate::domain::models::{
+    BOT_USER_ROLE, CustomRole, CustomRoleResponse, PermissionSet, SaveCustomRoleRequest,
+    SaveStackGrantRequest, StackGrant, StackGrantResponse, TeamRole,
+};
+use crate::domain::role_repository::RoleRepository;
+use crate::domain::stack_grant_repository::StackGrantRepository;
+use crate::domain::stack_repository::StackRepository;
+use crate::domain::user_repository::UserRepository;
+use crate::error::{AppError, Result};
+use crate::usecase::audit::AuditUsecase;
+use crate::usecase::authorization::AuthorizationUsecase;
+
+/// Manages custom team roles and per-stack grants. Nobody can hand out a
+/// permission they do not hold themselves.
+pub struct RoleUsecase {
+    authz: Arc<AuthorizationUsecase>,
+    role_repo: Arc<dyn RoleRepository>,
+    grant_repo: Arc<dyn StackGrantRepository>,
+    team_repo: Arc<dyn TeamRepository>,
+    user_repo: Arc<dyn UserRepository>,
+    stack_repo: Arc<dyn StackRepository>,
+    audit: Arc<AuditUsecase>,
+}
+
+fn timestamp() -> String {
+    Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn check_grantable(actor: &PermissionSet, permissions: &[String]) -> Result<()> {
+    if !actor.covers(permissions.iter().map(String::as_str)) {
+        return Err(AppError::Forbidden(
+            "Cannot grant permissions you do not have".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+impl RoleUsecase {
+    pub fn new(
+        authz: Arc<AuthorizationUsecase>,
+        role_repo: Arc<dyn RoleRepository>,
+        grant_repo: Arc<dyn StackGrantRepository>,
+        team_repo: Arc<dyn TeamRepository>,
+        user_repo: Arc<dyn UserRepository>,
+        stack_repo: Arc<dyn StackRepository>,
+        audit: Arc<AuditUsecase>,
+    ) -> Self {
+        Self {
+            authz,
+            role_repo,
+            grant_repo,
+            team_repo,
+            user_repo,
+            stack_repo,
+            audit,
+        }
+    }
+
+    async fn find_role(&self, team_id: &str, role_id: &str) -> Result<CustomRole> {
+        self.role_repo
+            .find_by_id(role_id)
+            .await?
+            .filter(|r| r.team_id == team_id)
+            .ok_or(AppError::NotFound("Role not found".to_string()))
+    }
+
+    async fn validate_request(
+        &self,
+        team_id: &str,
+        actor: &PermissionSet,
+        request: &SaveCustomRoleRequest,
+        existing_id: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let name = request.name.trim();
+        if name.is_empty() {
+            return Err(AppError::Validation("Role name is required".to_string()));
+        }
+        let taken = self
+            .role_repo
+            .list_by_team(team_id)
+            .await?
+            .into_iter()
+            .any(|r| r.name.eq_ignore_ascii_case(name) && Some(r.id.as_str()) != existing_id);
+        if taken {
+            return Err(AppError::Conflict(format!(
+                "A role named '{}' already exists",
+                name
+            )));
+        }
+
+        let permissions = validate_permissions(&request.permissions, PERMISSIONS)
+            .map_err(AppError::Validation)?;
+        if permissions.iter().any(|p| p == permission::TEAM_DELETE) {
+            return Err(AppError::Validation(
+                "Deleting a team is reserved for its owner".to_string(),
+            ));
+        }
+        check_grantable(actor, &permissions)?;
+        Ok(permissions)
+    }
+
+    /// Effective permissions of the user in a team
+    pub async fn team_permissions(&self, team_id: &str, user_id: &str) -> Result<PermissionSet> {
+        self.authz.team_permissions(team_id, user_id).await
+    }
+
+    /// Effective permissions of the user on a stack
+    pub async fn stack_permissions(&self, stack_id: &str, user_id: &str) -> Result<PermissionSet> {
+        let stack = self.stack_repo.find_by_id_internal(stack_id).await?;
+        self.authz.stack_permissions(&stack, user_id).await
+    }
+
+    pub async fn list_roles(
+        &self,
+        team_id: &str,
+        user_id: &str,
+    ) -> Result<Vec<CustomRoleResponse>> {
+        self.authz
+            .require(team_id, user_id, permission::TEAM_VIEW)
+            .await?;
+        let roles = self.role_repo.list_by_team(team_id).await?;
+        Ok(roles.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn create_role(
+        &self,
+        team_id: &str,
+        user_id: &str,
+        request: SaveCustomRoleRequest,
+    ) -> Result<CustomRoleResponse> {
+        self.authz
+            .require(team_id, user_id, permission::TEAM_ROLES_MANAGE)
+            .await?;
+        let actor = self.authz.team_permissions(team_id, user_id).await?;
+        let permissions = self
+            .validate_request(team_id, &actor, &request, None)
+            .await?;
+
+        let now = timestamp();
+        let role = CustomRole {
+            id: Uuid::new_v4().to_string(),
+            team_id: team_id.to_string(),
+            name: request.name.trim().to_string(),
+            description: request.description.filter(|d| !d.trim().is_empty()),
+            permissions: permissions.join(" "),
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        self.role_repo.save(&role).await?;
+
+        self.audit
+            .record(
+                Some(user_id),
+                Some(team_id),
+                "team.role_create",
+                "role",
+                &role.id,
+                serde_json::json!({ "name": role.name, "permissions": permissions }),
+            )
+            .await?;
+        Ok(role.into())
+    }
+
+    pub async fn update_role(
+        &self,
+        team_id: &str,
+        role_id: &str,
+        user_id: &str,
+        request: SaveCustomRoleRequest,
+    ) -> Result<CustomRoleResponse> {
+        self.authz
+            .require(team_id, user_id, permission::TEAM_ROLES_MANAGE)
+            .await?;
+        let mut role = self.find_role(team_id, role_id).await?;
+        let actor = self.authz.team_permissions(team_id, user_id).await?;
+        // Editing a role also takes away its old permissions, so those must be held too
+        check_grantable(&actor, &role.permission_list())?;
+        let permissions = self
+            .validate_request(team_id, &actor, &request, Some(role_id))
+            .await?;
+
+        let previous = role.permission_list();
+        role.name = request.name.trim().to_string();
+        role.description = request.description.filter(|d| !d.trim().is_empty());
+        role.permissions = permissions.join(" ");
+        role.updated_at = timestamp();
+        self.role_repo.save(&role).await?;
+
+        self.audit
+            .record(
+                Some(user_id),
+                Some(team_id),
+                "team.role_update",
+                "role",
+                &role.id,
+                serde_json::json!({
+                    "name": role.name,
+                    "from": previous,
+                    "to": permissions,
+                }),
+            )
+            .await?;
+        Ok(role.into())
+    }
+
+    pub async fn delete_role(&self, team_id: &str, role_id: &str, user_id: &str) -> Result<()> {
+        self.authz
+            .require(team_id, user_id, permission::TEAM_ROLES_MANAGE)
+            .await?;
+        let role = self.find_role(team_id, role_id).await?;
+        let actor = self.authz.team_permissions(team_id, user_id).await?;
+        check_grantable(&actor, &role.permission_list())?;
+
+        self.role_repo.delete(role_id).await?;
+        self.audit
+            .record(
+                Some(user_id),
+                Some(team_id),
+                "team.role_delete",
+                "role",
+                role_id,
+                serde_json::json!({ "name": role.name }),
+            )
+            .await
+    }
+
+    /// Give a member a custom role, or take it away with `role_id: None`
+    pub async fn assign_role(
+        &self,
+        team_id: &str,
+        member_id: &str,
+        role_id: Option<&str>,
+        actor_id: &str,
+    ) -> Result<()> {
+        let actor_role = self
+            .authz
+            .require(team_id, actor_id, permission::TEAM_ROLES_MANAGE)
+            .await?;
+        if member_id == actor_id {
+            return Err(AppError::BadRequest(
+                "Cannot change your own role".to_string(),
+            ));
+        }
+
+        let target_role = self
+            .team_repo
+            .get_user_role(team_id, member_id)
+            .await?
+            .ok_or(AppError::NotFound("Member not found".to_string()))?;
+        if target_role == TeamRole::Owner {
+            return Err(AppError::BadRequest(
+                "Cannot change the role of the team owner".to_string(),
+            ));
+        }
+        if actor_role != TeamRole::Owner && target_role.rank() >= actor_role.rank() {
+            return Err(AppError::Forbidden(
+                "Cannot modify a member with equal or higher role".to_string(),
+            ));
+        }
+
+        let actor = self.authz.team_permissions(team_id, actor_id).await?;
+        if let Some(current) = self.role_repo.find_member_role(team_id, member_id).await? {
+            check_grantable(&actor, &current.permission_list())?;
+        }
+        let role = match role_id {
+            Some(role_id) => {
+                let role = self.find_role(team_id, role_id).await?;
+                check_grantable(&actor, &role.permission_list())?;
+                Some(role)
+            }
+            None => None,
+        };
+
+        self.role_repo
+            .set_member_role(team_id, member_id, role.as_ref().map(|r| r.id.as_str()))
+            .await?;
+        self.audit
+            .record(
+                Some(actor_id),
+                Some(team_id),
+                "team.role_assign",
+                "team",
+                team_id,
+                serde_json::json!({
+                    "user_id": member_id,
+                    "role": role.map(|r| r.name),
+                }),
+            )
+            .await
+    }
+
+    pub async fn list_grants(
+        &self,
+        stack_id: &str,
+        user_id: &str,
+    ) -> Result<Vec<StackGrantResponse>> {
+        let stack = self.stack_repo.find_by_id_internal(stack_id).await?;
+        self.authz
+            .require(&stack.team_id, user_id, permission::TEAM_ROLES_MANAGE)
+            .await?;
+        let grants = self.grant_repo.list_by_stack(stack_id).await?;
+        Ok(grants.into_iter().map(Into::into).collect())
+    }
+
+    /// Create or replace the grant of one user on a stack
+    pub async fn save_grant(
+        &self,
+        stack_id: &str,
+        actor_id: &str,
+        request: SaveStackGrantRequest,
+    ) -> Result<()> {
+        let stack = self.stack_repo.find_by_id_internal(stack_id).await?;
+        self.authz
+            .require(&stack.team_id, actor_id, permission::TEAM_ROLES_MANAGE)
+            .await?;
+
+        let mut permissions = validate_permissions(&request.permissions, STACK_PERMISSIONS)
+            .map_err(AppError::Validation)?;
+        if !permissions.iter().any(|p| p == permission::STACK_VIEW) {
+            permissions.push(permission::STACK_VIEW.to_string());
+            permissions.sort();
+        }
+        let actor = self.authz.stack_permissions(&stack, actor_id).await?;
+        check_grantable(&actor, &permissions)?;
+
+        let user = self
+            .user_repo
+            .find_by_email(request.email.trim())
+            .await?
+            .filter(|u| u.role != BOT_USER_ROLE && u.deactivated_at.is_none())
+            .ok_or(AppError::NotFound("No user with this email".to_string()))?;
+        if user.id == actor_id {
+            return Err(AppError::BadRequest(
+                "Cannot grant permissions to yourself".to_string(),
+            ));
+        }
+
+        let now = timestamp();
+        self.grant_repo
+            .save(&StackGrant {
+                stack_id: stack.id.clone(),
+                user_id: user.id.clone(),
+                permissions: permissions.join(" "),
+                granted_by: Some(actor_id.to_string()),
+                created_at: now.clone(),
+                updated_at: now,
+            })
+            .await?;
+
+        self.audit
+            .record(
+                Some(actor_id),
+                Some(&stack.team_id),
+                "stack.grant",
+                "stack",
+                &stack.id,
+                serde_json::json!({
+                    "user_id": user.id,
+                    "email": user.email,
+                    "permissions": permissions,
+                }),
+            )
+            .await
+    }
+
+    pub async fn revoke_grant(&self, stack_id: &str, user_id: &str, actor_id: &str) -> Result<()> {
+        let stack = self.stack_repo.find_by_id_internal(stack_id).await?;
+        self.authz
+            .require(&stack.team_id, actor_id, permission::TEAM_ROLES_MANAGE)
+            .await?;
+        let grant = self
+            .grant_repo
+            .find(stack_id, user_id)
+            .await?
+            .ok_or(AppError::NotFound("Grant not found".to_string()))?;
+        let actor = self.authz.stack_permissions(&stack, actor_id).await?;
+        check_grantable(&actor, &grant.permission_list())?;
+
+        self.grant_repo.delete(stack_id, user_id).await?;
+        self.audit
+            .record(
+                Some(actor_id),
+                Some(&stack.team_id),
+                "stack.grant_revoke",
+                "stack",
+                &stack.id,
+                serde_json::json!({ "user_id": user_id }),
+            )
+            .await
+    }
+}
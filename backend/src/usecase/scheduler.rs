@@ -62,7 +62,7 @@ impl AutomationScheduler {
                 let usecase = self.stack_usecase.clone();
 
                 tokio::spawn(async move {
-                    if let Err(e) = usecase.redeploy_stack(&stack_id).await {
+                    if let Err(e) = usecase.redeploy_stack(&stack_id, None, "schedule").await {
                         tracing::error!("Scheduled redeploy failed for stack {}: {}", stack_id, e);
                     }
                 });
@@ -0,0 +1,133 @@
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use std::sync::Arc;
+
+use crate::domain::secret_cipher::SecretCipher;
+use crate::domain::secret_repository::{SecretKind, SecretRepository};
+use crate::error::Result;
+
+pub struct SecretUsecase {
+    repo: Arc<dyn SecretRepository>,
+    cipher: Arc<dyn SecretCipher>,
+}
+
+impl SecretUsecase {
+    pub fn new(repo: Arc<dyn SecretRepository>, cipher: Arc<dyn SecretCipher>) -> Self {
+        Self { repo, cipher }
+    }
+
+    /// Encrypt rows written before encryption at rest existed
+    pub async fn encrypt_legacy_secrets(&self) -> Result<usize> {
+        let mut pending = Vec::new();
+        for mut secret in self.repo.list_all().await? {
+            if self.cipher.is_encrypted(&secret.value) {
+                continue;
+            }
+
+            // Registry passwords used to be stored as plain base64
+            let plaintext = match secret.kind {
+                SecretKind::RegistryPassword => BASE64
+                    .decode(&secret.value)
+                    .ok()
+                    .and_then(|bytes| String::from_utf8(bytes).ok())
+                    .unwrap_or_else(|| secret.value.clone()),
+                _ => secret.value.clone(),
+            };
+
+            secret.value = self.cipher.encrypt(&plaintext)?;
+            pending.push(secret);
+        }
+
+        if !pending.is_empty() {
+            self.repo.update_all(&pending).await?;
+        }
+
+        Ok(pending.len())
+    }
+
+    /// Re-encrypt every secret under `new_cipher` in one transaction
+    pub async fn rotate(&self, new_cipher: &dyn SecretCipher) -> Result<usize> {
+        let mut secrets = self.repo.list_all().await?;
+        for secret in &mut secrets {
+            let plaintext = self.cipher.decrypt(&secret.value)?;
+            secret.value = new_cipher.encrypt(&plaintext)?;
+        }
+
+        self.repo.update_all(&secrets).await?;
+        Ok(secrets.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::crypto::envelope::EnvelopeCipher;
+    use crate::infrastructure::sqlite::secret::SqliteSecretRepository;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[tokio::test]
+    async fn test_rotate_reencrypts_every_secret() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        crate::db::run_migrations(&pool).await.unwrap();
+
+        let old = Arc::new(EnvelopeCipher::generate());
+        for query in [
+            "INSERT INTO users (id, email, password_hash) VALUES ('u1', 'u1@example.com', '')",
+            "INSERT INTO teams (id, name, created_at, updated_at) VALUES ('t1', 't1', '', '')",
+        ] {
+            sqlx::query(query).execute(&pool).await.unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO dns_configs (id, team_id, provider, config, created_at, updated_at) VALUES ('c1', 't1', 'Cloudflare', ?, '', '')",
+        )
+        .bind(old.encrypt("{}").unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO user_totp (user_id, secret, created_at) VALUES ('u1', ?, '')")
+            .bind(old.encrypt("JBSWY3DPEHPK3PXP").unwrap())
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO stacks (id, name, user_id, team_id, created_at, updated_at) VALUES ('s1', 's1', 'u1', 't1', '', '')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO domains (id, stack_id, container_name, domain, custom_certificate, custom_private_key, created_at) VALUES ('d1', 's1', 'web', 'app.example.com', ?, ?, '')",
+        )
+        .bind(old.encrypt("CERT PEM").unwrap())
+        .bind(old.encrypt("KEY PEM").unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+        // Domains on ACME certificates hold nothing to rotate
+        sqlx::query(
+            "INSERT INTO domains (id, stack_id, container_name, domain, created_at) VALUES ('d2', 's1', 'web', 'b.example.com', '')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let repo = Arc::new(SqliteSecretRepository::new(pool));
+        let new = EnvelopeCipher::generate();
+        let usecase = SecretUsecase::new(repo.clone(), old.clone());
+        assert_eq!(usecase.rotate(&new).await.unwrap(), 4);
+
+        let stored = repo.list_all().await.unwrap();
+        let plaintext = |kind: SecretKind| {
+            let secret = stored.iter().find(|s| s.kind == kind).unwrap();
+            assert!(old.decrypt(&secret.value).is_err());
+            new.decrypt(&secret.value).unwrap()
+        };
+        assert_eq!(plaintext(SecretKind::DnsConfig), "{}");
+        assert_eq!(plaintext(SecretKind::TotpSecret), "JBSWY3DPEHPK3PXP");
+        assert_eq!(plaintext(SecretKind::DomainCertificate), "CERT PEM");
+        assert_eq!(plaintext(SecretKind::DomainPrivateKey), "KEY PEM");
+    }
+}
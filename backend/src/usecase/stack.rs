@@ -1,22 +1,35 @@
 use chrono::Utc;
 use rand::distr::{Alphanumeric, SampleString};
+use sha2::{Digest, Sha256};
 use std::path::Path;
 use std::sync::Arc;
 use tokio_stream::StreamExt;
 use uuid::Uuid;
 
-use crate::domain::compose::{parse_compose, service_to_container_request};
-use crate::domain::models::TeamRole;
+use crate::domain::compose::{
+    FileObjectSource, ParsedCompose, ParsedFileMount, ParsedService, parse_compose,
+    service_to_container_request,
+};
+use crate::domain::models::permission;
 use crate::domain::models::*;
 use crate::domain::resource_repository::ResourceRepository;
-use crate::domain::runtime::RuntimePort;
+use crate::domain::runtime::{RuntimePort, ServiceFileMount, SwarmObjectInfo};
+use crate::domain::secret_cipher::SecretCipher;
 use crate::domain::stack_repository::StackRepository;
 use crate::error::{AppError, Result};
+use crate::infrastructure::crypto::passphrase::PassphraseCipher;
+use crate::infrastructure::secret_files::SecretFileStore;
+use crate::usecase::audit::AuditUsecase;
+use crate::usecase::authorization::AuthorizationUsecase;
+use crate::usecase::caddy_reconciler::CaddyReconciler;
 use crate::usecase::environment::EnvironmentUsecase;
 use crate::usecase::registry::RegistryUsecase;
 
 use crate::domain::TeamRepository;
 
+/// Largest maintenance or error page accepted; pages are part of every Caddy config push
+const MAX_PAGE_BYTES: usize = 64 * 1024;
+
 pub struct StackUsecase {
     repo: Arc<dyn StackRepository>,
     runtime: Arc<dyn RuntimePort>,
@@ -24,11 +37,16 @@ pub struct StackUsecase {
     registry_usecase: Arc<RegistryUsecase>,
     resource_repo: Arc<dyn ResourceRepository>,
     team_repo: Arc<dyn TeamRepository>,
+    authz: Arc<AuthorizationUsecase>,
+    audit: Arc<AuditUsecase>,
+    caddy: Arc<CaddyReconciler>,
     git_service: Arc<crate::infrastructure::git::GitService>,
+    secret_files: Arc<SecretFileStore>,
     build_log_tx: tokio::sync::broadcast::Sender<BuildLogMessage>,
 }
 
 impl StackUsecase {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         repo: Arc<dyn StackRepository>,
         runtime: Arc<dyn RuntimePort>,
@@ -36,6 +54,9 @@ impl StackUsecase {
         registry_usecase: Arc<RegistryUsecase>,
         resource_repo: Arc<dyn ResourceRepository>,
         team_repo: Arc<dyn TeamRepository>,
+        authz: Arc<AuthorizationUsecase>,
+        audit: Arc<AuditUsecase>,
+        caddy: Arc<CaddyReconciler>,
     ) -> Self {
         let (build_log_tx, _) = tokio::sync::broadcast::channel(1024);
         Self {
@@ -45,7 +66,11 @@ impl StackUsecase {
             registry_usecase,
             resource_repo,
             team_repo,
+            authz,
+            audit,
+            caddy,
             git_service: Arc::new(crate::infrastructure::git::GitService::new()),
+            secret_files: Arc::new(SecretFileStore::new()),
             build_log_tx,
         }
     }
@@ -58,33 +83,32 @@ impl StackUsecase {
         self.runtime.clone()
     }
 
-    /// Verify user has required role for the team
-    async fn verify_permission(
+    async fn record_activity(
         &self,
-        team_id: &str,
-        user_id: &str,
-        required_role: TeamRole,
+        stack: &Stack,
+        actor_id: Option<&str>,
+        action: &str,
+        details: serde_json::Value,
     ) -> Result<()> {
-        let role = self
-            .team_repo
-            .get_user_role(team_id, user_id)
-            .await?
-            .ok_or(AppError::Forbidden("Access denied".to_string()))?;
-
-        let role_priority = |r: TeamRole| match r {
-            TeamRole::Owner => 4,
-            TeamRole::Admin => 3,
-            TeamRole::Developer => 2,
-            TeamRole::Viewer => 1,
-        };
-
-        if role_priority(role) < role_priority(required_role) {
-            return Err(AppError::Forbidden(
-                "Insufficient permissions for this operation".to_string(),
-            ));
-        }
+        self.audit
+            .record(
+                actor_id,
+                Some(&stack.team_id),
+                action,
+                "stack",
+                &stack.id,
+                details,
+            )
+            .await
+    }
 
-        Ok(())
+    /// Load a stack after checking the user holds `permission` on it
+    pub async fn authorize(&self, id: &str, user_id: &str, permission: &str) -> Result<Stack> {
+        let stack = self.repo.find_by_id_internal(id).await?;
+        self.authz
+            .require_stack(&stack, user_id, permission)
+            .await?;
+        Ok(stack)
     }
 
     pub async fn list_stacks(&self, user_id: &str) -> Result<Vec<Stack>> {
@@ -94,6 +118,11 @@ impl StackUsecase {
             let mut stacks = self.repo.list_by_team(&team.id).await?;
             all_stacks.append(&mut stacks);
         }
+        for stack_id in self.authz.granted_stack_ids(user_id).await? {
+            if !all_stacks.iter().any(|s| s.id == stack_id) {
+                all_stacks.push(self.repo.find_by_id_internal(&stack_id).await?);
+            }
+        }
 
         let mut enriched_stacks = Vec::new();
         for mut stack in all_stacks {
@@ -115,13 +144,7 @@ impl StackUsecase {
     }
 
     pub async fn get_stack_no_health(&self, id: &str, user_id: &str) -> Result<Stack> {
-        let stack = self.repo.find_by_id_internal(id).await?;
-        let _role = self
-            .team_repo
-            .get_user_role(&stack.team_id, user_id)
-            .await?
-            .ok_or(AppError::Forbidden("Access denied".to_string()))?;
-        Ok(stack)
+        self.authorize(id, user_id, permission::STACK_VIEW).await
     }
 
     pub async fn create_stack(
@@ -132,7 +155,8 @@ impl StackUsecase {
         team_id: &str,
         env_vars: Option<std::collections::HashMap<String, String>>,
     ) -> Result<Stack> {
-        self.verify_permission(team_id, user_id, TeamRole::Developer)
+        self.authz
+            .require(team_id, user_id, permission::STACK_CREATE)
             .await?;
 
         let id = Uuid::new_v4().to_string();
@@ -154,6 +178,13 @@ impl StackUsecase {
             git_url: None,
             git_branch: None,
             last_commit_hash: None,
+            push_registry_id: None,
+            push_namespace: None,
+            push_retention: 5,
+            env_conflict_policy: ENV_SOURCE_COMPOSE.to_string(),
+            maintenance_mode: false,
+            maintenance_page: None,
+            error_pages: "{}".to_string(),
             created_at: now.clone(),
             updated_at: now,
         };
@@ -164,7 +195,10 @@ impl StackUsecase {
         if let Some(vars) = env_vars {
             let env_list: Vec<(String, String, bool)> =
                 vars.into_iter().map(|(k, v)| (k, v, false)).collect();
-            let _ = self.environment_usecase.bulk_set(&id, "", env_list).await;
+            let _ = self
+                .environment_usecase
+                .bulk_set(&id, "", env_list, user_id)
+                .await;
         }
 
         // Sync config from YAML to DB
@@ -174,6 +208,13 @@ impl StackUsecase {
             .await?;
 
         self.repo.update_status(&id, "stopped").await?;
+        self.record_activity(
+            &stack,
+            Some(user_id),
+            "stack.create",
+            serde_json::json!({ "name": name }),
+        )
+        .await?;
         self.get_stack(&id, user_id).await
     }
 
@@ -188,7 +229,8 @@ impl StackUsecase {
         team_id: &str,
         env_vars: Option<std::collections::HashMap<String, String>>,
     ) -> Result<Stack> {
-        self.verify_permission(team_id, user_id, TeamRole::Developer)
+        self.authz
+            .require(team_id, user_id, permission::STACK_CREATE)
             .await?;
 
         // 1. Setup git directory
@@ -228,6 +270,13 @@ impl StackUsecase {
             git_url: Some(git_url.to_string()),
             git_branch: Some(git_branch.to_string()),
             last_commit_hash: Some(commit_hash),
+            push_registry_id: None,
+            push_namespace: None,
+            push_retention: 5,
+            env_conflict_policy: ENV_SOURCE_COMPOSE.to_string(),
+            maintenance_mode: false,
+            maintenance_page: None,
+            error_pages: "{}".to_string(),
             created_at: now.clone(),
             updated_at: now,
         };
@@ -238,7 +287,10 @@ impl StackUsecase {
         if let Some(vars) = env_vars {
             let env_list: Vec<(String, String, bool)> =
                 vars.into_iter().map(|(k, v)| (k, v, false)).collect();
-            let _ = self.environment_usecase.bulk_set(&id, "", env_list).await;
+            let _ = self
+                .environment_usecase
+                .bulk_set(&id, "", env_list, user_id)
+                .await;
         }
 
         // Sync config from YAML to DB
@@ -249,12 +301,19 @@ impl StackUsecase {
             .await?;
 
         self.repo.update_status(&id, "stopped").await?;
+        self.record_activity(
+            &stack,
+            Some(user_id),
+            "stack.create",
+            serde_json::json!({ "name": name, "git_url": git_url, "git_branch": git_branch }),
+        )
+        .await?;
         self.get_stack(&id, user_id).await
     }
 
     pub async fn sync_git(&self, id: &str, user_id: &str) -> Result<()> {
-        let stack = self.get_stack(id, user_id).await?;
-        self.verify_permission(&stack.team_id, user_id, TeamRole::Developer)
+        let stack = self
+            .authorize(id, user_id, permission::STACK_DEPLOY)
             .await?;
         let git_url = stack
             .git_url
@@ -290,7 +349,7 @@ impl StackUsecase {
         let _ = self.sync_compose_to_db(id).await;
 
         // 4. Redeploy
-        self.redeploy_stack(id).await?;
+        self.redeploy_stack(id, Some(user_id), "git_sync").await?;
 
         Ok(())
     }
@@ -311,6 +370,9 @@ impl StackUsecase {
             self.runtime.ensure_network(net_name).await?;
         }
 
+        let mut swarm_objects = std::collections::HashSet::new();
+        let secret_keys = file_secret_keys(&parsed);
+
         for service in &parsed.services {
             if let Some(target) = service_name
                 && service.name != target
@@ -319,6 +381,7 @@ impl StackUsecase {
                 continue;
             }
             let mut config = service_to_container_request(service, &stack.id, &stack.name);
+            let mut registry_auth = None;
 
             // 1. Handle image preparation (Pull or Build)
             if let Some(build) = &service.build {
@@ -354,6 +417,15 @@ impl StackUsecase {
                             }
                         }
                     }
+
+                    // Push to the team registry so every swarm node can pull the build
+                    if let Some(registry_id) = &stack.push_registry_id {
+                        let (image, auth) = self
+                            .push_built_image(stack, registry_id, &service.name, &config.image)
+                            .await?;
+                        config.image = image;
+                        registry_auth = Some(auth);
+                    }
                 } else {
                     return Err(AppError::BadRequest(format!(
                         "Service '{}' specifies a build context but no base path is provided (is this a Git stack?)",
@@ -366,15 +438,19 @@ impl StackUsecase {
                     .registry_usecase
                     .get_credentials_for_image_internal(&stack.team_id, &config.image)
                     .await?;
-                self.runtime.pull_image(&config.image, creds).await?;
+                self.runtime
+                    .pull_image(&config.image, creds.clone())
+                    .await?;
+                registry_auth = creds;
             }
 
             // 2. Prepare environment and resource limits
-            let db_env = self
+            let mut db_env = self
                 .environment_usecase
                 .get_env_map_for_container(&stack.id, &service.name)
                 .await
                 .unwrap_or_default();
+            db_env.retain(|key, _| !secret_keys.contains(key));
 
             if !db_env.is_empty() {
                 let mut merged_env = config.env.unwrap_or_default();
@@ -429,7 +505,21 @@ impl StackUsecase {
                     cpu_limit: config.cpu_limit,
                     memory_limit: config.memory_limit,
                     constraints,
+                    registry_auth,
+                    secrets: self
+                        .prepare_swarm_files(stack, &parsed, service, base_path, true)
+                        .await?,
+                    configs: self
+                        .prepare_swarm_files(stack, &parsed, service, base_path, false)
+                        .await?,
                 };
+                swarm_objects.extend(
+                    svc_config
+                        .secrets
+                        .iter()
+                        .chain(&svc_config.configs)
+                        .map(|m| m.object_name.clone()),
+                );
 
                 // Check if service already exists
                 if let Ok(Some(_)) = self.runtime.inspect_service(&swarm_service_name).await {
@@ -446,6 +536,9 @@ impl StackUsecase {
                 }
             } else {
                 // === STANDALONE MODE: Create Container ===
+                self.mount_standalone_files(stack, &parsed, service, base_path, &mut config)
+                    .await?;
+
                 let containers = self.get_stack_containers(&stack.id).await?;
                 let prefix = format!("/{}-{}", stack.name, service.name);
                 for c in containers {
@@ -457,13 +550,456 @@ impl StackUsecase {
                 self.runtime.create_container(config).await?;
             }
         }
+
+        if is_swarm && service_name.is_none() {
+            self.prune_swarm_files(stack, &swarm_objects).await;
+        }
+        Ok(())
+    }
+
+    /// Read the content of a compose secret or config.
+    /// Secrets must be stack-wide secret variables; configs may also be plain variables,
+    /// inline content or a file from the Git checkout.
+    async fn resolve_file_content(
+        &self,
+        stack: &Stack,
+        name: &str,
+        source: &FileObjectSource,
+        base_path: Option<&str>,
+        is_secret: bool,
+    ) -> Result<Vec<u8>> {
+        let kind = if is_secret { "Secret" } else { "Config" };
+        match source {
+            FileObjectSource::Store(key) => {
+                let vars = self.environment_usecase.get_raw_vars(&stack.id).await?;
+                let var = vars
+                    .into_iter()
+                    .find(|v| v.container_name.is_empty() && &v.key == key)
+                    .ok_or_else(|| {
+                        AppError::Validation(format!(
+                            "{} '{}' has no value: add '{}' as a stack environment variable",
+                            kind, name, key
+                        ))
+                    })?;
+                if is_secret && !var.is_secret {
+                    return Err(AppError::Validation(format!(
+                        "Secret '{}' must be stored as a secret variable ('{}' is plain text)",
+                        name, key
+                    )));
+                }
+                Ok(var.value.into_bytes())
+            }
+            FileObjectSource::Content(content) => Ok(content.clone().into_bytes()),
+            FileObjectSource::File(file) => {
+                let base = base_path.ok_or_else(|| {
+                    AppError::BadRequest(format!(
+                        "{} '{}' references a file but the stack has no Git repository",
+                        kind, name
+                    ))
+                })?;
+                read_checkout_file(base, file).await.map_err(|e| {
+                    AppError::Validation(format!(
+                        "Failed to read {} file '{}': {}",
+                        kind.to_lowercase(),
+                        file,
+                        e
+                    ))
+                })
+            }
+            FileObjectSource::External(_) => Err(AppError::Validation(format!(
+                "External {} '{}' is only available in Swarm mode",
+                kind.to_lowercase(),
+                name
+            ))),
+        }
+    }
+
+    /// Create (or reuse) the swarm secrets/configs a service mounts.
+    /// Objects are immutable, so their names carry a content hash and a changed value
+    /// produces a new object that the rolling update switches to.
+    async fn prepare_swarm_files(
+        &self,
+        stack: &Stack,
+        parsed: &ParsedCompose,
+        service: &ParsedService,
+        base_path: Option<&str>,
+        is_secret: bool,
+    ) -> Result<Vec<ServiceFileMount>> {
+        let (mounts, declared) = if is_secret {
+            (&service.secrets, &parsed.secrets)
+        } else {
+            (&service.configs, &parsed.configs)
+        };
+        if mounts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut existing = if is_secret {
+            self.runtime.list_secrets().await?
+        } else {
+            self.runtime.list_configs().await?
+        };
+
+        let mut result = Vec::new();
+        for mount in mounts {
+            let source = &declared[&mount.source];
+            let object = if let FileObjectSource::External(external_name) = source {
+                existing
+                    .iter()
+                    .find(|o| &o.name == external_name)
+                    .cloned()
+                    .ok_or_else(|| {
+                        AppError::Validation(format!(
+                            "External {} '{}' does not exist in the swarm",
+                            if is_secret { "secret" } else { "config" },
+                            external_name
+                        ))
+                    })?
+            } else {
+                let data = self
+                    .resolve_file_content(stack, &mount.source, source, base_path, is_secret)
+                    .await?;
+                let digest: String = Sha256::digest(&data)[..6]
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect();
+                let object_name = format!("{}_{}_{}", stack.name, mount.source, digest);
+
+                match existing.iter().find(|o| o.name == object_name) {
+                    Some(object) => object.clone(),
+                    None => {
+                        let labels = std::collections::HashMap::from([
+                            ("labuh.managed".to_string(), "true".to_string()),
+                            ("labuh.stack.id".to_string(), stack.id.clone()),
+                            ("labuh.stack.name".to_string(), stack.name.clone()),
+                        ]);
+                        let id = if is_secret {
+                            self.runtime
+                                .create_secret(&object_name, &data, labels.clone())
+                                .await?
+                        } else {
+                            self.runtime
+                                .create_config(&object_name, &data, labels.clone())
+                                .await?
+                        };
+                        let object = SwarmObjectInfo {
+                            id,
+                            name: object_name,
+                            labels,
+                        };
+                        existing.push(object.clone());
+                        object
+                    }
+                }
+            };
+
+            result.push(ServiceFileMount {
+                object_id: object.id,
+                object_name: object.name,
+                target: mount.target.clone(),
+                uid: mount.uid.clone(),
+                gid: mount.gid.clone(),
+                mode: mount.mode,
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// Remove swarm secrets/configs of this stack that are not in `keep`.
+    /// Docker refuses to delete objects a service still references, so failures are ignored.
+    async fn prune_swarm_files(&self, stack: &Stack, keep: &std::collections::HashSet<String>) {
+        let owned = |o: &SwarmObjectInfo| {
+            o.labels.get("labuh.stack.id") == Some(&stack.id) && !keep.contains(&o.name)
+        };
+
+        for secret in self.runtime.list_secrets().await.unwrap_or_default() {
+            if owned(&secret) {
+                let _ = self.runtime.remove_secret(&secret.id).await;
+            }
+        }
+        for config in self.runtime.list_configs().await.unwrap_or_default() {
+            if owned(&config) {
+                let _ = self.runtime.remove_config(&config.id).await;
+            }
+        }
+    }
+
+    /// Write a standalone service's secrets and configs to disk and bind-mount them read-only
+    async fn mount_standalone_files(
+        &self,
+        stack: &Stack,
+        parsed: &ParsedCompose,
+        service: &ParsedService,
+        base_path: Option<&str>,
+        config: &mut crate::domain::runtime::ContainerConfig,
+    ) -> Result<()> {
+        self.secret_files
+            .clear_service(&stack.id, &service.name)
+            .await?;
+
+        let mounts = service
+            .secrets
+            .iter()
+            .map(|m| (m, &parsed.secrets, true))
+            .chain(service.configs.iter().map(|m| (m, &parsed.configs, false)));
+
+        let mut binds = Vec::new();
+        for (mount, declared, is_secret) in mounts {
+            let data = self
+                .resolve_file_content(
+                    stack,
+                    &mount.source,
+                    &declared[&mount.source],
+                    base_path,
+                    is_secret,
+                )
+                .await?;
+            let host_path = self
+                .secret_files
+                .write_file(
+                    &stack.id,
+                    &service.name,
+                    &mount.target,
+                    &data,
+                    mount.mode.unwrap_or(0o444),
+                    file_owner(mount),
+                )
+                .await?;
+            binds.push(format!("{}:{}:ro", host_path, mount.target));
+        }
+
+        if !binds.is_empty() {
+            config.volumes.get_or_insert_with(Vec::new).extend(binds);
+        }
+        Ok(())
+    }
+
+    /// Tag a freshly built image for the stack's push registry, push it and prune old tags.
+    /// Returns the pushed reference and the credentials needed to pull it.
+    async fn push_built_image(
+        &self,
+        stack: &Stack,
+        registry_id: &str,
+        service_name: &str,
+        local_image: &str,
+    ) -> Result<(String, (String, String))> {
+        let (cred, password) = self
+            .registry_usecase
+            .get_push_target(&stack.team_id, registry_id)
+            .await?;
+
+        let timestamp = Utc::now().format("%Y%m%d%H%M%S").to_string();
+        let tag = match &stack.last_commit_hash {
+            Some(hash) => format!("{}-{}", &hash[..hash.len().min(12)], timestamp),
+            None => timestamp,
+        };
+        let image_ref = self.registry_usecase.push_reference(
+            &cred,
+            stack.push_namespace.as_deref(),
+            &format!("{}-{}", stack.name, service_name),
+            &tag,
+        );
+
+        let _ = self.build_log_tx.send(BuildLogMessage {
+            stack_id: stack.id.clone(),
+            service: service_name.to_string(),
+            message: format!("Pushing image {}", image_ref),
+            is_error: false,
+        });
+
+        self.runtime.tag_image(local_image, &image_ref).await?;
+        let auth = (cred.username.clone(), password.clone());
+        if let Err(e) = self
+            .runtime
+            .push_image(&image_ref, Some(auth.clone()))
+            .await
+        {
+            let _ = self.build_log_tx.send(BuildLogMessage {
+                stack_id: stack.id.clone(),
+                service: service_name.to_string(),
+                message: e.to_string(),
+                is_error: true,
+            });
+            return Err(e);
+        }
+
+        self.repo
+            .record_pushed_image(PushedImage {
+                id: Uuid::new_v4().to_string(),
+                stack_id: stack.id.clone(),
+                service_name: service_name.to_string(),
+                image_ref: image_ref.clone(),
+                pushed_at: Utc::now().to_rfc3339(),
+            })
+            .await?;
+
+        if let Err(e) = self
+            .prune_pushed_images(stack, service_name, &cred, &password)
+            .await
+        {
+            tracing::warn!("Failed to prune pushed images for {}: {}", stack.name, e);
+        }
+
+        Ok((image_ref, auth))
+    }
+
+    /// Remove pushed tags beyond the stack's retention count, keeping the rollback images
+    async fn prune_pushed_images(
+        &self,
+        stack: &Stack,
+        service_name: &str,
+        cred: &RegistryCredential,
+        password: &str,
+    ) -> Result<()> {
+        let stable_images: std::collections::HashMap<String, String> = stack
+            .last_stable_images
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default();
+
+        let pushed = self
+            .repo
+            .list_pushed_images(&stack.id, Some(service_name))
+            .await?;
+        let retention = stack.push_retention.max(1) as usize;
+
+        for image in pushed.into_iter().skip(retention) {
+            if stable_images.values().any(|i| i == &image.image_ref) {
+                continue;
+            }
+
+            tracing::info!("Removing old pushed image {}", image.image_ref);
+            let _ = self.runtime.remove_image(&image.image_ref, false).await;
+            if let Err(e) = self
+                .registry_usecase
+                .delete_remote_image(cred, password, &image.image_ref)
+                .await
+            {
+                tracing::warn!("Failed to delete {} from registry: {}", image.image_ref, e);
+            }
+            self.repo.delete_pushed_image(&image.id).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn update_registry_push(
+        &self,
+        id: &str,
+        user_id: &str,
+        registry_id: Option<String>,
+        namespace: Option<String>,
+        retention: i32,
+    ) -> Result<()> {
+        let stack = self.authorize(id, user_id, permission::STACK_EDIT).await?;
+
+        if retention < 1 {
+            return Err(AppError::Validation(
+                "Retention must keep at least one image".to_string(),
+            ));
+        }
+
+        if let Some(registry_id) = &registry_id {
+            self.registry_usecase
+                .get_push_target(&stack.team_id, registry_id)
+                .await?;
+        }
+
+        self.repo
+            .update_registry_push(id, registry_id, namespace, retention)
+            .await
+    }
+
+    /// Choose whether compose (`compose`) or dashboard/API values (`ui`) win when both set a variable
+    pub async fn update_env_conflict_policy(
+        &self,
+        id: &str,
+        user_id: &str,
+        policy: &str,
+    ) -> Result<()> {
+        self.authorize(id, user_id, permission::STACK_EDIT).await?;
+        if policy != ENV_SOURCE_COMPOSE && policy != ENV_SOURCE_UI {
+            return Err(AppError::Validation(
+                "Conflict policy must be 'compose' or 'ui'".to_string(),
+            ));
+        }
+        self.repo.update_env_conflict_policy(id, policy).await
+    }
+
+    /// Serve the maintenance page for the stack's domains instead of its containers
+    pub async fn set_maintenance_mode(&self, id: &str, user_id: &str, enabled: bool) -> Result<()> {
+        let stack = self
+            .authorize(id, user_id, permission::STACK_DEPLOY)
+            .await?;
+        self.repo.update_maintenance_mode(id, enabled).await?;
+        self.caddy.reconcile().await?;
+        self.record_activity(
+            &stack,
+            Some(user_id),
+            "stack.maintenance",
+            serde_json::json!({ "enabled": enabled }),
+        )
+        .await
+    }
+
+    /// Replace the maintenance page and the 502/503/504 pages; empty pages fall
+    /// back to the built-in maintenance page and Caddy's plain errors
+    pub async fn update_pages(
+        &self,
+        id: &str,
+        user_id: &str,
+        maintenance_page: Option<String>,
+        mut error_pages: std::collections::BTreeMap<u16, String>,
+    ) -> Result<()> {
+        self.authorize(id, user_id, permission::DOMAIN_MANAGE)
+            .await?;
+        error_pages.retain(|_, page| !page.trim().is_empty());
+        if let Some(status) = error_pages
+            .keys()
+            .find(|status| !ERROR_PAGE_STATUSES.contains(status))
+        {
+            return Err(AppError::Validation(format!(
+                "No custom page for status {}; only 502, 503 and 504 are supported",
+                status
+            )));
+        }
+        let maintenance_page = maintenance_page.filter(|page| !page.trim().is_empty());
+        if maintenance_page
+            .iter()
+            .chain(error_pages.values())
+            .any(|page| page.len() > MAX_PAGE_BYTES)
+        {
+            return Err(AppError::Validation(format!(
+                "Pages are limited to {} KB",
+                MAX_PAGE_BYTES / 1024
+            )));
+        }
+
+        let error_pages =
+            serde_json::to_string(&error_pages).map_err(|e| AppError::Internal(e.to_string()))?;
+        self.repo
+            .update_pages(id, maintenance_page.as_deref(), &error_pages)
+            .await?;
+        self.caddy.reconcile().await?;
         Ok(())
     }
 
+    pub async fn list_pushed_images(&self, id: &str, user_id: &str) -> Result<Vec<PushedImage>> {
+        let stack = self.get_stack_no_health(id, user_id).await?;
+        self.repo.list_pushed_images(&stack.id, None).await
+    }
+
     pub async fn start_stack(&self, id: &str, user_id: &str) -> Result<()> {
-        let stack = self.get_stack(id, user_id).await?;
-        self.verify_permission(&stack.team_id, user_id, TeamRole::Developer)
+        let stack = self
+            .authorize(id, user_id, permission::STACK_DEPLOY)
             .await?;
+        self.start_containers(&stack).await?;
+        self.record_activity(&stack, Some(user_id), "stack.start", serde_json::json!({}))
+            .await
+    }
+
+    async fn start_containers(&self, stack: &Stack) -> Result<()> {
         let containers = self.get_stack_containers(&stack.id).await?;
 
         for container in containers {
@@ -472,13 +1008,13 @@ impl StackUsecase {
             }
         }
 
-        self.repo.update_status(id, "running").await?;
+        self.repo.update_status(&stack.id, "running").await?;
         Ok(())
     }
 
     pub async fn stop_stack(&self, id: &str, user_id: &str) -> Result<()> {
-        let stack = self.get_stack(id, user_id).await?;
-        self.verify_permission(&stack.team_id, user_id, TeamRole::Developer)
+        let stack = self
+            .authorize(id, user_id, permission::STACK_DEPLOY)
             .await?;
         let containers = self.get_stack_containers(&stack.id).await?;
 
@@ -489,10 +1025,67 @@ impl StackUsecase {
         }
 
         self.repo.update_status(id, "stopped").await?;
-        Ok(())
+        self.record_activity(&stack, Some(user_id), "stack.stop", serde_json::json!({}))
+            .await
+    }
+
+    /// Rebuild and restart a stack. `actor_id` is `None` for webhook and scheduled
+    /// deploys; `trigger` says what started it and is kept in the audit log.
+    pub async fn redeploy_stack(
+        &self,
+        id: &str,
+        actor_id: Option<&str>,
+        trigger: &str,
+    ) -> Result<()> {
+        self.caddy.set_deploying(id, true).await;
+        let result = self.deploy(id).await;
+        self.caddy.set_deploying(id, false).await;
+
+        let stack = self.repo.find_by_id_internal(id).await?;
+        self.record_activity(
+            &stack,
+            actor_id,
+            "stack.deploy",
+            serde_json::json!({
+                "trigger": trigger,
+                "success": result.is_ok(),
+                "error": result.as_ref().err().map(|e| e.to_string()),
+            }),
+        )
+        .await?;
+
+        result
     }
 
-    pub async fn redeploy_stack(&self, id: &str) -> Result<()> {
+    /// Redeploy each stack the user may deploy, one after another. A failing stack
+    /// is reported and does not stop the others.
+    pub async fn redeploy_stacks(
+        &self,
+        stack_ids: Vec<String>,
+        user_id: &str,
+        trigger: &str,
+    ) -> BulkRedeployResult {
+        let mut result = BulkRedeployResult::default();
+        for stack_id in stack_ids {
+            let redeployed = match self
+                .authorize(&stack_id, user_id, permission::STACK_DEPLOY)
+                .await
+            {
+                Ok(_) => self.redeploy_stack(&stack_id, Some(user_id), trigger).await,
+                Err(e) => Err(e),
+            };
+            match redeployed {
+                Ok(()) => result.redeployed.push(stack_id),
+                Err(e) => result.failed.push(RedeployFailure {
+                    stack_id,
+                    error: e.to_string(),
+                }),
+            }
+        }
+        result
+    }
+
+    async fn deploy(&self, id: &str) -> Result<()> {
         let stack = self.repo.find_by_id_internal(id).await?;
         let compose_content = stack.compose_content.clone().ok_or_else(|| {
             crate::error::AppError::BadRequest("Stack has no compose content".to_string())
@@ -515,7 +1108,7 @@ impl StackUsecase {
             .await?;
 
         // 4. Start all containers
-        self.start_stack(id, &stack.user_id).await?;
+        self.start_containers(&stack).await?;
 
         // 5. Perform health check
         if let Err(e) = self.perform_health_check(id).await {
@@ -524,7 +1117,7 @@ impl StackUsecase {
                 id,
                 e
             );
-            self.rollback_stack(id, &stack.user_id).await?;
+            self.rollback(&stack).await?;
             return Err(e);
         }
 
@@ -532,8 +1125,8 @@ impl StackUsecase {
     }
 
     pub async fn build_stack(&self, id: &str, user_id: &str) -> Result<()> {
-        let stack = self.get_stack(id, user_id).await?;
-        self.verify_permission(&stack.team_id, user_id, TeamRole::Developer)
+        let stack = self
+            .authorize(id, user_id, permission::STACK_DEPLOY)
             .await?;
         let compose_content = stack.compose_content.clone().ok_or_else(|| {
             crate::error::AppError::BadRequest("Stack has no compose content".to_string())
@@ -564,8 +1157,8 @@ impl StackUsecase {
     }
 
     pub async fn build_service(&self, id: &str, service_name: &str, user_id: &str) -> Result<()> {
-        let stack = self.get_stack(id, user_id).await?;
-        self.verify_permission(&stack.team_id, user_id, TeamRole::Developer)
+        let stack = self
+            .authorize(id, user_id, permission::STACK_DEPLOY)
             .await?;
         let compose_content = stack.compose_content.clone().ok_or_else(|| {
             crate::error::AppError::BadRequest("Stack has no compose content".to_string())
@@ -601,8 +1194,8 @@ impl StackUsecase {
     }
 
     pub async fn remove_stack(&self, id: &str, user_id: &str) -> Result<()> {
-        let stack = self.get_stack(id, user_id).await?;
-        self.verify_permission(&stack.team_id, user_id, TeamRole::Developer)
+        let stack = self
+            .authorize(id, user_id, permission::STACK_DELETE)
             .await?;
 
         let is_swarm = self.runtime.is_swarm_enabled().await.unwrap_or(false);
@@ -626,8 +1219,20 @@ impl StackUsecase {
             }
         }
 
+        if is_swarm {
+            self.prune_swarm_files(&stack, &std::collections::HashSet::new())
+                .await;
+        }
+        let _ = self.secret_files.remove_stack(&stack.id).await;
+
         self.repo.delete(id).await?;
-        Ok(())
+        self.record_activity(
+            &stack,
+            Some(user_id),
+            "stack.delete",
+            serde_json::json!({ "name": stack.name }),
+        )
+        .await
     }
 
     pub async fn get_stack_health(&self, id: &str, user_id: &str) -> Result<StackHealth> {
@@ -717,22 +1322,21 @@ impl StackUsecase {
         compose_content: &str,
         user_id: &str,
     ) -> Result<()> {
-        let stack = self.get_stack(id, user_id).await?;
-        self.verify_permission(&stack.team_id, user_id, TeamRole::Developer)
-            .await?;
+        self.authorize(id, user_id, permission::STACK_EDIT).await?;
         parse_compose(compose_content)?;
         self.repo.update_compose(id, compose_content).await?;
 
         // Sync updated config to DB
         self.sync_compose_to_db(id).await?;
 
-        self.redeploy_stack(id).await?;
+        self.redeploy_stack(id, Some(user_id), "compose_update")
+            .await?;
         Ok(())
     }
 
     pub async fn sync_compose_to_db(&self, stack_id: &str) -> Result<()> {
         let stack = self.repo.find_by_id_internal(stack_id).await?;
-        let compose_content = stack.compose_content.ok_or_else(|| {
+        let compose_content = stack.compose_content.clone().ok_or_else(|| {
             crate::error::AppError::BadRequest("Stack has no compose content".to_string())
         })?;
 
@@ -753,18 +1357,23 @@ impl StackUsecase {
 
             // 2. Sync environment variables
             if !service.env.is_empty() {
-                let vars: Vec<(String, String, bool)> = service
+                let vars: Vec<(String, String)> = service
                     .env
                     .iter()
                     .filter_map(|e| {
                         e.split_once('=')
-                            .map(|(k, v)| (k.to_string(), v.to_string(), false))
+                            .map(|(k, v)| (k.to_string(), v.to_string()))
                     })
                     .collect();
 
                 if !vars.is_empty() {
                     self.environment_usecase
-                        .bulk_set(stack_id, &service.name, vars)
+                        .sync_compose_vars(
+                            stack_id,
+                            &service.name,
+                            vars,
+                            stack.env_conflict_policy == ENV_SOURCE_COMPOSE,
+                        )
                         .await?;
                 }
             }
@@ -774,9 +1383,7 @@ impl StackUsecase {
     }
 
     pub async fn regenerate_webhook_token(&self, id: &str, user_id: &str) -> Result<String> {
-        let stack = self.get_stack(id, user_id).await?;
-        self.verify_permission(&stack.team_id, user_id, TeamRole::Developer)
-            .await?;
+        self.authorize(id, user_id, permission::STACK_EDIT).await?;
         let token: String = Alphanumeric.sample_string(&mut rand::rng(), 32);
         self.repo.update_webhook_token(id, &token).await?;
         Ok(token)
@@ -790,9 +1397,7 @@ impl StackUsecase {
         health_path: Option<String>,
         health_interval: i32,
     ) -> Result<()> {
-        let stack = self.get_stack(id, user_id).await?;
-        self.verify_permission(&stack.team_id, user_id, TeamRole::Developer)
-            .await?;
+        self.authorize(id, user_id, permission::STACK_EDIT).await?;
         self.repo
             .update_automation(id, cron, health_path, health_interval)
             .await?;
@@ -868,8 +1473,25 @@ impl StackUsecase {
     }
 
     pub async fn rollback_stack(&self, id: &str, user_id: &str) -> Result<()> {
-        let stack = self.get_stack(id, user_id).await?;
-        let stable_images_json = stack.last_stable_images.ok_or_else(|| {
+        let stack = self
+            .authorize(id, user_id, permission::STACK_DEPLOY)
+            .await?;
+        self.caddy.set_deploying(id, true).await;
+        let result = self.rollback(&stack).await;
+        self.caddy.set_deploying(id, false).await;
+        result?;
+        self.record_activity(
+            &stack,
+            Some(user_id),
+            "stack.rollback",
+            serde_json::json!({}),
+        )
+        .await
+    }
+
+    async fn rollback(&self, stack: &Stack) -> Result<()> {
+        let id = stack.id.as_str();
+        let stable_images_json = stack.last_stable_images.clone().ok_or_else(|| {
             AppError::BadRequest("No stable version available for rollback".to_string())
         })?;
 
@@ -879,10 +1501,12 @@ impl StackUsecase {
 
         let compose_content = stack
             .compose_content
+            .clone()
             .ok_or_else(|| AppError::BadRequest("Stack has no compose content".to_string()))?;
 
         self.repo.update_status(id, "rolling_back").await?;
         let parsed = parse_compose(&compose_content)?;
+        let secret_keys = file_secret_keys(&parsed);
 
         for service in &parsed.services {
             // Check if we have a stable image for this service
@@ -895,11 +1519,12 @@ impl StackUsecase {
             config.image = image.clone();
 
             // Apply env vars
-            let db_env = self
+            let mut db_env = self
                 .environment_usecase
                 .get_env_map_for_container(id, &service.name)
                 .await
                 .unwrap_or_default();
+            db_env.retain(|key, _| !secret_keys.contains(key));
 
             if !db_env.is_empty() {
                 let mut merged_env = config.env.unwrap_or_default();
@@ -924,6 +1549,13 @@ impl StackUsecase {
                 .await?;
             self.runtime.pull_image(&config.image, creds).await?;
 
+            let base_path = stack
+                .git_url
+                .as_ref()
+                .map(|_| format!("backend/data/git/{}", id));
+            self.mount_standalone_files(stack, &parsed, service, base_path.as_deref(), &mut config)
+                .await?;
+
             let containers = self.get_stack_containers(&stack.id).await?;
             let prefix = format!("/{}-{}", stack.name, service.name);
             for c in containers {
@@ -936,7 +1568,7 @@ impl StackUsecase {
             self.runtime.create_container(config).await?;
         }
 
-        self.start_stack(id, &stack.user_id).await?;
+        self.start_containers(stack).await?;
         self.repo.update_status(id, "rolled_back").await?;
         Ok(())
     }
@@ -947,7 +1579,9 @@ impl StackUsecase {
         service_name: &str,
         user_id: &str,
     ) -> Result<()> {
-        let stack = self.get_stack(stack_id, user_id).await?;
+        let stack = self
+            .authorize(stack_id, user_id, permission::STACK_DEPLOY)
+            .await?;
         let compose_content = stack.compose_content.clone().ok_or_else(|| {
             crate::error::AppError::BadRequest("Stack has no compose content".to_string())
         })?;
@@ -994,10 +1628,12 @@ impl StackUsecase {
             .collect())
     }
 
+    /// Check the container belongs to a stack the user holds `permission` on
     pub async fn verify_container_ownership(
         &self,
         container_id: &str,
         user_id: &str,
+        permission: &str,
     ) -> Result<crate::domain::runtime::ContainerInfo> {
         let container = self.runtime.inspect_container(container_id).await?;
         let stack_id = container
@@ -1005,32 +1641,31 @@ impl StackUsecase {
             .get("labuh.stack.id")
             .ok_or_else(|| AppError::Forbidden("Container not managed by Labuh".to_string()))?;
 
-        // Verify stack ownership (which checks team membership)
-        self.get_stack(stack_id, user_id).await?;
+        self.authorize(stack_id, user_id, permission).await?;
 
         Ok(container)
     }
 
     pub async fn start_container(&self, container_id: &str, user_id: &str) -> Result<()> {
-        self.verify_container_ownership(container_id, user_id)
+        self.verify_container_ownership(container_id, user_id, permission::STACK_DEPLOY)
             .await?;
         self.runtime.start_container(container_id).await
     }
 
     pub async fn stop_container(&self, container_id: &str, user_id: &str) -> Result<()> {
-        self.verify_container_ownership(container_id, user_id)
+        self.verify_container_ownership(container_id, user_id, permission::STACK_DEPLOY)
             .await?;
         self.runtime.stop_container(container_id).await
     }
 
     pub async fn restart_container(&self, container_id: &str, user_id: &str) -> Result<()> {
-        self.verify_container_ownership(container_id, user_id)
+        self.verify_container_ownership(container_id, user_id, permission::STACK_DEPLOY)
             .await?;
         self.runtime.restart_container(container_id).await
     }
 
     pub async fn remove_container(&self, container_id: &str, user_id: &str) -> Result<()> {
-        self.verify_container_ownership(container_id, user_id)
+        self.verify_container_ownership(container_id, user_id, permission::STACK_DEPLOY)
             .await?;
         self.runtime.remove_container(container_id, true).await
     }
@@ -1041,7 +1676,7 @@ impl StackUsecase {
         user_id: &str,
         tail: usize,
     ) -> Result<Vec<String>> {
-        self.verify_container_ownership(container_id, user_id)
+        self.verify_container_ownership(container_id, user_id, permission::STACK_VIEW)
             .await?;
         self.runtime.get_logs(container_id, tail).await
     }
@@ -1051,43 +1686,107 @@ impl StackUsecase {
         container_id: &str,
         user_id: &str,
     ) -> Result<crate::domain::runtime::ContainerStats> {
-        self.verify_container_ownership(container_id, user_id)
+        self.verify_container_ownership(container_id, user_id, permission::STACK_VIEW)
             .await?;
         self.runtime.get_stats(container_id).await
     }
 
+    /// Export a stack. Secrets are encrypted with `passphrase` (requires `env.secret.read`),
+    /// otherwise they are left out and must be supplied again on restore.
     pub async fn get_stack_backup(
         &self,
         id: &str,
         user_id: &str,
-    ) -> Result<crate::domain::models::stack::StackBackup> {
+        passphrase: Option<&str>,
+    ) -> Result<StackBackup> {
         let stack = self.get_stack(id, user_id).await?;
-        let env_vars = self.environment_usecase.get_raw_vars(&stack.id).await?;
 
-        let backup_envs = env_vars
-            .into_iter()
-            .map(|e| crate::domain::models::stack::BackupEnvVar {
-                container_name: e.container_name,
-                key: e.key,
-                value: e.value,
-                is_secret: e.is_secret,
-            })
-            .collect();
+        let backup_cipher = match passphrase {
+            Some(passphrase) => {
+                self.authz
+                    .require_stack(&stack, user_id, permission::ENV_SECRET_READ)
+                    .await?;
+                Some(PassphraseCipher::new(passphrase)?)
+            }
+            None => None,
+        };
 
-        Ok(crate::domain::models::stack::StackBackup {
+        let env_vars = self
+            .environment_usecase
+            .export_vars(
+                &stack.id,
+                user_id,
+                backup_cipher.as_ref().map(|c| c as &dyn SecretCipher),
+            )
+            .await?;
+
+        Ok(StackBackup {
             name: stack.name,
             compose_content: stack.compose_content.unwrap_or_default(),
-            env_vars: backup_envs,
+            env_vars,
+            secrets_salt: backup_cipher.map(|c| c.salt()),
         })
     }
 
     pub async fn restore_stack(
         &self,
-        backup: crate::domain::models::stack::StackBackup,
+        backup: StackBackup,
         user_id: &str,
         team_id: &str,
+        passphrase: Option<&str>,
+        secrets: Vec<RestoreSecret>,
     ) -> Result<Stack> {
-        // 1. Create the stack
+        self.authz
+            .require(team_id, user_id, permission::ENV_WRITE)
+            .await?;
+
+        // 1. Resolve secret values before touching anything
+        let backup_cipher = match (&backup.secrets_salt, passphrase) {
+            (Some(salt), Some(passphrase)) => Some(PassphraseCipher::with_salt(passphrase, salt)?),
+            (Some(_), None) => {
+                return Err(AppError::Validation(
+                    "This backup has encrypted secrets, a passphrase is required".to_string(),
+                ));
+            }
+            (None, _) => None,
+        };
+
+        let mut env_vars = Vec::new();
+        let mut missing = Vec::new();
+        for env in backup.env_vars {
+            let value = if env.is_secret && env.omitted {
+                match secrets
+                    .iter()
+                    .find(|s| s.container_name == env.container_name && s.key == env.key)
+                {
+                    Some(secret) => secret.value.clone(),
+                    None => {
+                        missing.push(if env.container_name.is_empty() {
+                            env.key.clone()
+                        } else {
+                            format!("{}/{}", env.container_name, env.key)
+                        });
+                        continue;
+                    }
+                }
+            } else if env.is_secret
+                && let Some(cipher) = &backup_cipher
+            {
+                cipher.decrypt(&env.value)?
+            } else {
+                env.value
+            };
+            env_vars.push((env.container_name, env.key, value, env.is_secret));
+        }
+
+        if !missing.is_empty() {
+            return Err(AppError::Validation(format!(
+                "Values are required for omitted secrets: {}",
+                missing.join(", ")
+            )));
+        }
+
+        // 2. Create the stack
         let stack = self
             .create_stack(
                 &backup.name,
@@ -1098,21 +1797,16 @@ impl StackUsecase {
             )
             .await?;
 
-        // 2. Restore env vars
-        for env in backup.env_vars {
+        // 3. Restore env vars
+        for (container_name, key, value, is_secret) in env_vars {
             self.environment_usecase
-                .set_var(
-                    &stack.id,
-                    &env.container_name,
-                    &env.key,
-                    &env.value,
-                    env.is_secret,
-                )
+                .set_var(&stack.id, &container_name, &key, &value, is_secret, user_id)
                 .await?;
         }
 
-        // 3. Redeploy to apply the newly set env vars
-        self.redeploy_stack(&stack.id).await?;
+        // 4. Redeploy to apply the newly set env vars
+        self.redeploy_stack(&stack.id, Some(user_id), "restore")
+            .await?;
 
         self.get_stack(&stack.id, user_id).await
     }
@@ -1124,7 +1818,9 @@ impl StackUsecase {
         replicas: u64,
         user_id: &str,
     ) -> Result<()> {
-        let stack = self.get_stack(stack_id, user_id).await?;
+        let stack = self
+            .authorize(stack_id, user_id, permission::STACK_DEPLOY)
+            .await?;
 
         // Construct service name (usually stackname_servicename in Docker Swarm)
         let swarm_service_name = format!("{}_{}", stack.name, service_name);
@@ -1153,3 +1849,72 @@ impl StackUsecase {
         Ok(())
     }
 }
+
+/// Stack variables consumed as compose secret files; these are not injected as env vars
+fn file_secret_keys(parsed: &ParsedCompose) -> std::collections::HashSet<String> {
+    parsed
+        .secrets
+        .values()
+        .filter_map(|source| match source {
+            FileObjectSource::Store(key) => Some(key.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Read a file of the Git checkout at `base`. The path is resolved first, so a
+/// symlink committed to the repository cannot point outside the checkout.
+async fn read_checkout_file(base: &str, file: &str) -> std::io::Result<Vec<u8>> {
+    let base = tokio::fs::canonicalize(base).await?;
+    let path = tokio::fs::canonicalize(base.join(file)).await?;
+    if !path.starts_with(&base) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "path leaves the repository",
+        ));
+    }
+    tokio::fs::read(path).await
+}
+
+fn file_owner(mount: &ParsedFileMount) -> Option<(u32, u32)> {
+    let uid = mount.uid.as_deref()?.parse().ok()?;
+    let gid = mount.gid.as_deref().unwrap_or("0").parse().ok()?;
+    Some((uid, gid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_checkout_file_stays_in_repository() {
+        let root = std::env::temp_dir().join(format!("labuh-{}", uuid::Uuid::new_v4()));
+        let repo = root.join("repo");
+        tokio::fs::create_dir_all(repo.join("config"))
+            .await
+            .unwrap();
+        tokio::fs::write(repo.join("config/app.toml"), "port = 80")
+            .await
+            .unwrap();
+        tokio::fs::write(root.join("host-secret"), "hunter22")
+            .await
+            .unwrap();
+        std::os::unix::fs::symlink(root.join("host-secret"), repo.join("leak")).unwrap();
+        std::os::unix::fs::symlink("config/app.toml", repo.join("app.toml")).unwrap();
+
+        let base = repo.to_str().unwrap();
+        assert_eq!(
+            read_checkout_file(base, "config/app.toml").await.unwrap(),
+            b"port = 80"
+        );
+        // Links within the checkout are fine, links out of it are not
+        assert_eq!(
+            read_checkout_file(base, "app.toml").await.unwrap(),
+            b"port = 80"
+        );
+        let leak = read_checkout_file(base, "leak").await.unwrap_err();
+        assert_eq!(leak.kind(), std::io::ErrorKind::PermissionDenied);
+
+        tokio::fs::remove_dir_all(root).await.unwrap();
+    }
+}
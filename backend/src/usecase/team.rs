@@ -3,25 +3,63 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::domain::TeamRepository;
-use crate::domain::models::{Team, TeamMember, TeamResponse, TeamRole};
+use crate::domain::models::permission;
+use crate::domain::models::{Team, TeamMember, TeamResponse, TeamRole, USER_ROLE};
+use crate::domain::session_repository::SessionRepository;
 use crate::error::{AppError, Result};
+use crate::usecase::audit::AuditUsecase;
+use crate::usecase::authorization::AuthorizationUsecase;
 
 pub struct TeamUsecase {
     team_repo: Arc<dyn TeamRepository>,
     user_repo: Arc<dyn crate::domain::user_repository::UserRepository>,
+    session_repo: Arc<dyn SessionRepository>,
+    authz: Arc<AuthorizationUsecase>,
+    audit: Arc<AuditUsecase>,
 }
 
 impl TeamUsecase {
     pub fn new(
         team_repo: Arc<dyn TeamRepository>,
         user_repo: Arc<dyn crate::domain::user_repository::UserRepository>,
+        session_repo: Arc<dyn SessionRepository>,
+        authz: Arc<AuthorizationUsecase>,
+        audit: Arc<AuditUsecase>,
     ) -> Self {
         Self {
             team_repo,
             user_repo,
+            session_repo,
+            authz,
+            audit,
         }
     }
 
+    async fn record_activity(
+        &self,
+        actor_id: &str,
+        team_id: &str,
+        action: &str,
+        details: serde_json::Value,
+    ) -> Result<()> {
+        self.audit
+            .record(
+                Some(actor_id),
+                Some(team_id),
+                action,
+                "team",
+                team_id,
+                details,
+            )
+            .await
+    }
+
+    /// Sign a member out everywhere after their access changed
+    async fn revoke_sessions(&self, user_id: &str) -> Result<()> {
+        let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        self.session_repo.revoke_all_for_user(user_id, &now).await
+    }
+
     pub async fn create_team(&self, name: &str, owner_id: &str) -> Result<Team> {
         let id = Uuid::new_v4().to_string();
         let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
@@ -29,6 +67,7 @@ impl TeamUsecase {
         let team = Team {
             id: id.clone(),
             name: name.to_string(),
+            require_2fa: false,
             created_at: now.clone(),
             updated_at: now.clone(),
         };
@@ -42,7 +81,8 @@ impl TeamUsecase {
     }
 
     pub async fn delete_team(&self, team_id: &str, actor_id: &str) -> Result<()> {
-        self.verify_permission(team_id, actor_id, TeamRole::Owner)
+        self.authz
+            .require(team_id, actor_id, permission::TEAM_DELETE)
             .await?;
         self.team_repo.delete(team_id).await
     }
@@ -52,33 +92,53 @@ impl TeamUsecase {
         let mut responses = Vec::new();
 
         for team in teams {
-            let role = self
-                .team_repo
-                .get_user_role(&team.id, user_id)
-                .await?
-                .ok_or(AppError::NotFound(
-                    "Role not found for team member".to_string(),
-                ))?;
-
-            responses.push(TeamResponse { team, role });
+            let access = self.authz.team_permissions(&team.id, user_id).await?;
+            let role = access.role.ok_or(AppError::NotFound(
+                "Role not found for team member".to_string(),
+            ))?;
+
+            responses.push(TeamResponse {
+                team,
+                role,
+                custom_role: access.custom_role,
+                permissions: access.permissions.into_iter().collect(),
+            });
         }
 
         Ok(responses)
     }
 
-    #[allow(dead_code)]
-    pub async fn add_member(
+    /// Add a user who already has an account; new users are invited instead
+    pub async fn add_existing_member(
         &self,
         team_id: &str,
-        user_id: &str,
+        email: &str,
         role: TeamRole,
         actor_id: &str,
     ) -> Result<()> {
-        self.verify_permission(team_id, actor_id, TeamRole::Admin)
+        self.authz
+            .require(team_id, actor_id, permission::TEAM_MEMBERS_MANAGE)
             .await?;
 
-        self.team_repo.add_member(team_id, user_id, role).await?;
-        Ok(())
+        let user = self
+            .user_repo
+            .find_by_email(email.trim())
+            .await?
+            .filter(|u| u.role != crate::domain::models::BOT_USER_ROLE)
+            .ok_or(AppError::NotFound(
+                "No user with this email; send an invite instead".to_string(),
+            ))?;
+
+        self.team_repo
+            .add_member(team_id, &user.id, role.clone())
+            .await?;
+        self.record_activity(
+            actor_id,
+            team_id,
+            "team.member_add",
+            serde_json::json!({ "user_id": user.id, "email": user.email, "role": role.to_string() }),
+        )
+        .await
     }
 
     pub async fn add_member_with_credentials(
@@ -90,7 +150,8 @@ impl TeamUsecase {
         role: TeamRole,
         actor_id: &str,
     ) -> Result<()> {
-        self.verify_permission(team_id, actor_id, TeamRole::Admin)
+        self.authz
+            .require(team_id, actor_id, permission::TEAM_MEMBERS_MANAGE)
             .await?;
 
         // 1. Check if user already exists
@@ -108,21 +169,31 @@ impl TeamUsecase {
                 email: email.to_string(),
                 password_hash,
                 name: Some(name.to_string()),
-                role: "user".to_string(),
+                role: USER_ROLE.to_string(),
                 created_at: now.clone(),
                 updated_at: now,
+                deactivated_at: None,
             };
             self.user_repo.create(new_user).await?
         };
 
         // 3. Add to team
-        self.team_repo.add_member(team_id, &user.id, role).await?;
-        Ok(())
+        self.team_repo
+            .add_member(team_id, &user.id, role.clone())
+            .await?;
+        self.record_activity(
+            actor_id,
+            team_id,
+            "team.member_add",
+            serde_json::json!({ "user_id": user.id, "email": user.email, "role": role.to_string() }),
+        )
+        .await
     }
 
     pub async fn remove_member(&self, team_id: &str, user_id: &str, actor_id: &str) -> Result<()> {
         if user_id != actor_id {
-            self.verify_permission(team_id, actor_id, TeamRole::Admin)
+            self.authz
+                .require(team_id, actor_id, permission::TEAM_MEMBERS_MANAGE)
                 .await?;
         }
 
@@ -140,7 +211,16 @@ impl TeamUsecase {
         }
 
         self.team_repo.remove_member(team_id, user_id).await?;
-        Ok(())
+        if user_id != actor_id {
+            self.revoke_sessions(user_id).await?;
+        }
+        self.record_activity(
+            actor_id,
+            team_id,
+            "team.member_remove",
+            serde_json::json!({ "user_id": user_id }),
+        )
+        .await
     }
 
     pub async fn update_member_role(
@@ -157,7 +237,9 @@ impl TeamUsecase {
             ));
         }
 
-        self.verify_permission(team_id, actor_id, TeamRole::Admin)
+        let actor_role = self
+            .authz
+            .require(team_id, actor_id, permission::TEAM_MEMBERS_MANAGE)
             .await?;
 
         // Cannot change the owner's role
@@ -173,72 +255,41 @@ impl TeamUsecase {
             ));
         }
 
-        // Get actor's role
-        let actor_role = self
-            .team_repo
-            .get_user_role(team_id, actor_id)
-            .await?
-            .ok_or(AppError::Forbidden("Access denied".to_string()))?;
-
-        let role_priority = |r: TeamRole| match r {
-            TeamRole::Owner => 4,
-            TeamRole::Admin => 3,
-            TeamRole::Developer => 2,
-            TeamRole::Viewer => 1,
-        };
-
         // Cannot assign role equal or higher than your own (except Owner can do anything)
-        if actor_role != TeamRole::Owner
-            && role_priority(role.clone()) >= role_priority(actor_role.clone())
-        {
+        if actor_role != TeamRole::Owner && role.rank() >= actor_role.rank() {
             return Err(AppError::Forbidden(
                 "Cannot assign a role equal to or higher than your own".to_string(),
             ));
         }
 
         // Cannot modify someone with equal or higher role (except Owner)
-        if actor_role != TeamRole::Owner
-            && role_priority(target_role.clone()) >= role_priority(actor_role)
-        {
+        if actor_role != TeamRole::Owner && target_role.rank() >= actor_role.rank() {
             return Err(AppError::Forbidden(
                 "Cannot modify a member with equal or higher role".to_string(),
             ));
         }
 
         self.team_repo
-            .update_member_role(team_id, user_id, role)
-            .await
+            .update_member_role(team_id, user_id, role.clone())
+            .await?;
+        self.revoke_sessions(user_id).await?;
+        self.record_activity(
+            actor_id,
+            team_id,
+            "team.member_role",
+            serde_json::json!({
+                "user_id": user_id,
+                "from": target_role.to_string(),
+                "to": role.to_string(),
+            }),
+        )
+        .await
     }
 
     pub async fn get_members(&self, team_id: &str, actor_id: &str) -> Result<Vec<TeamMember>> {
-        self.verify_permission(team_id, actor_id, TeamRole::Viewer)
+        self.authz
+            .require(team_id, actor_id, permission::TEAM_VIEW)
             .await?;
         self.team_repo.get_members(team_id).await
     }
-
-    pub async fn verify_permission(
-        &self,
-        team_id: &str,
-        user_id: &str,
-        required_role: TeamRole,
-    ) -> Result<()> {
-        let role = self
-            .team_repo
-            .get_user_role(team_id, user_id)
-            .await?
-            .ok_or(AppError::Forbidden("Access denied".to_string()))?;
-
-        let role_priority = |r: TeamRole| match r {
-            TeamRole::Owner => 4,
-            TeamRole::Admin => 3,
-            TeamRole::Developer => 2,
-            TeamRole::Viewer => 1,
-        };
-
-        if role_priority(role) < role_priority(required_role) {
-            return Err(AppError::Forbidden("Access denied".to_string()));
-        }
-
-        Ok(())
-    }
 }
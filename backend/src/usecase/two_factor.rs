@@ -0,0 +1,266 @@
+use chrono::Utc;
+use rand::distr::{Alphanumeric, SampleString};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::domain::TeamRepository;
+use crate::domain::models::permission;
+use crate::domain::models::{
+    RecoveryCode, RecoveryCodes, TotpEnrollment, TwoFactorStatus, UserTotp,
+};
+use crate::domain::secret_cipher::SecretCipher;
+use crate::domain::two_factor_repository::TwoFactorRepository;
+use crate::error::{AppError, Result};
+use crate::infrastructure::auth::password::PasswordService;
+use crate::infrastructure::auth::totp;
+use crate::usecase::audit::AuditUsecase;
+use crate::usecase::authorization::AuthorizationUsecase;
+
+const TOTP_ISSUER: &str = "Labuh";
+const RECOVERY_CODE_COUNT: usize = 10;
+
+pub struct TwoFactorUsecase {
+    repo: Arc<dyn TwoFactorRepository>,
+    team_repo: Arc<dyn TeamRepository>,
+    authz: Arc<AuthorizationUsecase>,
+    audit: Arc<AuditUsecase>,
+    cipher: Arc<dyn SecretCipher>,
+}
+
+fn timestamp() -> String {
+    Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+impl TwoFactorUsecase {
+    pub fn new(
+        repo: Arc<dyn TwoFactorRepository>,
+        team_repo: Arc<dyn TeamRepository>,
+        authz: Arc<AuthorizationUsecase>,
+        audit: Arc<AuditUsecase>,
+        cipher: Arc<dyn SecretCipher>,
+    ) -> Self {
+        Self {
+            repo,
+            team_repo,
+            authz,
+            audit,
+            cipher,
+        }
+    }
+
+    async fn enabled_totp(&self, user_id: &str) -> Result<Option<UserTotp>> {
+        Ok(self
+            .repo
+            .find_totp(user_id)
+            .await?
+            .filter(|t| t.enabled_at.is_some()))
+    }
+
+    pub async fn is_enabled(&self, user_id: &str) -> Result<bool> {
+        Ok(self.enabled_totp(user_id).await?.is_some())
+    }
+
+    /// Whether the user must enroll before using the API (team policy, not yet enabled)
+    pub async fn enrollment_required(&self, user_id: &str) -> Result<bool> {
+        self.repo.enrollment_required(user_id).await
+    }
+
+    pub async fn status(&self, user_id: &str) -> Result<TwoFactorStatus> {
+        let enabled = self.is_enabled(user_id).await?;
+        let remaining = if enabled {
+            self.repo.list_unused_recovery_codes(user_id).await?.len() as i64
+        } else {
+            0
+        };
+
+        Ok(TwoFactorStatus {
+            enabled,
+            recovery_codes_remaining: remaining,
+            required: self.repo.is_required(user_id).await?,
+        })
+    }
+
+    /// Start enrollment with a fresh secret. It only takes effect once `confirm` succeeds.
+    pub async fn enroll(&self, user_id: &str, account: &str) -> Result<TotpEnrollment> {
+        if self.is_enabled(user_id).await? {
+            return Err(AppError::Conflict(
+                "Two-factor authentication is already enabled".to_string(),
+            ));
+        }
+
+        let secret = totp::generate_secret();
+        self.repo
+            .save_totp(UserTotp {
+                user_id: user_id.to_string(),
+                secret: self.cipher.encrypt(&secret)?,
+                enabled_at: None,
+                last_used_step: None,
+                created_at: timestamp(),
+            })
+            .await?;
+
+        Ok(TotpEnrollment {
+            otpauth_uri: totp::otpauth_uri(TOTP_ISSUER, account, &secret),
+            secret,
+        })
+    }
+
+    /// Activate a pending enrollment with a first code and hand out recovery codes
+    pub async fn confirm(&self, user_id: &str, code: &str) -> Result<RecoveryCodes> {
+        let pending = self
+            .repo
+            .find_totp(user_id)
+            .await?
+            .ok_or_else(|| AppError::BadRequest("No pending 2FA enrollment".to_string()))?;
+        if pending.enabled_at.is_some() {
+            return Err(AppError::Conflict(
+                "Two-factor authentication is already enabled".to_string(),
+            ));
+        }
+
+        self.check_totp(&pending, code).await?;
+        self.repo.enable_totp(user_id, &timestamp()).await?;
+        let codes = self.issue_recovery_codes(user_id).await?;
+
+        self.audit
+            .record(
+                Some(user_id),
+                None,
+                "2fa.enroll",
+                "user",
+                user_id,
+                serde_json::json!({}),
+            )
+            .await?;
+
+        Ok(codes)
+    }
+
+    pub async fn disable(&self, user_id: &str, code: &str) -> Result<()> {
+        if self.repo.is_required(user_id).await? {
+            return Err(AppError::Forbidden(
+                "Two-factor authentication is required by one of your teams".to_string(),
+            ));
+        }
+
+        self.verify_second_factor(user_id, code).await?;
+        self.repo.delete(user_id).await?;
+
+        self.audit
+            .record(
+                Some(user_id),
+                None,
+                "2fa.disable",
+                "user",
+                user_id,
+                serde_json::json!({}),
+            )
+            .await
+    }
+
+    /// Replace all recovery codes; requires a valid current code
+    pub async fn regenerate_recovery_codes(
+        &self,
+        user_id: &str,
+        code: &str,
+    ) -> Result<RecoveryCodes> {
+        let totp = self.enabled_totp(user_id).await?.ok_or_else(|| {
+            AppError::BadRequest("Two-factor authentication is not enabled".to_string())
+        })?;
+        self.check_totp(&totp, code).await?;
+
+        self.issue_recovery_codes(user_id).await
+    }
+
+    /// Check a TOTP code or consume a recovery code
+    pub async fn verify_second_factor(&self, user_id: &str, code: &str) -> Result<()> {
+        let totp = self.enabled_totp(user_id).await?.ok_or_else(|| {
+            AppError::BadRequest("Two-factor authentication is not enabled".to_string())
+        })?;
+
+        let code = code.trim();
+        if code.len() == 6 && code.chars().all(|c| c.is_ascii_digit()) {
+            return self.check_totp(&totp, code).await;
+        }
+
+        let normalized = code.to_lowercase().replace('-', "");
+        for recovery in self.repo.list_unused_recovery_codes(user_id).await? {
+            if PasswordService::verify_password(&normalized, &recovery.code_hash).is_ok()
+                && self
+                    .repo
+                    .use_recovery_code(&recovery.id, &timestamp())
+                    .await?
+            {
+                return self
+                    .audit
+                    .record(
+                        Some(user_id),
+                        None,
+                        "2fa.recovery_code_used",
+                        "user",
+                        user_id,
+                        serde_json::json!({}),
+                    )
+                    .await;
+            }
+        }
+
+        Err(AppError::InvalidCredentials)
+    }
+
+    async fn check_totp(&self, totp: &UserTotp, code: &str) -> Result<()> {
+        let secret = self.cipher.decrypt(&totp.secret)?;
+        let step = totp::verify(&secret, code, Utc::now().timestamp(), totp.last_used_step)
+            .ok_or(AppError::InvalidCredentials)?;
+
+        // A concurrent request may have used the same code in the meantime
+        if !self.repo.record_step(&totp.user_id, step).await? {
+            return Err(AppError::InvalidCredentials);
+        }
+        Ok(())
+    }
+
+    async fn issue_recovery_codes(&self, user_id: &str) -> Result<RecoveryCodes> {
+        let now = timestamp();
+        let mut plain = Vec::with_capacity(RECOVERY_CODE_COUNT);
+        let mut stored = Vec::with_capacity(RECOVERY_CODE_COUNT);
+
+        for _ in 0..RECOVERY_CODE_COUNT {
+            let code = Alphanumeric
+                .sample_string(&mut rand::rng(), 10)
+                .to_lowercase();
+            stored.push(RecoveryCode {
+                id: Uuid::new_v4().to_string(),
+                user_id: user_id.to_string(),
+                code_hash: PasswordService::hash_password(&code)?,
+                used_at: None,
+                created_at: now.clone(),
+            });
+            plain.push(format!("{}-{}", &code[..5], &code[5..]));
+        }
+
+        self.repo.replace_recovery_codes(user_id, stored).await?;
+        Ok(RecoveryCodes {
+            recovery_codes: plain,
+        })
+    }
+
+    /// Require Owners and Admins of the team to have 2FA enabled
+    pub async fn set_team_policy(&self, team_id: &str, user_id: &str, require: bool) -> Result<()> {
+        self.authz
+            .require(team_id, user_id, permission::TEAM_SETTINGS_MANAGE)
+            .await?;
+        self.team_repo.set_require_2fa(team_id, require).await?;
+
+        self.audit
+            .record(
+                Some(user_id),
+                Some(team_id),
+                "team.2fa_policy",
+                "team",
+                team_id,
+                serde_json::json!({ "require_2fa": require }),
+            )
+            .await
+    }
+}
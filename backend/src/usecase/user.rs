@@ -0,0 +1,727 @@
+use chrono::{Duration, Utc};
+use rand::distr::{Alphanumeric, SampleString};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::domain::TeamRepository;
+use crate::domain::invite_repository::InviteRepository;
+use crate::domain::models::permission;
+use crate::domain::models::{
+    AcceptInviteRequest, AuthResponse, BOT_USER_ROLE, ChangePasswordRequest, CreateInviteRequest,
+    CreatedInvite, InviteDetails, MIN_PASSWORD_LENGTH, NO_PASSWORD_HASH, PasswordReset,
+    PasswordResetLink, ResetPasswordRequest, TeamRole, USER_ROLE, UpdateProfileRequest, User,
+    UserInvite, UserInviteResponse, UserResponse,
+};
+use crate::domain::user_repository::UserRepository;
+use crate::error::{AppError, Result};
+use crate::infrastructure::auth::api_token;
+use crate::infrastructure::auth::password::PasswordService;
+use crate::usecase::audit::AuditUsecase;
+use crate::usecase::auth::AuthUsecase;
+use crate::usecase::authorization::AuthorizationUsecase;
+
+const INVITE_TTL_DAYS: i64 = 7;
+const PASSWORD_RESET_TTL_HOURS: i64 = 24;
+
+pub struct UserUsecase {
+    user_repo: Arc<dyn UserRepository>,
+    team_repo: Arc<dyn TeamRepository>,
+    authz: Arc<AuthorizationUsecase>,
+    invite_repo: Arc<dyn InviteRepository>,
+    audit: Arc<AuditUsecase>,
+    auth_usecase: Arc<AuthUsecase>,
+}
+
+fn timestamp(at: chrono::DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn validate_password(password: &str) -> Result<()> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(AppError::Validation(format!(
+            "Password must be at least {} characters",
+            MIN_PASSWORD_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+fn new_link_token() -> (String, String) {
+    let token = Alphanumeric.sample_string(&mut rand::rng(), 48);
+    let hash = api_token::hash(&token);
+    (token, hash)
+}
+
+impl UserUsecase {
+    pub fn new(
+        user_repo: Arc<dyn UserRepository>,
+        team_repo: Arc<dyn TeamRepository>,
+        authz: Arc<AuthorizationUsecase>,
+        invite_repo: Arc<dyn InviteRepository>,
+        audit: Arc<AuditUsecase>,
+        auth_usecase: Arc<AuthUsecase>,
+    ) -> Self {
+        Self {
+            user_repo,
+            team_repo,
+            authz,
+            invite_repo,
+            audit,
+            auth_usecase,
+        }
+    }
+
+    /// Account-wide operations are reserved for Labuh administrators
+    async fn ensure_admin(&self, actor_id: &str) -> Result<()> {
+        let actor = self.find_user(actor_id).await?;
+        if !actor.is_admin() {
+            return Err(AppError::Forbidden(
+                "Only administrators can manage users".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn find_user(&self, id: &str) -> Result<User> {
+        self.user_repo
+            .find_by_id(id)
+            .await?
+            .filter(|u| u.role != BOT_USER_ROLE)
+            .ok_or(AppError::NotFound("User not found".to_string()))
+    }
+
+    pub async fn update_profile(
+        &self,
+        user_id: &str,
+        request: UpdateProfileRequest,
+    ) -> Result<UserResponse> {
+        let name = request
+            .name
+            .as_deref()
+            .map(str::trim)
+            .filter(|n| !n.is_empty());
+        self.user_repo
+            .update_name(user_id, name, &timestamp(Utc::now()))
+            .await?;
+
+        Ok(self.find_user(user_id).await?.into())
+    }
+
+    /// Change the password and sign out every other device. The caller gets a
+    /// fresh session in place of the one that was revoked.
+    pub async fn change_password(
+        &self,
+        user_id: &str,
+        request: ChangePasswordRequest,
+        user_agent: Option<String>,
+    ) -> Result<AuthResponse> {
+        let user = self.find_user(user_id).await?;
+        if user.password_hash == NO_PASSWORD_HASH {
+            return Err(AppError::BadRequest(
+                "This account signs in with SSO and has no password".to_string(),
+            ));
+        }
+        PasswordService::verify_password(&request.current_password, &user.password_hash)?;
+        validate_password(&request.new_password)?;
+
+        let hash = PasswordService::hash_password(&request.new_password)?;
+        self.user_repo
+            .update_password(user_id, &hash, &timestamp(Utc::now()))
+            .await?;
+        self.auth_usecase.revoke_all_sessions(user_id).await?;
+
+        self.audit
+            .record(
+                Some(user_id),
+                None,
+                "user.password_change",
+                "user",
+                user_id,
+                serde_json::json!({}),
+            )
+            .await?;
+
+        self.auth_usecase.start_session(user, user_agent).await
+    }
+
+    pub async fn list_users(&self, actor_id: &str) -> Result<Vec<UserResponse>> {
+        self.ensure_admin(actor_id).await?;
+        let users = self.user_repo.list().await?;
+        Ok(users.into_iter().map(Into::into).collect())
+    }
+
+    /// Issue a single-use link that lets the user choose a new password
+    pub async fn issue_password_reset(
+        &self,
+        actor_id: &str,
+        user_id: &str,
+    ) -> Result<PasswordResetLink> {
+        self.auth_usecase.ensure_password_login()?;
+        self.ensure_admin(actor_id).await?;
+        let user = self.find_user(user_id).await?;
+
+        let now = Utc::now();
+        let (token, token_hash) = new_link_token();
+        let expires_at = timestamp(now + Duration::hours(PASSWORD_RESET_TTL_HOURS));
+        self.invite_repo
+            .create_password_reset(PasswordReset {
+                id: Uuid::new_v4().to_string(),
+                user_id: user.id.clone(),
+                token_hash,
+                created_by: Some(actor_id.to_string()),
+                expires_at: expires_at.clone(),
+                used_at: None,
+                created_at: timestamp(now),
+            })
+            .await?;
+
+        self.audit
+            .record(
+                Some(actor_id),
+                None,
+                "user.password_reset_issued",
+                "user",
+                &user.id,
+                serde_json::json!({ "email": user.email }),
+            )
+            .await?;
+
+        Ok(PasswordResetLink { token, expires_at })
+    }
+
+    /// Set a new password from a reset link; all existing sessions are revoked
+    pub async fn reset_password(&self, request: ResetPasswordRequest) -> Result<()> {
+        self.auth_usecase.ensure_password_login()?;
+        let invalid = || AppError::BadRequest("Invalid or expired reset link".to_string());
+        let now = timestamp(Utc::now());
+
+        let reset = self
+            .invite_repo
+            .find_password_reset(&api_token::hash(&request.token))
+            .await?
+            .filter(|r| r.used_at.is_none() && r.expires_at > now)
+            .ok_or_else(invalid)?;
+        validate_password(&request.password)?;
+
+        if !self.invite_repo.use_password_reset(&reset.id, &now).await? {
+            return Err(invalid());
+        }
+
+        let hash = PasswordService::hash_password(&request.password)?;
+        self.user_repo
+            .update_password(&reset.user_id, &hash, &now)
+            .await?;
+        self.auth_usecase
+            .revoke_all_sessions(&reset.user_id)
+            .await?;
+
+        self.audit
+            .record(
+                Some(&reset.user_id),
+                None,
+                "user.password_reset",
+                "user",
+                &reset.user_id,
+                serde_json::json!({}),
+            )
+            .await
+    }
+
+    /// Deactivate or reactivate an account. Deactivation signs the user out everywhere.
+    pub async fn set_active(&self, actor_id: &str, user_id: &str, active: bool) -> Result<()> {
+        self.ensure_admin(actor_id).await?;
+        if actor_id == user_id {
+            return Err(AppError::BadRequest(
+                "Cannot change the status of your own account".to_string(),
+            ));
+        }
+        let user = self.find_user(user_id).await?;
+
+        let now = timestamp(Utc::now());
+        if active {
+            self.user_repo.set_deactivated(&user.id, None).await?;
+        } else {
+            self.user_repo.set_deactivated(&user.id, Some(&now)).await?;
+            self.auth_usecase.revoke_all_sessions(&user.id).await?;
+        }
+
+        let action = if active {
+            "user.activate"
+        } else {
+            "user.deactivate"
+        };
+        self.audit
+            .record(
+                Some(actor_id),
+                None,
+                action,
+                "user",
+                &user.id,
+                serde_json::json!({ "email": user.email }),
+            )
+            .await
+    }
+
+    pub async fn create_invite(
+        &self,
+        team_id: &str,
+        actor_id: &str,
+        request: CreateInviteRequest,
+    ) -> Result<CreatedInvite> {
+        // Without password login, SSO provisions accounts on first sign-in instead
+        self.auth_usecase.ensure_password_login()?;
+        let actor_role = self
+            .authz
+            .require(team_id, actor_id, permission::TEAM_MEMBERS_MANAGE)
+            .await?;
+        if request.role == TeamRole::Owner || request.role.rank() > actor_role.rank() {
+            return Err(AppError::Forbidden(
+                "Cannot invite with a higher role than your own".to_string(),
+            ));
+        }
+
+        let email = request.email.trim();
+        if email.is_empty() || !email.contains('@') {
+            return Err(AppError::Validation(
+                "A valid email is required".to_string(),
+            ));
+        }
+        if self.user_repo.find_by_email(email).await?.is_some() {
+            return Err(AppError::Conflict(
+                "A user with this email already exists; add them as a member instead".to_string(),
+            ));
+        }
+
+        let now = Utc::now();
+        let (token, token_hash) = new_link_token();
+        let invite = self
+            .invite_repo
+            .create(UserInvite {
+                id: Uuid::new_v4().to_string(),
+                team_id: team_id.to_string(),
+                email: email.to_string(),
+                name: request.name.filter(|n| !n.trim().is_empty()),
+                role: request.role.to_string(),
+                token_hash,
+                invited_by: Some(actor_id.to_string()),
+                expires_at: timestamp(now + Duration::days(INVITE_TTL_DAYS)),
+                accepted_at: None,
+                created_at: timestamp(now),
+            })
+            .await?;
+
+        self.audit
+            .record(
+                Some(actor_id),
+                Some(team_id),
+                "team.invite",
+                "team",
+                team_id,
+                serde_json::json!({ "email": invite.email, "role": invite.role }),
+            )
+            .await?;
+
+        Ok(CreatedInvite {
+            token,
+            info: invite.into(),
+        })
+    }
+
+    pub async fn list_invites(
+        &self,
+        team_id: &str,
+        actor_id: &str,
+    ) -> Result<Vec<UserInviteResponse>> {
+        self.authz
+            .require(team_id, actor_id, permission::TEAM_MEMBERS_MANAGE)
+            .await?;
+        let invites = self
+            .invite_repo
+            .list_pending(team_id, &timestamp(Utc::now()))
+            .await?;
+        Ok(invites.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn revoke_invite(&self, team_id: &str, id: &str, actor_id: &str) -> Result<()> {
+        self.authz
+            .require(team_id, actor_id, permission::TEAM_MEMBERS_MANAGE)
+            .await?;
+
+        let invite = self
+            .invite_repo
+            .find_by_id(id)
+            .await?
+            .filter(|i| i.team_id == team_id)
+            .ok_or(AppError::NotFound("Invite not found".to_string()))?;
+
+        self.invite_repo.delete(&invite.id).await
+    }
+
+    async fn pending_invite(&self, token: &str) -> Result<UserInvite> {
+        let now = timestamp(Utc::now());
+        self.invite_repo
+            .find_by_hash(&api_token::hash(token))
+            .await?
+            .filter(|i| i.accepted_at.is_none() && i.expires_at > now)
+            .ok_or_else(|| AppError::BadRequest("Invalid or expired invitation".to_string()))
+    }
+
+    pub async fn invite_details(&self, token: &str) -> Result<InviteDetails> {
+        let invite = self.pending_invite(token).await?;
+        let team = self
+            .team_repo
+            .find_by_id(&invite.team_id)
+            .await?
+            .ok_or(AppError::NotFound("Team not found".to_string()))?;
+
+        Ok(InviteDetails {
+            email: invite.email,
+            name: invite.name,
+            team_name: team.name,
+            role: TeamRole::from(invite.role),
+            expires_at: invite.expires_at,
+        })
+    }
+
+    /// Create the invitee's account with the password they chose and sign them in
+    pub async fn accept_invite(
+        &self,
+        request: AcceptInviteRequest,
+        user_agent: Option<String>,
+    ) -> Result<AuthResponse> {
+        self.auth_usecase.ensure_password_login()?;
+        let invite = self.pending_invite(&request.token).await?;
+        validate_password(&request.password)?;
+
+        if self.user_repo.find_by_email(&invite.email).await?.is_some() {
+            return Err(AppError::Conflict(
+                "An account with this email already exists".to_string(),
+            ));
+        }
+
+        let now = timestamp(Utc::now());
+        if !self.invite_repo.mark_accepted(&invite.id, &now).await? {
+            return Err(AppError::BadRequest(
+                "Invalid or expired invitation".to_string(),
+            ));
+        }
+
+        let name = request
+            .name
+            .filter(|n| !n.trim().is_empty())
+            .or(invite.name);
+        let user = self
+            .user_repo
+            .create(User {
+                id: Uuid::new_v4().to_string(),
+                email: invite.email.clone(),
+                password_hash: PasswordService::hash_password(&request.password)?,
+                name,
+                role: USER_ROLE.to_string(),
+                created_at: now.clone(),
+                updated_at: now,
+                deactivated_at: None,
+            })
+            .await?;
+        self.team_repo
+            .add_member(&invite.team_id, &user.id, TeamRole::from(invite.role))
+            .await?;
+
+        self.audit
+            .record(
+                Some(&user.id),
+                Some(&invite.team_id),
+                "team.invite_accepted",
+                "team",
+                &invite.team_id,
+                serde_json::json!({ "invite_id": invite.id }),
+            )
+            .await?;
+
+        self.auth_usecase.start_session(user, user_agent).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::{ADMIN_ROLE, LoginRequest, LoginResponse};
+    use crate::infrastructure::sqlite::{
+        activity_log::SqliteActivityLogRepository, invite::SqliteInviteRepository,
+        role::SqliteRoleRepository, stack::SqliteStackRepository,
+        stack_grant::SqliteStackGrantRepository, team::SqliteTeamRepository,
+        user::SqliteUserRepository,
+    };
+    use crate::usecase::auth::tests::{auth_usecase, create_user, memory_pool};
+    use sqlx::SqlitePool;
+
+    struct Fixture {
+        users: UserUsecase,
+        auth: Arc<AuthUsecase>,
+        team_repo: Arc<SqliteTeamRepository>,
+        admin: User,
+    }
+
+    /// A team `t1` owned by an administrator
+    async fn fixture(pool: &SqlitePool, password_enabled: bool) -> Fixture {
+        let admin = create_user(pool, "admin@example.com", Some("hunter22"), ADMIN_ROLE).await;
+        sqlx::query(
+            "INSERT INTO teams (id, name, created_at, updated_at) VALUES ('t1', 'Web', '', '')",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+        let team_repo = Arc::new(SqliteTeamRepository::new(pool.clone()));
+        team_repo
+            .add_member("t1", &admin.id, TeamRole::Owner)
+            .await
+            .unwrap();
+
+        let authz = Arc::new(AuthorizationUsecase::new(
+            team_repo.clone(),
+            Arc::new(SqliteRoleRepository::new(pool.clone())),
+            Arc::new(SqliteStackGrantRepository::new(pool.clone())),
+        ));
+        let audit = Arc::new(AuditUsecase::new(
+            Arc::new(SqliteActivityLogRepository::new(pool.clone())),
+            authz.clone(),
+            Arc::new(SqliteStackRepository::new(pool.clone())),
+        ));
+        let auth = Arc::new(auth_usecase(pool, password_enabled));
+        let users = UserUsecase::new(
+            Arc::new(SqliteUserRepository::new(pool.clone())),
+            team_repo.clone(),
+            authz,
+            Arc::new(SqliteInviteRepository::new(pool.clone())),
+            audit,
+            auth.clone(),
+        );
+        Fixture {
+            users,
+            auth,
+            team_repo,
+            admin,
+        }
+    }
+
+    fn invite_request(email: &str, role: TeamRole) -> CreateInviteRequest {
+        CreateInviteRequest {
+            email: email.to_string(),
+            name: None,
+            role,
+        }
+    }
+
+    async fn login(auth: &AuthUsecase, email: &str, password: &str) -> Result<LoginResponse> {
+        let request = LoginRequest {
+            email: email.to_string(),
+            password: password.to_string(),
+        };
+        auth.login(request, "127.0.0.1", None).await
+    }
+
+    #[tokio::test]
+    async fn test_invite_creates_member_once() {
+        let pool = memory_pool().await;
+        let f = fixture(&pool, true).await;
+
+        let invite = f
+            .users
+            .create_invite(
+                "t1",
+                &f.admin.id,
+                invite_request("dev@example.com", TeamRole::Developer),
+            )
+            .await
+            .unwrap();
+        let details = f.users.invite_details(&invite.token).await.unwrap();
+        assert_eq!(details.team_name, "Web");
+
+        let accept = |password: &str| AcceptInviteRequest {
+            token: invite.token.clone(),
+            name: None,
+            password: password.to_string(),
+        };
+        assert!(matches!(
+            f.users.accept_invite(accept("short"), None).await,
+            Err(AppError::Validation(_))
+        ));
+        let session = f
+            .users
+            .accept_invite(accept("correct horse"), None)
+            .await
+            .unwrap();
+        let role = f
+            .team_repo
+            .get_user_role("t1", &session.user.id)
+            .await
+            .unwrap();
+        assert_eq!(role, Some(TeamRole::Developer));
+        assert!(
+            f.users
+                .accept_invite(accept("correct horse"), None)
+                .await
+                .is_err()
+        );
+
+        // Existing accounts and owner invites are refused
+        for (email, role) in [
+            ("admin@example.com", TeamRole::Viewer),
+            ("new@example.com", TeamRole::Owner),
+        ] {
+            assert!(
+                f.users
+                    .create_invite("t1", &f.admin.id, invite_request(email, role))
+                    .await
+                    .is_err()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_password_reset_revokes_sessions() {
+        let pool = memory_pool().await;
+        let f = fixture(&pool, true).await;
+        let user = create_user(&pool, "dev@example.com", Some("old password"), USER_ROLE).await;
+        let old_session = f.auth.start_session(user.clone(), None).await.unwrap();
+
+        // Only administrators can issue links
+        assert!(
+            f.users
+                .issue_password_reset(&user.id, &user.id)
+                .await
+                .is_err()
+        );
+        let link = f
+            .users
+            .issue_password_reset(&f.admin.id, &user.id)
+            .await
+            .unwrap();
+        let reset = || ResetPasswordRequest {
+            token: link.token.clone(),
+            password: "new password".to_string(),
+        };
+        f.users.reset_password(reset()).await.unwrap();
+        assert!(f.users.reset_password(reset()).await.is_err());
+
+        assert!(f.auth.verify_token(&old_session.token).await.is_err());
+        assert!(
+            login(&f.auth, "dev@example.com", "old password")
+                .await
+                .is_err()
+        );
+        assert!(
+            login(&f.auth, "dev@example.com", "new password")
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deactivation_signs_user_out() {
+        let pool = memory_pool().await;
+        let f = fixture(&pool, true).await;
+        let user = create_user(&pool, "dev@example.com", Some("hunter22"), USER_ROLE).await;
+        let session = f.auth.start_session(user.clone(), None).await.unwrap();
+
+        assert!(
+            f.users
+                .set_active(&f.admin.id, &f.admin.id, false)
+                .await
+                .is_err()
+        );
+        assert!(
+            f.users
+                .set_active(&user.id, &f.admin.id, false)
+                .await
+                .is_err()
+        );
+        f.users
+            .set_active(&f.admin.id, &user.id, false)
+            .await
+            .unwrap();
+        assert!(f.auth.verify_token(&session.token).await.is_err());
+        assert!(f.auth.refresh(&session.refresh_token).await.is_err());
+        assert!(matches!(
+            login(&f.auth, "dev@example.com", "hunter22").await,
+            Err(AppError::Forbidden(_))
+        ));
+
+        f.users
+            .set_active(&f.admin.id, &user.id, true)
+            .await
+            .unwrap();
+        assert!(login(&f.auth, "dev@example.com", "hunter22").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_sso_only_mode_refuses_password_credentials() {
+        let pool = memory_pool().await;
+        let enabled = fixture(&pool, true).await;
+        let invite = enabled
+            .users
+            .create_invite(
+                "t1",
+                &enabled.admin.id,
+                invite_request("dev@example.com", TeamRole::Viewer),
+            )
+            .await
+            .unwrap();
+        let link = enabled
+            .users
+            .issue_password_reset(&enabled.admin.id, &enabled.admin.id)
+            .await
+            .unwrap();
+
+        // The same links once password login is turned off
+        let f = Fixture {
+            users: UserUsecase::new(
+                Arc::new(SqliteUserRepository::new(pool.clone())),
+                enabled.team_repo.clone(),
+                enabled.users.authz.clone(),
+                Arc::new(SqliteInviteRepository::new(pool.clone())),
+                enabled.users.audit.clone(),
+                Arc::new(auth_usecase(&pool, false)),
+            ),
+            ..enabled
+        };
+        let forbidden = |result: Result<_>| matches!(result, Err(AppError::Forbidden(_)));
+        assert!(forbidden(
+            f.users
+                .accept_invite(
+                    AcceptInviteRequest {
+                        token: invite.token,
+                        name: None,
+                        password: "correct horse".to_string(),
+                    },
+                    None,
+                )
+                .await
+                .map(|_| ())
+        ));
+        assert!(forbidden(
+            f.users
+                .reset_password(ResetPasswordRequest {
+                    token: link.token,
+                    password: "new password".to_string(),
+                })
+                .await
+        ));
+        assert!(forbidden(
+            f.users
+                .create_invite(
+                    "t1",
+                    &f.admin.id,
+                    invite_request("other@example.com", TeamRole::Viewer),
+                )
+                .await
+                .map(|_| ())
+        ));
+        assert!(forbidden(
+            f.users
+                .issue_password_reset(&f.admin.id, &f.admin.id)
+                .await
+                .map(|_| ())
+        ));
+    }
+}
@@ -19,9 +19,12 @@ systemctl stop labuh 2>/dev/null || true
 
 # Create backup
 echo "📦 Creating backup archive..."
-tar -czf "$BACKUP_FILE" -C "$LABUH_DIR" labuh.db .env 2>/dev/null || {
-    tar -czf "$BACKUP_FILE" -C "$LABUH_DIR" labuh.db 2>/dev/null
-}
+# The master key is required to decrypt secrets stored in the database
+FILES="labuh.db"
+for f in .env master.key; do
+    [ -f "$LABUH_DIR/$f" ] && FILES="$FILES $f"
+done
+tar -czf "$BACKUP_FILE" -C "$LABUH_DIR" $FILES
 
 # Restart service
 echo "▶️  Starting Labuh service..."
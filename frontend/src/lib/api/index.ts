@@ -11,9 +11,38 @@ interface ApiResponse<T> {
   message?: string;
 }
 
+let refreshing: Promise<boolean> | null = null;
+
+// Exchange the stored refresh token for a new access token (single flight)
+async function refreshSession(): Promise<boolean> {
+  const refreshToken = browser ? localStorage.getItem("refresh_token") : null;
+  if (!refreshToken) return false;
+
+  refreshing ??= (async () => {
+    try {
+      const response = await fetch(`${API_URL}/api/auth/refresh`, {
+        method: "POST",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify({ refresh_token: refreshToken }),
+      });
+      if (!response.ok) return false;
+      const data: AuthResponse = await response.json();
+      auth.login(data.token, data.user, data.refresh_token);
+      return true;
+    } catch {
+      return false;
+    } finally {
+      refreshing = null;
+    }
+  })();
+
+  return refreshing;
+}
+
 async function fetchApi<T>(
   endpoint: string,
   options: RequestInit = {},
+  retried = false,
 ): Promise<ApiResponse<T>> {
   const token = browser ? localStorage.getItem("token") : null;
 
@@ -34,13 +63,28 @@ async function fetchApi<T>(
 
     const data = await response.json();
 
-    if (!response.ok) {
-      // Auto-logout if user no longer exists (e.g., database was reset)
-      if (response.status === 401 && data.error === "user_not_found") {
-        localStorage.removeItem("token");
-        localStorage.removeItem("user");
+    const isCredentialRequest =
+      endpoint === "/auth/login" ||
+      endpoint === "/auth/login/2fa" ||
+      endpoint === "/me/password" ||
+      endpoint === "/auth/register";
+
+    if (!response.ok && !isCredentialRequest) {
+      // Access tokens are short-lived: refresh once and retry
+      if (response.status === 401 && token && !retried) {
+        if (await refreshSession()) {
+          return fetchApi<T>(endpoint, options, true);
+        }
+      }
+
+      // Auto-logout if user no longer exists or the session was revoked
+      if (response.status === 401 && token) {
+        auth.logout();
         window.location.href = "/login";
       }
+    }
+
+    if (!response.ok) {
       return { error: data.error || "Request failed", message: data.message };
     }
 
@@ -62,11 +106,95 @@ export interface RegisterRequest {
   name?: string;
 }
 
+export interface LoginOptions {
+  password_enabled: boolean;
+  sso_provider: string | null;
+}
+
 export interface AuthResponse {
   token: string;
+  refresh_token: string;
+  expires_in: number;
   user: User;
 }
 
+// Returned by login instead of a session when the user has 2FA enabled
+export interface MfaChallenge {
+  mfa_required: true;
+  mfa_token: string;
+}
+
+export type LoginResponse = AuthResponse | MfaChallenge;
+
+export interface UserAccount {
+  id: string;
+  email: string;
+  name: string | null;
+  role: string;
+  created_at: string;
+  deactivated_at: string | null;
+}
+
+export interface UserInvite {
+  id: string;
+  team_id: string;
+  email: string;
+  name: string | null;
+  role: TeamRole;
+  invited_by: string | null;
+  expires_at: string;
+  created_at: string;
+}
+
+export interface AuditLogEntry {
+  id: string;
+  user_id: string | null;
+  user_email: string | null;
+  user_name: string | null;
+  action: string;
+  resource_type: string | null;
+  resource_id: string | null;
+  details: Record<string, unknown> | null;
+  created_at: string;
+}
+
+export interface AuditLogPage {
+  entries: AuditLogEntry[];
+  total: number;
+  page: number;
+  per_page: number;
+}
+
+export interface AuditLogQuery {
+  action?: string;
+  user_id?: string;
+  resource_type?: string;
+  resource_id?: string;
+  since?: string;
+  until?: string;
+  page?: number;
+  per_page?: number;
+}
+
+export interface InviteDetails {
+  email: string;
+  name: string | null;
+  team_name: string;
+  role: TeamRole;
+  expires_at: string;
+}
+
+export interface TwoFactorStatus {
+  enabled: boolean;
+  recovery_codes_remaining: number;
+  required: boolean;
+}
+
+export interface TotpEnrollment {
+  secret: string;
+  otpauth_uri: string;
+}
+
 export interface Container {
   id: string;
   names: string[];
@@ -133,6 +261,7 @@ export type TeamRole = "Owner" | "Admin" | "Developer" | "Viewer";
 export interface Team {
   id: string;
   name: string;
+  require_2fa: boolean;
   created_at: string;
   updated_at: string;
 }
@@ -143,6 +272,7 @@ export interface TeamMember {
   user_name: string;
   user_email: string;
   role: TeamRole;
+  custom_role_id?: string | null;
   created_at: string;
   updated_at: string;
 }
@@ -150,6 +280,36 @@ export interface TeamMember {
 export interface TeamResponse {
   team: Team;
   role: TeamRole;
+  custom_role?: string | null;
+  permissions: string[];
+}
+
+export interface CustomRole {
+  id: string;
+  team_id: string;
+  name: string;
+  description?: string | null;
+  permissions: string[];
+  created_at: string;
+  updated_at: string;
+}
+
+export interface StackGrant {
+  stack_id: string;
+  user_id: string;
+  user_email: string;
+  user_name?: string | null;
+  permissions: string[];
+  granted_by?: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+// Effective permissions of the current user in a team or on a stack
+export interface PermissionSet {
+  role: TeamRole | null;
+  custom_role: string | null;
+  permissions: string[];
 }
 
 export interface Stack {
@@ -167,6 +327,10 @@ export interface Stack {
   git_url?: string;
   git_branch?: string;
   last_commit_hash?: string;
+  maintenance_mode: boolean;
+  maintenance_page: string | null;
+  // Custom HTML keyed by status code (502, 503, 504)
+  error_pages: Record<string, string>;
   container_count: number;
   created_at: string;
   updated_at: string;
@@ -203,6 +367,88 @@ export interface CreateStackFromGit {
 
 export type DomainProvider = "Custom" | "Cloudflare" | "CPanel";
 export type DomainType = "Caddy" | "Tunnel";
+export type LoadBalancingPolicy =
+  | "round_robin"
+  | "least_conn"
+  | "random"
+  | "ip_hash"
+  | "first";
+// How Caddy proves control of the hostname; wildcards need "dns"
+export type TlsChallenge = "http" | "dns";
+
+export interface CertificateStatus {
+  domain: string;
+  state: "valid" | "expiring" | "expired" | "missing" | "disabled" | "external";
+  // Uploaded certificate rather than ACME
+  custom: boolean;
+  subject?: string;
+  issuer?: string;
+  sans: string[];
+  not_before?: string;
+  not_after?: string;
+  days_remaining?: number;
+  error?: string;
+  // Latest obtain/renewal error logged by Caddy
+  last_error?: string;
+  warning?: string;
+}
+
+// Omitted fields keep their current value
+export interface UpdateDomainTls {
+  ssl_enabled?: boolean;
+  tls_challenge?: TlsChallenge;
+  // PEM, uploaded together
+  certificate?: string;
+  private_key?: string;
+  remove_certificate?: boolean;
+}
+
+export interface RedirectRule {
+  from: string;
+  to: string;
+  permanent?: boolean;
+}
+
+export interface HeaderRule {
+  name: string;
+  // null deletes the header
+  value: string | null;
+}
+
+export interface CorsPolicy {
+  allowed_origins: string[];
+  allowed_methods?: string[];
+  allowed_headers?: string[];
+  allow_credentials?: boolean;
+  max_age?: number;
+}
+
+export interface DomainMiddleware {
+  https_redirect: boolean;
+  www_redirect: boolean;
+  redirects: RedirectRule[];
+  hsts_max_age?: number | null;
+  security_headers: boolean;
+  cors?: CorsPolicy | null;
+  request_headers: HeaderRule[];
+  response_headers: HeaderRule[];
+  ip_allow: string[];
+  ip_deny: string[];
+  max_body_bytes?: number | null;
+  compression: boolean;
+}
+
+export interface UpdateDomainMiddleware extends DomainMiddleware {
+  // Omit password to keep an existing user's password
+  basic_auth: { username: string; password?: string }[];
+}
+
+export interface UpstreamSettings {
+  upstreams?: string[];
+  lb_policy?: LoadBalancingPolicy;
+  health_check_path?: string;
+  health_check_interval?: number;
+}
 
 export interface RemoteDnsRecord {
   id: string;
@@ -227,10 +473,60 @@ export interface Domain {
   dns_record_id?: string;
   proxied: boolean;
   show_branding: boolean;
+  upstreams: string[];
+  lb_policy: LoadBalancingPolicy;
+  health_check_path?: string;
+  health_check_interval: number;
+  middleware: DomainMiddleware;
+  basic_auth_users: string[];
+  tls_challenge: TlsChallenge;
+  custom_certificate: boolean;
+  created_at: string;
+}
+
+export interface DomainRoute {
+  id: string;
+  domain_id: string;
+  path: string;
+  strip_prefix: boolean;
+  container_name: string;
+  container_port: number;
+  upstreams: string[];
+  lb_policy: LoadBalancingPolicy;
+  health_check_path?: string;
+  health_check_interval: number;
   created_at: string;
 }
 
-export interface CreateDomain {
+export type L4Protocol = "tcp" | "udp";
+
+export interface L4Route {
+  id: string;
+  stack_id: string;
+  protocol: L4Protocol;
+  listen_port: number;
+  sni?: string;
+  container_name: string;
+  container_port: number;
+  created_at: string;
+}
+
+export interface CreateL4Route {
+  protocol: L4Protocol;
+  listen_port: number;
+  sni?: string;
+  container_name: string;
+  container_port: number;
+}
+
+export interface CreateDomainRoute extends UpstreamSettings {
+  path: string;
+  strip_prefix?: boolean;
+  container_name: string;
+  container_port?: number;
+}
+
+export interface CreateDomain extends UpstreamSettings {
   domain: string;
   container_name: string;
   container_port?: number;
@@ -241,6 +537,7 @@ export interface CreateDomain {
   dns_record_type?: string;
   dns_record_content?: string;
   proxied?: boolean;
+  tls_challenge?: TlsChallenge;
 }
 
 export interface DnsConfig {
@@ -347,6 +644,40 @@ export interface HistoricalContainerMetrics {
   timestamp: string;
 }
 
+export interface DomainTrafficStats {
+  domain: string;
+  timestamp: string;
+  requests: number;
+  status_1xx: number;
+  status_2xx: number;
+  status_3xx: number;
+  status_4xx: number;
+  status_5xx: number;
+  bytes_sent: number;
+  avg_latency_ms: number | null;
+  p50_latency_ms: number | null;
+  p95_latency_ms: number | null;
+  p99_latency_ms: number | null;
+}
+
+export interface DomainTraffic {
+  summary: DomainTrafficStats;
+  series: DomainTrafficStats[];
+}
+
+export interface AccessLogEntry {
+  timestamp: string;
+  domain: string;
+  host: string;
+  method: string;
+  uri: string;
+  proto: string;
+  status: number;
+  bytes: number;
+  duration_ms: number;
+  client_ip: string;
+}
+
 export interface Template {
   id: string;
   name: string;
@@ -387,12 +718,31 @@ export interface SwarmNode {
 export const api = {
   auth: {
     login: async (data: LoginRequest) => {
-      const result = await fetchApi<AuthResponse>("/auth/login", {
+      const result = await fetchApi<LoginResponse>("/auth/login", {
         method: "POST",
         body: JSON.stringify(data),
       });
+      if (result.data && !("mfa_required" in result.data)) {
+        auth.login(
+          result.data.token,
+          result.data.user,
+          result.data.refresh_token,
+        );
+      }
+      return result;
+    },
+
+    loginTwoFactor: async (mfaToken: string, code: string) => {
+      const result = await fetchApi<AuthResponse>("/auth/login/2fa", {
+        method: "POST",
+        body: JSON.stringify({ mfa_token: mfaToken, code }),
+      });
       if (result.data) {
-        auth.login(result.data.token, result.data.user);
+        auth.login(
+          result.data.token,
+          result.data.user,
+          result.data.refresh_token,
+        );
       }
       return result;
     },
@@ -403,12 +753,22 @@ export const api = {
         body: JSON.stringify(data),
       });
       if (result.data) {
-        auth.login(result.data.token, result.data.user);
+        auth.login(
+          result.data.token,
+          result.data.user,
+          result.data.refresh_token,
+        );
       }
       return result;
     },
 
-    logout: () => {
+    logout: async () => {
+      await fetchApi("/auth/logout", { method: "POST" });
+      auth.logout();
+    },
+
+    logoutAll: async () => {
+      await fetchApi("/auth/logout-all", { method: "POST" });
       auth.logout();
     },
 
@@ -416,9 +776,159 @@ export const api = {
       return fetchApi<User>("/me");
     },
 
+    // Single-use credential for WebSocket and SSE connections
+    streamTicket: async () => {
+      return fetchApi<{ ticket: string; expires_in: number }>(
+        "/auth/stream-ticket",
+        { method: "POST" },
+      );
+    },
+
     isSetupRequired: async () => {
       return fetchApi<boolean>("/auth/setup-required");
     },
+
+    providers: async () => {
+      return fetchApi<LoginOptions>("/auth/providers");
+    },
+
+    inviteDetails: async (token: string) => {
+      return fetchApi<InviteDetails>("/auth/invite", {
+        method: "POST",
+        body: JSON.stringify({ token }),
+      });
+    },
+
+    acceptInvite: async (data: {
+      token: string;
+      name?: string;
+      password: string;
+    }) => {
+      const result = await fetchApi<AuthResponse>("/auth/invite/accept", {
+        method: "POST",
+        body: JSON.stringify(data),
+      });
+      if (result.data) {
+        auth.login(
+          result.data.token,
+          result.data.user,
+          result.data.refresh_token,
+        );
+      }
+      return result;
+    },
+
+    resetPassword: async (token: string, password: string) => {
+      return fetchApi<{ status: string }>("/auth/password-reset", {
+        method: "POST",
+        body: JSON.stringify({ token, password }),
+      });
+    },
+
+    updateProfile: async (name: string) => {
+      return fetchApi<User>("/me", {
+        method: "PUT",
+        body: JSON.stringify({ name }),
+      });
+    },
+
+    // Other sessions are signed out; this one continues with fresh tokens
+    changePassword: async (currentPassword: string, newPassword: string) => {
+      const result = await fetchApi<AuthResponse>("/me/password", {
+        method: "POST",
+        body: JSON.stringify({
+          current_password: currentPassword,
+          new_password: newPassword,
+        }),
+      });
+      if (result.data) {
+        auth.login(
+          result.data.token,
+          result.data.user,
+          result.data.refresh_token,
+        );
+      }
+      return result;
+    },
+
+    ssoLoginUrl: () => `${API_URL}/api/auth/oidc/login`,
+
+    // Store tokens handed over by the SSO callback, then load the user
+    completeSso: async (token: string, refreshToken: string) => {
+      localStorage.setItem("token", token);
+      localStorage.setItem("refresh_token", refreshToken);
+      const result = await fetchApi<User>("/me");
+      if (result.data) {
+        auth.login(token, result.data, refreshToken);
+      }
+      return result;
+    },
+  },
+
+  users: {
+    list: async () => {
+      return fetchApi<UserAccount[]>("/users");
+    },
+
+    issuePasswordReset: async (id: string) => {
+      return fetchApi<{ token: string; expires_at: string }>(
+        `/users/${id}/password-reset`,
+        { method: "POST" },
+      );
+    },
+
+    deactivate: async (id: string) => {
+      return fetchApi<{ status: string }>(`/users/${id}/deactivate`, {
+        method: "POST",
+      });
+    },
+
+    activate: async (id: string) => {
+      return fetchApi<{ status: string }>(`/users/${id}/activate`, {
+        method: "POST",
+      });
+    },
+  },
+
+  twoFactor: {
+    status: async () => {
+      return fetchApi<TwoFactorStatus>("/auth/2fa");
+    },
+
+    enroll: async () => {
+      return fetchApi<TotpEnrollment>("/auth/2fa/enroll", { method: "POST" });
+    },
+
+    confirm: async (code: string) => {
+      return fetchApi<{ recovery_codes: string[] }>("/auth/2fa/confirm", {
+        method: "POST",
+        body: JSON.stringify({ code }),
+      });
+    },
+
+    disable: async (code: string) => {
+      return fetchApi<{ status: string }>("/auth/2fa/disable", {
+        method: "POST",
+        body: JSON.stringify({ code }),
+      });
+    },
+
+    regenerateRecoveryCodes: async (code: string) => {
+      return fetchApi<{ recovery_codes: string[] }>(
+        "/auth/2fa/recovery-codes",
+        {
+          method: "POST",
+          body: JSON.stringify({ code }),
+        },
+      );
+    },
+
+    setTeamPolicy: async (teamId: string, require2fa: boolean) => {
+      return fetchApi<{ status: string }>(`/teams/${teamId}/2fa-policy`, {
+        method: "PUT",
+        body: JSON.stringify({ require_2fa: require2fa }),
+      });
+    },
   },
 
   health: {
@@ -516,6 +1026,27 @@ export const api = {
       return fetchApi<Stack[]>(`/stacks?team_id=${teamId}`);
     },
 
+    permissions: async (id: string) => {
+      return fetchApi<PermissionSet>(`/stacks/${id}/permissions`);
+    },
+
+    listGrants: async (id: string) => {
+      return fetchApi<StackGrant[]>(`/stacks/${id}/grants`);
+    },
+
+    saveGrant: async (id: string, email: string, permissions: string[]) => {
+      return fetchApi<StackGrant[]>(`/stacks/${id}/grants`, {
+        method: "POST",
+        body: JSON.stringify({ email, permissions }),
+      });
+    },
+
+    revokeGrant: async (id: string, userId: string) => {
+      return fetchApi<{ status: string }>(`/stacks/${id}/grants/${userId}`, {
+        method: "DELETE",
+      });
+    },
+
     get: async (id: string) => {
       return fetchApi<Stack>(`/stacks/${id}`);
     },
@@ -634,6 +1165,23 @@ export const api = {
       });
     },
 
+    setMaintenance: async (id: string, enabled: boolean) => {
+      return fetchApi<{ status: string }>(`/stacks/${id}/maintenance`, {
+        method: "PUT",
+        body: JSON.stringify({ enabled }),
+      });
+    },
+
+    updatePages: async (
+      id: string,
+      data: { maintenance_page: string | null; error_pages: Record<string, string> },
+    ) => {
+      return fetchApi<{ status: string }>(`/stacks/${id}/pages`, {
+        method: "PUT",
+        body: JSON.stringify(data),
+      });
+    },
+
     deploymentLogs: async (id: string) => {
       return fetchApi<DeploymentLog[]>(`/stacks/${id}/deployments`);
     },
@@ -704,7 +1252,7 @@ export const api = {
         domain: string,
         showBranding: boolean,
       ) => {
-        return fetchApi<{ status: string; show_branding: boolean }>(
+        return fetchApi<{ status: string; show_branding: boolean; badge_active: boolean }>(
           `/stacks/${stackId}/domains/${encodeURIComponent(domain)}/branding`,
           {
             method: "PUT",
@@ -712,6 +1260,109 @@ export const api = {
           },
         );
       },
+
+      updateUpstreams: async (
+        stackId: string,
+        domain: string,
+        settings: UpstreamSettings,
+      ) => {
+        return fetchApi<Domain>(
+          `/stacks/${stackId}/domains/${encodeURIComponent(domain)}/upstreams`,
+          {
+            method: "PUT",
+            body: JSON.stringify(settings),
+          },
+        );
+      },
+
+      updateMiddleware: async (
+        stackId: string,
+        domain: string,
+        data: UpdateDomainMiddleware,
+      ) => {
+        return fetchApi<Domain>(
+          `/stacks/${stackId}/domains/${encodeURIComponent(domain)}/middleware`,
+          {
+            method: "PUT",
+            body: JSON.stringify(data),
+          },
+        );
+      },
+
+      updateTls: async (
+        stackId: string,
+        domain: string,
+        data: UpdateDomainTls,
+      ) => {
+        return fetchApi<Domain>(
+          `/stacks/${stackId}/domains/${encodeURIComponent(domain)}/tls`,
+          {
+            method: "PUT",
+            body: JSON.stringify(data),
+          },
+        );
+      },
+
+      certificates: async (teamId: string) => {
+        return fetchApi<CertificateStatus[]>(
+          `/stacks/domains/certificates?team_id=${teamId}`,
+        );
+      },
+
+      certificate: async (stackId: string, domain: string) => {
+        return fetchApi<CertificateStatus>(
+          `/stacks/${stackId}/domains/${encodeURIComponent(domain)}/certificate`,
+        );
+      },
+
+      listRoutes: async (stackId: string, domain: string) => {
+        return fetchApi<DomainRoute[]>(
+          `/stacks/${stackId}/domains/${encodeURIComponent(domain)}/routes`,
+        );
+      },
+
+      addRoute: async (
+        stackId: string,
+        domain: string,
+        data: CreateDomainRoute,
+      ) => {
+        return fetchApi<DomainRoute>(
+          `/stacks/${stackId}/domains/${encodeURIComponent(domain)}/routes`,
+          {
+            method: "POST",
+            body: JSON.stringify(data),
+          },
+        );
+      },
+
+      removeRoute: async (stackId: string, domain: string, routeId: string) => {
+        return fetchApi<{ status: string }>(
+          `/stacks/${stackId}/domains/${encodeURIComponent(domain)}/routes/${routeId}`,
+          {
+            method: "DELETE",
+          },
+        );
+      },
+
+      listL4: async (stackId: string) => {
+        return fetchApi<L4Route[]>(`/stacks/${stackId}/domains/l4`);
+      },
+
+      addL4: async (stackId: string, data: CreateL4Route) => {
+        return fetchApi<L4Route>(`/stacks/${stackId}/domains/l4`, {
+          method: "POST",
+          body: JSON.stringify(data),
+        });
+      },
+
+      removeL4: async (stackId: string, routeId: string) => {
+        return fetchApi<{ status: string }>(
+          `/stacks/${stackId}/domains/l4/${routeId}`,
+          {
+            method: "DELETE",
+          },
+        );
+      },
     },
 
     // Stack health overview
@@ -858,6 +1509,90 @@ export const api = {
         },
       );
     },
+
+    permissions: async (teamId: string) => {
+      return fetchApi<PermissionSet>(`/teams/${teamId}/permissions`);
+    },
+
+    listRoles: async (teamId: string) => {
+      return fetchApi<CustomRole[]>(`/teams/${teamId}/roles`);
+    },
+
+    createRole: async (
+      teamId: string,
+      data: { name: string; description?: string; permissions: string[] },
+    ) => {
+      return fetchApi<CustomRole>(`/teams/${teamId}/roles`, {
+        method: "POST",
+        body: JSON.stringify(data),
+      });
+    },
+
+    updateRole: async (
+      teamId: string,
+      roleId: string,
+      data: { name: string; description?: string; permissions: string[] },
+    ) => {
+      return fetchApi<CustomRole>(`/teams/${teamId}/roles/${roleId}`, {
+        method: "PUT",
+        body: JSON.stringify(data),
+      });
+    },
+
+    deleteRole: async (teamId: string, roleId: string) => {
+      return fetchApi<{ status: string }>(`/teams/${teamId}/roles/${roleId}`, {
+        method: "DELETE",
+      });
+    },
+
+    assignCustomRole: async (
+      teamId: string,
+      userId: string,
+      roleId: string | null,
+    ) => {
+      return fetchApi<{ status: string }>(
+        `/teams/${teamId}/members/${userId}/custom-role`,
+        {
+          method: "PUT",
+          body: JSON.stringify({ role_id: roleId }),
+        },
+      );
+    },
+
+    audit: async (teamId: string, query: AuditLogQuery = {}) => {
+      const params = new URLSearchParams();
+      for (const [key, value] of Object.entries(query)) {
+        if (value !== undefined && value !== "") params.set(key, String(value));
+      }
+      const qs = params.toString();
+      return fetchApi<AuditLogPage>(
+        `/teams/${teamId}/audit${qs ? `?${qs}` : ""}`,
+      );
+    },
+
+    listInvites: async (teamId: string) => {
+      return fetchApi<UserInvite[]>(`/teams/${teamId}/invites`);
+    },
+
+    createInvite: async (
+      teamId: string,
+      data: { email: string; name?: string; role: TeamRole },
+    ) => {
+      return fetchApi<UserInvite & { token: string }>(
+        `/teams/${teamId}/invites`,
+        {
+          method: "POST",
+          body: JSON.stringify(data),
+        },
+      );
+    },
+
+    revokeInvite: async (teamId: string, inviteId: string) => {
+      return fetchApi<{ status: string }>(
+        `/teams/${teamId}/invites/${inviteId}`,
+        { method: "DELETE" },
+      );
+    },
   },
 
   templates: {
@@ -978,6 +1713,35 @@ export const api = {
         `/metrics/stacks/${stackId}/containers/${containerId}/metrics${query}`,
       );
     },
+
+    getDomainTraffic: async (stackId: string, lastHours?: number) => {
+      const query = lastHours ? `?last_hours=${lastHours}` : "";
+      return fetchApi<DomainTrafficStats[]>(
+        `/metrics/stacks/${stackId}/domains/traffic${query}`,
+      );
+    },
+
+    getDomainTrafficSeries: async (
+      stackId: string,
+      domain: string,
+      lastHours?: number,
+    ) => {
+      const query = lastHours ? `?last_hours=${lastHours}` : "";
+      return fetchApi<DomainTraffic>(
+        `/metrics/stacks/${stackId}/domains/${encodeURIComponent(domain)}/traffic${query}`,
+      );
+    },
+
+    getRecentRequests: async (
+      stackId: string,
+      domain: string,
+      limit?: number,
+    ) => {
+      const query = limit ? `?limit=${limit}` : "";
+      return fetchApi<AccessLogEntry[]>(
+        `/metrics/stacks/${stackId}/domains/${encodeURIComponent(domain)}/requests${query}`,
+      );
+    },
   },
 
   networks: {
@@ -1,5 +1,5 @@
 import { goto } from "$app/navigation";
-import { api } from "$lib/api";
+import { api, type LoginOptions } from "$lib/api";
 
 export class AuthController {
   name = $state("");
@@ -9,6 +9,36 @@ export class AuthController {
   error = $state("");
   loading = $state(false);
   setupRequired = $state<boolean | null>(null);
+  // Set when the password (or SSO) step passed and a 2FA code is needed
+  mfaToken = $state<string | null>(null);
+  mfaCode = $state("");
+  loginOptions = $state<LoginOptions>({
+    password_enabled: true,
+    sso_provider: null,
+  });
+
+  async loadLoginOptions() {
+    const result = await api.auth.providers();
+    if (result.data) {
+      this.loginOptions = result.data;
+    }
+    const error = new URLSearchParams(window.location.search).get("error");
+    if (error) {
+      this.error = error;
+    }
+    // SSO logins that need a second factor come back with the token in the fragment
+    const mfaToken = new URLSearchParams(window.location.hash.slice(1)).get(
+      "mfa_token",
+    );
+    if (mfaToken) {
+      this.mfaToken = mfaToken;
+      history.replaceState(null, "", window.location.pathname);
+    }
+  }
+
+  loginWithSso() {
+    window.location.href = api.auth.ssoLoginUrl();
+  }
 
   async checkSetup() {
     const result = await api.auth.isSetupRequired();
@@ -33,9 +63,38 @@ export class AuthController {
       return;
     }
 
+    if (result.data && "mfa_required" in result.data) {
+      this.mfaToken = result.data.mfa_token;
+      this.loading = false;
+      return;
+    }
+
     goto("/dashboard");
   }
 
+  async verifyMfa(e: Event) {
+    e.preventDefault();
+    if (!this.mfaToken) return;
+    this.error = "";
+    this.loading = true;
+
+    const result = await api.auth.loginTwoFactor(this.mfaToken, this.mfaCode);
+
+    if (result.error) {
+      this.error = result.message || "Invalid code";
+      this.loading = false;
+      return;
+    }
+
+    goto("/dashboard");
+  }
+
+  cancelMfa() {
+    this.mfaToken = null;
+    this.mfaCode = "";
+    this.error = "";
+  }
+
   async register(e: Event) {
     e.preventDefault();
     this.error = "";
@@ -1,6 +1,7 @@
 import {
   api,
   type Domain,
+  type CertificateStatus,
   type DnsConfig,
   type RemoteDnsRecord,
 } from "$lib/api";
@@ -10,6 +11,8 @@ import { get } from "svelte/store";
 
 export class DomainController {
   domains = $state<Domain[]>([]);
+  // Keyed by hostname
+  certificates = $state<Record<string, CertificateStatus>>({});
   dnsConfigs = $state<DnsConfig[]>([]);
   remoteRecords = $state<RemoteDnsRecord[]>([]);
   loading = $state(false);
@@ -76,6 +79,9 @@ export class DomainController {
         if (dnsRes.data) {
           this.dnsConfigs = dnsRes.data;
         }
+
+        // Probing every certificate can take a while; don't hold the list back
+        this.loadCertificates(team.team.id);
       }
     } catch (err) {
       console.error("Failed to load domains data", err);
@@ -84,6 +90,15 @@ export class DomainController {
     }
   }
 
+  async loadCertificates(teamId: string) {
+    const res = await api.stacks.domains.certificates(teamId);
+    if (res.data) {
+      this.certificates = Object.fromEntries(
+        res.data.map((status) => [status.domain, status]),
+      );
+    }
+  }
+
   async saveDnsConfig() {
     const team = get(activeTeam);
     if (!team?.team?.id) return;
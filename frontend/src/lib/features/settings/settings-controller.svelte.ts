@@ -1,4 +1,10 @@
-import { api, type RegistryCredential } from "$lib/api";
+import {
+  api,
+  type RegistryCredential,
+  type TotpEnrollment,
+  type TwoFactorStatus,
+  type UserAccount,
+} from "$lib/api";
 import { auth, activeTeam } from "$lib/stores";
 import { toast } from "svelte-sonner";
 import { get } from "svelte/store";
@@ -15,12 +21,108 @@ export class SettingsController {
   });
   addingRegistry = $state(false);
 
+  currentPassword = $state("");
+  newPassword = $state("");
+  changingPassword = $state(false);
+
+  // Administration (instance admins only)
+  users = $state<UserAccount[]>([]);
+  resetLink = $state<{ email: string; url: string } | null>(null);
+
+  twoFactor = $state<TwoFactorStatus | null>(null);
+  enrollment = $state<TotpEnrollment | null>(null);
+  recoveryCodes = $state<string[]>([]);
+  twoFactorCode = $state("");
+
   // UI States
   showRemoveRegistryConfirm = $state(false);
   registryToRemove = $state<string | null>(null);
 
   async init() {
-    await this.loadRegistries();
+    const loads = [this.loadRegistries(), this.loadTwoFactor()];
+    if (get(auth).user?.role === "admin") {
+      loads.push(this.loadUsers());
+    }
+    await Promise.all(loads);
+  }
+
+  async loadTwoFactor() {
+    const result = await api.twoFactor.status();
+    if (result.data) {
+      this.twoFactor = result.data;
+    }
+  }
+
+  async startTwoFactorEnrollment() {
+    const result = await api.twoFactor.enroll();
+    if (result.data) {
+      this.enrollment = result.data;
+      this.recoveryCodes = [];
+      this.twoFactorCode = "";
+    } else {
+      toast.error(result.message || result.error || "Failed to start setup");
+    }
+  }
+
+  async confirmTwoFactor() {
+    const result = await api.twoFactor.confirm(this.twoFactorCode);
+    if (result.data) {
+      toast.success("Two-factor authentication enabled");
+      this.recoveryCodes = result.data.recovery_codes;
+      this.enrollment = null;
+      this.twoFactorCode = "";
+      await this.loadTwoFactor();
+    } else {
+      toast.error(result.message || result.error || "Invalid code");
+    }
+  }
+
+  async disableTwoFactor() {
+    const result = await api.twoFactor.disable(this.twoFactorCode);
+    if (!result.error) {
+      toast.success("Two-factor authentication disabled");
+      this.twoFactorCode = "";
+      this.recoveryCodes = [];
+      await this.loadTwoFactor();
+    } else {
+      toast.error(result.message || result.error);
+    }
+  }
+
+  async regenerateRecoveryCodes() {
+    const result = await api.twoFactor.regenerateRecoveryCodes(
+      this.twoFactorCode,
+    );
+    if (result.data) {
+      toast.success("New recovery codes generated");
+      this.recoveryCodes = result.data.recovery_codes;
+      this.twoFactorCode = "";
+      await this.loadTwoFactor();
+    } else {
+      toast.error(result.message || result.error || "Invalid code");
+    }
+  }
+
+  async setTeamTwoFactorPolicy(require2fa: boolean) {
+    const team = get(activeTeam)?.team;
+    if (!team) return;
+    const result = await api.twoFactor.setTeamPolicy(team.id, require2fa);
+    if (!result.error) {
+      toast.success(
+        require2fa
+          ? "Owners and Admins must now use 2FA"
+          : "2FA is no longer required",
+      );
+      const current = get(activeTeam);
+      if (current) {
+        activeTeam.setActiveTeam({
+          ...current,
+          team: { ...current.team, require_2fa: require2fa },
+        });
+      }
+    } else {
+      toast.error(result.message || result.error);
+    }
   }
 
   async loadRegistries() {
@@ -92,6 +194,65 @@ export class SettingsController {
   }
 
   async saveProfile() {
-    toast.info("Profile update not yet implemented on backend");
+    const result = await api.auth.updateProfile(this.name);
+    const token = get(auth).token;
+    if (result.data && token) {
+      auth.login(token, result.data, localStorage.getItem("refresh_token") ?? undefined);
+      toast.success("Profile updated");
+    } else {
+      toast.error(result.message || result.error || "Failed to update profile");
+    }
+  }
+
+  async changePassword() {
+    this.changingPassword = true;
+    const result = await api.auth.changePassword(
+      this.currentPassword,
+      this.newPassword,
+    );
+    if (result.data) {
+      toast.success("Password changed. Other sessions were signed out.");
+      this.currentPassword = "";
+      this.newPassword = "";
+    } else {
+      toast.error(result.message || result.error || "Failed to change password");
+    }
+    this.changingPassword = false;
+  }
+
+  async loadUsers() {
+    const result = await api.users.list();
+    if (result.data) {
+      this.users = result.data;
+    }
+  }
+
+  async issuePasswordReset(user: UserAccount) {
+    const result = await api.users.issuePasswordReset(user.id);
+    if (result.data) {
+      this.resetLink = {
+        email: user.email,
+        url: `${window.location.origin}/reset-password#token=${result.data.token}`,
+      };
+    } else {
+      toast.error(result.message || result.error || "Failed to create reset link");
+    }
+  }
+
+  async setUserActive(user: UserAccount, active: boolean) {
+    const result = active
+      ? await api.users.activate(user.id)
+      : await api.users.deactivate(user.id);
+    if (!result.error) {
+      toast.success(active ? "User reactivated" : "User deactivated");
+      await this.loadUsers();
+    } else {
+      toast.error(result.message || result.error);
+    }
+  }
+
+  async signOutEverywhere() {
+    await api.auth.logoutAll();
+    window.location.href = "/login";
   }
 }
@@ -8,6 +8,18 @@ import {
   type EnvVar,
   type ContainerResource,
   type ResourceMetric,
+  type PermissionSet,
+  type StackGrant,
+  type DomainRoute,
+  type CreateDomainRoute,
+  type L4Route,
+  type CreateL4Route,
+  type UpstreamSettings,
+  type UpdateDomainMiddleware,
+  type UpdateDomainTls,
+  type CertificateStatus,
+  type DomainTraffic,
+  type AccessLogEntry,
 } from "$lib/api";
 import { toast } from "svelte-sonner";
 import { goto } from "$app/navigation";
@@ -19,12 +31,26 @@ export class StackController {
   stack = $state<Stack | null>(null);
   containers = $state<Container[]>([]);
   domains = $state<Domain[]>([]);
+  // Path routes keyed by hostname
+  domainRoutes = $state<Record<string, DomainRoute[]>>({});
+  // Raw TCP/UDP routes of the stack
+  l4Routes = $state<L4Route[]>([]);
+  // Certificate status keyed by hostname, fetched on demand
+  certificates = $state<Record<string, CertificateStatus>>({});
+  // Request analytics and latest requests keyed by hostname, fetched on demand
+  domainTraffic = $state<Record<string, DomainTraffic>>({});
+  recentRequests = $state<Record<string, AccessLogEntry[]>>({});
   deployments = $state<DeploymentLog[]>([]);
   logs = $state<Map<string, string[]>>(new Map());
   health = $state<StackHealth | null>(null);
   envVars = $state<EnvVar[]>([]);
   resourceLimits = $state<ContainerResource[]>([]);
   metrics = $state<ResourceMetric[]>([]);
+  permissions = $state<PermissionSet | null>(null);
+  grants = $state<StackGrant[]>([]);
+  grantEmail = $state("");
+  grantPermissions = $state<string[]>(["stack.view"]);
+  savingGrant = $state(false);
 
   loading = $state(true);
   actionLoading = $state(false);
@@ -76,10 +102,63 @@ export class StackController {
         this.loadEnvVars(),
         this.loadResourceLimits(),
         this.loadMetrics(),
+        this.loadPermissions(),
       ]);
     }
   }
 
+  can(permission: string): boolean {
+    return this.permissions?.permissions.includes(permission) ?? false;
+  }
+
+  async loadPermissions() {
+    const result = await api.stacks.permissions(this.id);
+    this.permissions = result.data ?? null;
+    if (this.can("team.roles.manage")) {
+      await this.loadGrants();
+    }
+  }
+
+  async loadGrants() {
+    const result = await api.stacks.listGrants(this.id);
+    this.grants = result.data ?? [];
+  }
+
+  toggleGrantPermission(permission: string) {
+    this.grantPermissions = this.grantPermissions.includes(permission)
+      ? this.grantPermissions.filter((p) => p !== permission)
+      : [...this.grantPermissions, permission];
+  }
+
+  async saveGrant() {
+    if (!this.grantEmail) return;
+    this.savingGrant = true;
+    const result = await api.stacks.saveGrant(
+      this.id,
+      this.grantEmail.trim(),
+      this.grantPermissions,
+    );
+    if (result.data) {
+      toast.success(`Access granted to ${this.grantEmail}`);
+      this.grants = result.data;
+      this.grantEmail = "";
+      this.grantPermissions = ["stack.view"];
+    } else {
+      toast.error(result.message || result.error || "Failed to grant access");
+    }
+    this.savingGrant = false;
+  }
+
+  async revokeGrant(userId: string) {
+    const result = await api.stacks.revokeGrant(this.id, userId);
+    if (!result.error) {
+      toast.success("Access revoked");
+      await this.loadGrants();
+    } else {
+      toast.error(result.message || result.error);
+    }
+  }
+
   async loadStack() {
     const result = await api.stacks.get(this.id);
     if (result.data) {
@@ -99,6 +178,26 @@ export class StackController {
     const result = await api.stacks.domains.list(this.id);
     if (result.data) {
       this.domains = result.data;
+      await Promise.all([
+        ...result.data
+          .filter((d) => d.type === "Caddy")
+          .map((d) => this.loadDomainRoutes(d.domain)),
+        this.loadL4Routes(),
+      ]);
+    }
+  }
+
+  async loadL4Routes() {
+    const result = await api.stacks.domains.listL4(this.id);
+    if (result.data) {
+      this.l4Routes = result.data;
+    }
+  }
+
+  async loadDomainRoutes(domain: string) {
+    const result = await api.stacks.domains.listRoutes(this.id, domain);
+    if (result.data) {
+      this.domainRoutes = { ...this.domainRoutes, [domain]: result.data };
     }
   }
 
@@ -390,13 +489,133 @@ export class StackController {
     if (result.error) {
       toast.error(result.message || result.error);
     } else {
-      toast.success(
-        `Labuh badge ${showBranding ? "enabled" : "disabled"} for ${domain}`,
-      );
+      if (showBranding && !result.data?.badge_active) {
+        toast.warning(
+          `Labuh badge saved for ${domain}, but Caddy needs the replace-response module to show it`,
+        );
+      } else {
+        toast.success(
+          `Labuh badge ${showBranding ? "enabled" : "disabled"} for ${domain}`,
+        );
+      }
       await this.loadDomains();
     }
   }
 
+  async addDomainRoute(domain: string, data: CreateDomainRoute) {
+    const result = await api.stacks.domains.addRoute(this.id, domain, data);
+    if (result.error) {
+      toast.error(result.message || result.error);
+      return false;
+    }
+    toast.success(`Route ${data.path} added to ${domain}`);
+    await this.loadDomainRoutes(domain);
+    return true;
+  }
+
+  async removeDomainRoute(domain: string, routeId: string) {
+    const result = await api.stacks.domains.removeRoute(
+      this.id,
+      domain,
+      routeId,
+    );
+    if (result.error) {
+      toast.error(result.message || result.error);
+    } else {
+      toast.success("Route removed");
+      await this.loadDomainRoutes(domain);
+    }
+  }
+
+  async addL4Route(data: CreateL4Route) {
+    const result = await api.stacks.domains.addL4(this.id, data);
+    if (result.error) {
+      toast.error(result.message || result.error);
+      return false;
+    }
+    toast.success(
+      `Port ${data.listen_port}/${data.protocol} now routes to ${data.container_name}`,
+    );
+    await this.loadL4Routes();
+    return true;
+  }
+
+  async removeL4Route(routeId: string) {
+    const result = await api.stacks.domains.removeL4(this.id, routeId);
+    if (result.error) {
+      toast.error(result.message || result.error);
+    } else {
+      toast.success("L4 route removed");
+      await this.loadL4Routes();
+    }
+  }
+
+  async saveUpstreams(domain: string, settings: UpstreamSettings) {
+    const result = await api.stacks.domains.updateUpstreams(
+      this.id,
+      domain,
+      settings,
+    );
+    if (result.error) {
+      toast.error(result.message || result.error);
+      return false;
+    }
+    toast.success(`Upstreams updated for ${domain}`);
+    await this.loadDomains();
+    return true;
+  }
+
+  async saveMiddleware(domain: string, data: UpdateDomainMiddleware) {
+    const result = await api.stacks.domains.updateMiddleware(
+      this.id,
+      domain,
+      data,
+    );
+    if (result.error) {
+      toast.error(result.message || result.error);
+      return false;
+    }
+    toast.success(`HTTP settings updated for ${domain}`);
+    await this.loadDomains();
+    return true;
+  }
+
+  async saveTls(domain: string, data: UpdateDomainTls) {
+    const result = await api.stacks.domains.updateTls(this.id, domain, data);
+    if (result.error) {
+      toast.error(result.message || result.error);
+      return false;
+    }
+    toast.success(`TLS settings updated for ${domain}`);
+    await this.loadDomains();
+    await this.loadCertificate(domain);
+    return true;
+  }
+
+  async loadCertificate(domain: string) {
+    const result = await api.stacks.domains.certificate(this.id, domain);
+    if (result.data) {
+      this.certificates = { ...this.certificates, [domain]: result.data };
+    } else if (result.error) {
+      toast.error(result.message || result.error);
+    }
+  }
+
+  async loadDomainTraffic(domain: string, lastHours = 24) {
+    const [traffic, requests] = await Promise.all([
+      api.metrics.getDomainTrafficSeries(this.id, domain, lastHours),
+      api.metrics.getRecentRequests(this.id, domain, 20),
+    ]);
+    if (traffic.data) {
+      this.domainTraffic = { ...this.domainTraffic, [domain]: traffic.data };
+    } else if (traffic.error) {
+      toast.error(traffic.message || traffic.error);
+    }
+    if (requests.data) {
+      this.recentRequests = { ...this.recentRequests, [domain]: requests.data };
+    }
+  }
+
   async saveAutomation(payload: {
     cron_schedule: string;
     health_check_path: string;
@@ -418,6 +637,29 @@ export class StackController {
     }
   }
 
+  async setMaintenance(enabled: boolean) {
+    const result = await api.stacks.setMaintenance(this.id, enabled);
+    if (result.error) {
+      toast.error(result.message || result.error);
+      return;
+    }
+    toast.success(enabled ? "Maintenance page is now served" : "Traffic restored to the stack");
+    await this.loadStack();
+  }
+
+  async savePages(maintenancePage: string, errorPages: Record<string, string>) {
+    const result = await api.stacks.updatePages(this.id, {
+      maintenance_page: maintenancePage || null,
+      error_pages: errorPages,
+    });
+    if (result.error) {
+      toast.error(result.message || result.error);
+      return;
+    }
+    toast.success("Pages updated");
+    await this.loadStack();
+  }
+
   async toggleSecretVisibility(envId: string) {
     const newSet = new Set(this.showSecrets);
     if (newSet.has(envId)) {
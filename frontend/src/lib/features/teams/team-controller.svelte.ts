@@ -1,8 +1,11 @@
 import {
   api,
+  type AuditLogEntry,
+  type CustomRole,
   type TeamResponse,
   type TeamMember,
   type TeamRole,
+  type UserInvite,
 } from "$lib/api";
 import { activeTeam } from "$lib/stores";
 import { toast } from "svelte-sonner";
@@ -21,6 +24,68 @@ export class TeamController {
   invitePassword = $state("");
   inviteRole = $state<TeamRole>("Developer");
   inviting = $state(false);
+  pendingInvites = $state<UserInvite[]>([]);
+  // Shown once after creating an invite; the token cannot be retrieved later
+  inviteLink = $state<string | null>(null);
+
+  auditEntries = $state<AuditLogEntry[]>([]);
+  auditTotal = $state(0);
+  auditPage = $state(1);
+  auditAction = $state("");
+  loadingAudit = $state(false);
+  readonly auditPageSize = 25;
+
+  customRoles = $state<CustomRole[]>([]);
+  loadingRoles = $state(false);
+  editingRoleId = $state<string | null>(null);
+  roleName = $state("");
+  roleDescription = $state("");
+  rolePermissions = $state<string[]>([]);
+  savingRole = $state(false);
+
+  // Mirrors the permission list in the backend
+  readonly permissionGroups: { label: string; permissions: string[] }[] = [
+    {
+      label: "Team",
+      permissions: [
+        "team.view",
+        "team.members.manage",
+        "team.roles.manage",
+        "team.settings.manage",
+        "team.tokens.manage",
+        "audit.read",
+      ],
+    },
+    {
+      label: "Stacks",
+      permissions: [
+        "stack.view",
+        "stack.create",
+        "stack.edit",
+        "stack.deploy",
+        "stack.delete",
+        "stack.exec",
+      ],
+    },
+    {
+      label: "Environment",
+      permissions: [
+        "env.read",
+        "env.write",
+        "env.secret.read",
+        "env_group.manage",
+      ],
+    },
+    {
+      label: "Infrastructure",
+      permissions: [
+        "domain.manage",
+        "dns.manage",
+        "resource.manage",
+        "registry.manage",
+      ],
+    },
+  ];
 
   // UI States (Modals)
   showDeleteTeamConfirm = $state(false);
@@ -39,7 +104,12 @@ export class TeamController {
     const result = await api.teams.list();
     if (result.data) {
       this.teams = result.data;
-      if (!get(activeTeam) && this.teams.length > 0) {
+      const current = get(activeTeam);
+      // Refresh the stored entry so role and permission changes take effect
+      const fresh = current && this.teams.find((t) => t.team.id === current.team.id);
+      if (fresh) {
+        activeTeam.setActiveTeam(fresh);
+      } else if (!current && this.teams.length > 0) {
         activeTeam.setActiveTeam(this.teams[0]);
       }
     }
@@ -99,9 +169,23 @@ export class TeamController {
     this.loadingMembers = false;
   }
 
+  async loadInvites(teamId: string) {
+    const result = await api.teams.listInvites(teamId);
+    this.pendingInvites = result.data ?? [];
+  }
+
+  // Without a password the invitee gets a link to choose their own
   async inviteMember(teamId: string) {
-    if (!this.inviteEmail || !this.inviteName || !this.invitePassword) {
-      toast.error("Please fill in all fields (Name, Email, Password)");
+    if (!this.inviteEmail) {
+      toast.error("Email is required");
+      return;
+    }
+    if (!this.invitePassword) {
+      await this.createInviteLink(teamId);
+      return;
+    }
+    if (!this.inviteName) {
+      toast.error("Name is required when setting an initial password");
       return;
     }
     this.inviting = true;
@@ -123,6 +207,35 @@ export class TeamController {
     this.inviting = false;
   }
 
+  async createInviteLink(teamId: string) {
+    this.inviting = true;
+    const result = await api.teams.createInvite(teamId, {
+      email: this.inviteEmail,
+      name: this.inviteName || undefined,
+      role: this.inviteRole,
+    });
+    if (result.data) {
+      this.inviteLink = `${window.location.origin}/invite#token=${result.data.token}`;
+      toast.success(`Invite created for ${this.inviteEmail}`);
+      this.inviteName = "";
+      this.inviteEmail = "";
+      await this.loadInvites(teamId);
+    } else {
+      toast.error(result.message || result.error || "Failed to create invite");
+    }
+    this.inviting = false;
+  }
+
+  async revokeInvite(teamId: string, inviteId: string) {
+    const result = await api.teams.revokeInvite(teamId, inviteId);
+    if (!result.error) {
+      toast.success("Invite revoked");
+      await this.loadInvites(teamId);
+    } else {
+      toast.error(result.message || result.error);
+    }
+  }
+
   requestRemoveMember(teamId: string, userId: string) {
     this.memberToRemove = { teamId, userId };
     this.showRemoveMemberConfirm = true;
@@ -151,4 +264,86 @@ export class TeamController {
       toast.error(result.message || result.error);
     }
   }
+
+  async loadAudit(teamId: string, page = this.auditPage) {
+    this.loadingAudit = true;
+    const result = await api.teams.audit(teamId, {
+      action: this.auditAction.trim() || undefined,
+      page,
+      per_page: this.auditPageSize,
+    });
+    if (result.data) {
+      this.auditEntries = result.data.entries;
+      this.auditTotal = result.data.total;
+      this.auditPage = result.data.page;
+    } else {
+      toast.error(result.message || result.error || "Failed to load audit log");
+    }
+    this.loadingAudit = false;
+  }
+
+  async loadRoles(teamId: string) {
+    this.loadingRoles = true;
+    const result = await api.teams.listRoles(teamId);
+    this.customRoles = result.data ?? [];
+    this.loadingRoles = false;
+  }
+
+  editRole(role: CustomRole | null) {
+    this.editingRoleId = role?.id ?? null;
+    this.roleName = role?.name ?? "";
+    this.roleDescription = role?.description ?? "";
+    this.rolePermissions = role ? [...role.permissions] : [];
+  }
+
+  toggleRolePermission(permission: string) {
+    this.rolePermissions = this.rolePermissions.includes(permission)
+      ? this.rolePermissions.filter((p) => p !== permission)
+      : [...this.rolePermissions, permission];
+  }
+
+  async saveRole(teamId: string) {
+    if (!this.roleName.trim()) {
+      toast.error("Role name is required");
+      return;
+    }
+    this.savingRole = true;
+    const data = {
+      name: this.roleName.trim(),
+      description: this.roleDescription.trim() || undefined,
+      permissions: this.rolePermissions,
+    };
+    const result = this.editingRoleId
+      ? await api.teams.updateRole(teamId, this.editingRoleId, data)
+      : await api.teams.createRole(teamId, data);
+    if (result.data) {
+      toast.success(`Role "${result.data.name}" saved`);
+      this.editRole(null);
+      await this.loadRoles(teamId);
+    } else {
+      toast.error(result.message || result.error || "Failed to save role");
+    }
+    this.savingRole = false;
+  }
+
+  async deleteRole(teamId: string, roleId: string) {
+    const result = await api.teams.deleteRole(teamId, roleId);
+    if (!result.error) {
+      toast.success("Role deleted");
+      if (this.editingRoleId === roleId) this.editRole(null);
+      await Promise.all([this.loadRoles(teamId), this.loadMembers(teamId)]);
+    } else {
+      toast.error(result.message || result.error);
+    }
+  }
+
+  async assignCustomRole(teamId: string, userId: string, roleId: string | null) {
+    const result = await api.teams.assignCustomRole(teamId, userId, roleId);
+    if (!result.error) {
+      toast.success("Custom role updated");
+      await this.loadMembers(teamId);
+    } else {
+      toast.error(result.message || result.error);
+    }
+  }
 }
@@ -36,16 +36,20 @@ function createAuthStore() {
 
   return {
     subscribe,
-    login: (token: string, user: User) => {
+    login: (token: string, user: User, refreshToken?: string) => {
       if (browser) {
         localStorage.setItem("token", token);
         localStorage.setItem("user", JSON.stringify(user));
+        if (refreshToken) {
+          localStorage.setItem("refresh_token", refreshToken);
+        }
       }
       set({ user, token, isAuthenticated: true });
     },
     logout: () => {
       if (browser) {
         localStorage.removeItem("token");
+        localStorage.removeItem("refresh_token");
         localStorage.removeItem("user");
       }
       set({ user: null, token: null, isAuthenticated: false });
@@ -1,4 +1,4 @@
 export { theme } from "./theme";
 export { auth } from "./auth";
-export { activeTeam } from "./team";
+export { activeTeam, hasPermission } from "./team";
 export type { User, AuthState } from "./auth";
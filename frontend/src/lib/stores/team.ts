@@ -46,3 +46,11 @@ function createTeamStore() {
 }
 
 export const activeTeam = createTeamStore();
+
+// Entries saved before permissions were returned fall back to no permissions
+export function hasPermission(
+  team: TeamResponse | null,
+  permission: string,
+): boolean {
+  return team?.permissions?.includes(permission) ?? false;
+}
@@ -2,6 +2,7 @@ import { redirect } from "@sveltejs/kit";
 import type { LayoutLoad } from "./$types";
 import { browser } from "$app/environment";
 import { api } from "$lib/api";
+import { auth } from "$lib/stores";
 
 export const load: LayoutLoad = async () => {
   // Only check auth on browser (client-side)
@@ -18,8 +19,7 @@ export const load: LayoutLoad = async () => {
 
     if (result.error) {
       // Token is invalid or expired, clear it and redirect
-      localStorage.removeItem("token");
-      localStorage.removeItem("user");
+      auth.logout();
       throw redirect(302, "/login");
     }
 